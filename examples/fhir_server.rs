@@ -0,0 +1,16 @@
+//! Runs a FHIR server over an in-memory repository.
+//!
+//! ```bash
+//! cargo run --example fhir_server --features server -- 127.0.0.1:8080
+//! ```
+
+use fhir_resources_rs::repository::MemoryRepository;
+use fhir_resources_rs::server::FhirServer;
+
+fn main() -> std::io::Result<()> {
+    let address = std::env::args().nth(1).unwrap_or_else(|| "127.0.0.1:8080".to_string());
+    let handle = FhirServer::new(MemoryRepository::new()).start(&address)?;
+    println!("Serving FHIR at {}", handle.base_url());
+    handle.join();
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use crate::identifier::Identifier;
+use crate::meta::Meta;
 use crate::data_types::codeable_concept::CodeableConcept;
 use crate::data_types::reference::Reference;
 use crate::period::Period;
@@ -8,47 +9,53 @@ use crate::account_diagnosis::AccountDiagnosis;
 use crate::account_procedure::AccountProcedure;
 use crate::account_related_account::AccountRelatedAccount;
 use crate::account_balance::AccountBalance;
+use crate::data_types::date_time::{Date, Instant};
+use crate::data_types::positive_int::PositiveInt;
+use crate::currency::{Currency, ISO_4217};
 use serde::{Deserialize, Serialize};
 
-
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Account {
     #[serde(rename = "resourceType")]
     resource_type: String,
-    #[serde(rename = "identifier")]
+    #[serde(rename = "id", skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(rename = "meta", skip_serializing_if = "Option::is_none")]
+    meta: Option<Meta>,
+    #[serde(rename = "identifier", default, skip_serializing_if = "Vec::is_empty")]
     identifier: Vec<Identifier>,
-    #[serde(rename = "status")]
+    #[serde(rename = "status", skip_serializing_if = "Option::is_none")]
     status: Option<String>, // active | inactive | entered-in-error | on-hold | unknown
-    #[serde(rename = "billingStatus")]
+    #[serde(rename = "billingStatus", skip_serializing_if = "Option::is_none")]
     billing_status: Option<CodeableConcept>,
-    #[serde(rename = "type")]
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
     account_type: Option<CodeableConcept>,
-    #[serde(rename = "name")]
+    #[serde(rename = "name", skip_serializing_if = "Option::is_none")]
     name: Option<String>,
-    #[serde(rename = "subject")]
+    #[serde(rename = "subject", default, skip_serializing_if = "Vec::is_empty")]
     subject: Vec<Reference>,
-    #[serde(rename = "servicePeriod")]
+    #[serde(rename = "servicePeriod", skip_serializing_if = "Option::is_none")]
     service_period: Option<Period>,
-    #[serde(rename = "coverage")]
+    #[serde(rename = "coverage", default, skip_serializing_if = "Vec::is_empty")]
     coverage: Vec<AccountCoverage>,
-    #[serde(rename = "owner")]
+    #[serde(rename = "owner", skip_serializing_if = "Option::is_none")]
     owner: Option<Reference>,
-    #[serde(rename = "description")]
+    #[serde(rename = "description", skip_serializing_if = "Option::is_none")]
     description: Option<String>,
-    #[serde(rename = "guarantor")]
+    #[serde(rename = "guarantor", default, skip_serializing_if = "Vec::is_empty")]
     guarantor: Vec<AccountGuarantor>,
-    #[serde(rename = "diagnosis")]
+    #[serde(rename = "diagnosis", default, skip_serializing_if = "Vec::is_empty")]
     diagnosis: Vec<AccountDiagnosis>,
-    #[serde(rename = "procedure")]
+    #[serde(rename = "procedure", default, skip_serializing_if = "Vec::is_empty")]
     procedure: Vec<AccountProcedure>,
-    #[serde(rename = "relatedAccount")]
+    #[serde(rename = "relatedAccount", default, skip_serializing_if = "Vec::is_empty")]
     related_account: Vec<AccountRelatedAccount>,
-    #[serde(rename = "currency")]
+    #[serde(rename = "currency", skip_serializing_if = "Option::is_none")]
     currency: Option<CodeableConcept>,
-    #[serde(rename = "balance")]
+    #[serde(rename = "balance", default, skip_serializing_if = "Vec::is_empty")]
     balance: Vec<AccountBalance>,
-    #[serde(rename = "calculatedAt")]
-    calculated_at: Option<String>,
+    #[serde(rename = "calculatedAt", skip_serializing_if = "Option::is_none")]
+    calculated_at: Option<Instant>,
 }
 
 
@@ -57,6 +64,8 @@ impl Account {
     pub fn new() -> Self {
         Self {
             resource_type: "Account".to_string(),
+            id: None,
+            meta: None,
             identifier: Vec::new(),
             status: None,
             billing_status: None,
@@ -81,6 +90,8 @@ impl Account {
     pub fn with_resource_type(resource_type: String) -> Self {
         Self {
             resource_type,
+            id: None,
+            meta: None,
             identifier: Vec::new(),
             status: None,
             billing_status: None,
@@ -106,6 +117,14 @@ impl Account {
         &self.resource_type
     }
 
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    pub fn meta(&self) -> Option<&Meta> {
+        self.meta.as_ref()
+    }
+
     pub fn identifiers(&self) -> &[Identifier] {
         &self.identifier
     }
@@ -166,12 +185,54 @@ impl Account {
         self.currency.as_ref()
     }
 
+    /// Returns the ISO 4217 code of the account currency: the code of a
+    /// coding in the `urn:iso:std:iso:4217` system, or else any coding whose
+    /// code is a known currency.
+    pub fn currency_code(&self) -> Option<&str> {
+        let coding = self.currency.as_ref()?.coding();
+        coding
+            .iter()
+            .find(|coding| coding.system() == Some(ISO_4217))
+            .and_then(|coding| coding.code())
+            .or_else(|| coding.iter().filter_map(|coding| coding.code()).find(|code| Currency::is_valid(code)))
+    }
+
+    /// Returns who pays for services on a date: the coverages by priority,
+    /// and the guarantors not on hold whose period includes the date, in
+    /// the order listed. Coverages have no dates of their own on the
+    /// account, so all of them apply. Two coverages with the same priority,
+    /// or one without a priority when there are several, are ambiguous.
+    pub fn payers_on(&self, on: &Date) -> Result<Payers<'_>, PayerError> {
+        let mut coverages: Vec<&AccountCoverage> = self.coverage.iter().collect();
+        if coverages.len() > 1 {
+            if let Some(coverage) = coverages.iter().find(|coverage| coverage.priority().is_none()) {
+                return Err(PayerError::MissingPriority(describe(coverage.coverage())));
+            }
+            coverages.sort_by_key(|coverage| coverage.priority());
+            if let Some(pair) = coverages.windows(2).find(|pair| pair[0].priority() == pair[1].priority()) {
+                return Err(PayerError::DuplicatePriority(
+                    pair[0].priority().unwrap(),
+                    describe(pair[0].coverage()),
+                    describe(pair[1].coverage()),
+                ));
+            }
+        }
+        let day = Period::new(Some(on.clone().into()), Some(on.clone().into()));
+        let guarantors = self
+            .guarantor
+            .iter()
+            .filter(|guarantor| guarantor.on_hold() != Some(true))
+            .filter(|guarantor| guarantor.period().is_none_or(|period| period.overlaps(&day)))
+            .collect();
+        Ok(Payers { coverages, guarantors })
+    }
+
     pub fn balance(&self) -> &[AccountBalance] {
         &self.balance
     }
 
-    pub fn calculated_at(&self) -> Option<&str> {
-        self.calculated_at.as_deref()
+    pub fn calculated_at(&self) -> Option<&Instant> {
+        self.calculated_at.as_ref()
     }
 
     // Setters
@@ -179,6 +240,14 @@ impl Account {
         self.resource_type = resource_type;
     }
 
+    pub fn set_id(&mut self, id: Option<String>) {
+        self.id = id;
+    }
+
+    pub fn set_meta(&mut self, meta: Option<Meta>) {
+        self.meta = meta;
+    }
+
     pub fn set_identifiers(&mut self, identifiers: Vec<Identifier>) {
         self.identifier = identifiers;
     }
@@ -275,7 +344,7 @@ impl Account {
         self.balance.push(balance);
     }
 
-    pub fn set_calculated_at(&mut self, calculated_at: Option<String>) {
+    pub fn set_calculated_at(&mut self, calculated_at: Option<Instant>) {
         self.calculated_at = calculated_at;
     }
 
@@ -296,4 +365,34 @@ impl Default for Account {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PayerError {
+    #[error("Coverages {1} and {2} have the same priority {0}")]
+    DuplicatePriority(PositiveInt, String, String),
+    #[error("Coverage {0} has no priority and is not the only coverage")]
+    MissingPriority(String),
+}
+
+/// The coverages and guarantors that pay for services on a date.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Payers<'a> {
+    coverages: Vec<&'a AccountCoverage>,
+    guarantors: Vec<&'a AccountGuarantor>,
+}
+
+impl<'a> Payers<'a> {
+    /// Returns the coverages, primary first.
+    pub fn coverages(&self) -> &[&'a AccountCoverage] {
+        &self.coverages
+    }
 
+    /// Returns the guarantors, in the order listed on the account.
+    pub fn guarantors(&self) -> &[&'a AccountGuarantor] {
+        &self.guarantors
+    }
+}
+
+/// Names a reference in an error message.
+fn describe(reference: &Reference) -> String {
+    reference.reference().or(reference.display()).unwrap_or("(unidentified)").to_string()
+}
@@ -4,11 +4,11 @@ use crate::money::Money;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AccountBalance {
-    #[serde(rename = "aggregate")]
+    #[serde(rename = "aggregate", skip_serializing_if = "Option::is_none")]
     aggregate: Option<CodeableConcept>,
-    #[serde(rename = "term")]
+    #[serde(rename = "term", skip_serializing_if = "Option::is_none")]
     term: Option<CodeableConcept>,
-    #[serde(rename = "estimate")]
+    #[serde(rename = "estimate", skip_serializing_if = "Option::is_none")]
     estimate: Option<bool>,
     #[serde(rename = "amount")]
     amount: Money,
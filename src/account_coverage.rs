@@ -1,3 +1,4 @@
+use crate::data_types::positive_int::PositiveInt;
 use crate::data_types::reference::Reference;
 use serde::{Deserialize, Serialize};
 
@@ -5,8 +6,8 @@ use serde::{Deserialize, Serialize};
 pub struct AccountCoverage {
     #[serde(rename = "coverage")]
     coverage: Reference,
-    #[serde(rename = "priority")]
-    priority: Option<String>, // positiveInt
+    #[serde(rename = "priority", skip_serializing_if = "Option::is_none")]
+    priority: Option<PositiveInt>,
 }
 
 // Implementations for nested structures
@@ -18,7 +19,7 @@ impl AccountCoverage {
         }
     }
 
-    pub fn with_priority(coverage: Reference, priority: String) -> Self {
+    pub fn with_priority(coverage: Reference, priority: PositiveInt) -> Self {
         Self {
             coverage,
             priority: Some(priority),
@@ -29,15 +30,15 @@ impl AccountCoverage {
         &self.coverage
     }
 
-    pub fn priority(&self) -> Option<&str> {
-        self.priority.as_deref()
+    pub fn priority(&self) -> Option<PositiveInt> {
+        self.priority
     }
 
     pub fn set_coverage(&mut self, coverage: Reference) {
         self.coverage = coverage;
     }
 
-    pub fn set_priority(&mut self, priority: Option<String>) {
+    pub fn set_priority(&mut self, priority: Option<PositiveInt>) {
         self.priority = priority;
     }
 }
@@ -1,20 +1,21 @@
 use crate::data_types::codeable_concept::CodeableConcept;
 use crate::data_types::reference::Reference;
+use crate::data_types::date_time::DateTime;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AccountDiagnosis {
-    #[serde(rename = "sequence")]
+    #[serde(rename = "sequence", skip_serializing_if = "Option::is_none")]
     sequence: Option<String>, // positiveInt
     #[serde(rename = "condition")]
     condition: Reference, // CodeableReference(Condition) - simplified as Reference for now
-    #[serde(rename = "dateOfDiagnosis")]
-    date_of_diagnosis: Option<String>, // dateTime
-    #[serde(rename = "type")]
+    #[serde(rename = "dateOfDiagnosis", skip_serializing_if = "Option::is_none")]
+    date_of_diagnosis: Option<DateTime>,
+    #[serde(rename = "type", default, skip_serializing_if = "Vec::is_empty")]
     diagnosis_type: Vec<CodeableConcept>,
-    #[serde(rename = "onAdmission")]
+    #[serde(rename = "onAdmission", skip_serializing_if = "Option::is_none")]
     on_admission: Option<bool>,
-    #[serde(rename = "packageCode")]
+    #[serde(rename = "packageCode", default, skip_serializing_if = "Vec::is_empty")]
     package_code: Vec<CodeableConcept>,
 }
 
@@ -41,8 +42,8 @@ impl AccountDiagnosis {
         self.sequence.as_deref()
     }
 
-    pub fn date_of_diagnosis(&self) -> Option<&str> {
-        self.date_of_diagnosis.as_deref()
+    pub fn date_of_diagnosis(&self) -> Option<&DateTime> {
+        self.date_of_diagnosis.as_ref()
     }
 
     pub fn diagnosis_type(&self) -> &[CodeableConcept] {
@@ -65,7 +66,7 @@ impl AccountDiagnosis {
         self.sequence = sequence;
     }
 
-    pub fn set_date_of_diagnosis(&mut self, date_of_diagnosis: Option<String>) {
+    pub fn set_date_of_diagnosis(&mut self, date_of_diagnosis: Option<DateTime>) {
         self.date_of_diagnosis = date_of_diagnosis;
     }
 
@@ -6,9 +6,9 @@ use serde::{Deserialize, Serialize};
 pub struct AccountGuarantor {
     #[serde(rename = "party")]
     party: Reference,
-    #[serde(rename = "onHold")]
+    #[serde(rename = "onHold", skip_serializing_if = "Option::is_none")]
     on_hold: Option<bool>,
-    #[serde(rename = "period")]
+    #[serde(rename = "period", skip_serializing_if = "Option::is_none")]
     period: Option<Period>,
 }
 
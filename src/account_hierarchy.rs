@@ -0,0 +1,237 @@
+//! Account hierarchies over `Account.relatedAccount`.
+//!
+//! An account lists the accounts it is related to, each with a relationship
+//! code such as `parent` or `guarantor`; a link from a child to its parent
+//! account makes the child a descendant of the parent. An [`AccountGraph`]
+//! holds the accounts of a repository and their links, finds cycles, walks
+//! ancestors and descendants, and rolls the balances of an account and its
+//! descendants up into one [`BalanceSummary`].
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::account::Account;
+use crate::balance_summary::{self, BalanceError, BalanceSummary};
+use crate::repository::{Repository, RepositoryError};
+
+#[derive(Debug, thiserror::Error)]
+pub enum AccountHierarchyError {
+    #[error("Account not found: {0}")]
+    NotFound(String),
+    #[error("Accounts form a cycle: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+    #[error(transparent)]
+    Balance(#[from] BalanceError),
+}
+
+/// A link from an account to a related account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountLink {
+    child: String,
+    parent: String,
+    relationship: Option<String>,
+}
+
+impl AccountLink {
+    /// Returns the id of the account holding the `relatedAccount`.
+    pub fn child(&self) -> &str {
+        &self.child
+    }
+
+    /// Returns the id of the related account.
+    pub fn parent(&self) -> &str {
+        &self.parent
+    }
+
+    /// Returns the relationship code, e.g. `parent` or `guarantor`.
+    pub fn relationship(&self) -> Option<&str> {
+        self.relationship.as_deref()
+    }
+}
+
+/// Accounts and the links between them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AccountGraph {
+    accounts: BTreeMap<String, Account>,
+    links: Vec<AccountLink>,
+    dangling: Vec<AccountLink>,
+}
+
+impl AccountGraph {
+    /// Builds the graph of every Account in a repository.
+    pub fn build(repository: &dyn Repository) -> Result<Self, AccountHierarchyError> {
+        let accounts = repository
+            .list("Account")?
+            .into_iter()
+            .filter_map(|resource| resource.downcast::<Account>())
+            .collect();
+        Ok(Self::from_accounts(accounts))
+    }
+
+    /// Builds the graph of a set of accounts. Accounts without an id are
+    /// skipped, and links to Accounts outside the set are kept apart in
+    /// [`AccountGraph::dangling`] rather than failing the whole graph.
+    pub fn from_accounts(accounts: Vec<Account>) -> Self {
+        let accounts: BTreeMap<String, Account> = accounts
+            .into_iter()
+            .filter_map(|account| Some((account.id()?.to_string(), account)))
+            .collect();
+        let mut links = Vec::new();
+        let mut dangling = Vec::new();
+        for (id, account) in &accounts {
+            for related in account.related_account() {
+                let Some(("Account", parent)) = related.account().target() else { continue };
+                let link = AccountLink {
+                    child: id.clone(),
+                    parent: parent.to_string(),
+                    relationship: balance_summary::code(related.relationship()).map(str::to_string),
+                };
+                if accounts.contains_key(parent) { links.push(link) } else { dangling.push(link) }
+            }
+        }
+        Self { accounts, links, dangling }
+    }
+
+    /// Returns an account by id.
+    pub fn account(&self, id: &str) -> Option<&Account> {
+        self.accounts.get(id)
+    }
+
+    /// Returns every link, grouped by child account.
+    pub fn links(&self) -> &[AccountLink] {
+        &self.links
+    }
+
+    /// Returns the links to Accounts that are not in the graph; they are
+    /// left out of every traversal.
+    pub fn dangling(&self) -> &[AccountLink] {
+        &self.dangling
+    }
+
+    /// Returns the accounts an account is directly related to.
+    pub fn parents(&self, id: &str) -> Vec<&str> {
+        self.links.iter().filter(|link| link.child == id).map(|link| link.parent.as_str()).collect()
+    }
+
+    /// Returns the accounts directly related to an account.
+    pub fn children(&self, id: &str) -> Vec<&str> {
+        self.links.iter().filter(|link| link.parent == id).map(|link| link.child.as_str()).collect()
+    }
+
+    /// Returns a cycle of links, starting and ending with the same account,
+    /// or `None` if the accounts form a hierarchy.
+    pub fn find_cycle(&self) -> Option<Vec<String>> {
+        let mut done = BTreeSet::new();
+        self.accounts
+            .keys()
+            .find_map(|id| self.cycle_from(id, &|id| self.parents(id), &mut Vec::new(), &mut done))
+    }
+
+    /// Returns every account above an account, nearest first.
+    pub fn ancestors(&self, id: &str) -> Result<Vec<String>, AccountHierarchyError> {
+        self.walk(id, |id| self.parents(id))
+    }
+
+    /// Returns every account below an account, nearest first.
+    pub fn descendants(&self, id: &str) -> Result<Vec<String>, AccountHierarchyError> {
+        self.walk(id, |id| self.children(id))
+    }
+
+    /// Totals the balances of an account and of every account below it
+    /// through links with the given relationship code; accounts linked in
+    /// other ways are left out. Links are followed from the related account
+    /// to the account naming it: with `parent`, an account's children are
+    /// the accounts naming it as their parent, and with `guarantor`, the
+    /// accounts naming it as their guarantor account. Fails on a cycle,
+    /// which would count balances twice.
+    pub fn rollup(&self, id: &str, relationship: &str) -> Result<Rollup, AccountHierarchyError> {
+        if !self.accounts.contains_key(id) {
+            return Err(AccountHierarchyError::NotFound(id.to_string()));
+        }
+        let children = |parent: &str| -> Vec<&str> {
+            self.links
+                .iter()
+                .filter(|link| link.parent == parent && link.relationship.as_deref() == Some(relationship))
+                .map(|link| link.child.as_str())
+                .collect()
+        };
+        if let Some(cycle) = self.cycle_from(id, &children, &mut Vec::new(), &mut BTreeSet::new()) {
+            return Err(AccountHierarchyError::Cycle(cycle));
+        }
+        let mut accounts = vec![id.to_string()];
+        accounts.extend(self.walk(id, children)?);
+        let balances: Vec<_> = accounts
+            .iter()
+            .filter_map(|id| self.accounts.get(id))
+            .flat_map(|account| account.balance().iter().cloned())
+            .collect();
+        Ok(Rollup {
+            summary: BalanceSummary::from_balances(&balances)?,
+            accounts,
+        })
+    }
+
+    /// Breadth-first walk from an account, excluding it, visiting each
+    /// account once.
+    fn walk<'a>(&'a self, id: &str, next: impl Fn(&str) -> Vec<&'a str>) -> Result<Vec<String>, AccountHierarchyError> {
+        if !self.accounts.contains_key(id) {
+            return Err(AccountHierarchyError::NotFound(id.to_string()));
+        }
+        let mut seen = BTreeSet::from([id.to_string()]);
+        let mut found = Vec::new();
+        let mut index = 0;
+        let mut frontier = next(id);
+        while index < frontier.len() {
+            let current = frontier[index];
+            index += 1;
+            if seen.insert(current.to_string()) {
+                found.push(current.to_string());
+                frontier.extend(next(current));
+            }
+        }
+        Ok(found)
+    }
+
+    /// Depth-first search for a cycle through the accounts on the path.
+    fn cycle_from<'a>(
+        &'a self,
+        id: &'a str,
+        next: &dyn Fn(&str) -> Vec<&'a str>,
+        path: &mut Vec<&'a str>,
+        done: &mut BTreeSet<&'a str>,
+    ) -> Option<Vec<String>> {
+        if let Some(start) = path.iter().position(|visited| *visited == id) {
+            let mut cycle: Vec<String> = path[start..].iter().map(|id| id.to_string()).collect();
+            cycle.push(id.to_string());
+            return Some(cycle);
+        }
+        if done.contains(id) {
+            return None;
+        }
+        path.push(id);
+        let cycle = next(id).into_iter().find_map(|next_id| self.cycle_from(next_id, next, path, done));
+        path.pop();
+        done.insert(id);
+        cycle
+    }
+}
+
+/// The balances of an account and its descendants, totalled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rollup {
+    accounts: Vec<String>,
+    summary: BalanceSummary,
+}
+
+impl Rollup {
+    /// Returns the ids of the accounts included, the top account first.
+    pub fn accounts(&self) -> &[String] {
+        &self.accounts
+    }
+
+    /// Returns the totals of their balances.
+    pub fn summary(&self) -> &BalanceSummary {
+        &self.summary
+    }
+}
@@ -0,0 +1,297 @@
+//! The lifecycle of an Account's `status` and `billingStatus`.
+//!
+//! [`AccountStatus`] and [`BillingStatus`] list the states and the moves
+//! allowed between them. An [`AccountLifecycle`] applies changes to an
+//! account, checking guards that depend on the account as a whole, such as
+//! a zero balance before closing, and records who made each change and when.
+
+use std::fmt;
+
+use crate::account::Account;
+use crate::balance_summary::{self, BalanceAggregate, BalanceError, BalanceSummary};
+use crate::data_types::codeable_concept::{CodeableConcept, Coding};
+use crate::data_types::date_time::Instant;
+use crate::data_types::reference::Reference;
+
+/// The code system of account billing statuses.
+pub const BILLING_STATUS_SYSTEM: &str = "http://hl7.org/fhir/account-billing-status";
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LifecycleError {
+    #[error("Unknown account status: {0}")]
+    UnknownStatus(String),
+    #[error("Unknown billing status: {0}")]
+    UnknownBillingStatus(String),
+    #[error("Account status cannot change from {0} to {1}")]
+    InvalidStatus(String, String),
+    #[error("Billing status cannot change from {0} to {1}")]
+    InvalidBillingStatus(String, String),
+    #[error("Account has an outstanding balance of {0}")]
+    OutstandingBalance(String),
+    #[error("Billing status cannot change while the account is {0}")]
+    Locked(AccountStatus),
+    #[error(transparent)]
+    Balance(#[from] BalanceError),
+}
+
+/// The state of an account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccountStatus {
+    Active,
+    Inactive,
+    OnHold,
+    EnteredInError,
+    Unknown,
+}
+
+impl AccountStatus {
+    pub fn parse(code: &str) -> Result<Self, LifecycleError> {
+        match code {
+            "active" => Ok(AccountStatus::Active),
+            "inactive" => Ok(AccountStatus::Inactive),
+            "on-hold" => Ok(AccountStatus::OnHold),
+            "entered-in-error" => Ok(AccountStatus::EnteredInError),
+            "unknown" => Ok(AccountStatus::Unknown),
+            other => Err(LifecycleError::UnknownStatus(other.to_string())),
+        }
+    }
+
+    /// Reads the status of an account; `None` if it has none.
+    pub fn of(account: &Account) -> Result<Option<Self>, LifecycleError> {
+        account.status().map(Self::parse).transpose()
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AccountStatus::Active => "active",
+            AccountStatus::Inactive => "inactive",
+            AccountStatus::OnHold => "on-hold",
+            AccountStatus::EnteredInError => "entered-in-error",
+            AccountStatus::Unknown => "unknown",
+        }
+    }
+
+    /// Returns true if an account may move from this status to another.
+    /// `entered-in-error` is final, an inactive account must be reopened
+    /// before it is put on hold, and no account moves to `unknown`.
+    pub fn can_become(&self, to: AccountStatus) -> bool {
+        match (self, to) {
+            (from, to) if *from == to => false,
+            (AccountStatus::EnteredInError, _) | (_, AccountStatus::Unknown) => false,
+            (AccountStatus::Inactive, AccountStatus::OnHold) => false,
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for AccountStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Where an account is in the billing workflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BillingStatus {
+    Open,
+    CareCompleteNotBilled,
+    Billing,
+    ClosedBadDebt,
+    ClosedVoided,
+    ClosedCompleted,
+    ClosedCombined,
+}
+
+impl BillingStatus {
+    pub fn parse(code: &str) -> Result<Self, LifecycleError> {
+        match code {
+            "open" => Ok(BillingStatus::Open),
+            "carecomplete-notbilled" => Ok(BillingStatus::CareCompleteNotBilled),
+            "billing" => Ok(BillingStatus::Billing),
+            "closed-baddebt" => Ok(BillingStatus::ClosedBadDebt),
+            "closed-voided" => Ok(BillingStatus::ClosedVoided),
+            "closed-completed" => Ok(BillingStatus::ClosedCompleted),
+            "closed-combined" => Ok(BillingStatus::ClosedCombined),
+            other => Err(LifecycleError::UnknownBillingStatus(other.to_string())),
+        }
+    }
+
+    /// Reads the billing status of an account from its first coded value
+    /// or text; `None` if it has none.
+    pub fn of(account: &Account) -> Result<Option<Self>, LifecycleError> {
+        balance_summary::code(account.billing_status()).map(Self::parse).transpose()
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            BillingStatus::Open => "open",
+            BillingStatus::CareCompleteNotBilled => "carecomplete-notbilled",
+            BillingStatus::Billing => "billing",
+            BillingStatus::ClosedBadDebt => "closed-baddebt",
+            BillingStatus::ClosedVoided => "closed-voided",
+            BillingStatus::ClosedCompleted => "closed-completed",
+            BillingStatus::ClosedCombined => "closed-combined",
+        }
+    }
+
+    /// Returns true for the closed statuses.
+    pub fn is_closed(&self) -> bool {
+        matches!(
+            self,
+            BillingStatus::ClosedBadDebt
+                | BillingStatus::ClosedVoided
+                | BillingStatus::ClosedCompleted
+                | BillingStatus::ClosedCombined
+        )
+    }
+
+    /// Returns true if the balance was written off rather than paid.
+    pub fn is_written_off(&self) -> bool {
+        matches!(self, BillingStatus::ClosedBadDebt | BillingStatus::ClosedVoided)
+    }
+
+    /// Returns true if an account may move from this billing status to
+    /// another. Closed statuses are final, and an account being billed
+    /// cannot go back to waiting for billing.
+    pub fn can_become(&self, to: BillingStatus) -> bool {
+        match (self, to) {
+            (from, to) if *from == to || from.is_closed() => false,
+            (BillingStatus::Billing, BillingStatus::CareCompleteNotBilled) => false,
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for BillingStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// A change of status; `from` is `None` if the account had none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusChange {
+    Status { from: Option<AccountStatus>, to: AccountStatus },
+    BillingStatus { from: Option<BillingStatus>, to: BillingStatus },
+}
+
+/// A change applied to an account, with who made it and when.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transition {
+    change: StatusChange,
+    by: Reference,
+    at: Instant,
+}
+
+impl Transition {
+    pub fn change(&self) -> StatusChange {
+        self.change
+    }
+
+    /// Returns who made the change, e.g. a Practitioner.
+    pub fn by(&self) -> &Reference {
+        &self.by
+    }
+
+    /// Returns when the change was made.
+    pub fn at(&self) -> &Instant {
+        &self.at
+    }
+}
+
+/// An account moving through its lifecycle, with the transitions applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountLifecycle {
+    account: Account,
+    history: Vec<Transition>,
+}
+
+impl AccountLifecycle {
+    pub fn new(account: Account) -> Self {
+        Self { account, history: Vec::new() }
+    }
+
+    pub fn account(&self) -> &Account {
+        &self.account
+    }
+
+    /// Returns the transitions applied, oldest first.
+    pub fn history(&self) -> &[Transition] {
+        &self.history
+    }
+
+    pub fn into_account(self) -> Account {
+        self.account
+    }
+
+    /// Moves the account to a new status. An account without a status may
+    /// take any. Closing an account (`inactive`) requires a zero balance,
+    /// unless its billing status shows the balance was written off.
+    pub fn change_status(&mut self, to: AccountStatus, by: Reference, at: Instant) -> Result<&Transition, LifecycleError> {
+        let from = AccountStatus::of(&self.account)?;
+        if let Some(from) = from
+            && !from.can_become(to)
+        {
+            return Err(LifecycleError::InvalidStatus(from.to_string(), to.to_string()));
+        }
+        if to == AccountStatus::Inactive
+            && !BillingStatus::of(&self.account)?.is_some_and(|billing| billing.is_written_off())
+        {
+            check_settled(&self.account)?;
+        }
+        self.account.set_status(Some(to.code().to_string()));
+        Ok(self.record(StatusChange::Status { from, to }, by, at))
+    }
+
+    /// Moves the account to a new billing status. Billing cannot change on
+    /// an account entered in error, and `closed-completed` requires a zero
+    /// balance.
+    pub fn change_billing_status(
+        &mut self,
+        to: BillingStatus,
+        by: Reference,
+        at: Instant,
+    ) -> Result<&Transition, LifecycleError> {
+        if let Some(status @ AccountStatus::EnteredInError) = AccountStatus::of(&self.account)? {
+            return Err(LifecycleError::Locked(status));
+        }
+        let from = BillingStatus::of(&self.account)?;
+        if let Some(from) = from
+            && !from.can_become(to)
+        {
+            return Err(LifecycleError::InvalidBillingStatus(from.to_string(), to.to_string()));
+        }
+        if to == BillingStatus::ClosedCompleted {
+            check_settled(&self.account)?;
+        }
+        let coding = Coding::with_system_and_code(BILLING_STATUS_SYSTEM.to_string(), to.code().to_string());
+        self.account.set_billing_status(Some(CodeableConcept::with_coding(coding)));
+        Ok(self.record(StatusChange::BillingStatus { from, to }, by, at))
+    }
+
+    fn record(&mut self, change: StatusChange, by: Reference, at: Instant) -> &Transition {
+        self.history.push(Transition { change, by, at });
+        self.history.last().unwrap()
+    }
+}
+
+/// Checks that the account's actual balance is zero: the total aggregate
+/// if there is one, and otherwise patient plus insurance. Estimates are
+/// ignored.
+fn check_settled(account: &Account) -> Result<(), LifecycleError> {
+    let summary = BalanceSummary::of(account)?;
+    let outstanding = match summary.by_aggregate(BalanceAggregate::Total)? {
+        Some(total) => Some(total),
+        None => {
+            let patient = summary.by_aggregate(BalanceAggregate::Patient)?;
+            let insurance = summary.by_aggregate(BalanceAggregate::Insurance)?;
+            balance_summary::sum(patient.iter().chain(insurance.iter()))?
+        }
+    };
+    match outstanding {
+        Some(amount) if !amount.value().is_none_or(|value| value.is_zero()) => {
+            Err(LifecycleError::OutstandingBalance(amount.format().map_err(BalanceError::from)?))
+        }
+        _ => Ok(()),
+    }
+}
@@ -4,17 +4,17 @@ use crate::data_types::reference::Reference;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AccountProcedure {
-    #[serde(rename = "sequence")]
+    #[serde(rename = "sequence", skip_serializing_if = "Option::is_none")]
     sequence: Option<String>, // positiveInt
     #[serde(rename = "code")]
     code: Reference, // CodeableReference(Procedure) - simplified as Reference for now
-    #[serde(rename = "dateOfService")]
+    #[serde(rename = "dateOfService", skip_serializing_if = "Option::is_none")]
     date_of_service: Option<String>, // dateTime
-    #[serde(rename = "type")]
+    #[serde(rename = "type", default, skip_serializing_if = "Vec::is_empty")]
     procedure_type: Vec<CodeableConcept>,
-    #[serde(rename = "packageCode")]
+    #[serde(rename = "packageCode", default, skip_serializing_if = "Vec::is_empty")]
     package_code: Vec<CodeableConcept>,
-    #[serde(rename = "device")]
+    #[serde(rename = "device", default, skip_serializing_if = "Vec::is_empty")]
     device: Vec<Reference>,
 }
 
@@ -5,7 +5,7 @@ use crate::data_types::reference::Reference;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AccountRelatedAccount {
-    #[serde(rename = "relationship")]
+    #[serde(rename = "relationship", skip_serializing_if = "Option::is_none")]
     relationship: Option<CodeableConcept>,
     #[serde(rename = "account")]
     account: Reference,
@@ -0,0 +1,308 @@
+//! Summaries of `Account.balance`.
+//!
+//! A [`BalanceSummary`] totals an account's balances by aggregate (who pays:
+//! patient, insurance or total) and term (age of the debt: current, 30, 60,
+//! 90 or 120 days), keeping estimates apart from actual amounts, and checks
+//! that the patient and insurance parts add up to the total. An
+//! [`AgingReport`] lines up the actual totals of many accounts by term.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::account::Account;
+use crate::account_balance::AccountBalance;
+use crate::data_types::codeable_concept::CodeableConcept;
+use crate::money::{Money, MoneyError};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BalanceError {
+    #[error("Unknown balance aggregate: {0}")]
+    UnknownAggregate(String),
+    #[error("Unknown balance term: {0}")]
+    UnknownTerm(String),
+    #[error("Patient {1} and insurance {2} do not add up to the total {3} for the {0} term")]
+    Unbalanced(String, String, String, String),
+    #[error(transparent)]
+    Money(#[from] MoneyError),
+}
+
+/// Who is expected to pay a balance. A balance without an aggregate is
+/// read as the total.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BalanceAggregate {
+    Patient,
+    Insurance,
+    Total,
+}
+
+impl BalanceAggregate {
+    /// Reads the aggregate of a balance.
+    pub fn of(balance: &AccountBalance) -> Result<Self, BalanceError> {
+        match code(balance.aggregate()) {
+            None | Some("total") => Ok(BalanceAggregate::Total),
+            Some("patient") => Ok(BalanceAggregate::Patient),
+            Some("insurance") => Ok(BalanceAggregate::Insurance),
+            Some(other) => Err(BalanceError::UnknownAggregate(other.to_string())),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            BalanceAggregate::Patient => "patient",
+            BalanceAggregate::Insurance => "insurance",
+            BalanceAggregate::Total => "total",
+        }
+    }
+}
+
+/// How long a balance has been outstanding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BalanceTerm {
+    Current,
+    Days30,
+    Days60,
+    Days90,
+    Days120,
+}
+
+impl BalanceTerm {
+    /// All terms, from the most recent.
+    pub const ALL: [BalanceTerm; 5] =
+        [BalanceTerm::Current, BalanceTerm::Days30, BalanceTerm::Days60, BalanceTerm::Days90, BalanceTerm::Days120];
+
+    /// Reads the term of a balance; `None` if the balance is not aged.
+    pub fn of(balance: &AccountBalance) -> Result<Option<Self>, BalanceError> {
+        match code(balance.term()) {
+            None => Ok(None),
+            Some("current") => Ok(Some(BalanceTerm::Current)),
+            Some("30") => Ok(Some(BalanceTerm::Days30)),
+            Some("60") => Ok(Some(BalanceTerm::Days60)),
+            Some("90") => Ok(Some(BalanceTerm::Days90)),
+            Some("120") => Ok(Some(BalanceTerm::Days120)),
+            Some(other) => Err(BalanceError::UnknownTerm(other.to_string())),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            BalanceTerm::Current => "current",
+            BalanceTerm::Days30 => "30",
+            BalanceTerm::Days60 => "60",
+            BalanceTerm::Days90 => "90",
+            BalanceTerm::Days120 => "120",
+        }
+    }
+}
+
+impl fmt::Display for BalanceTerm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// The first coded value of a concept, or its text.
+pub(crate) fn code(concept: Option<&CodeableConcept>) -> Option<&str> {
+    let concept = concept?;
+    concept.coding().iter().find_map(|coding| coding.code()).or(concept.text())
+}
+
+type Key = (BalanceAggregate, Option<BalanceTerm>);
+
+/// An account's balances totalled by aggregate and term, with estimates
+/// kept apart. All balances must be in the same currency.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BalanceSummary {
+    actual: BTreeMap<Key, Money>,
+    estimates: BTreeMap<Key, Money>,
+}
+
+impl BalanceSummary {
+    /// Totals the balances of an account.
+    pub fn of(account: &Account) -> Result<Self, BalanceError> {
+        Self::from_balances(account.balance())
+    }
+
+    /// Totals a list of balances.
+    pub fn from_balances(balances: &[AccountBalance]) -> Result<Self, BalanceError> {
+        let mut summary = Self::default();
+        for balance in balances {
+            let key = (BalanceAggregate::of(balance)?, BalanceTerm::of(balance)?);
+            let totals = if balance.estimate() == Some(true) { &mut summary.estimates } else { &mut summary.actual };
+            let sum = match totals.get(&key) {
+                Some(sum) => sum.checked_add(balance.amount())?,
+                None => balance.amount().clone(),
+            };
+            totals.insert(key, sum);
+        }
+        Ok(summary)
+    }
+
+    /// Returns the actual amount for an aggregate and term; a `None` term
+    /// is the balance that is not aged.
+    pub fn amount(&self, aggregate: BalanceAggregate, term: Option<BalanceTerm>) -> Option<&Money> {
+        self.actual.get(&(aggregate, term))
+    }
+
+    /// Returns the estimated amount for an aggregate and term.
+    pub fn estimate(&self, aggregate: BalanceAggregate, term: Option<BalanceTerm>) -> Option<&Money> {
+        self.estimates.get(&(aggregate, term))
+    }
+
+    /// Returns true if any balance is an estimate.
+    pub fn has_estimates(&self) -> bool {
+        !self.estimates.is_empty()
+    }
+
+    /// Returns the actual amount of an aggregate: the balance that is not
+    /// aged if there is one, and otherwise the sum over the terms.
+    pub fn by_aggregate(&self, aggregate: BalanceAggregate) -> Result<Option<Money>, BalanceError> {
+        if let Some(unaged) = self.amount(aggregate, None) {
+            return Ok(Some(unaged.clone()));
+        }
+        sum(BalanceTerm::ALL.iter().filter_map(|term| self.amount(aggregate, Some(*term))))
+    }
+
+    /// Totals the actual amounts of a term across all aggregates. The total
+    /// aggregate is used when present, and otherwise patient plus insurance.
+    pub fn by_term(&self, term: Option<BalanceTerm>) -> Result<Option<Money>, BalanceError> {
+        term_total(&self.actual, term)
+    }
+
+    /// Totals the estimated amounts of a term, like [`BalanceSummary::by_term`].
+    pub fn estimate_by_term(&self, term: Option<BalanceTerm>) -> Result<Option<Money>, BalanceError> {
+        term_total(&self.estimates, term)
+    }
+
+    /// Checks, for each term with a total and at least one part, that the
+    /// patient and insurance amounts add up to the total.
+    pub fn check_totals(&self) -> Result<(), BalanceError> {
+        let terms: BTreeSet<Option<BalanceTerm>> = self.actual.keys().map(|(_, term)| *term).collect();
+        for term in terms {
+            let Some(total) = self.amount(BalanceAggregate::Total, term) else { continue };
+            let patient = self.amount(BalanceAggregate::Patient, term);
+            let insurance = self.amount(BalanceAggregate::Insurance, term);
+            let Some(parts) = sum(patient.into_iter().chain(insurance))? else { continue };
+            let difference = parts.checked_sub(total)?;
+            if !difference.value().is_some_and(|value| value.is_zero()) {
+                let text = |money: Option<&Money>| money.and_then(Money::value).map_or("0".to_string(), ToString::to_string);
+                return Err(BalanceError::Unbalanced(
+                    term.map_or("unaged", |term| term.code()).to_string(),
+                    text(patient),
+                    text(insurance),
+                    text(Some(total)),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The total aggregate of a term, or else patient plus insurance.
+fn term_total(totals: &BTreeMap<Key, Money>, term: Option<BalanceTerm>) -> Result<Option<Money>, BalanceError> {
+    if let Some(total) = totals.get(&(BalanceAggregate::Total, term)) {
+        return Ok(Some(total.clone()));
+    }
+    sum([BalanceAggregate::Patient, BalanceAggregate::Insurance]
+        .iter()
+        .filter_map(|aggregate| totals.get(&(*aggregate, term))))
+}
+
+/// Adds amounts, or `None` if there are none.
+pub(crate) fn sum<'a>(amounts: impl IntoIterator<Item = &'a Money>) -> Result<Option<Money>, BalanceError> {
+    let mut total: Option<Money> = None;
+    for amount in amounts {
+        total = Some(match total {
+            Some(total) => total.checked_add(amount)?,
+            None => amount.clone(),
+        });
+    }
+    Ok(total)
+}
+
+/// One line of an aging report: an account's total by term, with the
+/// estimates apart.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AgingRow {
+    account: Option<String>,
+    amounts: BTreeMap<BalanceTerm, Money>,
+    estimates: BTreeMap<BalanceTerm, Money>,
+}
+
+impl AgingRow {
+    /// Returns the account id, or `None` for the totals row.
+    pub fn account(&self) -> Option<&str> {
+        self.account.as_deref()
+    }
+
+    /// Returns the actual amount outstanding for a term.
+    pub fn amount(&self, term: BalanceTerm) -> Option<&Money> {
+        self.amounts.get(&term)
+    }
+
+    /// Returns the estimated amount for a term.
+    pub fn estimate(&self, term: BalanceTerm) -> Option<&Money> {
+        self.estimates.get(&term)
+    }
+
+    /// Returns the actual amount outstanding across all terms.
+    pub fn total(&self) -> Result<Option<Money>, BalanceError> {
+        sum(self.amounts.values())
+    }
+
+    fn add(&mut self, other: &AgingRow) -> Result<(), BalanceError> {
+        for (totals, amounts) in [(&mut self.amounts, &other.amounts), (&mut self.estimates, &other.estimates)] {
+            for (term, amount) in amounts {
+                let sum = match totals.get(term) {
+                    Some(sum) => sum.checked_add(amount)?,
+                    None => amount.clone(),
+                };
+                totals.insert(*term, sum);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Outstanding balances of many accounts by term, with a totals row. Only
+/// aged balances are included, and all must be in one currency; restate
+/// accounts in other currencies first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AgingReport {
+    rows: Vec<AgingRow>,
+    totals: AgingRow,
+}
+
+impl AgingReport {
+    pub fn new(accounts: &[Account]) -> Result<Self, BalanceError> {
+        let mut report = Self::default();
+        for account in accounts {
+            let summary = BalanceSummary::of(account)?;
+            summary.check_totals()?;
+            let mut row = AgingRow {
+                account: account.id().map(str::to_string),
+                ..AgingRow::default()
+            };
+            for term in BalanceTerm::ALL {
+                if let Some(amount) = summary.by_term(Some(term))? {
+                    row.amounts.insert(term, amount);
+                }
+                if let Some(estimate) = summary.estimate_by_term(Some(term))? {
+                    row.estimates.insert(term, estimate);
+                }
+            }
+            report.totals.add(&row)?;
+            report.rows.push(row);
+        }
+        Ok(report)
+    }
+
+    /// Returns a row per account, in the order given.
+    pub fn rows(&self) -> &[AgingRow] {
+        &self.rows
+    }
+
+    /// Returns the sums over all accounts.
+    pub fn totals(&self) -> &AgingRow {
+        &self.totals
+    }
+}
@@ -0,0 +1,566 @@
+//! Bulk Data `$export` at system, patient and group level.
+//!
+//! An export runs in two steps. [`BulkExporter::collect`] selects the
+//! resources from a repository, which fixes the transaction time, and
+//! [`ExportData::write`] writes one NDJSON file per resource type and returns
+//! the manifest. [`ExportJob`] runs the write on a background thread so a
+//! server can answer the kickoff request at once and be polled for status.
+//!
+//! The crate has no Group resource, so groups are registered with the
+//! exporter as lists of patient ids.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use serde::{Deserialize, Serialize};
+
+use crate::compartment::{CompartmentDefinition, CompartmentError};
+use crate::data_types::date_time::Instant;
+use crate::meta::{now, Meta};
+use crate::repository::{Repository, RepositoryError};
+use crate::resource::{AnyResource, RESOURCE_TYPES};
+use crate::search::{SearchEngine, SearchError, SearchQuery};
+
+/// The NDJSON media type of export files.
+pub const FHIR_NDJSON: &str = "application/fhir+ndjson";
+
+#[derive(Debug, thiserror::Error)]
+pub enum BulkExportError {
+    #[error("Invalid export parameter: {0}")]
+    InvalidParameter(String),
+    #[error("Resource type {0} cannot be exported at this level")]
+    UnsupportedType(String),
+    #[error("Unsupported output format: {0}")]
+    UnsupportedFormat(String),
+    #[error("Group not found: {0}")]
+    GroupNotFound(String),
+    #[error("Cannot write export files: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Search(#[from] SearchError),
+    #[error(transparent)]
+    Compartment(#[from] CompartmentError),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+impl BulkExportError {
+    /// Returns the HTTP status code for the error.
+    pub fn status(&self) -> u16 {
+        match self {
+            BulkExportError::GroupNotFound(_) => 404,
+            BulkExportError::Io(_) => 500,
+            BulkExportError::Compartment(error) => error.status(),
+            _ => 400,
+        }
+    }
+
+    /// Returns the OperationOutcome issue type code for the error.
+    pub fn issue_code(&self) -> &'static str {
+        match self {
+            BulkExportError::UnsupportedType(_) | BulkExportError::UnsupportedFormat(_) => "not-supported",
+            BulkExportError::GroupNotFound(_) => "not-found",
+            BulkExportError::Io(_) => "exception",
+            BulkExportError::Compartment(error) => error.issue_code(),
+            _ => "invalid",
+        }
+    }
+}
+
+/// Which resources an export covers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportLevel {
+    /// Every resource on the server: `[base]/$export`.
+    System,
+    /// The compartments of every patient: `[base]/Patient/$export`.
+    Patient,
+    /// The compartments of a group's patients: `[base]/Group/[id]/$export`.
+    Group(String),
+}
+
+impl ExportLevel {
+    /// Returns the path of the kickoff request, relative to the base URL.
+    pub fn path(&self) -> String {
+        match self {
+            ExportLevel::System => "$export".to_string(),
+            ExportLevel::Patient => "Patient/$export".to_string(),
+            ExportLevel::Group(id) => format!("Group/{}/$export", id),
+        }
+    }
+}
+
+/// The parameters of an export kickoff request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportRequest {
+    level: ExportLevel,
+    query: String,
+    types: Vec<String>,
+    since: Option<Instant>,
+    type_filters: Vec<(String, SearchQuery)>,
+}
+
+impl ExportRequest {
+    /// Creates a request exporting every resource type at a level.
+    pub fn new(level: ExportLevel) -> Self {
+        Self {
+            level,
+            query: String::new(),
+            types: Vec::new(),
+            since: None,
+            type_filters: Vec::new(),
+        }
+    }
+
+    /// Parses the query of a kickoff request: `_type` and `_typeFilter` as
+    /// comma-separated lists, `_since`, which must be an instant, and
+    /// `_outputFormat`, which must be NDJSON.
+    pub fn parse(level: ExportLevel, query: &str) -> Result<Self, BulkExportError> {
+        let mut request = Self::new(level);
+        request.query = query.trim_start_matches('?').to_string();
+        for param in SearchQuery::parse(query).params() {
+            let values = param.value().split(',').map(str::trim).filter(|value| !value.is_empty());
+            match param.name() {
+                "_type" => request.types.extend(values.map(str::to_string)),
+                "_since" => {
+                    let since = Instant::parse(param.value()).map_err(|_| {
+                        BulkExportError::InvalidParameter(format!("_since {} is not an instant", param.value()))
+                    })?;
+                    request.since = Some(since);
+                }
+                "_typeFilter" => {
+                    for filter in split_type_filters(param.value()) {
+                        request.add_type_filter(&filter)?;
+                    }
+                }
+                "_outputFormat" => match param.value() {
+                    "application/fhir+ndjson" | "application/ndjson" | "ndjson" => {}
+                    format => return Err(BulkExportError::UnsupportedFormat(format.to_string())),
+                },
+                _ => {}
+            }
+        }
+        Ok(request)
+    }
+
+    /// Returns the export level.
+    pub fn level(&self) -> &ExportLevel {
+        &self.level
+    }
+
+    /// Returns the resource types to export; empty for every type.
+    pub fn types(&self) -> &[String] {
+        &self.types
+    }
+
+    /// Returns the instant before which changed resources are left out.
+    pub fn since(&self) -> Option<&Instant> {
+        self.since.as_ref()
+    }
+
+    /// Returns the search queries restricting each resource type.
+    pub fn type_filters(&self) -> &[(String, SearchQuery)] {
+        &self.type_filters
+    }
+
+    /// Returns the kickoff URL relative to the base URL.
+    pub fn url(&self) -> String {
+        match self.query.is_empty() {
+            true => self.level.path(),
+            false => format!("{}?{}", self.level.path(), self.query),
+        }
+    }
+
+    /// Only exports the given resource types.
+    pub fn set_types(&mut self, types: Vec<String>) {
+        self.types = types;
+    }
+
+    /// Only exports resources changed at or after an instant.
+    pub fn set_since(&mut self, since: Option<Instant>) {
+        self.since = since;
+    }
+
+    /// Adds a `_typeFilter` such as `Patient?active=true`. A resource type with
+    /// filters is only exported where it matches at least one of them.
+    pub fn add_type_filter(&mut self, filter: &str) -> Result<(), BulkExportError> {
+        let (resource_type, query) = filter
+            .split_once('?')
+            .filter(|(resource_type, query)| !resource_type.is_empty() && !query.is_empty())
+            .ok_or_else(|| BulkExportError::InvalidParameter(format!("_typeFilter {} is not [type]?[query]", filter)))?;
+        self.type_filters.push((resource_type.to_string(), SearchQuery::parse(query)));
+        Ok(())
+    }
+}
+
+/// Splits a comma-separated `_typeFilter` value. A comma only starts a new
+/// filter when followed by `[type]?`, so filters may use OR lists such as
+/// `Patient?gender=male,female`.
+fn split_type_filters(value: &str) -> Vec<String> {
+    let mut filters: Vec<String> = Vec::new();
+    for segment in value.split(',') {
+        let starts_filter = segment
+            .split_once('?')
+            .is_some_and(|(resource_type, _)| !resource_type.is_empty() && resource_type.chars().all(char::is_alphanumeric));
+        match filters.last_mut() {
+            Some(filter) if !starts_filter => {
+                filter.push(',');
+                filter.push_str(segment);
+            }
+            _ => filters.push(segment.trim().to_string()),
+        }
+    }
+    filters.retain(|filter| !filter.is_empty());
+    filters
+}
+
+/// Selects resources for export.
+#[derive(Debug, Clone, Default)]
+pub struct BulkExporter {
+    groups: HashMap<String, Vec<String>>,
+}
+
+impl BulkExporter {
+    /// Creates a new BulkExporter without groups.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a group by the ids of its member patients.
+    pub fn add_group(&mut self, id: &str, members: Vec<String>) {
+        self.groups.insert(id.to_string(), members);
+    }
+
+    /// Returns the member patient ids of a group.
+    pub fn group(&self, id: &str) -> Option<&[String]> {
+        self.groups.get(id).map(Vec::as_slice)
+    }
+
+    /// Selects the resources an export request covers, grouped by type.
+    /// Patient and group exports cover the patients' compartments; members
+    /// missing from the repository are skipped.
+    pub fn collect(
+        &self,
+        engine: &SearchEngine,
+        repository: &dyn Repository,
+        request: &ExportRequest,
+    ) -> Result<ExportData, BulkExportError> {
+        let transaction_time = now();
+        let compartment = CompartmentDefinition::patient();
+        let patients: Option<Vec<String>> = match &request.level {
+            ExportLevel::System => None,
+            ExportLevel::Patient => Some(
+                repository
+                    .list("Patient")?
+                    .iter()
+                    .filter_map(|patient| patient.id().map(str::to_string))
+                    .collect(),
+            ),
+            ExportLevel::Group(id) => Some(self.group(id).ok_or_else(|| BulkExportError::GroupNotFound(id.clone()))?.to_vec()),
+        };
+        let exportable = |resource_type: &str| {
+            RESOURCE_TYPES.contains(&resource_type) && (patients.is_none() || compartment.contains(resource_type))
+        };
+        let types: Vec<String> = match request.types.is_empty() {
+            true => RESOURCE_TYPES.iter().filter(|code| exportable(code)).map(|code| code.to_string()).collect(),
+            false => request.types.clone(),
+        };
+        if let Some(code) = types.iter().find(|code| !exportable(code)) {
+            return Err(BulkExportError::UnsupportedType(code.clone()));
+        }
+        if let Some((code, _)) = request.type_filters.iter().find(|(code, _)| !types.contains(code)) {
+            return Err(BulkExportError::InvalidParameter(format!("_typeFilter for {} which is not exported", code)));
+        }
+
+        let mut resources = Vec::new();
+        for resource_type in types {
+            let mut selected: Vec<AnyResource> = match &patients {
+                None => repository.list(&resource_type)?,
+                Some(ids) => {
+                    let mut members: Vec<AnyResource> = Vec::new();
+                    for id in ids {
+                        if resource_type == compartment.code() {
+                            match repository.read(&resource_type, id) {
+                                Ok(patient) => members.push(patient),
+                                Err(RepositoryError::NotFound(_, _)) => continue,
+                                Err(error) => return Err(error.into()),
+                            }
+                        }
+                        for member in compartment.members(engine, repository, &resource_type, id)? {
+                            if !members.iter().any(|found| found.id() == member.id()) {
+                                members.push(member);
+                            }
+                        }
+                    }
+                    members
+                }
+            };
+            let filters: Vec<&SearchQuery> = request
+                .type_filters
+                .iter()
+                .filter(|(code, _)| *code == resource_type)
+                .map(|(_, query)| query)
+                .collect();
+            if !filters.is_empty() {
+                let mut matching = Vec::new();
+                for query in filters {
+                    matching.extend(engine.search(repository, &resource_type, query)?.iter().map(AnyResource::reference));
+                }
+                selected.retain(|resource| matching.contains(&resource.reference()));
+            }
+            if let Some(since) = request.since() {
+                selected.retain(|resource| {
+                    resource
+                        .meta()
+                        .and_then(Meta::last_updated)
+                        .and_then(|last_updated| Instant::parse(last_updated).ok())
+                        .is_some_and(|last_updated| last_updated.compare(since).is_ge())
+                });
+            }
+            if !selected.is_empty() {
+                resources.push((resource_type, selected));
+            }
+        }
+
+        let request_url = match engine.base_url() {
+            Some(base_url) => format!("{}/{}", base_url, request.url()),
+            None => request.url(),
+        };
+        Ok(ExportData {
+            transaction_time,
+            request: request_url,
+            resources,
+        })
+    }
+
+    /// Selects and writes an export in one call, returning the manifest.
+    pub fn export(
+        &self,
+        engine: &SearchEngine,
+        repository: &dyn Repository,
+        request: &ExportRequest,
+        directory: &Path,
+        output_url: Option<&str>,
+    ) -> Result<ExportManifest, BulkExportError> {
+        self.collect(engine, repository, request)?.write(directory, output_url)
+    }
+}
+
+/// The resources selected for an export, by type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportData {
+    transaction_time: String,
+    request: String,
+    resources: Vec<(String, Vec<AnyResource>)>,
+}
+
+impl ExportData {
+    /// Returns when the resources were selected.
+    pub fn transaction_time(&self) -> &str {
+        &self.transaction_time
+    }
+
+    /// Returns the kickoff request URL.
+    pub fn request(&self) -> &str {
+        &self.request
+    }
+
+    /// Returns the selected resources of a type.
+    pub fn resources(&self, resource_type: &str) -> &[AnyResource] {
+        self.resources
+            .iter()
+            .find(|(code, _)| code == resource_type)
+            .map_or(&[], |(_, resources)| resources)
+    }
+
+    /// Writes `[type].ndjson` for each type with resources into a directory,
+    /// creating it. File URLs in the manifest are under `output_url` if given,
+    /// otherwise the file paths.
+    pub fn write(&self, directory: &Path, output_url: Option<&str>) -> Result<ExportManifest, BulkExportError> {
+        fs::create_dir_all(directory)?;
+        let mut manifest = ExportManifest::new(self.transaction_time.clone(), self.request.clone());
+        for (resource_type, resources) in &self.resources {
+            let file_name = format!("{}.ndjson", resource_type);
+            let path = directory.join(&file_name);
+            let mut ndjson = String::new();
+            for resource in resources {
+                ndjson.push_str(&serde_json::to_string(resource).unwrap());
+                ndjson.push('\n');
+            }
+            fs::write(&path, ndjson)?;
+            let url = match output_url {
+                Some(output_url) => format!("{}/{}", output_url, file_name),
+                None => path.display().to_string(),
+            };
+            manifest.output.push(ExportOutput::new(resource_type.clone(), url, Some(resources.len() as u64)));
+        }
+        Ok(manifest)
+    }
+}
+
+/// The completion manifest of an export.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportManifest {
+    #[serde(rename = "transactionTime")]
+    transaction_time: String,
+    #[serde(rename = "request")]
+    request: String,
+    #[serde(rename = "requiresAccessToken")]
+    requires_access_token: bool,
+    #[serde(rename = "output", default, skip_serializing_if = "Vec::is_empty")]
+    output: Vec<ExportOutput>,
+    #[serde(rename = "error", default, skip_serializing_if = "Vec::is_empty")]
+    error: Vec<ExportOutput>,
+}
+
+/// A file listed in an export manifest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportOutput {
+    #[serde(rename = "type")]
+    resource_type: String,
+    #[serde(rename = "url")]
+    url: String,
+    #[serde(rename = "count", skip_serializing_if = "Option::is_none")]
+    count: Option<u64>,
+}
+
+impl ExportManifest {
+    /// Creates a manifest without files.
+    pub fn new(transaction_time: String, request: String) -> Self {
+        Self {
+            transaction_time,
+            request,
+            requires_access_token: false,
+            output: Vec::new(),
+            error: Vec::new(),
+        }
+    }
+
+    /// Returns when the exported resources were selected.
+    pub fn transaction_time(&self) -> &str {
+        &self.transaction_time
+    }
+
+    /// Returns the kickoff request URL.
+    pub fn request(&self) -> &str {
+        &self.request
+    }
+
+    /// Returns true if the files need an access token to download.
+    pub fn requires_access_token(&self) -> bool {
+        self.requires_access_token
+    }
+
+    /// Returns the exported files.
+    pub fn output(&self) -> &[ExportOutput] {
+        &self.output
+    }
+
+    /// Returns the files of OperationOutcomes for resources that failed to export.
+    pub fn error(&self) -> &[ExportOutput] {
+        &self.error
+    }
+
+    /// Converts the manifest to a JSON string.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Converts a JSON string to a manifest.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+impl ExportOutput {
+    /// Creates a new ExportOutput.
+    pub fn new(resource_type: String, url: String, count: Option<u64>) -> Self {
+        Self { resource_type, url, count }
+    }
+
+    /// Returns the resource type in the file.
+    pub fn resource_type(&self) -> &str {
+        &self.resource_type
+    }
+
+    /// Returns the URL of the file.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns the number of resources in the file.
+    pub fn count(&self) -> Option<u64> {
+        self.count
+    }
+}
+
+/// The state of an export job.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportStatus {
+    InProgress,
+    Complete(ExportManifest),
+    Failed(String),
+}
+
+/// An export whose files are written on a background thread.
+#[derive(Debug)]
+pub struct ExportJob {
+    directory: PathBuf,
+    status: Arc<Mutex<ExportStatus>>,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl ExportJob {
+    /// Starts writing the selected resources into a directory.
+    pub fn start(data: ExportData, directory: PathBuf, output_url: Option<String>) -> Self {
+        let status = Arc::new(Mutex::new(ExportStatus::InProgress));
+        let handle = {
+            let status = Arc::clone(&status);
+            let directory = directory.clone();
+            thread::spawn(move || {
+                let result = match data.write(&directory, output_url.as_deref()) {
+                    Ok(manifest) => ExportStatus::Complete(manifest),
+                    Err(error) => ExportStatus::Failed(error.to_string()),
+                };
+                *status.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = result;
+            })
+        };
+        Self {
+            directory,
+            status,
+            handle: Mutex::new(Some(handle)),
+        }
+    }
+
+    /// Returns the directory the files are written to.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Returns the current state.
+    pub fn status(&self) -> ExportStatus {
+        self.status.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    /// Waits for the job to finish and returns its final state.
+    pub fn wait(&self) -> ExportStatus {
+        let handle = self.handle.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+        if let Some(handle) = handle
+            && handle.join().is_err()
+        {
+            return ExportStatus::Failed("export thread panicked".to_string());
+        }
+        self.status()
+    }
+
+    /// Waits for the job to finish and deletes its files.
+    pub fn cancel(self) -> Result<(), BulkExportError> {
+        self.wait();
+        match fs::remove_dir_all(&self.directory) {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(error.into()),
+            _ => Ok(()),
+        }
+    }
+}
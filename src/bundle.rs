@@ -0,0 +1,414 @@
+use crate::meta::Meta;
+use crate::operation_outcome::OperationOutcome;
+use crate::resource::AnyResource;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bundle {
+    #[serde(rename = "resourceType")]
+    resource_type: String,
+    #[serde(rename = "id", skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(rename = "meta", skip_serializing_if = "Option::is_none")]
+    meta: Option<Meta>,
+    #[serde(rename = "type")]
+    bundle_type: String, // document | message | transaction | transaction-response | batch | batch-response | history | searchset | collection
+    #[serde(rename = "total", skip_serializing_if = "Option::is_none")]
+    total: Option<u32>,
+    #[serde(rename = "link", default, skip_serializing_if = "Vec::is_empty")]
+    link: Vec<BundleLink>,
+    #[serde(rename = "entry", default, skip_serializing_if = "Vec::is_empty")]
+    entry: Vec<BundleEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleLink {
+    #[serde(rename = "relation")]
+    relation: String, // self | first | previous | next | last
+    #[serde(rename = "url")]
+    url: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleEntry {
+    #[serde(rename = "fullUrl", skip_serializing_if = "Option::is_none")]
+    full_url: Option<String>,
+    #[serde(rename = "resource", skip_serializing_if = "Option::is_none")]
+    resource: Option<AnyResource>,
+    #[serde(rename = "search", skip_serializing_if = "Option::is_none")]
+    search: Option<BundleEntrySearch>,
+    #[serde(rename = "request", skip_serializing_if = "Option::is_none")]
+    request: Option<BundleEntryRequest>,
+    #[serde(rename = "response", skip_serializing_if = "Option::is_none")]
+    response: Option<BundleEntryResponse>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleEntrySearch {
+    #[serde(rename = "mode", skip_serializing_if = "Option::is_none")]
+    mode: Option<String>, // match | include | outcome
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleEntryRequest {
+    #[serde(rename = "method")]
+    method: String, // GET | HEAD | POST | PUT | DELETE | PATCH
+    #[serde(rename = "url")]
+    url: String,
+    #[serde(rename = "ifMatch", skip_serializing_if = "Option::is_none")]
+    if_match: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleEntryResponse {
+    #[serde(rename = "status")]
+    status: String,
+    #[serde(rename = "location", skip_serializing_if = "Option::is_none")]
+    location: Option<String>,
+    #[serde(rename = "etag", skip_serializing_if = "Option::is_none")]
+    etag: Option<String>,
+    #[serde(rename = "lastModified", skip_serializing_if = "Option::is_none")]
+    last_modified: Option<String>, // instant
+    #[serde(rename = "outcome", skip_serializing_if = "Option::is_none")]
+    outcome: Option<OperationOutcome>,
+}
+
+impl Bundle {
+    /// Creates a new, empty Bundle of the given type.
+    pub fn new(bundle_type: String) -> Self {
+        Self {
+            resource_type: "Bundle".to_string(),
+            id: None,
+            meta: None,
+            bundle_type,
+            total: None,
+            link: Vec::new(),
+            entry: Vec::new(),
+        }
+    }
+
+    /// Returns the resource type.
+    pub fn resource_type(&self) -> &str {
+        &self.resource_type
+    }
+
+    /// Returns the logical id.
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Returns the resource metadata.
+    pub fn meta(&self) -> Option<&Meta> {
+        self.meta.as_ref()
+    }
+
+    /// Returns the bundle type.
+    pub fn bundle_type(&self) -> &str {
+        &self.bundle_type
+    }
+
+    /// Returns the total number of matches, for search and history bundles.
+    pub fn total(&self) -> Option<u32> {
+        self.total
+    }
+
+    /// Returns the navigation links.
+    pub fn link(&self) -> &[BundleLink] {
+        &self.link
+    }
+
+    /// Returns the URL of the link with the given relation.
+    pub fn link_url(&self, relation: &str) -> Option<&str> {
+        self.link
+            .iter()
+            .find(|link| link.relation == relation)
+            .map(|link| link.url.as_str())
+    }
+
+    /// Returns the entries.
+    pub fn entry(&self) -> &[BundleEntry] {
+        &self.entry
+    }
+
+    /// Returns the resources of entries with the given search mode.
+    pub fn resources_with_mode(&self, mode: &str) -> Vec<&AnyResource> {
+        self.entry
+            .iter()
+            .filter(|entry| entry.search_mode() == Some(mode))
+            .filter_map(|entry| entry.resource())
+            .collect()
+    }
+
+    /// Sets the logical id.
+    pub fn set_id(&mut self, id: Option<String>) {
+        self.id = id;
+    }
+
+    /// Sets the resource metadata.
+    pub fn set_meta(&mut self, meta: Option<Meta>) {
+        self.meta = meta;
+    }
+
+    /// Sets the bundle type.
+    pub fn set_bundle_type(&mut self, bundle_type: String) {
+        self.bundle_type = bundle_type;
+    }
+
+    /// Sets the total number of matches.
+    pub fn set_total(&mut self, total: Option<u32>) {
+        self.total = total;
+    }
+
+    /// Sets the navigation links.
+    pub fn set_link(&mut self, link: Vec<BundleLink>) {
+        self.link = link;
+    }
+
+    /// Adds a navigation link.
+    pub fn add_link(&mut self, link: BundleLink) {
+        self.link.push(link);
+    }
+
+    /// Sets the entries.
+    pub fn set_entry(&mut self, entry: Vec<BundleEntry>) {
+        self.entry = entry;
+    }
+
+    /// Adds an entry.
+    pub fn add_entry(&mut self, entry: BundleEntry) {
+        self.entry.push(entry);
+    }
+
+    /// Converts the Bundle to a JSON string.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Converts a JSON string to a Bundle.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+impl BundleLink {
+    /// Creates a new BundleLink.
+    pub fn new(relation: String, url: String) -> Self {
+        Self { relation, url }
+    }
+
+    /// Returns the relation.
+    pub fn relation(&self) -> &str {
+        &self.relation
+    }
+
+    /// Returns the URL.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl BundleEntry {
+    /// Creates a new, empty BundleEntry.
+    pub fn new() -> Self {
+        Self {
+            full_url: None,
+            resource: None,
+            search: None,
+            request: None,
+            response: None,
+        }
+    }
+
+    /// Creates a new BundleEntry holding a resource.
+    pub fn with_resource(full_url: Option<String>, resource: AnyResource) -> Self {
+        Self {
+            full_url,
+            resource: Some(resource),
+            search: None,
+            request: None,
+            response: None,
+        }
+    }
+
+    /// Returns the full URL.
+    pub fn full_url(&self) -> Option<&str> {
+        self.full_url.as_deref()
+    }
+
+    /// Returns the resource.
+    pub fn resource(&self) -> Option<&AnyResource> {
+        self.resource.as_ref()
+    }
+
+    /// Returns the search information.
+    pub fn search(&self) -> Option<&BundleEntrySearch> {
+        self.search.as_ref()
+    }
+
+    /// Returns the search mode.
+    pub fn search_mode(&self) -> Option<&str> {
+        self.search.as_ref().and_then(|search| search.mode())
+    }
+
+    /// Returns the request information.
+    pub fn request(&self) -> Option<&BundleEntryRequest> {
+        self.request.as_ref()
+    }
+
+    /// Returns the response information.
+    pub fn response(&self) -> Option<&BundleEntryResponse> {
+        self.response.as_ref()
+    }
+
+    /// Sets the full URL.
+    pub fn set_full_url(&mut self, full_url: Option<String>) {
+        self.full_url = full_url;
+    }
+
+    /// Sets the resource.
+    pub fn set_resource(&mut self, resource: Option<AnyResource>) {
+        self.resource = resource;
+    }
+
+    /// Sets the search information.
+    pub fn set_search(&mut self, search: Option<BundleEntrySearch>) {
+        self.search = search;
+    }
+
+    /// Sets the request information.
+    pub fn set_request(&mut self, request: Option<BundleEntryRequest>) {
+        self.request = request;
+    }
+
+    /// Sets the response information.
+    pub fn set_response(&mut self, response: Option<BundleEntryResponse>) {
+        self.response = response;
+    }
+}
+
+impl Default for BundleEntry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BundleEntrySearch {
+    /// Creates a new BundleEntrySearch with a mode.
+    pub fn with_mode(mode: String) -> Self {
+        Self { mode: Some(mode) }
+    }
+
+    /// Returns the search mode.
+    pub fn mode(&self) -> Option<&str> {
+        self.mode.as_deref()
+    }
+
+    /// Sets the search mode.
+    pub fn set_mode(&mut self, mode: Option<String>) {
+        self.mode = mode;
+    }
+}
+
+impl BundleEntryRequest {
+    /// Creates a new BundleEntryRequest.
+    pub fn new(method: String, url: String) -> Self {
+        Self {
+            method,
+            url,
+            if_match: None,
+        }
+    }
+
+    /// Returns the HTTP method.
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// Returns the request URL, relative to the server base.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns the ETag the current version must match, as for `If-Match`.
+    pub fn if_match(&self) -> Option<&str> {
+        self.if_match.as_deref()
+    }
+
+    /// Sets the HTTP method.
+    pub fn set_method(&mut self, method: String) {
+        self.method = method;
+    }
+
+    /// Sets the request URL.
+    pub fn set_url(&mut self, url: String) {
+        self.url = url;
+    }
+
+    /// Sets the ETag the current version must match.
+    pub fn set_if_match(&mut self, if_match: Option<String>) {
+        self.if_match = if_match;
+    }
+}
+
+impl BundleEntryResponse {
+    /// Creates a new BundleEntryResponse with a status, e.g. `201 Created`.
+    pub fn new(status: String) -> Self {
+        Self {
+            status,
+            location: None,
+            etag: None,
+            last_modified: None,
+            outcome: None,
+        }
+    }
+
+    /// Returns the status.
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+
+    /// Returns the location of a created or updated resource.
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
+
+    /// Returns the ETag of the resource version.
+    pub fn etag(&self) -> Option<&str> {
+        self.etag.as_deref()
+    }
+
+    /// Returns when the resource was last modified.
+    pub fn last_modified(&self) -> Option<&str> {
+        self.last_modified.as_deref()
+    }
+
+    /// Returns the outcome of a failed batch entry.
+    pub fn outcome(&self) -> Option<&OperationOutcome> {
+        self.outcome.as_ref()
+    }
+
+    /// Sets the status.
+    pub fn set_status(&mut self, status: String) {
+        self.status = status;
+    }
+
+    /// Sets the location.
+    pub fn set_location(&mut self, location: Option<String>) {
+        self.location = location;
+    }
+
+    /// Sets the ETag.
+    pub fn set_etag(&mut self, etag: Option<String>) {
+        self.etag = etag;
+    }
+
+    /// Sets when the resource was last modified.
+    pub fn set_last_modified(&mut self, last_modified: Option<String>) {
+        self.last_modified = last_modified;
+    }
+
+    /// Sets the outcome.
+    pub fn set_outcome(&mut self, outcome: Option<OperationOutcome>) {
+        self.outcome = outcome;
+    }
+}
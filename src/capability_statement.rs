@@ -0,0 +1,627 @@
+//! The CapabilityStatement resource and a builder deriving it from what a
+//! server supports.
+
+use serde::{Deserialize, Serialize};
+
+use crate::conditional::ConditionalDeleteMode;
+use crate::meta::now;
+use crate::resource::FHIR_JSON;
+use crate::search::params::{self, SearchParamType};
+use crate::search::RESULT_PARAMETERS;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityStatement {
+    #[serde(rename = "resourceType")]
+    resource_type: String,
+    #[serde(rename = "status")]
+    status: String, // draft | active | retired | unknown
+    #[serde(rename = "date")]
+    date: String, // dateTime
+    #[serde(rename = "kind")]
+    kind: String, // instance | capability | requirements
+    #[serde(rename = "software", skip_serializing_if = "Option::is_none")]
+    software: Option<CapabilityStatementSoftware>,
+    #[serde(rename = "fhirVersion")]
+    fhir_version: String,
+    #[serde(rename = "format", default, skip_serializing_if = "Vec::is_empty")]
+    format: Vec<String>,
+    #[serde(rename = "patchFormat", default, skip_serializing_if = "Vec::is_empty")]
+    patch_format: Vec<String>,
+    #[serde(rename = "rest", default, skip_serializing_if = "Vec::is_empty")]
+    rest: Vec<CapabilityStatementRest>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityStatementSoftware {
+    #[serde(rename = "name")]
+    name: String,
+    #[serde(rename = "version", skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityStatementRest {
+    #[serde(rename = "mode")]
+    mode: String, // client | server
+    #[serde(rename = "documentation", skip_serializing_if = "Option::is_none")]
+    documentation: Option<String>,
+    #[serde(rename = "resource", default, skip_serializing_if = "Vec::is_empty")]
+    resource: Vec<CapabilityStatementResource>,
+    #[serde(rename = "interaction", default, skip_serializing_if = "Vec::is_empty")]
+    interaction: Vec<CapabilityStatementInteraction>,
+    #[serde(rename = "operation", default, skip_serializing_if = "Vec::is_empty")]
+    operation: Vec<CapabilityStatementOperation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityStatementResource {
+    #[serde(rename = "type")]
+    resource_type: String,
+    #[serde(rename = "interaction", default, skip_serializing_if = "Vec::is_empty")]
+    interaction: Vec<CapabilityStatementInteraction>,
+    #[serde(rename = "versioning", skip_serializing_if = "Option::is_none")]
+    versioning: Option<String>, // no-version | versioned | versioned-update
+    #[serde(rename = "readHistory", skip_serializing_if = "Option::is_none")]
+    read_history: Option<bool>,
+    #[serde(rename = "updateCreate", skip_serializing_if = "Option::is_none")]
+    update_create: Option<bool>,
+    #[serde(rename = "conditionalCreate", skip_serializing_if = "Option::is_none")]
+    conditional_create: Option<bool>,
+    #[serde(rename = "conditionalUpdate", skip_serializing_if = "Option::is_none")]
+    conditional_update: Option<bool>,
+    #[serde(rename = "conditionalDelete", skip_serializing_if = "Option::is_none")]
+    conditional_delete: Option<String>, // not-supported | single | multiple
+    #[serde(rename = "searchInclude", default, skip_serializing_if = "Vec::is_empty")]
+    search_include: Vec<String>,
+    #[serde(rename = "searchRevInclude", default, skip_serializing_if = "Vec::is_empty")]
+    search_rev_include: Vec<String>,
+    #[serde(rename = "searchParam", default, skip_serializing_if = "Vec::is_empty")]
+    search_param: Vec<CapabilityStatementSearchParam>,
+    #[serde(rename = "operation", default, skip_serializing_if = "Vec::is_empty")]
+    operation: Vec<CapabilityStatementOperation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityStatementInteraction {
+    #[serde(rename = "code")]
+    code: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityStatementSearchParam {
+    #[serde(rename = "name")]
+    name: String,
+    #[serde(rename = "type")]
+    param_type: String, // number | date | string | token | reference | composite | quantity | uri | special
+    #[serde(rename = "documentation", skip_serializing_if = "Option::is_none")]
+    documentation: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityStatementOperation {
+    #[serde(rename = "name")]
+    name: String,
+    #[serde(rename = "definition")]
+    definition: String, // canonical(OperationDefinition)
+}
+
+/// The interactions a server can support on a resource type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TypeInteraction {
+    Read,
+    Vread,
+    Update,
+    Patch,
+    Delete,
+    HistoryInstance,
+    HistoryType,
+    Create,
+    SearchType,
+}
+
+impl TypeInteraction {
+    /// Every type interaction, in the order the specification lists them.
+    pub const ALL: &'static [TypeInteraction] = &[
+        TypeInteraction::Read,
+        TypeInteraction::Vread,
+        TypeInteraction::Update,
+        TypeInteraction::Patch,
+        TypeInteraction::Delete,
+        TypeInteraction::HistoryInstance,
+        TypeInteraction::HistoryType,
+        TypeInteraction::Create,
+        TypeInteraction::SearchType,
+    ];
+
+    /// Returns the FHIR code of the interaction.
+    pub fn code(&self) -> &'static str {
+        match self {
+            TypeInteraction::Read => "read",
+            TypeInteraction::Vread => "vread",
+            TypeInteraction::Update => "update",
+            TypeInteraction::Patch => "patch",
+            TypeInteraction::Delete => "delete",
+            TypeInteraction::HistoryInstance => "history-instance",
+            TypeInteraction::HistoryType => "history-type",
+            TypeInteraction::Create => "create",
+            TypeInteraction::SearchType => "search-type",
+        }
+    }
+}
+
+/// The interactions a server can support across all resource types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SystemInteraction {
+    Transaction,
+    Batch,
+    SearchSystem,
+    HistorySystem,
+}
+
+impl SystemInteraction {
+    /// Returns the FHIR code of the interaction.
+    pub fn code(&self) -> &'static str {
+        match self {
+            SystemInteraction::Transaction => "transaction",
+            SystemInteraction::Batch => "batch",
+            SystemInteraction::SearchSystem => "search-system",
+            SystemInteraction::HistorySystem => "history-system",
+        }
+    }
+}
+
+/// An operation supported by a server, either on a resource type or system wide.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationRegistration {
+    name: String,
+    definition: String,
+    resource_types: Vec<String>,
+}
+
+impl OperationRegistration {
+    /// Creates a system-wide operation, named without the leading `$`.
+    pub fn system(name: &str, definition: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            definition: definition.to_string(),
+            resource_types: Vec::new(),
+        }
+    }
+
+    /// Creates an operation on the given resource types.
+    pub fn on_types(name: &str, definition: &str, resource_types: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            definition: definition.to_string(),
+            resource_types: resource_types.iter().map(|resource_type| resource_type.to_string()).collect(),
+        }
+    }
+
+    /// Returns the operation name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the canonical URL of the OperationDefinition.
+    pub fn definition(&self) -> &str {
+        &self.definition
+    }
+
+    /// Returns the resource types the operation is invoked on; empty for system operations.
+    pub fn resource_types(&self) -> &[String] {
+        &self.resource_types
+    }
+}
+
+/// Builds a server CapabilityStatement from the resource types, interactions
+/// and operations registered with it. Search parameters, `_include` and
+/// `_revinclude` values are taken from the crate's search parameter table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapabilityStatementBuilder {
+    software: Option<CapabilityStatementSoftware>,
+    formats: Vec<String>,
+    patch_formats: Vec<String>,
+    resources: Vec<(String, Vec<TypeInteraction>)>,
+    system_interactions: Vec<SystemInteraction>,
+    operations: Vec<OperationRegistration>,
+    conditional: Option<ConditionalDeleteMode>,
+    versioned_update: bool,
+}
+
+impl CapabilityStatementBuilder {
+    /// Creates a builder for a server speaking FHIR JSON, with nothing registered.
+    pub fn new() -> Self {
+        Self {
+            software: None,
+            formats: vec![FHIR_JSON.to_string(), "json".to_string()],
+            patch_formats: Vec::new(),
+            resources: Vec::new(),
+            system_interactions: Vec::new(),
+            operations: Vec::new(),
+            conditional: None,
+            versioned_update: false,
+        }
+    }
+
+    /// Names the server software.
+    pub fn software(mut self, name: &str, version: Option<&str>) -> Self {
+        self.software = Some(CapabilityStatementSoftware {
+            name: name.to_string(),
+            version: version.map(str::to_string),
+        });
+        self
+    }
+
+    /// Adds a supported patch format, e.g. `application/json-patch+json`.
+    pub fn patch_format(mut self, format: &str) -> Self {
+        self.patch_formats.push(format.to_string());
+        self
+    }
+
+    /// Registers a resource type with its supported interactions.
+    pub fn resource(mut self, resource_type: &str, interactions: &[TypeInteraction]) -> Self {
+        self.resources.push((resource_type.to_string(), interactions.to_vec()));
+        self
+    }
+
+    /// Registers a system-wide interaction.
+    pub fn system_interaction(mut self, interaction: SystemInteraction) -> Self {
+        self.system_interactions.push(interaction);
+        self
+    }
+
+    /// Declares conditional create, update and delete on the resource types
+    /// supporting create, update and delete.
+    pub fn conditional(mut self, delete_mode: ConditionalDeleteMode) -> Self {
+        self.conditional = Some(delete_mode);
+        self
+    }
+
+    /// Declares that updates are checked against the current version, so
+    /// versioned resource types report `versioned-update`.
+    pub fn versioned_update(mut self) -> Self {
+        self.versioned_update = true;
+        self
+    }
+
+    /// Registers an operation.
+    pub fn operation(mut self, operation: OperationRegistration) -> Self {
+        self.operations.push(operation);
+        self
+    }
+
+    /// Builds the CapabilityStatement, dated now.
+    pub fn build(&self) -> CapabilityStatement {
+        let resources = self
+            .resources
+            .iter()
+            .map(|(resource_type, interactions)| self.build_resource(resource_type, interactions))
+            .collect();
+        // Result parameters are not search parameters, so they are described here.
+        let searchable = self
+            .resources
+            .iter()
+            .any(|(_, interactions)| interactions.contains(&TypeInteraction::SearchType));
+        let rest = CapabilityStatementRest {
+            mode: "server".to_string(),
+            documentation: searchable
+                .then(|| format!("Searches accept the result parameters {}.", RESULT_PARAMETERS.join(", "))),
+            resource: resources,
+            interaction: self
+                .system_interactions
+                .iter()
+                .map(|interaction| CapabilityStatementInteraction::new(interaction.code()))
+                .collect(),
+            operation: self
+                .operations
+                .iter()
+                .filter(|operation| operation.resource_types.is_empty())
+                .map(CapabilityStatementOperation::from)
+                .collect(),
+        };
+        CapabilityStatement {
+            resource_type: "CapabilityStatement".to_string(),
+            status: "active".to_string(),
+            date: now(),
+            kind: "instance".to_string(),
+            software: self.software.clone(),
+            fhir_version: "5.0.0".to_string(),
+            format: self.formats.clone(),
+            patch_format: self.patch_formats.clone(),
+            rest: vec![rest],
+        }
+    }
+
+    fn build_resource(&self, resource_type: &str, interactions: &[TypeInteraction]) -> CapabilityStatementResource {
+        let searchable = interactions.contains(&TypeInteraction::SearchType);
+        let mut search_param = Vec::new();
+        let mut search_include = Vec::new();
+        let mut search_rev_include = Vec::new();
+        if searchable {
+            for definition in params::definitions_for(resource_type) {
+                search_param.push(CapabilityStatementSearchParam {
+                    name: definition.name.to_string(),
+                    param_type: definition.param_type.code().to_string(),
+                    documentation: Some(definition.expression.to_string()),
+                });
+                if definition.param_type == SearchParamType::Reference {
+                    search_include.push(format!("{}:{}", resource_type, definition.name));
+                }
+            }
+            for (other_type, other_interactions) in &self.resources {
+                if !other_interactions.contains(&TypeInteraction::SearchType) {
+                    continue;
+                }
+                for definition in params::definitions_for(other_type) {
+                    if definition.param_type == SearchParamType::Reference && definition.targets.contains(&resource_type) {
+                        search_rev_include.push(format!("{}:{}", other_type, definition.name));
+                    }
+                }
+            }
+        }
+        let versioned = interactions.contains(&TypeInteraction::Vread);
+        CapabilityStatementResource {
+            resource_type: resource_type.to_string(),
+            interaction: interactions
+                .iter()
+                .map(|interaction| CapabilityStatementInteraction::new(interaction.code()))
+                .collect(),
+            versioning: Some(
+                match (versioned, self.versioned_update) {
+                    (true, true) => "versioned-update",
+                    (true, false) => "versioned",
+                    (false, _) => "no-version",
+                }
+                .to_string(),
+            ),
+            read_history: Some(versioned),
+            update_create: Some(interactions.contains(&TypeInteraction::Update)),
+            conditional_create: self.conditional.map(|_| interactions.contains(&TypeInteraction::Create)),
+            conditional_update: self.conditional.map(|_| interactions.contains(&TypeInteraction::Update)),
+            conditional_delete: self.conditional.map(|mode| {
+                if interactions.contains(&TypeInteraction::Delete) { mode.code() } else { "not-supported" }.to_string()
+            }),
+            search_include,
+            search_rev_include,
+            search_param,
+            operation: self
+                .operations
+                .iter()
+                .filter(|operation| operation.resource_types.iter().any(|name| name == resource_type))
+                .map(CapabilityStatementOperation::from)
+                .collect(),
+        }
+    }
+}
+
+impl Default for CapabilityStatementBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CapabilityStatement {
+    /// Returns the resource type.
+    pub fn resource_type(&self) -> &str {
+        &self.resource_type
+    }
+
+    /// Returns the publication status.
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+
+    /// Returns when the statement was generated.
+    pub fn date(&self) -> &str {
+        &self.date
+    }
+
+    /// Returns the kind of statement.
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// Returns the server software.
+    pub fn software(&self) -> Option<&CapabilityStatementSoftware> {
+        self.software.as_ref()
+    }
+
+    /// Returns the FHIR version.
+    pub fn fhir_version(&self) -> &str {
+        &self.fhir_version
+    }
+
+    /// Returns the supported formats.
+    pub fn format(&self) -> &[String] {
+        &self.format
+    }
+
+    /// Returns the supported patch formats.
+    pub fn patch_format(&self) -> &[String] {
+        &self.patch_format
+    }
+
+    /// Returns the REST capabilities.
+    pub fn rest(&self) -> &[CapabilityStatementRest] {
+        &self.rest
+    }
+
+    /// Returns the capabilities for a resource type on the server.
+    pub fn resource(&self, resource_type: &str) -> Option<&CapabilityStatementResource> {
+        self.rest
+            .iter()
+            .flat_map(|rest| rest.resource.iter())
+            .find(|resource| resource.resource_type == resource_type)
+    }
+
+    /// Converts the CapabilityStatement to a JSON string.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Converts a JSON string to a CapabilityStatement.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+impl CapabilityStatementSoftware {
+    /// Returns the software name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the software version.
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+}
+
+impl CapabilityStatementRest {
+    /// Returns the mode.
+    pub fn mode(&self) -> &str {
+        &self.mode
+    }
+
+    /// Returns the description of the server's REST capabilities.
+    pub fn documentation(&self) -> Option<&str> {
+        self.documentation.as_deref()
+    }
+
+    /// Returns the resource capabilities.
+    pub fn resource(&self) -> &[CapabilityStatementResource] {
+        &self.resource
+    }
+
+    /// Returns the system-wide interactions.
+    pub fn interaction(&self) -> &[CapabilityStatementInteraction] {
+        &self.interaction
+    }
+
+    /// Returns the system-wide operations.
+    pub fn operation(&self) -> &[CapabilityStatementOperation] {
+        &self.operation
+    }
+
+    /// Returns true if the system-wide interaction is supported.
+    pub fn supports(&self, interaction: SystemInteraction) -> bool {
+        self.interaction.iter().any(|supported| supported.code == interaction.code())
+    }
+}
+
+impl CapabilityStatementResource {
+    /// Returns the resource type.
+    pub fn resource_type(&self) -> &str {
+        &self.resource_type
+    }
+
+    /// Returns the interactions.
+    pub fn interaction(&self) -> &[CapabilityStatementInteraction] {
+        &self.interaction
+    }
+
+    /// Returns true if the interaction is supported.
+    pub fn supports(&self, interaction: TypeInteraction) -> bool {
+        self.interaction.iter().any(|supported| supported.code == interaction.code())
+    }
+
+    /// Returns the versioning policy.
+    pub fn versioning(&self) -> Option<&str> {
+        self.versioning.as_deref()
+    }
+
+    /// Returns whether vread and history are supported.
+    pub fn read_history(&self) -> Option<bool> {
+        self.read_history
+    }
+
+    /// Returns whether update can create resources.
+    pub fn update_create(&self) -> Option<bool> {
+        self.update_create
+    }
+
+    /// Returns whether conditional create is supported.
+    pub fn conditional_create(&self) -> Option<bool> {
+        self.conditional_create
+    }
+
+    /// Returns whether conditional update is supported.
+    pub fn conditional_update(&self) -> Option<bool> {
+        self.conditional_update
+    }
+
+    /// Returns the conditional delete support.
+    pub fn conditional_delete(&self) -> Option<&str> {
+        self.conditional_delete.as_deref()
+    }
+
+    /// Returns the supported `_include` values.
+    pub fn search_include(&self) -> &[String] {
+        &self.search_include
+    }
+
+    /// Returns the supported `_revinclude` values.
+    pub fn search_rev_include(&self) -> &[String] {
+        &self.search_rev_include
+    }
+
+    /// Returns the search parameters.
+    pub fn search_param(&self) -> &[CapabilityStatementSearchParam] {
+        &self.search_param
+    }
+
+    /// Returns the operations.
+    pub fn operation(&self) -> &[CapabilityStatementOperation] {
+        &self.operation
+    }
+}
+
+impl CapabilityStatementInteraction {
+    /// Creates a new CapabilityStatementInteraction.
+    pub fn new(code: &str) -> Self {
+        Self { code: code.to_string() }
+    }
+
+    /// Returns the interaction code.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+}
+
+impl CapabilityStatementSearchParam {
+    /// Returns the parameter name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the parameter type.
+    pub fn param_type(&self) -> &str {
+        &self.param_type
+    }
+
+    /// Returns the documentation.
+    pub fn documentation(&self) -> Option<&str> {
+        self.documentation.as_deref()
+    }
+}
+
+impl CapabilityStatementOperation {
+    /// Returns the operation name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the canonical URL of the OperationDefinition.
+    pub fn definition(&self) -> &str {
+        &self.definition
+    }
+}
+
+impl From<&OperationRegistration> for CapabilityStatementOperation {
+    fn from(operation: &OperationRegistration) -> Self {
+        Self {
+            name: operation.name.clone(),
+            definition: operation.definition.clone(),
+        }
+    }
+}
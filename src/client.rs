@@ -0,0 +1,248 @@
+//! A typed FHIR REST client, behind the `client` feature.
+//!
+//! ```no_run
+//! use fhir_resources_rs::account::Account;
+//! use fhir_resources_rs::client::FhirClient;
+//! use fhir_resources_rs::patient::Patient;
+//!
+//! let client = FhirClient::new("http://localhost:8080");
+//! let patient: Patient = client.read("123")?;
+//! let accounts = client.search_all::<Account>(&[("status", "active")])?;
+//! # Ok::<(), fhir_resources_rs::client::ClientError>(())
+//! ```
+
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use crate::bundle::Bundle;
+use crate::operation_outcome::OperationOutcome;
+use crate::resource::{AnyResource, Resource, FHIR_JSON};
+use crate::search::percent_encode;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("Server returned HTTP {0}")]
+    Status(u16, Option<OperationOutcome>),
+    #[error("Transport error: {0}")]
+    Transport(String),
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+    #[error("Resource has no id")]
+    MissingId,
+    #[error("Expected a {0} but received a {1}")]
+    UnexpectedResourceType(String, String),
+}
+
+impl ClientError {
+    /// Returns the HTTP status of an error response.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ClientError::Status(status, _) => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// Returns the OperationOutcome of an error response.
+    pub fn outcome(&self) -> Option<&OperationOutcome> {
+        match self {
+            ClientError::Status(_, outcome) => outcome.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+/// One page of search results for a resource type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchPage<R: Resource> {
+    bundle: Bundle,
+    resource: PhantomData<R>,
+}
+
+impl<R: Resource> SearchPage<R> {
+    /// Wraps a searchset Bundle.
+    pub fn new(bundle: Bundle) -> Self {
+        Self {
+            bundle,
+            resource: PhantomData,
+        }
+    }
+
+    /// Returns the Bundle.
+    pub fn bundle(&self) -> &Bundle {
+        &self.bundle
+    }
+
+    /// Returns the total number of matches, if the server reported it.
+    pub fn total(&self) -> Option<u32> {
+        self.bundle.total()
+    }
+
+    /// Returns the matches on this page.
+    pub fn resources(&self) -> Vec<R> {
+        self.bundle
+            .entry()
+            .iter()
+            .filter(|entry| entry.search_mode() != Some("include"))
+            .filter_map(|entry| entry.resource())
+            .filter_map(|resource| R::from_any(resource.clone()))
+            .collect()
+    }
+
+    /// Returns the resources included by `_include` or `_revinclude`.
+    pub fn included(&self) -> Vec<AnyResource> {
+        self.bundle.resources_with_mode("include").into_iter().cloned().collect()
+    }
+
+    /// Returns the URL of the next page.
+    pub fn next_url(&self) -> Option<&str> {
+        self.bundle.link_url("next")
+    }
+}
+
+/// A client for a FHIR server's RESTful API.
+#[derive(Debug, Clone)]
+pub struct FhirClient {
+    base_url: String,
+    agent: ureq::Agent,
+}
+
+impl FhirClient {
+    /// Creates a new FhirClient for a server base URL.
+    pub fn new(base_url: &str) -> Self {
+        let agent = ureq::AgentBuilder::new().timeout(Duration::from_secs(30)).build();
+        Self::with_agent(base_url, agent)
+    }
+
+    /// Creates a new FhirClient using a configured `ureq` agent, e.g. for proxies or timeouts.
+    pub fn with_agent(base_url: &str, agent: ureq::Agent) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            agent,
+        }
+    }
+
+    /// Returns the base URL.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Reads the current version of a resource.
+    pub fn read<R: Resource>(&self, id: &str) -> Result<R, ClientError> {
+        let url = self.url(&format!("{}/{}", R::RESOURCE_TYPE, id));
+        self.typed(self.send(self.request("GET", &url), None)?)
+    }
+
+    /// Reads a specific version of a resource.
+    pub fn vread<R: Resource>(&self, id: &str, version_id: &str) -> Result<R, ClientError> {
+        let url = self.url(&format!("{}/{}/_history/{}", R::RESOURCE_TYPE, id, version_id));
+        self.typed(self.send(self.request("GET", &url), None)?)
+    }
+
+    /// Creates a resource, returning it as stored by the server.
+    pub fn create<R: Resource>(&self, resource: &R) -> Result<R, ClientError> {
+        let url = self.url(R::RESOURCE_TYPE);
+        let body = serde_json::to_string(resource).unwrap();
+        let request = self.request("POST", &url).set("Content-Type", FHIR_JSON);
+        self.typed(self.send(request, Some(&body))?)
+    }
+
+    /// Updates a resource. When the resource has a `meta.versionId` the update
+    /// is sent with `If-Match`, so it fails if the server holds a newer version.
+    pub fn update<R: Resource>(&self, resource: &R) -> Result<R, ClientError> {
+        let id = resource.id().ok_or(ClientError::MissingId)?;
+        let url = self.url(&format!("{}/{}", R::RESOURCE_TYPE, id));
+        let body = serde_json::to_string(resource).unwrap();
+        let mut request = self.request("PUT", &url).set("Content-Type", FHIR_JSON);
+        if let Some(version_id) = resource.meta().and_then(|meta| meta.version_id()) {
+            request = request.set("If-Match", &format!("W/\"{}\"", version_id));
+        }
+        self.typed(self.send(request, Some(&body))?)
+    }
+
+    /// Deletes a resource.
+    pub fn delete<R: Resource>(&self, id: &str) -> Result<(), ClientError> {
+        let url = self.url(&format!("{}/{}", R::RESOURCE_TYPE, id));
+        self.send(self.request("DELETE", &url), None).map(|_| ())
+    }
+
+    /// Searches a resource type with name/value parameters, returning the first page.
+    pub fn search<R: Resource>(&self, params: &[(&str, &str)]) -> Result<SearchPage<R>, ClientError> {
+        let query: Vec<String> = params
+            .iter()
+            .map(|(name, value)| format!("{}={}", percent_encode(name), percent_encode(value)))
+            .collect();
+        let url = if query.is_empty() {
+            self.url(R::RESOURCE_TYPE)
+        } else {
+            format!("{}?{}", self.url(R::RESOURCE_TYPE), query.join("&"))
+        };
+        Ok(SearchPage::new(self.bundle(self.send(self.request("GET", &url), None)?)?))
+    }
+
+    /// Fetches the page after the given one, following its `next` link.
+    pub fn next_page<R: Resource>(&self, page: &SearchPage<R>) -> Result<Option<SearchPage<R>>, ClientError> {
+        let Some(next) = page.next_url() else {
+            return Ok(None);
+        };
+        let url = if next.contains("://") { next.to_string() } else { self.url(next) };
+        let bundle = self.bundle(self.send(self.request("GET", &url), None)?)?;
+        Ok(Some(SearchPage::new(bundle)))
+    }
+
+    /// Searches a resource type and follows `next` links, returning every match.
+    pub fn search_all<R: Resource>(&self, params: &[(&str, &str)]) -> Result<Vec<R>, ClientError> {
+        let mut page = self.search::<R>(params)?;
+        let mut resources = page.resources();
+        while let Some(next) = self.next_page(&page)? {
+            resources.extend(next.resources());
+            page = next;
+        }
+        Ok(resources)
+    }
+
+    /// Posts a transaction or batch Bundle, returning the server's response Bundle.
+    pub fn transaction(&self, bundle: &Bundle) -> Result<Bundle, ClientError> {
+        let request = self.request("POST", &self.base_url).set("Content-Type", FHIR_JSON);
+        self.bundle(self.send(request, Some(&bundle.to_json()))?)
+    }
+
+    fn url(&self, relative: &str) -> String {
+        format!("{}/{}", self.base_url, relative.trim_start_matches('/'))
+    }
+
+    fn request(&self, method: &str, url: &str) -> ureq::Request {
+        self.agent.request(method, url).set("Accept", FHIR_JSON)
+    }
+
+    /// Sends a request, turning error statuses into [`ClientError::Status`].
+    fn send(&self, request: ureq::Request, body: Option<&str>) -> Result<String, ClientError> {
+        let result = match body {
+            Some(body) => request.send_string(body),
+            None => request.call(),
+        };
+        match result {
+            Ok(response) => response
+                .into_string()
+                .map_err(|error| ClientError::Transport(error.to_string())),
+            Err(ureq::Error::Status(status, response)) => {
+                let outcome = response
+                    .into_string()
+                    .ok()
+                    .and_then(|body| OperationOutcome::from_json(&body).ok());
+                Err(ClientError::Status(status, outcome))
+            }
+            Err(error) => Err(ClientError::Transport(error.to_string())),
+        }
+    }
+
+    fn typed<R: Resource>(&self, body: String) -> Result<R, ClientError> {
+        let resource = AnyResource::from_json(&body).map_err(|error| ClientError::InvalidResponse(error.to_string()))?;
+        let resource_type = resource.resource_type();
+        R::from_any(resource)
+            .ok_or_else(|| ClientError::UnexpectedResourceType(R::RESOURCE_TYPE.to_string(), resource_type.to_string()))
+    }
+
+    fn bundle(&self, body: String) -> Result<Bundle, ClientError> {
+        Bundle::from_json(&body).map_err(|error| ClientError::InvalidResponse(error.to_string()))
+    }
+}
@@ -0,0 +1,310 @@
+//! Compartments and the Patient `$everything` operation.
+//!
+//! A [`CompartmentDefinition`] lists the resource types that belong to a
+//! compartment and the reference search parameters that link them to its
+//! focus resource. [`everything`] gathers a patient and every resource in its
+//! compartment into a `searchset` Bundle.
+
+use serde::{Deserialize, Serialize};
+
+use crate::bundle::{Bundle, BundleEntry, BundleEntrySearch, BundleLink};
+use crate::data_types::date_time::Instant;
+use crate::meta::Meta;
+use crate::repository::{Repository, RepositoryError};
+use crate::resource::AnyResource;
+use crate::search::{SearchEngine, SearchError, SearchQuery};
+
+#[derive(Debug, thiserror::Error)]
+pub enum CompartmentError {
+    #[error("{0} is not in the {1} compartment")]
+    NotInCompartment(String, String),
+    #[error("Invalid _since {0}: expected an instant")]
+    InvalidSince(String),
+    #[error(transparent)]
+    Search(#[from] SearchError),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+impl CompartmentError {
+    /// Returns the HTTP status code for the error.
+    pub fn status(&self) -> u16 {
+        match self {
+            CompartmentError::Repository(RepositoryError::NotFound(_, _))
+            | CompartmentError::Search(SearchError::Repository(RepositoryError::NotFound(_, _))) => 404,
+            _ => 400,
+        }
+    }
+
+    /// Returns the OperationOutcome issue type code for the error.
+    pub fn issue_code(&self) -> &'static str {
+        match self {
+            CompartmentError::NotInCompartment(_, _) => "not-supported",
+            _ if self.status() == 404 => "not-found",
+            _ => "invalid",
+        }
+    }
+}
+
+/// The resource types in a compartment and how they link to its focus.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompartmentDefinition {
+    #[serde(rename = "resourceType")]
+    resource_type: String,
+    #[serde(rename = "url")]
+    url: String,
+    #[serde(rename = "name")]
+    name: String,
+    #[serde(rename = "status")]
+    status: String,
+    #[serde(rename = "code")]
+    code: String,
+    #[serde(rename = "search")]
+    search: bool,
+    #[serde(rename = "resource", default, skip_serializing_if = "Vec::is_empty")]
+    resource: Vec<CompartmentDefinitionResource>,
+}
+
+/// A resource type in a compartment and the search parameters that refer to
+/// the focus resource. A type without parameters is never in the compartment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompartmentDefinitionResource {
+    #[serde(rename = "code")]
+    code: String,
+    #[serde(rename = "param", default, skip_serializing_if = "Vec::is_empty")]
+    param: Vec<String>,
+}
+
+impl CompartmentDefinition {
+    /// Creates a compartment definition without resources.
+    pub fn new(url: String, name: String, code: String) -> Self {
+        Self {
+            resource_type: "CompartmentDefinition".to_string(),
+            url,
+            name,
+            status: "active".to_string(),
+            code,
+            search: true,
+            resource: Vec::new(),
+        }
+    }
+
+    /// Returns the Patient compartment, restricted to the crate's resource types.
+    pub fn patient() -> Self {
+        let mut definition = Self::new(
+            "http://hl7.org/fhir/CompartmentDefinition/patient".to_string(),
+            "Base FHIR compartment definition for Patient".to_string(),
+            "Patient".to_string(),
+        );
+        definition.add_resource(CompartmentDefinitionResource::new("Account".to_string(), vec!["subject".to_string()]));
+        definition.add_resource(CompartmentDefinitionResource::new("Patient".to_string(), vec!["link".to_string()]));
+        definition
+    }
+
+    /// Returns the canonical URL.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns the name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the publication status.
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+
+    /// Returns the resource type at the focus of the compartment.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Returns true if the compartment can be searched.
+    pub fn search(&self) -> bool {
+        self.search
+    }
+
+    /// Returns the resource types in the compartment.
+    pub fn resources(&self) -> &[CompartmentDefinitionResource] {
+        &self.resource
+    }
+
+    /// Returns the search parameters linking a resource type to the focus.
+    pub fn params(&self, resource_type: &str) -> &[String] {
+        self.resource
+            .iter()
+            .find(|resource| resource.code == resource_type)
+            .map_or(&[], |resource| &resource.param)
+    }
+
+    /// Returns true if resources of a type can be in the compartment.
+    pub fn contains(&self, resource_type: &str) -> bool {
+        resource_type == self.code || !self.params(resource_type).is_empty()
+    }
+
+    /// Adds a resource type.
+    pub fn add_resource(&mut self, resource: CompartmentDefinitionResource) {
+        self.resource.push(resource);
+    }
+
+    /// Returns the resources of a type in the compartment of a focus resource,
+    /// i.e. those whose compartment parameters refer to it.
+    pub fn members(
+        &self,
+        engine: &SearchEngine,
+        repository: &dyn Repository,
+        resource_type: &str,
+        id: &str,
+    ) -> Result<Vec<AnyResource>, CompartmentError> {
+        let mut members: Vec<AnyResource> = Vec::new();
+        for param in self.params(resource_type) {
+            let mut query = SearchQuery::new();
+            query.add(param, format!("{}/{}", self.code, id));
+            for resource in engine.search(repository, resource_type, &query)? {
+                if !members.iter().any(|member| member.id() == resource.id()) {
+                    members.push(resource);
+                }
+            }
+        }
+        Ok(members)
+    }
+
+    /// Converts the CompartmentDefinition to a JSON string.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Converts a JSON string to a CompartmentDefinition.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+impl CompartmentDefinitionResource {
+    /// Creates a resource entry with its compartment parameters.
+    pub fn new(code: String, param: Vec<String>) -> Self {
+        Self { code, param }
+    }
+
+    /// Returns the resource type.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Returns the search parameters that refer to the focus resource.
+    pub fn param(&self) -> &[String] {
+        &self.param
+    }
+}
+
+/// The parameters of `Patient/[id]/$everything`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EverythingParams {
+    since: Option<Instant>,
+    types: Vec<String>,
+}
+
+impl EverythingParams {
+    /// Creates parameters that return the whole compartment.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads `_since`, which must be an instant, and a comma-separated
+    /// `_type` from a query.
+    pub fn parse(query: &SearchQuery) -> Result<Self, CompartmentError> {
+        let mut params = Self::new();
+        for param in query.params() {
+            match param.name() {
+                "_since" => {
+                    let since = Instant::parse(param.value())
+                        .map_err(|_| CompartmentError::InvalidSince(param.value().to_string()))?;
+                    params.since = Some(since);
+                }
+                "_type" => params
+                    .types
+                    .extend(param.value().split(',').map(str::trim).filter(|value| !value.is_empty()).map(str::to_string)),
+                _ => {}
+            }
+        }
+        Ok(params)
+    }
+
+    /// Returns the instant before which changed resources are left out.
+    pub fn since(&self) -> Option<&Instant> {
+        self.since.as_ref()
+    }
+
+    /// Returns the resource types to return; empty for every type.
+    pub fn types(&self) -> &[String] {
+        &self.types
+    }
+
+    /// Only returns resources changed at or after an instant.
+    pub fn set_since(&mut self, since: Option<Instant>) {
+        self.since = since;
+    }
+
+    /// Only returns resources of the given types.
+    pub fn set_types(&mut self, types: Vec<String>) {
+        self.types = types;
+    }
+
+    fn includes(&self, resource: &AnyResource) -> bool {
+        let type_matches = self.types.is_empty() || self.types.iter().any(|code| code == resource.resource_type());
+        let since_matches = self.since.as_ref().is_none_or(|since| {
+            resource
+                .meta()
+                .and_then(Meta::last_updated)
+                .and_then(|last_updated| Instant::parse(last_updated).ok())
+                .is_some_and(|last_updated| last_updated.compare(since).is_ge())
+        });
+        type_matches && since_matches
+    }
+}
+
+/// Runs `Patient/[id]/$everything`: the patient followed by every resource in
+/// its compartment, filtered by `_since` and `_type`. The patient must exist;
+/// `_type` may only name types in the Patient compartment.
+pub fn everything(
+    engine: &SearchEngine,
+    repository: &dyn Repository,
+    id: &str,
+    params: &EverythingParams,
+) -> Result<Bundle, CompartmentError> {
+    let compartment = CompartmentDefinition::patient();
+    if let Some(code) = params.types.iter().find(|code| !compartment.contains(code)) {
+        return Err(CompartmentError::NotInCompartment(code.clone(), compartment.code.clone()));
+    }
+    let patient = repository.read(&compartment.code, id)?;
+
+    let mut resources = vec![patient];
+    for resource in compartment.resources() {
+        for member in compartment.members(engine, repository, &resource.code, id)? {
+            if !resources
+                .iter()
+                .any(|found| found.resource_type() == member.resource_type() && found.id() == member.id())
+            {
+                resources.push(member);
+            }
+        }
+    }
+
+    let mut bundle = Bundle::new("searchset".to_string());
+    resources.retain(|resource| params.includes(resource));
+    bundle.set_total(Some(resources.len() as u32));
+    if let Some(base_url) = engine.base_url() {
+        bundle.add_link(BundleLink::new(
+            "self".to_string(),
+            format!("{}/{}/{}/$everything", base_url, compartment.code, id),
+        ));
+    }
+    for resource in resources {
+        let mut entry = BundleEntry::with_resource(engine.full_url(&resource), resource);
+        entry.set_search(Some(BundleEntrySearch::with_mode("match".to_string())));
+        bundle.add_entry(entry);
+    }
+    Ok(bundle)
+}
@@ -0,0 +1,207 @@
+//! Conditional create, update and delete.
+//!
+//! The target of a conditional interaction is found by search criteria
+//! rather than by id, e.g. `identifier=http://hospital.org/mrn|12345`, and
+//! the outcome depends on the number of matches as the specification
+//! defines. [`ConditionalRepository`] adds the interactions to every
+//! [`Repository`].
+
+use crate::repository::{Repository, RepositoryError};
+use crate::resource::AnyResource;
+use crate::search::{SearchEngine, SearchError, SearchQuery};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConditionalError {
+    #[error("Conditional interaction has no search criteria")]
+    MissingCriteria,
+    #[error("{2} {0} resources match {1}")]
+    MultipleMatches(String, String, usize),
+    #[error("Resource id {0} does not match the matching resource {1}")]
+    IdMismatch(String, String),
+    #[error("No resource matches {0} but {1} already exists")]
+    ExistingId(String, String),
+    #[error(transparent)]
+    Search(#[from] SearchError),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+impl ConditionalError {
+    /// Returns the HTTP status code for the error.
+    pub fn status(&self) -> u16 {
+        match self {
+            ConditionalError::MultipleMatches(_, _, _) => 412,
+            ConditionalError::ExistingId(_, _)
+            | ConditionalError::Repository(RepositoryError::VersionConflict(_, _, _, _)) => 409,
+            ConditionalError::Repository(RepositoryError::NotFound(_, _))
+            | ConditionalError::Search(SearchError::Repository(RepositoryError::NotFound(_, _))) => 404,
+            _ => 400,
+        }
+    }
+
+    /// Returns the OperationOutcome issue type code for the error.
+    pub fn issue_code(&self) -> &'static str {
+        match self {
+            ConditionalError::MultipleMatches(_, _, _) => "multiple-matches",
+            ConditionalError::ExistingId(_, _)
+            | ConditionalError::Repository(RepositoryError::VersionConflict(_, _, _, _)) => "conflict",
+            _ if self.status() == 404 => "not-found",
+            _ => "invalid",
+        }
+    }
+}
+
+/// How a conditional delete treats more than one match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConditionalDeleteMode {
+    /// Fails with [`ConditionalError::MultipleMatches`].
+    #[default]
+    Single,
+    /// Deletes every match.
+    Multiple,
+}
+
+impl ConditionalDeleteMode {
+    /// Returns the CapabilityStatement `conditionalDelete` code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ConditionalDeleteMode::Single => "single",
+            ConditionalDeleteMode::Multiple => "multiple",
+        }
+    }
+}
+
+/// The outcome of a conditional create.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConditionalCreate {
+    /// Nothing matched and the resource was created.
+    Created(AnyResource),
+    /// One resource matched and was left unchanged.
+    Existing(AnyResource),
+}
+
+/// The outcome of a conditional update.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConditionalUpdate {
+    /// Nothing matched and the resource was created.
+    Created(AnyResource),
+    /// One resource matched and was replaced.
+    Updated(AnyResource),
+}
+
+/// Conditional interactions, available on every [`Repository`]. The engine
+/// evaluates the criteria, so value sets registered with it can be used.
+pub trait ConditionalRepository: Repository + Sized {
+    /// Creates the resource unless a resource of its type matches the
+    /// criteria, as for `If-None-Exist`. One match is returned unchanged and
+    /// several fail with [`ConditionalError::MultipleMatches`].
+    fn conditional_create(
+        &mut self,
+        engine: &SearchEngine,
+        resource: AnyResource,
+        criteria: &str,
+    ) -> Result<ConditionalCreate, ConditionalError> {
+        let resource_type = resource.resource_type();
+        match single_match(self, engine, resource_type, criteria)? {
+            Some(existing) => Ok(ConditionalCreate::Existing(existing)),
+            None => {
+                let mut resource = resource;
+                resource.set_id(None);
+                Ok(ConditionalCreate::Created(self.create(resource)?))
+            }
+        }
+    }
+
+    /// Updates the resource matching the criteria, as for `PUT [type]?criteria`.
+    ///
+    /// With no match the resource is created, keeping its id if it has one
+    /// that is not already taken. With one match the resource replaces it,
+    /// and must have no id or the matching id. Several matches fail with
+    /// [`ConditionalError::MultipleMatches`].
+    fn conditional_update(
+        &mut self,
+        engine: &SearchEngine,
+        resource: AnyResource,
+        criteria: &str,
+    ) -> Result<ConditionalUpdate, ConditionalError> {
+        let resource_type = resource.resource_type();
+        let mut resource = resource;
+        match single_match(self, engine, resource_type, criteria)? {
+            Some(existing) => {
+                let existing_id = existing.id().unwrap_or_default().to_string();
+                match resource.id() {
+                    Some(id) if id != existing_id => {
+                        return Err(ConditionalError::IdMismatch(id.to_string(), existing_id));
+                    }
+                    Some(_) => {}
+                    None => resource.set_id(Some(existing_id)),
+                }
+                Ok(ConditionalUpdate::Updated(self.update(resource)?))
+            }
+            None => match resource.id() {
+                Some(id) if self.read(resource_type, id).is_ok() => {
+                    Err(ConditionalError::ExistingId(criteria.to_string(), format!("{}/{}", resource_type, id)))
+                }
+                Some(_) => Ok(ConditionalUpdate::Created(self.update(resource)?)),
+                None => Ok(ConditionalUpdate::Created(self.create(resource)?)),
+            },
+        }
+    }
+
+    /// Deletes the resources of a type matching the criteria, as for
+    /// `DELETE [type]?criteria`, returning how many were deleted. No match
+    /// succeeds without deleting anything.
+    fn conditional_delete(
+        &mut self,
+        engine: &SearchEngine,
+        resource_type: &str,
+        criteria: &str,
+        mode: ConditionalDeleteMode,
+    ) -> Result<usize, ConditionalError> {
+        let matches = matches(self, engine, resource_type, criteria)?;
+        if matches.len() > 1 && mode == ConditionalDeleteMode::Single {
+            return Err(ConditionalError::MultipleMatches(
+                resource_type.to_string(),
+                criteria.to_string(),
+                matches.len(),
+            ));
+        }
+        for resource in &matches {
+            self.delete(resource_type, resource.id().unwrap_or_default())?;
+        }
+        Ok(matches.len())
+    }
+}
+
+impl<R: Repository> ConditionalRepository for R {}
+
+fn matches<R: Repository>(
+    repository: &R,
+    engine: &SearchEngine,
+    resource_type: &str,
+    criteria: &str,
+) -> Result<Vec<AnyResource>, ConditionalError> {
+    let query = SearchQuery::parse(criteria);
+    if query.params().is_empty() {
+        return Err(ConditionalError::MissingCriteria);
+    }
+    Ok(engine.search(repository, resource_type, &query)?)
+}
+
+/// Returns the only match, if any, failing when several resources match.
+fn single_match<R: Repository>(
+    repository: &R,
+    engine: &SearchEngine,
+    resource_type: &str,
+    criteria: &str,
+) -> Result<Option<AnyResource>, ConditionalError> {
+    let mut matches = matches(repository, engine, resource_type, criteria)?;
+    if matches.len() > 1 {
+        return Err(ConditionalError::MultipleMatches(
+            resource_type.to_string(),
+            criteria.to_string(),
+            matches.len(),
+        ));
+    }
+    Ok(matches.pop())
+}
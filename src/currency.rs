@@ -0,0 +1,235 @@
+//! ISO 4217 currency codes with their minor units.
+
+/// The code system of ISO 4217 currency codes.
+pub const ISO_4217: &str = "urn:iso:std:iso:4217";
+
+/// An ISO 4217 currency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Currency {
+    code: &'static str,
+    minor_units: Option<u32>,
+    symbol: Option<&'static str>,
+}
+
+impl Currency {
+    /// Looks up an active ISO 4217 code, e.g. `USD`. Codes are upper case.
+    pub fn from_code(code: &str) -> Option<Currency> {
+        CURRENCIES.iter().find(|currency| currency.code == code).copied()
+    }
+
+    /// Returns true if the code is an active ISO 4217 code.
+    pub fn is_valid(code: &str) -> bool {
+        Self::from_code(code).is_some()
+    }
+
+    /// Returns the three-letter code.
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    /// Returns the number of digits after the decimal point, e.g. 2 for
+    /// `USD` and 0 for `JPY`. `None` for codes without minor units, such as
+    /// gold (`XAU`).
+    pub fn minor_units(&self) -> Option<u32> {
+        self.minor_units
+    }
+
+    /// Returns the usual symbol for widely used currencies, e.g. `$` for `USD`.
+    pub fn symbol(&self) -> Option<&'static str> {
+        self.symbol
+    }
+}
+
+const fn currency(code: &'static str, minor_units: u32) -> Currency {
+    Currency { code, minor_units: Some(minor_units), symbol: None }
+}
+
+const fn with_symbol(code: &'static str, minor_units: u32, symbol: &'static str) -> Currency {
+    Currency { code, minor_units: Some(minor_units), symbol: Some(symbol) }
+}
+
+const fn without_minor_units(code: &'static str) -> Currency {
+    Currency { code, minor_units: None, symbol: None }
+}
+
+static CURRENCIES: &[Currency] = &[
+    currency("AED", 2),
+    currency("AFN", 2),
+    currency("ALL", 2),
+    currency("AMD", 2),
+    currency("ANG", 2),
+    currency("AOA", 2),
+    currency("ARS", 2),
+    with_symbol("AUD", 2, "A$"),
+    currency("AWG", 2),
+    currency("AZN", 2),
+    currency("BAM", 2),
+    currency("BBD", 2),
+    currency("BDT", 2),
+    currency("BGN", 2),
+    currency("BHD", 3),
+    currency("BIF", 0),
+    currency("BMD", 2),
+    currency("BND", 2),
+    currency("BOB", 2),
+    currency("BOV", 2),
+    with_symbol("BRL", 2, "R$"),
+    currency("BSD", 2),
+    currency("BTN", 2),
+    currency("BWP", 2),
+    currency("BYN", 2),
+    currency("BZD", 2),
+    with_symbol("CAD", 2, "CA$"),
+    currency("CDF", 2),
+    currency("CHE", 2),
+    currency("CHF", 2),
+    currency("CHW", 2),
+    currency("CLF", 4),
+    currency("CLP", 0),
+    with_symbol("CNY", 2, "CN¥"),
+    currency("COP", 2),
+    currency("COU", 2),
+    currency("CRC", 2),
+    currency("CUP", 2),
+    currency("CVE", 2),
+    currency("CZK", 2),
+    currency("DJF", 0),
+    currency("DKK", 2),
+    currency("DOP", 2),
+    currency("DZD", 2),
+    currency("EGP", 2),
+    currency("ERN", 2),
+    currency("ETB", 2),
+    with_symbol("EUR", 2, "€"),
+    currency("FJD", 2),
+    currency("FKP", 2),
+    with_symbol("GBP", 2, "£"),
+    currency("GEL", 2),
+    currency("GHS", 2),
+    currency("GIP", 2),
+    currency("GMD", 2),
+    currency("GNF", 0),
+    currency("GTQ", 2),
+    currency("GYD", 2),
+    with_symbol("HKD", 2, "HK$"),
+    currency("HNL", 2),
+    currency("HTG", 2),
+    currency("HUF", 2),
+    currency("IDR", 2),
+    with_symbol("ILS", 2, "₪"),
+    with_symbol("INR", 2, "₹"),
+    currency("IQD", 3),
+    currency("IRR", 2),
+    currency("ISK", 0),
+    currency("JMD", 2),
+    currency("JOD", 3),
+    with_symbol("JPY", 0, "¥"),
+    currency("KES", 2),
+    currency("KGS", 2),
+    currency("KHR", 2),
+    currency("KMF", 0),
+    currency("KPW", 2),
+    with_symbol("KRW", 0, "₩"),
+    currency("KWD", 3),
+    currency("KYD", 2),
+    currency("KZT", 2),
+    currency("LAK", 2),
+    currency("LBP", 2),
+    currency("LKR", 2),
+    currency("LRD", 2),
+    currency("LSL", 2),
+    currency("LYD", 3),
+    currency("MAD", 2),
+    currency("MDL", 2),
+    currency("MGA", 2),
+    currency("MKD", 2),
+    currency("MMK", 2),
+    currency("MNT", 2),
+    currency("MOP", 2),
+    currency("MRU", 2),
+    currency("MUR", 2),
+    currency("MVR", 2),
+    currency("MWK", 2),
+    with_symbol("MXN", 2, "MX$"),
+    currency("MXV", 2),
+    currency("MYR", 2),
+    currency("MZN", 2),
+    currency("NAD", 2),
+    with_symbol("NGN", 2, "₦"),
+    currency("NIO", 2),
+    currency("NOK", 2),
+    currency("NPR", 2),
+    with_symbol("NZD", 2, "NZ$"),
+    currency("OMR", 3),
+    currency("PAB", 2),
+    currency("PEN", 2),
+    currency("PGK", 2),
+    with_symbol("PHP", 2, "₱"),
+    currency("PKR", 2),
+    currency("PLN", 2),
+    currency("PYG", 0),
+    currency("QAR", 2),
+    currency("RON", 2),
+    currency("RSD", 2),
+    currency("RUB", 2),
+    currency("RWF", 0),
+    currency("SAR", 2),
+    currency("SBD", 2),
+    currency("SCR", 2),
+    currency("SDG", 2),
+    currency("SEK", 2),
+    currency("SGD", 2),
+    currency("SHP", 2),
+    currency("SLE", 2),
+    currency("SOS", 2),
+    currency("SRD", 2),
+    currency("SSP", 2),
+    currency("STN", 2),
+    currency("SVC", 2),
+    currency("SYP", 2),
+    currency("SZL", 2),
+    currency("THB", 2),
+    currency("TJS", 2),
+    currency("TMT", 2),
+    currency("TND", 3),
+    currency("TOP", 2),
+    currency("TRY", 2),
+    currency("TTD", 2),
+    currency("TWD", 2),
+    currency("TZS", 2),
+    currency("UAH", 2),
+    currency("UGX", 0),
+    with_symbol("USD", 2, "$"),
+    currency("USN", 2),
+    currency("UYI", 0),
+    currency("UYU", 2),
+    currency("UYW", 4),
+    currency("UZS", 2),
+    currency("VED", 2),
+    currency("VES", 2),
+    with_symbol("VND", 0, "₫"),
+    currency("VUV", 0),
+    currency("WST", 2),
+    currency("XAF", 0),
+    without_minor_units("XAG"),
+    without_minor_units("XAU"),
+    without_minor_units("XBA"),
+    without_minor_units("XBB"),
+    without_minor_units("XBC"),
+    without_minor_units("XBD"),
+    currency("XCD", 2),
+    currency("XCG", 2),
+    without_minor_units("XDR"),
+    currency("XOF", 0),
+    without_minor_units("XPD"),
+    currency("XPF", 0),
+    without_minor_units("XPT"),
+    without_minor_units("XSU"),
+    without_minor_units("XTS"),
+    without_minor_units("XUA"),
+    without_minor_units("XXX"),
+    currency("YER", 2),
+    currency("ZAR", 2),
+    currency("ZMW", 2),
+    currency("ZWG", 2),
+];
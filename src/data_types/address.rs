@@ -1,27 +1,28 @@
+use crate::period::Period;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Address {
     #[serde(rename = "use")]
     r#use: Option<String>, // home | work | temp | old | billing
-    #[serde(rename = "type")]
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
     address_type: Option<String>, // postal | physical | both
-    #[serde(rename = "text")]
+    #[serde(rename = "text", skip_serializing_if = "Option::is_none")]
     text: Option<String>,
-    #[serde(rename = "line")]
+    #[serde(rename = "line", default, skip_serializing_if = "Vec::is_empty")]
     line: Vec<String>,
-    #[serde(rename = "city")]
+    #[serde(rename = "city", skip_serializing_if = "Option::is_none")]
     city: Option<String>,
-    #[serde(rename = "district")]
+    #[serde(rename = "district", skip_serializing_if = "Option::is_none")]
     district: Option<String>,
-    #[serde(rename = "state")]
+    #[serde(rename = "state", skip_serializing_if = "Option::is_none")]
     state: Option<String>,
-    #[serde(rename = "postalCode")]
+    #[serde(rename = "postalCode", skip_serializing_if = "Option::is_none")]
     postal_code: Option<String>,
-    #[serde(rename = "country")]
+    #[serde(rename = "country", skip_serializing_if = "Option::is_none")]
     country: Option<String>,
-    #[serde(rename = "period")]
-    period: Option<String>,
+    #[serde(rename = "period", skip_serializing_if = "Option::is_none")]
+    period: Option<Period>,
 }
 
 impl Address {
@@ -103,8 +104,8 @@ impl Address {
     }
 
     /// Returns the period.
-    pub fn period(&self) -> Option<&str> {
-        self.period.as_deref()
+    pub fn period(&self) -> Option<&Period> {
+        self.period.as_ref()
     }
 
     /// Sets the use value.
@@ -158,7 +159,7 @@ impl Address {
     }
 
     /// Sets the period.
-    pub fn set_period(&mut self, period: Option<String>) {
+    pub fn set_period(&mut self, period: Option<Period>) {
         self.period = period;
     }
 }
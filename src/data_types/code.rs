@@ -47,11 +47,6 @@ impl Code {
         &self.value
     }
 
-    /// Returns the code value as a string.
-    pub fn to_string(&self) -> String {
-        self.value.clone()
-    }
-
     /// Validates the code according to FHIR specifications.
     pub fn validate(&self) -> Result<(), CodeError> {
         // Check if empty
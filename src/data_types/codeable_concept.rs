@@ -2,23 +2,23 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CodeableConcept {
-    #[serde(rename = "coding")]
+    #[serde(rename = "coding", default, skip_serializing_if = "Vec::is_empty")]
     coding: Vec<Coding>,
-    #[serde(rename = "text")]
+    #[serde(rename = "text", skip_serializing_if = "Option::is_none")]
     text: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Coding {
-    #[serde(rename = "system")]
+    #[serde(rename = "system", skip_serializing_if = "Option::is_none")]
     system: Option<String>,
-    #[serde(rename = "version")]
+    #[serde(rename = "version", skip_serializing_if = "Option::is_none")]
     version: Option<String>,
-    #[serde(rename = "code")]
+    #[serde(rename = "code", skip_serializing_if = "Option::is_none")]
     code: Option<String>,
-    #[serde(rename = "display")]
+    #[serde(rename = "display", skip_serializing_if = "Option::is_none")]
     display: Option<String>,
-    #[serde(rename = "userSelected")]
+    #[serde(rename = "userSelected", skip_serializing_if = "Option::is_none")]
     user_selected: Option<bool>,
 }
 
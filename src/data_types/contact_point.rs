@@ -1,3 +1,4 @@
+use crate::period::Period;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -8,10 +9,10 @@ pub struct ContactPoint {
     value: String,
     #[serde(rename = "use")]
     r#use: Option<String>, // home | work | temp | old | mobile
-    #[serde(rename = "rank")]
+    #[serde(rename = "rank", skip_serializing_if = "Option::is_none")]
     rank: Option<i32>,
-    #[serde(rename = "period")]
-    period: Option<String>,
+    #[serde(rename = "period", skip_serializing_if = "Option::is_none")]
+    period: Option<Period>,
 }
 
 impl ContactPoint {
@@ -32,7 +33,7 @@ impl ContactPoint {
         value: String,
         use_value: Option<String>,
         rank: Option<i32>,
-        period: Option<String>,
+        period: Option<Period>,
     ) -> Self {
         Self {
             system,
@@ -64,8 +65,8 @@ impl ContactPoint {
     }
 
     /// Returns the period.
-    pub fn period(&self) -> Option<&str> {
-        self.period.as_deref()
+    pub fn period(&self) -> Option<&Period> {
+        self.period.as_ref()
     }
 
     /// Sets the system.
@@ -89,7 +90,7 @@ impl ContactPoint {
     }
 
     /// Sets the period.
-    pub fn set_period(&mut self, period: Option<String>) {
+    pub fn set_period(&mut self, period: Option<Period>) {
         self.period = period;
     }
 } 
\ No newline at end of file
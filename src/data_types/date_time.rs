@@ -0,0 +1,688 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use datetime::{DatePiece, LocalDate, LocalDateTime, LocalTime, Month, TimePiece};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DateTimeError {
+    #[error("Invalid {0}: {1}")]
+    InvalidFormat(&'static str, String),
+    #[error("No such calendar date: {0}")]
+    InvalidDate(String),
+    #[error("A time zone is needed: {0}")]
+    MissingTimeZone(String),
+    #[error("Invalid time zone offset: {0} minutes")]
+    InvalidOffset(i16),
+}
+
+/// How much of a date or time is known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Precision {
+    Year,
+    Month,
+    Day,
+    Second,
+    /// Fractional seconds with the given number of digits, 1 to 9.
+    Fraction(u8),
+}
+
+/// A FHIR `date`: `YYYY`, `YYYY-MM` or `YYYY-MM-DD`, without a time zone.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Date {
+    text: String,
+    parts: Parts,
+}
+
+/// A FHIR `dateTime`: a date at any precision, or a date and time to the
+/// second or fraction of a second, usually with a time zone offset.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DateTime {
+    text: String,
+    parts: Parts,
+}
+
+/// A FHIR `instant`: a date and time to at least the second, with a time zone.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Instant {
+    text: String,
+    parts: Parts,
+}
+
+/// A FHIR `time` of day: `hh:mm:ss` with optional fractional seconds.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Time {
+    text: String,
+    parts: Parts,
+}
+
+/// The parsed fields of a value. Fields beyond the precision are zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Parts {
+    year: i64,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+    nanosecond: u32,
+    precision: Precision,
+    /// The time zone offset in minutes east of UTC.
+    offset: Option<i16>,
+}
+
+const NANOS_PER_SECOND: i128 = 1_000_000_000;
+
+/// The largest time zone offset, 14:00, in minutes.
+const MAX_OFFSET: i16 = 14 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Date,
+    DateTime,
+    Instant,
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Date => "date",
+            Kind::DateTime => "dateTime",
+            Kind::Instant => "instant",
+        }
+    }
+}
+
+impl Date {
+    /// Parses and validates a date.
+    pub fn parse(text: &str) -> Result<Self, DateTimeError> {
+        Ok(Self {
+            text: text.to_string(),
+            parts: parse(text, Kind::Date)?,
+        })
+    }
+
+    /// Returns the date exactly as written.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the precision: year, month or day.
+    pub fn precision(&self) -> Precision {
+        self.parts.precision
+    }
+
+    /// Returns the year.
+    pub fn year(&self) -> i64 {
+        self.parts.year
+    }
+
+    /// Returns the month, from 1, if known.
+    pub fn month(&self) -> Option<u8> {
+        self.parts.field(Precision::Month, self.parts.month)
+    }
+
+    /// Returns the day of the month, if known.
+    pub fn day(&self) -> Option<u8> {
+        self.parts.field(Precision::Day, self.parts.day)
+    }
+
+    /// Compares two dates. Returns `None` when the answer depends on the
+    /// unknown part of the less precise one, e.g. `2020` and `2020-06`.
+    pub fn compare(&self, other: &Date) -> Option<Ordering> {
+        self.parts.compare(&other.parts)
+    }
+}
+
+impl DateTime {
+    /// Parses and validates a dateTime.
+    pub fn parse(text: &str) -> Result<Self, DateTimeError> {
+        Ok(Self {
+            text: text.to_string(),
+            parts: parse(text, Kind::DateTime)?,
+        })
+    }
+
+    /// Returns the dateTime exactly as written.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the precision.
+    pub fn precision(&self) -> Precision {
+        self.parts.precision
+    }
+
+    /// Returns true if the value includes a time of day.
+    pub fn has_time(&self) -> bool {
+        self.parts.precision >= Precision::Second
+    }
+
+    /// Returns the year.
+    pub fn year(&self) -> i64 {
+        self.parts.year
+    }
+
+    /// Returns the month, from 1, if known.
+    pub fn month(&self) -> Option<u8> {
+        self.parts.field(Precision::Month, self.parts.month)
+    }
+
+    /// Returns the day of the month, if known.
+    pub fn day(&self) -> Option<u8> {
+        self.parts.field(Precision::Day, self.parts.day)
+    }
+
+    /// Returns the time of day, if known.
+    pub fn time(&self) -> Option<Time> {
+        self.has_time().then(|| Time::from_parts(&self.parts))
+    }
+
+    /// Returns the time zone offset in minutes east of UTC, if given.
+    pub fn offset(&self) -> Option<i16> {
+        self.parts.offset
+    }
+
+    /// Returns the date part at its precision, dropping any time.
+    pub fn date(&self) -> Date {
+        let text = self.text.split('T').next().unwrap_or_default().to_string();
+        let mut parts = self.parts;
+        parts.precision = parts.precision.min(Precision::Day);
+        parts.hour = 0;
+        parts.minute = 0;
+        parts.second = 0;
+        parts.nanosecond = 0;
+        parts.offset = None;
+        Date { text, parts }
+    }
+
+    /// Returns the same moment written in UTC with a `Z` zone, keeping the
+    /// precision. Values without a time are returned unchanged; a time
+    /// without a zone cannot be placed and fails with `MissingTimeZone`.
+    pub fn to_utc(&self) -> Result<DateTime, DateTimeError> {
+        if !self.has_time() {
+            return Ok(self.clone());
+        }
+        let parts = self.parts.in_utc().ok_or_else(|| DateTimeError::MissingTimeZone(self.text.clone()))?;
+        Ok(Self {
+            text: parts.format(),
+            parts,
+        })
+    }
+
+    /// Gives a time without a zone the offset, in minutes east of UTC, of the
+    /// site that recorded it. Values with a zone or without a time are
+    /// returned unchanged.
+    pub fn assume_offset(&self, offset: i16) -> Result<DateTime, DateTimeError> {
+        if offset.abs() > MAX_OFFSET {
+            return Err(DateTimeError::InvalidOffset(offset));
+        }
+        if !self.has_time() || self.parts.offset.is_some() {
+            return Ok(self.clone());
+        }
+        let parts = Parts {
+            offset: Some(offset),
+            ..self.parts
+        };
+        Ok(Self {
+            text: parts.format(),
+            parts,
+        })
+    }
+
+    /// Returns the nanoseconds since the epoch covered by the value, as a
+    /// half-open range for a date and a single point for a time. A value
+    /// without a zone is read in UTC.
+    pub(crate) fn span(&self) -> (i128, i128) {
+        self.parts.span()
+    }
+
+    /// Returns the value as an instant when it has a time and a zone.
+    pub fn to_instant(&self) -> Option<Instant> {
+        (self.has_time() && self.parts.offset.is_some()).then(|| Instant {
+            text: self.text.clone(),
+            parts: self.parts,
+        })
+    }
+
+    /// Compares two dateTimes. Times with zones are compared as moments in
+    /// UTC, so `10:00:00+01:00` equals `09:00:00Z`, and times without zones
+    /// are compared as written. Returns `None` when the answer depends on
+    /// the unknown part of the less precise one, or when only one of two
+    /// times has a zone; see [`DateTime::assume_offset`].
+    pub fn compare(&self, other: &DateTime) -> Option<Ordering> {
+        self.parts.compare(&other.parts)
+    }
+}
+
+impl Instant {
+    /// Parses and validates an instant.
+    pub fn parse(text: &str) -> Result<Self, DateTimeError> {
+        Ok(Self {
+            text: text.to_string(),
+            parts: parse(text, Kind::Instant)?,
+        })
+    }
+
+    /// Returns the current time in UTC, to the millisecond.
+    pub fn now() -> Self {
+        Self::parse(&crate::meta::now()).expect("the clock yields a valid instant")
+    }
+
+    /// Returns the instant exactly as written.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the precision: seconds or a fraction of a second.
+    pub fn precision(&self) -> Precision {
+        self.parts.precision
+    }
+
+    /// Returns the time zone offset in minutes east of UTC.
+    pub fn offset(&self) -> i16 {
+        self.parts.offset.unwrap_or_default()
+    }
+
+    /// Returns the whole seconds since 1970-01-01T00:00:00Z.
+    pub fn epoch_seconds(&self) -> i64 {
+        self.parts.utc_seconds().unwrap_or_default()
+    }
+
+    /// Returns the fraction of the second in nanoseconds.
+    pub fn nanosecond(&self) -> u32 {
+        self.parts.nanosecond
+    }
+
+    /// Returns the nanoseconds since the epoch.
+    pub(crate) fn epoch_nanos(&self) -> i128 {
+        self.parts.epoch_nanos()
+    }
+
+    /// Returns the same moment written in UTC with a `Z` zone.
+    pub fn to_utc(&self) -> Instant {
+        let parts = self.parts.in_utc().unwrap_or(self.parts);
+        Self {
+            text: parts.format(),
+            parts,
+        }
+    }
+
+    /// Orders two instants as moments in time, whatever their offsets, so
+    /// `Account.calculated_at` values from different sites sort correctly.
+    pub fn compare(&self, other: &Instant) -> Ordering {
+        (self.epoch_seconds(), self.nanosecond()).cmp(&(other.epoch_seconds(), other.nanosecond()))
+    }
+}
+
+impl Time {
+    /// Parses and validates a time of day.
+    pub fn parse(text: &str) -> Result<Self, DateTimeError> {
+        let invalid = || DateTimeError::InvalidFormat("time", text.to_string());
+        let (hour, minute, second, nanosecond, precision) = parse_time(text).ok_or_else(invalid)?;
+        let parts = Parts {
+            year: 0,
+            month: 0,
+            day: 0,
+            hour,
+            minute,
+            second,
+            nanosecond,
+            precision,
+            offset: None,
+        };
+        Ok(Self {
+            text: text.to_string(),
+            parts,
+        })
+    }
+
+    fn from_parts(parts: &Parts) -> Self {
+        let mut text = format!("{:02}:{:02}:{:02}", parts.hour, parts.minute, parts.second);
+        if let Precision::Fraction(digits) = parts.precision {
+            let fraction = format!("{:09}", parts.nanosecond);
+            text.push('.');
+            text.push_str(&fraction[..digits as usize]);
+        }
+        Self {
+            text,
+            parts: Parts {
+                year: 0,
+                month: 0,
+                day: 0,
+                offset: None,
+                ..*parts
+            },
+        }
+    }
+
+    /// Returns the time exactly as written.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the precision: seconds or a fraction of a second.
+    pub fn precision(&self) -> Precision {
+        self.parts.precision
+    }
+
+    /// Returns the hour, 0 to 23.
+    pub fn hour(&self) -> u8 {
+        self.parts.hour
+    }
+
+    /// Returns the minute.
+    pub fn minute(&self) -> u8 {
+        self.parts.minute
+    }
+
+    /// Returns the second, 60 for a leap second.
+    pub fn second(&self) -> u8 {
+        self.parts.second
+    }
+
+    /// Returns the fraction of the second in nanoseconds.
+    pub fn nanosecond(&self) -> u32 {
+        self.parts.nanosecond
+    }
+
+    /// Compares two times of day.
+    pub fn compare(&self, other: &Time) -> Ordering {
+        self.parts.time().cmp(&other.parts.time())
+    }
+}
+
+impl Parts {
+    fn field(&self, precision: Precision, value: u8) -> Option<u8> {
+        (self.precision >= precision).then_some(value)
+    }
+
+    fn time(&self) -> (u8, u8, u8, u32) {
+        (self.hour, self.minute, self.second, self.nanosecond)
+    }
+
+    /// Seconds since 1970-01-01T00:00:00 on the clock as written. A leap
+    /// second counts as the first second of the next minute.
+    fn local_seconds(&self) -> i64 {
+        let month = Month::from_one(self.month.max(1) as i8).expect("validated when parsed");
+        let date = LocalDate::ymd(self.year, month, self.day.max(1) as i8).expect("validated when parsed");
+        LocalDateTime::new(date, LocalTime::midnight()).to_instant().seconds()
+            + i64::from(self.hour) * 3600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second)
+    }
+
+    /// Seconds since 1970-01-01T00:00:00Z, for a time with a zone.
+    fn utc_seconds(&self) -> Option<i64> {
+        let offset = self.offset?;
+        (self.precision >= Precision::Second).then(|| self.local_seconds() - i64::from(offset) * 60)
+    }
+
+    /// Nanoseconds since 1970-01-01T00:00:00Z, reading a value without a
+    /// zone in UTC.
+    fn epoch_nanos(&self) -> i128 {
+        let seconds = self.local_seconds() - i64::from(self.offset.unwrap_or_default()) * 60;
+        i128::from(seconds) * NANOS_PER_SECOND + i128::from(self.nanosecond)
+    }
+
+    /// The range covered: up to the start of the next year, month or day for
+    /// a date, or just the moment for a time.
+    fn span(&self) -> (i128, i128) {
+        let start = self.epoch_nanos();
+        let end = match self.precision {
+            Precision::Year => Parts { year: self.year + 1, ..*self }.epoch_nanos(),
+            Precision::Month if self.month == 12 => Parts { year: self.year + 1, month: 1, ..*self }.epoch_nanos(),
+            Precision::Month => Parts { month: self.month + 1, ..*self }.epoch_nanos(),
+            Precision::Day => start + 86_400 * NANOS_PER_SECOND,
+            Precision::Second | Precision::Fraction(_) => start,
+        };
+        (start, end)
+    }
+
+    /// Moves a time with a zone to UTC, keeping a leap second as second 60.
+    fn in_utc(&self) -> Option<Parts> {
+        let leap_second = self.second == 60;
+        let seconds = self.utc_seconds()? - i64::from(leap_second);
+        let utc = LocalDateTime::at(seconds);
+        Some(Parts {
+            year: utc.year(),
+            month: utc.month().months_from_january() as u8 + 1,
+            day: utc.day() as u8,
+            hour: utc.hour() as u8,
+            minute: utc.minute() as u8,
+            second: utc.second() as u8 + u8::from(leap_second),
+            offset: Some(0),
+            ..*self
+        })
+    }
+
+    /// Writes the parts back as text at their precision.
+    fn format(&self) -> String {
+        let mut text = format!("{:04}", self.year);
+        if self.precision >= Precision::Month {
+            text.push_str(&format!("-{:02}", self.month));
+        }
+        if self.precision >= Precision::Day {
+            text.push_str(&format!("-{:02}", self.day));
+        }
+        if self.precision >= Precision::Second {
+            text.push('T');
+            text.push_str(Time::from_parts(self).as_str());
+            match self.offset {
+                Some(0) => text.push('Z'),
+                Some(offset) => {
+                    let sign = if offset < 0 { '-' } else { '+' };
+                    text.push_str(&format!("{}{:02}:{:02}", sign, offset.abs() / 60, offset.abs() % 60));
+                }
+                None => {}
+            }
+        }
+        text
+    }
+
+    /// Compares field by field down to the coarser precision. Seconds and
+    /// fractions of a second count as the same precision, and two times
+    /// with zones are compared in UTC.
+    fn compare(&self, other: &Parts) -> Option<Ordering> {
+        let levels = [
+            (Precision::Year, self.year.cmp(&other.year)),
+            (Precision::Month, self.month.cmp(&other.month)),
+            (Precision::Day, self.day.cmp(&other.day)),
+            (Precision::Second, self.time().cmp(&other.time())),
+        ];
+        let common = self.precision.min(other.precision).min(Precision::Second);
+        if common == Precision::Second {
+            return match (self.utc_seconds(), other.utc_seconds()) {
+                (Some(seconds), Some(other_seconds)) => {
+                    Some((seconds, self.nanosecond).cmp(&(other_seconds, other.nanosecond)))
+                }
+                (None, None) => Some(levels.iter().map(|(_, ordering)| *ordering).fold(Ordering::Equal, Ordering::then)),
+                _ => None,
+            };
+        }
+        for (level, ordering) in levels {
+            if level > common {
+                break;
+            }
+            if ordering != Ordering::Equal {
+                return Some(ordering);
+            }
+        }
+        let same_precision = self.precision.min(Precision::Second) == other.precision.min(Precision::Second);
+        same_precision.then_some(Ordering::Equal)
+    }
+}
+
+/// Parses `YYYY[-MM[-DD[Thh:mm:ss[.fffffffff][Z|(+|-)hh:mm]]]]` for a kind.
+fn parse(text: &str, kind: Kind) -> Result<Parts, DateTimeError> {
+    let invalid = || DateTimeError::InvalidFormat(kind.name(), text.to_string());
+    let (date, time) = match text.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (text, None),
+    };
+
+    let mut fields = date.split('-');
+    let year = fields.next().and_then(|year| number(year, 4)).filter(|year| *year >= 1).ok_or_else(invalid)?;
+    let month = fields.next().map(|month| number(month, 2).filter(|month| (1..=12).contains(month)));
+    let day = fields.next().map(|day| number(day, 2).filter(|day| (1..=31).contains(day)));
+    if fields.next().is_some() || month == Some(None) || day == Some(None) {
+        return Err(invalid());
+    }
+    let mut parts = Parts {
+        year: i64::from(year),
+        month: month.flatten().unwrap_or(0) as u8,
+        day: day.flatten().unwrap_or(0) as u8,
+        hour: 0,
+        minute: 0,
+        second: 0,
+        nanosecond: 0,
+        precision: match (month, day) {
+            (None, _) => Precision::Year,
+            (Some(_), None) => Precision::Month,
+            _ => Precision::Day,
+        },
+        offset: None,
+    };
+    if parts.precision == Precision::Day {
+        let month = Month::from_one(parts.month as i8).map_err(|_| invalid())?;
+        LocalDate::ymd(parts.year, month, parts.day as i8).map_err(|_| DateTimeError::InvalidDate(text.to_string()))?;
+    }
+
+    match (kind, time) {
+        (Kind::Date, Some(_)) => return Err(invalid()),
+        (Kind::Instant, None) => return Err(invalid()),
+        (_, Some(_)) if parts.precision != Precision::Day => return Err(invalid()),
+        (_, Some(time)) => {
+            let zone_start = time.find(['Z', '+', '-']).unwrap_or(time.len());
+            let (hour, minute, second, nanosecond, precision) = parse_time(&time[..zone_start]).ok_or_else(invalid)?;
+            parts.hour = hour;
+            parts.minute = minute;
+            parts.second = second;
+            parts.nanosecond = nanosecond;
+            parts.precision = precision;
+            parts.offset = match &time[zone_start..] {
+                "" => None,
+                zone => Some(parse_offset(zone).ok_or_else(invalid)?),
+            };
+        }
+        _ => {}
+    }
+    if kind == Kind::Instant && parts.offset.is_none() {
+        return Err(DateTimeError::MissingTimeZone(text.to_string()));
+    }
+    Ok(parts)
+}
+
+/// Parses `hh:mm:ss[.fffffffff]`.
+fn parse_time(text: &str) -> Option<(u8, u8, u8, u32, Precision)> {
+    let (clock, fraction) = match text.split_once('.') {
+        Some((clock, fraction)) => (clock, Some(fraction)),
+        None => (text, None),
+    };
+    let mut fields = clock.split(':');
+    let hour = fields.next().and_then(|hour| number(hour, 2)).filter(|hour| *hour <= 23)?;
+    let minute = fields.next().and_then(|minute| number(minute, 2)).filter(|minute| *minute <= 59)?;
+    let second = fields.next().and_then(|second| number(second, 2)).filter(|second| *second <= 60)?;
+    if fields.next().is_some() {
+        return None;
+    }
+    let (nanosecond, precision) = match fraction {
+        None => (0, Precision::Second),
+        Some(fraction) => {
+            let digits = fraction.len();
+            let value = number(fraction, digits).filter(|_| (1..=9).contains(&digits))?;
+            (value * 10u32.pow(9 - digits as u32), Precision::Fraction(digits as u8))
+        }
+    };
+    Some((hour as u8, minute as u8, second as u8, nanosecond, precision))
+}
+
+/// Parses `Z` or `(+|-)hh:mm`, up to 14:00 either way, into minutes east of UTC.
+fn parse_offset(text: &str) -> Option<i16> {
+    if text == "Z" {
+        return Some(0);
+    }
+    let sign = match text.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let (hours, minutes) = text[1..].split_once(':')?;
+    let hours = number(hours, 2)?;
+    let minutes = number(minutes, 2).filter(|minutes| *minutes <= 59)?;
+    let offset = (hours * 60 + minutes) as i16;
+    (offset <= MAX_OFFSET).then_some(sign * offset)
+}
+
+/// Parses exactly `digits` ASCII digits.
+fn number(text: &str, digits: usize) -> Option<u32> {
+    (text.len() == digits && text.bytes().all(|byte| byte.is_ascii_digit()))
+        .then(|| text.parse().ok())
+        .flatten()
+}
+
+impl From<Date> for DateTime {
+    fn from(date: Date) -> Self {
+        Self {
+            text: date.text,
+            parts: date.parts,
+        }
+    }
+}
+
+impl From<Instant> for DateTime {
+    fn from(instant: Instant) -> Self {
+        Self {
+            text: instant.text,
+            parts: instant.parts,
+        }
+    }
+}
+
+macro_rules! impl_text {
+    ($type:ty) => {
+        impl FromStr for $type {
+            type Err = DateTimeError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::parse(s)
+            }
+        }
+
+        impl TryFrom<String> for $type {
+            type Error = DateTimeError;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                Self::parse(&value)
+            }
+        }
+
+        impl From<$type> for String {
+            fn from(value: $type) -> Self {
+                value.text
+            }
+        }
+
+        impl fmt::Display for $type {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.text)
+            }
+        }
+
+        impl AsRef<str> for $type {
+            fn as_ref(&self) -> &str {
+                &self.text
+            }
+        }
+    };
+}
+
+impl_text!(Date);
+impl_text!(DateTime);
+impl_text!(Instant);
+impl_text!(Time);
@@ -0,0 +1,267 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Neg;
+use std::str::FromStr;
+
+use serde::de::Error as _;
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The most significant digits a decimal can hold.
+const MAX_DIGITS: u32 = 38;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DecimalError {
+    #[error("Invalid decimal: {0}")]
+    InvalidFormat(String),
+    #[error("Decimal out of range: {0}")]
+    OutOfRange(String),
+}
+
+/// A FHIR `decimal`. The value is kept exactly, as `digits × 10^-scale`,
+/// together with the text it was written as, so `10.50` stays `10.50`.
+/// Arithmetic is exact and results keep the precision of their operands:
+/// `10.50 + 1.5` is `12.00`. Up to 38 significant digits are supported.
+///
+/// Equality compares the written form; use [`Decimal::compare`] to compare
+/// values, under which `10.5` and `10.50` are equal.
+///
+/// JSON keeps the written digits only with the `exact-decimal` feature,
+/// which enables `serde_json/arbitrary_precision` for the whole build.
+/// Without it, decimals are read and written through `f64`, so `10.50`
+/// becomes `10.5` and digits beyond the 17th are rounded.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    text: String,
+    digits: i128,
+    scale: i32,
+}
+
+impl Decimal {
+    /// Parses `-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?`.
+    pub fn parse(text: &str) -> Result<Self, DecimalError> {
+        let invalid = || DecimalError::InvalidFormat(text.to_string());
+        let out_of_range = || DecimalError::OutOfRange(text.to_string());
+        let (negative, unsigned) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        let (number, exponent) = match unsigned.split_once(['e', 'E']) {
+            Some((number, exponent)) => (number, Some(exponent)),
+            None => (unsigned, None),
+        };
+        let (integer, fraction) = match number.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (number, None),
+        };
+        let is_digits = |part: &str| !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_digit());
+        if !is_digits(integer) || (integer.len() > 1 && integer.starts_with('0')) || !fraction.is_none_or(is_digits) {
+            return Err(invalid());
+        }
+        let exponent: i32 = match exponent {
+            Some(exponent) => {
+                let unsigned = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+                if !is_digits(unsigned) {
+                    return Err(invalid());
+                }
+                exponent.parse().map_err(|_| out_of_range())?
+            }
+            None => 0,
+        };
+
+        let fraction = fraction.unwrap_or_default();
+        let significant = format!("{}{}", integer, fraction);
+        let significant = significant.trim_start_matches('0');
+        if significant.len() > MAX_DIGITS as usize {
+            return Err(out_of_range());
+        }
+        let magnitude: i128 = if significant.is_empty() { 0 } else { significant.parse().map_err(|_| out_of_range())? };
+        let scale = (fraction.len() as i32).checked_sub(exponent).ok_or_else(out_of_range)?;
+        if scale.unsigned_abs() > MAX_DIGITS {
+            return Err(out_of_range());
+        }
+        Ok(Self {
+            text: text.to_string(),
+            digits: if negative { -magnitude } else { magnitude },
+            scale,
+        })
+    }
+
+    /// Builds `digits × 10^-scale`, written in plain notation.
+    ///
+    /// # Panics
+    ///
+    /// If the digits or the scale exceed 38 digits.
+    pub fn new(digits: i128, scale: u32) -> Self {
+        i32::try_from(scale)
+            .ok()
+            .and_then(|scale| Self::from_parts(digits, scale))
+            .expect("at most 38 digits")
+    }
+
+    /// Writes `digits × 10^-scale` in plain notation, padding a negative
+    /// scale with zeros, or `None` if the result exceeds 38 digits.
+    fn from_parts(digits: i128, scale: i32) -> Option<Self> {
+        let (digits, scale) = match u32::try_from(-scale) {
+            Ok(shift) if shift > 0 => (digits.checked_mul(10i128.checked_pow(shift)?)?, 0),
+            _ => (digits, scale),
+        };
+        if digits.unsigned_abs() >= 10u128.pow(MAX_DIGITS) || scale > MAX_DIGITS as i32 {
+            return None;
+        }
+        let magnitude = digits.unsigned_abs().to_string();
+        let mut text = if digits < 0 { "-".to_string() } else { String::new() };
+        if scale == 0 {
+            text.push_str(&magnitude);
+        } else {
+            let scale = scale as usize;
+            let padded = format!("{:0>width$}", magnitude, width = scale + 1);
+            let (integer, fraction) = padded.split_at(padded.len() - scale);
+            text.push_str(integer);
+            text.push('.');
+            text.push_str(fraction);
+        }
+        Some(Self { text, digits, scale })
+    }
+
+    /// Returns the decimal exactly as written.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the number of digits after the decimal point, which is the
+    /// precision the value was written with.
+    pub fn scale(&self) -> u32 {
+        self.scale.max(0) as u32
+    }
+
+    /// Returns true if the value is zero.
+    pub fn is_zero(&self) -> bool {
+        self.digits == 0
+    }
+
+    /// Returns true if the value is below zero.
+    pub fn is_negative(&self) -> bool {
+        self.digits < 0
+    }
+
+    /// Returns the absolute value.
+    pub fn abs(&self) -> Decimal {
+        if self.is_negative() { -self.clone() } else { self.clone() }
+    }
+
+    /// Adds exactly, or `None` if the result exceeds 38 digits.
+    pub fn checked_add(&self, other: &Decimal) -> Option<Decimal> {
+        let scale = self.scale.max(other.scale);
+        let digits = self.rescaled(scale)?.checked_add(other.rescaled(scale)?)?;
+        Self::from_parts(digits, scale)
+    }
+
+    /// Subtracts exactly, or `None` if the result exceeds 38 digits.
+    pub fn checked_sub(&self, other: &Decimal) -> Option<Decimal> {
+        self.checked_add(&-other.clone())
+    }
+
+    /// Multiplies exactly, or `None` if the result exceeds 38 digits.
+    pub fn checked_mul(&self, other: &Decimal) -> Option<Decimal> {
+        let digits = self.digits.checked_mul(other.digits)?;
+        let scale = self.scale.checked_add(other.scale)?;
+        Self::from_parts(digits, scale)
+    }
+
+    /// Rounds or pads to exactly `scale` digits after the point, rounding
+    /// halves away from zero: `2.345` to 2 is `2.35`, `10.5` to 2 is `10.50`.
+    /// `None` if the result exceeds 38 digits.
+    pub fn round(&self, scale: u32) -> Option<Decimal> {
+        let target = i32::try_from(scale).ok()?;
+        if target >= self.scale {
+            return Self::from_parts(self.rescaled(target)?, target);
+        }
+        let divisor = 10i128.checked_pow((self.scale - target) as u32)?;
+        let mut digits = self.digits / divisor;
+        if (self.digits % divisor).unsigned_abs() * 2 >= divisor.unsigned_abs() {
+            digits += self.digits.signum();
+        }
+        Self::from_parts(digits, target)
+    }
+
+    /// Compares the values, ignoring how they are written.
+    pub fn compare(&self, other: &Decimal) -> Ordering {
+        let sign = |digits: i128| digits.signum();
+        if sign(self.digits) != sign(other.digits) || self.digits == 0 {
+            return sign(self.digits).cmp(&sign(other.digits));
+        }
+        // Same sign: compare magnitudes by their leading power of ten first,
+        // which keeps the aligned digits within range.
+        let magnitude = |decimal: &Decimal| decimal.digits.unsigned_abs().ilog10() as i32 - decimal.scale;
+        let ordering = magnitude(self).cmp(&magnitude(other)).then_with(|| {
+            let scale = self.scale.max(other.scale);
+            let aligned = |decimal: &Decimal| decimal.rescaled(scale).map(i128::unsigned_abs);
+            aligned(self).cmp(&aligned(other))
+        });
+        if self.digits < 0 { ordering.reverse() } else { ordering }
+    }
+
+    /// Returns the nearest `f64`.
+    pub fn to_f64(&self) -> f64 {
+        self.text.parse().unwrap_or(f64::NAN)
+    }
+
+    /// The digits at a larger scale, or `None` on overflow.
+    fn rescaled(&self, scale: i32) -> Option<i128> {
+        let shift = u32::try_from(scale - self.scale).ok()?;
+        self.digits.checked_mul(10i128.checked_pow(shift)?)
+    }
+}
+
+impl Neg for Decimal {
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal {
+        let text = match self.text.strip_prefix('-') {
+            Some(text) => text.to_string(),
+            None => format!("-{}", self.text),
+        };
+        Decimal {
+            text,
+            digits: -self.digits,
+            scale: self.scale,
+        }
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(value: i64) -> Self {
+        Self::new(i128::from(value), 0)
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = DecimalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// Written as a JSON number with the original digits.
+impl Serialize for Decimal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde_json::Number::from_str(&self.text)
+            .map_err(S::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let number = serde_json::Number::deserialize(deserializer)?;
+        Self::parse(&number.to_string()).map_err(D::Error::custom)
+    }
+}
@@ -4,3 +4,6 @@ pub mod contact_point;
 pub mod address;
 pub mod codeable_concept;
 pub mod reference;
+pub mod date_time;
+pub mod decimal;
+pub mod positive_int;
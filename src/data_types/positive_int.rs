@@ -0,0 +1,68 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// The largest value a FHIR integer can hold.
+const MAX_VALUE: u32 = i32::MAX as u32;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PositiveIntError {
+    #[error("Invalid positiveInt: {0}")]
+    InvalidFormat(String),
+    #[error("positiveInt out of range: {0}")]
+    OutOfRange(u32),
+}
+
+/// A FHIR `positiveInt`: a whole number from 1 to 2,147,483,647, written
+/// as a JSON number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u32", into = "u32")]
+pub struct PositiveInt(u32);
+
+impl PositiveInt {
+    pub fn new(value: u32) -> Result<Self, PositiveIntError> {
+        if value == 0 || value > MAX_VALUE {
+            return Err(PositiveIntError::OutOfRange(value));
+        }
+        Ok(Self(value))
+    }
+
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+}
+
+impl TryFrom<u32> for PositiveInt {
+    type Error = PositiveIntError;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<PositiveInt> for u32 {
+    fn from(value: PositiveInt) -> Self {
+        value.0
+    }
+}
+
+/// Parses `+?[1-9][0-9]*`, the lexical form of a positiveInt.
+impl FromStr for PositiveInt {
+    type Err = PositiveIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix('+').unwrap_or(s);
+        if digits.is_empty() || digits.starts_with('0') || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(PositiveIntError::InvalidFormat(s.to_string()));
+        }
+        let value = digits.parse().map_err(|_| PositiveIntError::InvalidFormat(s.to_string()))?;
+        Self::new(value)
+    }
+}
+
+impl fmt::Display for PositiveInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
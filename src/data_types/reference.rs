@@ -2,13 +2,13 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Reference {
-    #[serde(rename = "reference")]
+    #[serde(rename = "reference", skip_serializing_if = "Option::is_none")]
     reference: Option<String>,
-    #[serde(rename = "type")]
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
     reference_type: Option<String>,
-    #[serde(rename = "identifier")]
+    #[serde(rename = "identifier", skip_serializing_if = "Option::is_none")]
     identifier: Option<String>,
-    #[serde(rename = "display")]
+    #[serde(rename = "display", skip_serializing_if = "Option::is_none")]
     display: Option<String>,
 }
 
@@ -63,6 +63,28 @@ impl Reference {
         self.display.as_deref()
     }
 
+    /// Returns the resource type and id of a literal reference such as
+    /// `Patient/123` or `http://example.org/fhir/Patient/123/_history/2`.
+    ///
+    /// Returns `None` for contained (`#id`) and `urn:` references.
+    pub fn target(&self) -> Option<(&str, &str)> {
+        let reference = self.reference.as_deref()?;
+        if reference.starts_with('#') || reference.starts_with("urn:") {
+            return None;
+        }
+        let path = reference.split(['?', '#']).next()?;
+        let mut segments: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+        if let Some(history) = segments.iter().rposition(|segment| *segment == "_history") {
+            segments.truncate(history);
+        }
+        match segments.as_slice() {
+            [.., resource_type, id] if resource_type.starts_with(|c: char| c.is_ascii_uppercase()) => {
+                Some((resource_type, id))
+            }
+            _ => None,
+        }
+    }
+
     /// Sets the reference.
     pub fn set_reference(&mut self, reference: Option<String>) {
         self.reference = reference;
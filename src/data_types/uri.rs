@@ -42,11 +42,6 @@ impl Uri {
         &self.value
     }
 
-    /// Returns the URI value as a string.
-    pub fn to_string(&self) -> String {
-        self.value.clone()
-    }
-
     /// Validates the URI according to FHIR specifications.
     pub fn validate(&self) -> Result<(), UriError> {
         if self.value.is_empty() {
@@ -59,10 +54,8 @@ impl Uri {
         }
 
         // Check for UUID URIs - they must be lowercase
-        if self.value.starts_with("urn:uuid:") {
-            if self.value != self.value.to_lowercase() {
-                return Err(UriError::UuidNotLowercase(self.value.clone()));
-            }
+        if self.value.starts_with("urn:uuid:") && self.value != self.value.to_lowercase() {
+            return Err(UriError::UuidNotLowercase(self.value.clone()));
         }
 
         // Basic URI format validation
@@ -110,10 +103,10 @@ impl Uri {
         }
 
         // For absolute URIs, check for scheme
-        if let Some(scheme_part) = uri.split("://").next() {
-            if scheme_part.is_empty() || !scheme_part.chars().all(|c| c.is_alphanumeric() || c == '+' || c == '-' || c == '.') {
-                return false;
-            }
+        if let Some(scheme_part) = uri.split("://").next()
+            && (scheme_part.is_empty() || !scheme_part.chars().all(|c| c.is_alphanumeric() || c == '+' || c == '-' || c == '.'))
+        {
+            return false;
         }
 
         true
@@ -0,0 +1,276 @@
+//! Structural differences between two versions of a resource.
+//!
+//! Resources are compared element by element in their JSON form and each
+//! difference is reported at a FHIRPath-style path such as
+//! `Patient.telecom[1].value`. An absent element, `null` and an empty list
+//! are treated alike, and `meta.versionId` and `meta.lastUpdated` are ignored
+//! since they change with every version. A diff can be turned into a
+//! [`FhirPathPatch`] that transforms the old version into the new one.
+
+use std::collections::BTreeSet;
+
+use serde_json::{Map, Value};
+
+use crate::fhirpath_patch::{FhirPathOperation, FhirPathPatch};
+use crate::parameters::ParameterValue;
+use crate::resource::{AnyResource, Resource};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DiffError {
+    #[error("Cannot compare a {0} with a {1}")]
+    ResourceTypeMismatch(String, String),
+}
+
+/// How an element differs between the old and new versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// One element that differs between two versions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    path: String,
+    kind: ChangeKind,
+    old_value: Option<Value>,
+    new_value: Option<Value>,
+}
+
+/// The differences between two versions of a resource, ordered by element
+/// name and then by list index.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ResourceDiff {
+    changes: Vec<Change>,
+}
+
+/// Compares two versions of a resource.
+pub fn diff<R: Resource>(old: &R, new: &R) -> ResourceDiff {
+    diff_values(R::RESOURCE_TYPE, json(old), json(new))
+}
+
+/// Compares two versions of any resource, which must have the same type.
+pub fn diff_any(old: &AnyResource, new: &AnyResource) -> Result<ResourceDiff, DiffError> {
+    if old.resource_type() != new.resource_type() {
+        return Err(DiffError::ResourceTypeMismatch(
+            old.resource_type().to_string(),
+            new.resource_type().to_string(),
+        ));
+    }
+    Ok(diff_values(old.resource_type(), json(old), json(new)))
+}
+
+/// Compares two versions of a resource in JSON form, which must have the
+/// same `resourceType`. The counterpart of [`FhirPathPatch::apply_value`].
+pub fn diff_value(old: &Value, new: &Value) -> Result<ResourceDiff, DiffError> {
+    let resource_type = |value: &Value| value.get("resourceType").and_then(Value::as_str).unwrap_or_default().to_string();
+    let (old_type, new_type) = (resource_type(old), resource_type(new));
+    if old_type != new_type {
+        return Err(DiffError::ResourceTypeMismatch(old_type, new_type));
+    }
+    Ok(diff_values(&old_type, json(old), json(new)))
+}
+
+impl Change {
+    /// Returns the FHIRPath-style path of the element.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns how the element differs.
+    pub fn kind(&self) -> ChangeKind {
+        self.kind
+    }
+
+    /// Returns the old value, unless the element was added.
+    pub fn old_value(&self) -> Option<&Value> {
+        self.old_value.as_ref()
+    }
+
+    /// Returns the new value, unless the element was removed.
+    pub fn new_value(&self) -> Option<&Value> {
+        self.new_value.as_ref()
+    }
+}
+
+impl ResourceDiff {
+    /// Returns the differences.
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// Returns true if the versions have the same content.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns the differences of one kind.
+    pub fn of_kind(&self, kind: ChangeKind) -> impl Iterator<Item = &Change> {
+        self.changes.iter().filter(move |change| change.kind == kind)
+    }
+
+    /// Returns the change at a path.
+    pub fn change(&self, path: &str) -> Option<&Change> {
+        self.changes.iter().find(|change| change.path == path)
+    }
+
+    /// Converts the diff to a FHIRPath Patch that turns the old version into
+    /// the new one: replacements first, then deletions in reverse order so
+    /// list indexes stay valid, then additions.
+    pub fn to_fhirpath_patch(&self) -> FhirPathPatch {
+        let mut patch = FhirPathPatch::new();
+        for change in self.changes.iter().filter(|change| change.kind == ChangeKind::Changed && !change.is_structural()) {
+            if let Some(value) = &change.new_value {
+                patch.add_operation(FhirPathOperation::Replace {
+                    path: change.path.clone(),
+                    value: ParameterValue::infer(value),
+                });
+            }
+        }
+        for change in self.changes.iter().rev().filter(|change| change.kind == ChangeKind::Removed || change.is_structural()) {
+            patch.add_operation(FhirPathOperation::Delete { path: change.path.clone() });
+        }
+        for change in self.changes.iter().filter(|change| change.kind == ChangeKind::Added || change.is_structural()) {
+            if let Some(value) = &change.new_value {
+                add_element(&mut patch, &change.path, value);
+            }
+        }
+        patch
+    }
+}
+
+impl Change {
+    /// Returns true if an element changed between a primitive and a complex
+    /// value, which a patch expresses as a deletion and an addition.
+    fn is_structural(&self) -> bool {
+        self.kind == ChangeKind::Changed
+            && (self.old_value.as_ref().is_some_and(Value::is_object) || self.new_value.as_ref().is_some_and(Value::is_object))
+    }
+}
+
+fn json<T: serde::Serialize>(resource: &T) -> Value {
+    let mut value = serde_json::to_value(resource).unwrap_or(Value::Null);
+    if let Some(Value::Object(meta)) = value.get_mut("meta") {
+        meta.remove("versionId");
+        meta.remove("lastUpdated");
+    }
+    value
+}
+
+fn diff_values(resource_type: &str, old: Value, new: Value) -> ResourceDiff {
+    let mut changes = Vec::new();
+    compare(resource_type, Some(&old), Some(&new), &mut changes);
+    ResourceDiff { changes }
+}
+
+/// Returns the value unless it is absent, `null` or an empty list.
+fn present(value: Option<&Value>) -> Option<&Value> {
+    value.filter(|value| !value.is_null() && value.as_array().is_none_or(|items| !items.is_empty()))
+}
+
+fn compare(path: &str, old: Option<&Value>, new: Option<&Value>, changes: &mut Vec<Change>) {
+    match (present(old), present(new)) {
+        (None, None) => {}
+        (Some(Value::Object(old)), Some(Value::Object(new))) => {
+            let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+            for key in keys {
+                compare(&format!("{}.{}", path, key), old.get(key), new.get(key), changes);
+            }
+        }
+        (old @ (None | Some(Value::Array(_))), new @ (None | Some(Value::Array(_)))) => {
+            let empty = Vec::new();
+            let old = old.and_then(Value::as_array).unwrap_or(&empty);
+            let new = new.and_then(Value::as_array).unwrap_or(&empty);
+            for index in 0..old.len().max(new.len()) {
+                compare(&format!("{}[{}]", path, index), old.get(index), new.get(index), changes);
+            }
+        }
+        // A list replaced by a single value, or the reverse: the old items or
+        // value are removed and the new ones added
+        (Some(old @ Value::Array(_)), Some(new)) | (Some(old), Some(new @ Value::Array(_))) => {
+            compare(path, Some(old), None, changes);
+            compare(path, None, Some(new), changes);
+        }
+        (None, Some(new)) => changes.push(Change {
+            path: path.to_string(),
+            kind: ChangeKind::Added,
+            old_value: None,
+            new_value: Some(new.clone()),
+        }),
+        (Some(old), None) => changes.push(Change {
+            path: path.to_string(),
+            kind: ChangeKind::Removed,
+            old_value: Some(old.clone()),
+            new_value: None,
+        }),
+        (Some(old), Some(new)) if old != new => changes.push(Change {
+            path: path.to_string(),
+            kind: ChangeKind::Changed,
+            old_value: Some(old.clone()),
+            new_value: Some(new.clone()),
+        }),
+        _ => {}
+    }
+}
+
+/// Splits `Patient.name[0].given[1]` into the parent `Patient.name[0]`, the
+/// element name `given` and the list index `1`.
+fn split_path(path: &str) -> (&str, &str, Option<usize>) {
+    let (element, index) = match path.strip_suffix(']').and_then(|path| path.rsplit_once('[')) {
+        Some((element, index)) => (element, index.parse().ok()),
+        None => (path, None),
+    };
+    let (parent, name) = element.rsplit_once('.').unwrap_or(("", element));
+    (parent, name, index)
+}
+
+/// Adds the element at `path`. List items are inserted at their index, which
+/// also creates the list. Objects are added from parts holding their
+/// single-valued children, then their list items are inserted one by one.
+fn add_element(patch: &mut FhirPathPatch, path: &str, value: &Value) {
+    let mut lists = Vec::new();
+    let value = match value {
+        Value::Object(object) => ParameterValue::new("Element", Value::Object(parts(path, object, &mut lists))),
+        value => ParameterValue::infer(value),
+    };
+    let (parent, name, index) = split_path(path);
+    patch.add_operation(match index {
+        Some(index) => FhirPathOperation::Insert {
+            path: format!("{}.{}", parent, name),
+            value,
+            index,
+        },
+        None => FhirPathOperation::Add {
+            path: parent.to_string(),
+            name: name.to_string(),
+            value,
+        },
+    });
+    for (path, items) in lists {
+        for (index, item) in items.iter().enumerate() {
+            add_element(patch, &format!("{}[{}]", path, index), item);
+        }
+    }
+}
+
+/// Returns the single-valued children of an object, nested objects included,
+/// and collects the lists found at any depth with their paths.
+fn parts<'a>(path: &str, object: &'a Map<String, Value>, lists: &mut Vec<(String, &'a Vec<Value>)>) -> Map<String, Value> {
+    let mut parts = Map::new();
+    for (key, value) in object {
+        let child = format!("{}.{}", path, key);
+        match value {
+            Value::Null => {}
+            Value::Array(items) if items.is_empty() => {}
+            Value::Array(items) => lists.push((child, items)),
+            Value::Object(object) => {
+                parts.insert(key.clone(), Value::Object(self::parts(&child, object, lists)));
+            }
+            value => {
+                parts.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    parts
+}
@@ -0,0 +1,197 @@
+//! Currency conversion with dated exchange rates.
+//!
+//! Rates come from an [`ExchangeRates`] source; [`RateTable`] is a local
+//! table of rates with the dates they take effect. [`convert`] converts a
+//! [`Money`] value and [`restate_account`] restates every balance of an
+//! [`Account`] in the account currency, reporting the rates it used.
+
+use crate::account::Account;
+use crate::currency::Currency;
+use crate::data_types::date_time::Date;
+use crate::data_types::decimal::Decimal;
+use crate::money::{Money, MoneyError};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ConversionError {
+    #[error("No {0} to {1} exchange rate in effect on {2}")]
+    NoRate(String, String, String),
+    #[error("Invalid exchange rate {0} to {1}: {2}")]
+    InvalidRate(String, String, String),
+    #[error("Account has no ISO 4217 currency")]
+    NoAccountCurrency,
+    #[error(transparent)]
+    Money(#[from] MoneyError),
+}
+
+/// The rate to multiply an amount in one currency by to get another, from
+/// the day it takes effect.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExchangeRate {
+    from: String,
+    to: String,
+    rate: Decimal,
+    effective: Date,
+}
+
+impl ExchangeRate {
+    pub fn new(from: String, to: String, rate: Decimal, effective: Date) -> Self {
+        Self { from, to, rate, effective }
+    }
+
+    pub fn from(&self) -> &str {
+        &self.from
+    }
+
+    pub fn to(&self) -> &str {
+        &self.to
+    }
+
+    pub fn rate(&self) -> &Decimal {
+        &self.rate
+    }
+
+    pub fn effective(&self) -> &Date {
+        &self.effective
+    }
+}
+
+/// A source of exchange rates.
+pub trait ExchangeRates {
+    /// Returns the rate from one currency to another in effect on a date.
+    fn rate(&self, from: &str, to: &str, on: &Date) -> Option<ExchangeRate>;
+}
+
+/// A local table of exchange rates. The rate in effect on a date is the
+/// one with the latest effective date on or before it; only the pairs
+/// added are known, so add both directions where both are needed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RateTable {
+    rates: Vec<ExchangeRate>,
+}
+
+impl RateTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn rates(&self) -> &[ExchangeRate] {
+        &self.rates
+    }
+
+    /// Adds a rate after checking the currency codes and that the rate is
+    /// positive.
+    pub fn add_rate(&mut self, rate: ExchangeRate) -> Result<(), ConversionError> {
+        let invalid = |reason: &str| ConversionError::InvalidRate(rate.from.clone(), rate.to.clone(), reason.to_string());
+        for code in [&rate.from, &rate.to] {
+            if !Currency::is_valid(code) {
+                return Err(MoneyError::UnknownCurrency(code.clone()).into());
+            }
+        }
+        if rate.rate.is_negative() || rate.rate.is_zero() {
+            return Err(invalid("the rate must be positive"));
+        }
+        self.rates.push(rate);
+        Ok(())
+    }
+}
+
+impl ExchangeRates for RateTable {
+    /// Effective dates are compared with [`Date::compare`], so a rate whose
+    /// effective date is less precise than `on` and may be later is skipped.
+    fn rate(&self, from: &str, to: &str, on: &Date) -> Option<ExchangeRate> {
+        self.rates
+            .iter()
+            .filter(|rate| rate.from == from && rate.to == to)
+            .filter(|rate| rate.effective.compare(on).is_some_and(|ordering| ordering.is_le()))
+            .fold(None, |latest: Option<&ExchangeRate>, rate| match latest {
+                Some(latest) if !rate.effective.compare(&latest.effective).is_some_and(|ordering| ordering.is_gt()) => {
+                    Some(latest)
+                }
+                _ => Some(rate),
+            })
+            .cloned()
+    }
+}
+
+/// A converted amount and the rate used, if the currency changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conversion {
+    amount: Money,
+    rate: Option<ExchangeRate>,
+}
+
+impl Conversion {
+    pub fn amount(&self) -> &Money {
+        &self.amount
+    }
+
+    pub fn rate(&self) -> Option<&ExchangeRate> {
+        self.rate.as_ref()
+    }
+}
+
+/// Converts money into a currency at the rate in effect on a date, rounded
+/// to the minor units of the target currency. Money already in the target
+/// currency is returned unchanged.
+pub fn convert(rates: &dyn ExchangeRates, money: &Money, to: &str, on: &Date) -> Result<Conversion, ConversionError> {
+    let from = money.currency().ok_or(MoneyError::MissingCurrency)?;
+    money.validate()?;
+    if from == to {
+        return Ok(Conversion { amount: money.clone(), rate: None });
+    }
+    let rate = rates
+        .rate(from, to, on)
+        .ok_or_else(|| ConversionError::NoRate(from.to_string(), to.to_string(), on.to_string()))?;
+    let mut amount = money.scale(rate.rate())?;
+    amount.set_currency(Some(to.to_string()));
+    Ok(Conversion { amount: amount.round()?, rate: Some(rate) })
+}
+
+/// An account with its balances restated in the account currency.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Restatement {
+    account: Account,
+    rates: Vec<ExchangeRate>,
+}
+
+impl Restatement {
+    pub fn account(&self) -> &Account {
+        &self.account
+    }
+
+    /// Returns the distinct rates used, in the order first used.
+    pub fn rates(&self) -> &[ExchangeRate] {
+        &self.rates
+    }
+
+    pub fn into_account(self) -> Account {
+        self.account
+    }
+}
+
+/// Restates every balance of an account in the account currency at the
+/// rates in effect on a date. A balance without a currency is taken to be
+/// in the account currency already.
+pub fn restate_account(rates: &dyn ExchangeRates, account: &Account, on: &Date) -> Result<Restatement, ConversionError> {
+    let currency = account.currency_code().ok_or(ConversionError::NoAccountCurrency)?;
+    let mut restated = account.clone();
+    let mut used: Vec<ExchangeRate> = Vec::new();
+    let mut balances = Vec::with_capacity(account.balance().len());
+    for balance in account.balance() {
+        let mut balance = balance.clone();
+        let mut amount = balance.amount().clone();
+        if amount.currency().is_none() {
+            amount.set_currency(Some(currency.to_string()));
+        }
+        let conversion = convert(rates, &amount, currency, on)?;
+        if let Some(rate) = conversion.rate
+            && !used.contains(&rate)
+        {
+            used.push(rate);
+        }
+        balance.set_amount(conversion.amount);
+        balances.push(balance);
+    }
+    restated.set_balance(balances);
+    Ok(Restatement { account: restated, rates: used })
+}
@@ -0,0 +1,461 @@
+//! FHIRPath Patch: partial updates carried in a `Parameters` resource.
+//!
+//! Each `operation` parameter has a `type` of add, insert, delete, replace or
+//! move and a FHIRPath `path`. Paths are a subset of FHIRPath: element names,
+//! `[n]` indexes, `first()`, `last()` and `where(element = 'literal')`, e.g.
+//! `Patient.telecom.where(system = 'phone').value`. As with JSON Patch the
+//! operations are applied to the JSON form of the resource and the result is
+//! read back into the typed resource.
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::parameters::{ParameterValue, Parameters, ParametersParameter};
+use crate::patch::{check_immutable, pointer_mut, remove, PatchError};
+use crate::resource::{AnyResource, Resource};
+
+/// A single FHIRPath Patch operation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FhirPathOperation {
+    /// Adds a named child to the single element at the path, appending to lists.
+    Add { path: String, name: String, value: ParameterValue },
+    /// Inserts into the list at the path.
+    Insert { path: String, value: ParameterValue, index: usize },
+    /// Deletes the single element at the path; deleting nothing succeeds.
+    /// Deleting the last item of a list removes the list.
+    Delete { path: String },
+    /// Replaces the single element at the path.
+    Replace { path: String, value: ParameterValue },
+    /// Moves an item of the list at the path from one index to another.
+    Move { path: String, source: usize, destination: usize },
+}
+
+/// A FHIRPath Patch document, an ordered list of operations.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FhirPathPatch {
+    operations: Vec<FhirPathOperation>,
+}
+
+impl FhirPathPatch {
+    /// Creates a new, empty FhirPathPatch.
+    pub fn new() -> Self {
+        Self { operations: Vec::new() }
+    }
+
+    /// Returns the operations.
+    pub fn operations(&self) -> &[FhirPathOperation] {
+        &self.operations
+    }
+
+    /// Adds an operation.
+    pub fn add_operation(&mut self, operation: FhirPathOperation) {
+        self.operations.push(operation);
+    }
+
+    /// Reads the operations of a Parameters resource.
+    pub fn from_parameters(parameters: &Parameters) -> Result<Self, PatchError> {
+        let mut patch = Self::new();
+        for operation in parameters.named("operation") {
+            patch.add_operation(FhirPathOperation::from_parameter(operation)?);
+        }
+        Ok(patch)
+    }
+
+    /// Converts the patch to a Parameters resource.
+    pub fn to_parameters(&self) -> Parameters {
+        let mut parameters = Parameters::new();
+        for operation in &self.operations {
+            parameters.add_parameter(operation.to_parameter());
+        }
+        parameters
+    }
+
+    /// Applies the operations in order to the JSON form of a resource. On
+    /// error the value may be partially patched.
+    pub fn apply_value(&self, document: &mut Value) -> Result<(), PatchError> {
+        self.apply_operations(document, &|_| true)
+    }
+
+    /// Returns a patched copy of a resource. The resource type and id cannot be patched.
+    pub fn apply<R: Resource>(&self, resource: &R) -> Result<R, PatchError> {
+        let patched = self.patch_json::<R>(serde_json::to_value(resource)?)?;
+        Ok(serde_json::from_value(patched)?)
+    }
+
+    /// Returns a patched copy of any resource.
+    pub fn apply_any(&self, resource: &AnyResource) -> Result<AnyResource, PatchError> {
+        let patched = self.patch_json::<AnyResource>(serde_json::to_value(resource)?)?;
+        Ok(serde_json::from_value(patched)?)
+    }
+
+    /// Converts the patch to a Parameters JSON string.
+    pub fn to_json(&self) -> String {
+        self.to_parameters().to_json()
+    }
+
+    /// Converts a Parameters JSON string to a FhirPathPatch.
+    pub fn from_json(json: &str) -> Result<Self, PatchError> {
+        let parameters = Parameters::from_json(json).map_err(|error| PatchError::InvalidDocument(error.to_string()))?;
+        Self::from_parameters(&parameters)
+    }
+
+    /// Patches the JSON form of a resource of type `R`. Absent lists are
+    /// omitted from that form, so an element added where there is none is
+    /// made a list when `R` only reads it as one.
+    fn patch_json<R: DeserializeOwned>(&self, original: Value) -> Result<Value, PatchError> {
+        let mut patched = original.clone();
+        self.apply_operations(&mut patched, &|document| serde_json::from_value::<R>(document.clone()).is_ok())?;
+        check_immutable(&original, &patched)?;
+        Ok(patched)
+    }
+
+    fn apply_operations(&self, document: &mut Value, readable: &dyn Fn(&Value) -> bool) -> Result<(), PatchError> {
+        for operation in &self.operations {
+            operation.apply(document, readable)?;
+        }
+        Ok(())
+    }
+}
+
+impl FhirPathOperation {
+    /// Returns the operation type code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            FhirPathOperation::Add { .. } => "add",
+            FhirPathOperation::Insert { .. } => "insert",
+            FhirPathOperation::Delete { .. } => "delete",
+            FhirPathOperation::Replace { .. } => "replace",
+            FhirPathOperation::Move { .. } => "move",
+        }
+    }
+
+    /// Returns the FHIRPath the operation applies to.
+    pub fn path(&self) -> &str {
+        match self {
+            FhirPathOperation::Add { path, .. }
+            | FhirPathOperation::Insert { path, .. }
+            | FhirPathOperation::Delete { path }
+            | FhirPathOperation::Replace { path, .. }
+            | FhirPathOperation::Move { path, .. } => path,
+        }
+    }
+
+    /// Reads an `operation` parameter.
+    pub fn from_parameter(parameter: &ParametersParameter) -> Result<Self, PatchError> {
+        let string = |name: &str| {
+            parameter
+                .part_named(name)
+                .and_then(|part| part.value())
+                .and_then(ParameterValue::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| PatchError::InvalidDocument(format!("operation has no {}", name)))
+        };
+        let index = |name: &str| {
+            parameter
+                .part_named(name)
+                .and_then(|part| part.value())
+                .and_then(ParameterValue::as_integer)
+                .and_then(|index| usize::try_from(index).ok())
+                .ok_or_else(|| PatchError::InvalidDocument(format!("operation has no {}", name)))
+        };
+        let value = || {
+            parameter
+                .part_named("value")
+                .map(part_value)
+                .ok_or_else(|| PatchError::InvalidDocument("operation has no value".to_string()))
+        };
+        let path = string("path")?;
+        match string("type")?.as_str() {
+            "add" => Ok(FhirPathOperation::Add { path, name: string("name")?, value: value()? }),
+            "insert" => Ok(FhirPathOperation::Insert { path, value: value()?, index: index("index")? }),
+            "delete" => Ok(FhirPathOperation::Delete { path }),
+            "replace" => Ok(FhirPathOperation::Replace { path, value: value()? }),
+            "move" => Ok(FhirPathOperation::Move {
+                path,
+                source: index("source")?,
+                destination: index("destination")?,
+            }),
+            other => Err(PatchError::InvalidDocument(format!("unknown operation type {}", other))),
+        }
+    }
+
+    /// Converts the operation to an `operation` parameter.
+    pub fn to_parameter(&self) -> ParametersParameter {
+        let mut parameter = ParametersParameter::new("operation");
+        parameter.add_part(ParametersParameter::with_value("type", ParameterValue::code(self.code())));
+        parameter.add_part(ParametersParameter::with_value("path", ParameterValue::string(self.path())));
+        match self {
+            FhirPathOperation::Add { name, value, .. } => {
+                parameter.add_part(ParametersParameter::with_value("name", ParameterValue::string(name)));
+                parameter.add_part(value_part(value));
+            }
+            FhirPathOperation::Insert { value, index, .. } => {
+                parameter.add_part(value_part(value));
+                parameter.add_part(ParametersParameter::with_value("index", ParameterValue::integer(*index as i64)));
+            }
+            FhirPathOperation::Delete { .. } => {}
+            FhirPathOperation::Replace { value, .. } => {
+                parameter.add_part(value_part(value));
+            }
+            FhirPathOperation::Move { source, destination, .. } => {
+                parameter.add_part(ParametersParameter::with_value("source", ParameterValue::integer(*source as i64)));
+                parameter.add_part(ParametersParameter::with_value(
+                    "destination",
+                    ParameterValue::integer(*destination as i64),
+                ));
+            }
+        }
+        parameter
+    }
+
+    fn apply(&self, document: &mut Value, readable: &dyn Fn(&Value) -> bool) -> Result<(), PatchError> {
+        match self {
+            FhirPathOperation::Add { path, name, value } => {
+                let container = single(document, path)?;
+                let Some(Value::Object(object)) = document.pointer_mut(&container) else {
+                    return Err(PatchError::InvalidDocument(format!("{} is not an element with children", path)));
+                };
+                match object.get_mut(name.as_str()) {
+                    Some(Value::Array(items)) => items.push(value.value().clone()),
+                    None | Some(Value::Null) => {
+                        object.insert(name.clone(), value.value().clone());
+                        if !readable(document) {
+                            let added = pointer_mut(document, &format!("{}/{}", container, name))?;
+                            *added = Value::Array(vec![added.take()]);
+                            if !readable(document) {
+                                let added = pointer_mut(document, &format!("{}/{}/0", container, name))?.take();
+                                *pointer_mut(document, &format!("{}/{}", container, name))? = added;
+                            }
+                        }
+                    }
+                    Some(_) => return Err(PatchError::InvalidDocument(format!("{}.{} already has a value", path, name))),
+                }
+            }
+            FhirPathOperation::Insert { path, value, index } => {
+                let items = list(document, path)?;
+                if *index > items.len() {
+                    return Err(PatchError::PathNotFound(format!("{}[{}]", path, index)));
+                }
+                items.insert(*index, value.value().clone());
+            }
+            FhirPathOperation::Delete { path } => {
+                let matches = select(document, &parse_path(path)?);
+                match matches.as_slice() {
+                    [] => {}
+                    [pointer] => {
+                        remove(document, pointer)?;
+                        // FHIR JSON has no empty lists: deleting the last item deletes the element
+                        if let Some((list, _)) = pointer.rsplit_once('/')
+                            && document.pointer(list).and_then(Value::as_array).is_some_and(Vec::is_empty)
+                        {
+                            remove(document, list)?;
+                        }
+                    }
+                    _ => return Err(PatchError::MultipleMatches(path.clone())),
+                }
+            }
+            FhirPathOperation::Replace { path, value } => {
+                let pointer = single(document, path)?;
+                *pointer_mut(document, &pointer)? = value.value().clone();
+            }
+            FhirPathOperation::Move { path, source, destination } => {
+                let items = list(document, path)?;
+                if *source >= items.len() || *destination >= items.len() {
+                    return Err(PatchError::PathNotFound(format!("{}[{}]", path, source.max(destination))));
+                }
+                let item = items.remove(*source);
+                items.insert(*destination, item);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the value of a `value` part: its `value[x]`, or an element built
+/// from its named parts.
+fn part_value(part: &ParametersParameter) -> ParameterValue {
+    if let Some(value) = part.value() {
+        return value.clone();
+    }
+    let mut object = Map::new();
+    for child in part.part() {
+        object.insert(child.name().to_string(), part_value(child).value().clone());
+    }
+    ParameterValue::new("Element", Value::Object(object))
+}
+
+/// Returns the `value` part of an operation. Elements built from parts are
+/// written back as parts.
+fn value_part(value: &ParameterValue) -> ParametersParameter {
+    match value.value() {
+        Value::Object(object) if value.value_type() == "Element" => ParametersParameter::with_parts("value", element_parts(object)),
+        _ => ParametersParameter::with_value("value", value.clone()),
+    }
+}
+
+fn element_parts(object: &Map<String, Value>) -> Vec<ParametersParameter> {
+    object
+        .iter()
+        .map(|(name, value)| match value {
+            Value::Object(object) => ParametersParameter::with_parts(name, element_parts(object)),
+            value => ParametersParameter::with_value(name, ParameterValue::infer(value)),
+        })
+        .collect()
+}
+
+/// One step of a FHIRPath expression.
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Child(String),
+    Index(usize),
+    First,
+    Last,
+    Where(Vec<String>, Value),
+}
+
+/// Parses a path such as `Patient.telecom.where(system = 'phone')[0].value`.
+/// The leading resource type is checked when the path is evaluated.
+fn parse_path(path: &str) -> Result<Vec<Step>, PatchError> {
+    let invalid = |reason: &str| PatchError::InvalidPath(path.to_string(), reason.to_string());
+    let mut steps = Vec::new();
+    let mut rest = path.trim();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').ok_or_else(|| invalid("unclosed ["))?;
+            let index = after[..end].trim().parse().map_err(|_| invalid("invalid index"))?;
+            steps.push(Step::Index(index));
+            rest = &after[end + 1..];
+            continue;
+        }
+        rest = if steps.is_empty() { rest } else { rest.strip_prefix('.').ok_or_else(|| invalid("expected ."))? };
+        let end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        let name = &rest[..end];
+        if name.is_empty() {
+            return Err(invalid("expected an element name"));
+        }
+        rest = &rest[end..];
+        let Some(after) = rest.strip_prefix('(') else {
+            steps.push(Step::Child(name.to_string()));
+            continue;
+        };
+        let end = closing_parenthesis(after).ok_or_else(|| invalid("unclosed ("))?;
+        let argument = after[..end].trim();
+        rest = &after[end + 1..];
+        match (name, argument) {
+            ("first", "") => steps.push(Step::First),
+            ("last", "") => steps.push(Step::Last),
+            ("where", argument) => {
+                let (left, right) = argument.split_once('=').ok_or_else(|| invalid("where() needs an = comparison"))?;
+                let element = left.trim().split('.').map(str::to_string).collect();
+                steps.push(Step::Where(element, literal(right.trim()).ok_or_else(|| invalid("invalid literal"))?));
+            }
+            _ => return Err(invalid(&format!("unsupported function {}()", name))),
+        }
+    }
+    Ok(steps)
+}
+
+/// Finds the parenthesis closing an argument list, skipping quoted strings.
+fn closing_parenthesis(argument: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    for (index, c) in argument.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '\'' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted && depth == 0 => return Some(index),
+            ')' if !quoted => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn literal(text: &str) -> Option<Value> {
+    if let Some(quoted) = text.strip_prefix('\'').and_then(|text| text.strip_suffix('\'')) {
+        return Some(Value::String(quoted.replace("\\'", "'").replace("\\\\", "\\")));
+    }
+    match text {
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ => serde_json::from_str::<serde_json::Number>(text).ok().map(Value::Number),
+    }
+}
+
+/// Evaluates a parsed path, returning JSON Pointers to the selected elements.
+/// Lists are flattened, so `Patient.telecom` selects every ContactPoint.
+fn select(document: &Value, steps: &[Step]) -> Vec<String> {
+    let mut selected = match steps.first() {
+        Some(Step::Child(resource_type)) if document.get("resourceType").and_then(Value::as_str) == Some(resource_type) => {
+            vec![String::new()]
+        }
+        _ => return Vec::new(),
+    };
+    for step in &steps[1..] {
+        selected = match step {
+            Step::Child(name) => selected.iter().flat_map(|pointer| children(document, pointer, name)).collect(),
+            Step::Index(index) => selected.get(*index).cloned().into_iter().collect(),
+            Step::First => selected.first().cloned().into_iter().collect(),
+            Step::Last => selected.last().cloned().into_iter().collect(),
+            Step::Where(element, expected) => selected
+                .into_iter()
+                .filter(|pointer| {
+                    let mut values = vec![pointer.clone()];
+                    for name in element {
+                        values = values.iter().flat_map(|value| children(document, value, name)).collect();
+                    }
+                    values.iter().any(|value| document.pointer(value) == Some(expected))
+                })
+                .collect(),
+        };
+    }
+    selected
+}
+
+fn children(document: &Value, pointer: &str, name: &str) -> Vec<String> {
+    let child = format!("{}/{}", pointer, name);
+    match document.pointer(&child) {
+        Some(Value::Array(items)) => (0..items.len()).map(|index| format!("{}/{}", child, index)).collect(),
+        None | Some(Value::Null) => Vec::new(),
+        Some(_) => vec![child],
+    }
+}
+
+/// Returns the pointer to the only element at a path.
+fn single(document: &Value, path: &str) -> Result<String, PatchError> {
+    let mut matches = select(document, &parse_path(path)?);
+    match matches.len() {
+        0 => Err(PatchError::PathNotFound(path.to_string())),
+        1 => Ok(matches.remove(0)),
+        _ => Err(PatchError::MultipleMatches(path.to_string())),
+    }
+}
+
+/// Returns the list named by the last step of a path, creating it if absent.
+fn list<'a>(document: &'a mut Value, path: &str) -> Result<&'a mut Vec<Value>, PatchError> {
+    let mut steps = parse_path(path)?;
+    let Some(Step::Child(name)) = steps.pop() else {
+        return Err(PatchError::InvalidPath(path.to_string(), "expected a list element".to_string()));
+    };
+    let parent = match select(document, &steps).as_slice() {
+        [] => return Err(PatchError::PathNotFound(path.to_string())),
+        [parent] => parent.clone(),
+        _ => return Err(PatchError::MultipleMatches(path.to_string())),
+    };
+    let Some(Value::Object(object)) = document.pointer_mut(&parent) else {
+        return Err(PatchError::PathNotFound(path.to_string()));
+    };
+    let entry = object.entry(name).or_insert(Value::Null);
+    if entry.is_null() {
+        *entry = Value::Array(Vec::new());
+    }
+    match entry {
+        Value::Array(items) => Ok(items),
+        _ => Err(PatchError::InvalidPath(path.to_string(), "expected a list element".to_string())),
+    }
+}
@@ -1,3 +1,4 @@
+use crate::period::Period;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -5,10 +6,14 @@ pub struct HumanName {
     r#use: String,
     text: String,
     family: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     given: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     prefix: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     suffix: Vec<String>,
-    period: Option<String>, // Simplified for now, can be enhanced later
+    #[serde(skip_serializing_if = "Option::is_none")]
+    period: Option<Period>,
 }
 
 impl HumanName {
@@ -56,8 +61,8 @@ impl HumanName {
     }
 
     /// Returns the period.
-    pub fn period(&self) -> Option<&str> {
-        self.period.as_deref()
+    pub fn period(&self) -> Option<&Period> {
+        self.period.as_ref()
     }
 
     /// Sets the given names.
@@ -76,7 +81,7 @@ impl HumanName {
     }
 
     /// Sets the period.
-    pub fn set_period(&mut self, period: Option<String>) {
+    pub fn set_period(&mut self, period: Option<Period>) {
         self.period = period;
     }
 
@@ -1,12 +1,16 @@
 use crate::data_types::uri::Uri;
+use crate::data_types::codeable_concept::CodeableConcept;
 use serde::{Deserialize, Serialize};
 use crate::period::Period;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Identifier {
     r#use: Uri,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    identifier_type: Option<CodeableConcept>,
     system: Uri,
     value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     period: Option<Period>, // Using the Period struct
 }
 
@@ -15,6 +19,7 @@ impl Identifier {
     pub fn new(use_value: Uri, system: Uri, value: String) -> Self {
         Self {
             r#use: use_value,
+            identifier_type: None,
             system,
             value,
             period: None,
@@ -26,6 +31,11 @@ impl Identifier {
         &self.r#use
     }
 
+    /// Returns the identifier type.
+    pub fn identifier_type(&self) -> Option<&CodeableConcept> {
+        self.identifier_type.as_ref()
+    }
+
     /// Returns the system URI.
     pub fn system(&self) -> &Uri {
         &self.system
@@ -41,6 +51,11 @@ impl Identifier {
         self.period.as_ref()
     }
 
+    /// Sets the identifier type.
+    pub fn set_identifier_type(&mut self, identifier_type: Option<CodeableConcept>) {
+        self.identifier_type = identifier_type;
+    }
+
     /// Sets the period.
     pub fn set_period(&mut self, period: Option<Period>) {
         self.period = period;
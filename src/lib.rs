@@ -12,8 +12,33 @@ pub mod account_guarantor;
 pub mod account_diagnosis;
 pub mod account_procedure;
 pub mod account_related_account;
-pub mod account_balance;    
+pub mod account_balance;
+pub mod balance_summary;
+pub mod account_hierarchy;
+pub mod account_lifecycle;
 pub mod money;
+pub mod currency;
+pub mod exchange;
+pub mod search;
+pub mod meta;
+pub mod resource;
+pub mod repository;
+pub mod bundle;
+pub mod projection;
+pub mod operation_outcome;
+pub mod patch;
+pub mod parameters;
+pub mod fhirpath_patch;
+pub mod capability_statement;
+pub mod transaction;
+pub mod conditional;
+pub mod diff;
+pub mod compartment;
+pub mod bulk_export;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "client")]
+pub mod client;
 
 pub fn add(left: u64, right: u64) -> u64 {
     left + right
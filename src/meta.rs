@@ -0,0 +1,84 @@
+use crate::data_types::codeable_concept::Coding;
+use datetime::LocalDateTime;
+use datetime::ISO;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Meta {
+    #[serde(rename = "versionId", skip_serializing_if = "Option::is_none")]
+    version_id: Option<String>,
+    #[serde(rename = "lastUpdated", skip_serializing_if = "Option::is_none")]
+    last_updated: Option<String>, // instant
+    #[serde(rename = "tag", default, skip_serializing_if = "Vec::is_empty")]
+    tag: Vec<Coding>,
+}
+
+impl Meta {
+    /// Creates a new, empty Meta.
+    pub fn new() -> Self {
+        Self {
+            version_id: None,
+            last_updated: None,
+            tag: Vec::new(),
+        }
+    }
+
+    /// Creates a new Meta with a version id, last updated now.
+    pub fn with_version(version_id: String) -> Self {
+        Self {
+            version_id: Some(version_id),
+            last_updated: Some(now()),
+            tag: Vec::new(),
+        }
+    }
+
+    /// Returns the version id.
+    pub fn version_id(&self) -> Option<&str> {
+        self.version_id.as_deref()
+    }
+
+    /// Returns when the resource was last updated.
+    pub fn last_updated(&self) -> Option<&str> {
+        self.last_updated.as_deref()
+    }
+
+    /// Returns the tags.
+    pub fn tag(&self) -> &[Coding] {
+        &self.tag
+    }
+
+    /// Sets the version id.
+    pub fn set_version_id(&mut self, version_id: Option<String>) {
+        self.version_id = version_id;
+    }
+
+    /// Sets when the resource was last updated.
+    pub fn set_last_updated(&mut self, last_updated: Option<String>) {
+        self.last_updated = last_updated;
+    }
+
+    /// Sets the tags.
+    pub fn set_tag(&mut self, tag: Vec<Coding>) {
+        self.tag = tag;
+    }
+
+    /// Adds a tag.
+    pub fn add_tag(&mut self, tag: Coding) {
+        self.tag.push(tag);
+    }
+}
+
+impl Default for Meta {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the current time as a FHIR instant in UTC.
+pub(crate) fn now() -> String {
+    // `LocalDateTime::now` can report negative milliseconds, so read the clock here.
+    let elapsed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let now = LocalDateTime::at_ms(elapsed.as_secs() as i64, elapsed.subsec_millis() as i16);
+    format!("{}Z", now.iso())
+}
@@ -1,11 +1,28 @@
+use crate::currency::Currency;
+use crate::data_types::decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MoneyError {
+    #[error("Money has no value")]
+    MissingValue,
+    #[error("Money has no currency")]
+    MissingCurrency,
+    #[error("Cannot combine {0} with {1}")]
+    CurrencyMismatch(String, String),
+    #[error("Unknown ISO 4217 currency: {0}")]
+    UnknownCurrency(String),
+    #[error("Currency {0} has no minor units")]
+    NoMinorUnits(String),
+    #[error("Money amount out of range")]
+    Overflow,
+}
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Money {
-    #[serde(rename = "value")]
-    value: Option<f64>,
-    #[serde(rename = "currency")]
+    #[serde(rename = "value", skip_serializing_if = "Option::is_none")]
+    value: Option<Decimal>,
+    #[serde(rename = "currency", skip_serializing_if = "Option::is_none")]
     currency: Option<String>,
 }
 
@@ -18,35 +35,133 @@ impl Money {
         }
     }
 
-    pub fn with_value(value: f64) -> Self {
+    pub fn with_value(value: Decimal) -> Self {
         Self {
             value: Some(value),
             currency: None,
         }
     }
 
-    pub fn with_currency(value: f64, currency: String) -> Self {
+    pub fn with_currency(value: Decimal, currency: String) -> Self {
         Self {
             value: Some(value),
             currency: Some(currency),
         }
     }
 
-    pub fn value(&self) -> Option<f64> {
-        self.value
+    pub fn value(&self) -> Option<&Decimal> {
+        self.value.as_ref()
     }
 
     pub fn currency(&self) -> Option<&str> {
         self.currency.as_deref()
     }
 
-    pub fn set_value(&mut self, value: Option<f64>) {
+    pub fn set_value(&mut self, value: Option<Decimal>) {
         self.value = value;
     }
 
     pub fn set_currency(&mut self, currency: Option<String>) {
         self.currency = currency;
     }
+
+    /// Checks that the currency, if present, is an ISO 4217 code.
+    pub fn validate(&self) -> Result<(), MoneyError> {
+        self.iso_currency().map(|_| ())
+    }
+
+    /// Adds two amounts in the same currency.
+    pub fn checked_add(&self, other: &Money) -> Result<Money, MoneyError> {
+        let currency = self.same_currency(other)?;
+        let value = self.amount()?.checked_add(other.amount()?).ok_or(MoneyError::Overflow)?;
+        Ok(Self { value: Some(value), currency })
+    }
+
+    /// Subtracts an amount in the same currency.
+    pub fn checked_sub(&self, other: &Money) -> Result<Money, MoneyError> {
+        self.checked_add(&other.negate())
+    }
+
+    /// Returns the amount with its sign flipped.
+    pub fn negate(&self) -> Money {
+        Self {
+            value: self.value.clone().map(|value| -value),
+            currency: self.currency.clone(),
+        }
+    }
+
+    /// Multiplies the amount by a factor, e.g. a share or a quantity.
+    pub fn scale(&self, factor: &Decimal) -> Result<Money, MoneyError> {
+        let value = self.amount()?.checked_mul(factor).ok_or(MoneyError::Overflow)?;
+        Ok(Self { value: Some(value), currency: self.currency.clone() })
+    }
+
+    /// Rounds the amount to the minor units of its currency, halves away
+    /// from zero: `10.005 USD` becomes `10.01 USD` and `1234.5 JPY` `1235 JPY`.
+    pub fn round(&self) -> Result<Money, MoneyError> {
+        let currency = self.iso_currency()?.ok_or(MoneyError::MissingCurrency)?;
+        let minor_units = currency.minor_units().ok_or_else(|| MoneyError::NoMinorUnits(currency.code().to_string()))?;
+        let value = self.amount()?.round(minor_units).ok_or(MoneyError::Overflow)?;
+        Ok(Self { value: Some(value), currency: self.currency.clone() })
+    }
+
+    /// Formats the amount for display with thousands separators, rounded to
+    /// the minor units of its currency: `$1,234.50`, `-€12.00`,
+    /// `CHF 1,000.00`. An amount without a currency is written as is.
+    pub fn format(&self) -> Result<String, MoneyError> {
+        let (value, prefix) = match self.iso_currency()? {
+            Some(currency) => {
+                let value = match currency.minor_units() {
+                    Some(_) => self.round()?.value.ok_or(MoneyError::MissingValue)?,
+                    None => self.amount()?.clone(),
+                };
+                let prefix = match currency.symbol() {
+                    Some(symbol) => symbol.to_string(),
+                    None => format!("{} ", currency.code()),
+                };
+                (value, prefix)
+            }
+            None => (self.amount()?.clone(), String::new()),
+        };
+        // Written in plain notation, so `12e3` is grouped as `12,000`.
+        let text = value.abs().round(value.scale()).ok_or(MoneyError::Overflow)?.to_string();
+        let (integer, fraction) = match text.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (text.as_str(), None),
+        };
+        let mut grouped = String::new();
+        for (index, digit) in integer.chars().enumerate() {
+            if index > 0 && (integer.len() - index) % 3 == 0 {
+                grouped.push(',');
+            }
+            grouped.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            grouped.push('.');
+            grouped.push_str(fraction);
+        }
+        let sign = if value.is_negative() && !value.is_zero() { "-" } else { "" };
+        Ok(format!("{}{}{}", sign, prefix, grouped))
+    }
+
+    fn amount(&self) -> Result<&Decimal, MoneyError> {
+        self.value.as_ref().ok_or(MoneyError::MissingValue)
+    }
+
+    fn iso_currency(&self) -> Result<Option<Currency>, MoneyError> {
+        self.currency
+            .as_deref()
+            .map(|code| Currency::from_code(code).ok_or_else(|| MoneyError::UnknownCurrency(code.to_string())))
+            .transpose()
+    }
+
+    fn same_currency(&self, other: &Money) -> Result<Option<String>, MoneyError> {
+        if self.currency != other.currency {
+            let name = |currency: &Option<String>| currency.clone().unwrap_or_else(|| "no currency".to_string());
+            return Err(MoneyError::CurrencyMismatch(name(&self.currency), name(&other.currency)));
+        }
+        Ok(self.currency.clone())
+    }
 }
 
 
@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationOutcome {
+    #[serde(rename = "resourceType")]
+    resource_type: String,
+    #[serde(rename = "issue", default, skip_serializing_if = "Vec::is_empty")]
+    issue: Vec<OperationOutcomeIssue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationOutcomeIssue {
+    #[serde(rename = "severity")]
+    severity: String, // fatal | error | warning | information | success
+    #[serde(rename = "code")]
+    code: String, // invalid | not-found | deleted | conflict | not-supported | processing | exception | ...
+    #[serde(rename = "diagnostics", skip_serializing_if = "Option::is_none")]
+    diagnostics: Option<String>,
+    #[serde(rename = "expression", default, skip_serializing_if = "Vec::is_empty")]
+    expression: Vec<String>,
+}
+
+impl OperationOutcome {
+    /// Creates a new OperationOutcome without issues.
+    pub fn new() -> Self {
+        Self {
+            resource_type: "OperationOutcome".to_string(),
+            issue: Vec::new(),
+        }
+    }
+
+    /// Creates a new OperationOutcome holding a single error issue.
+    pub fn error(code: &str, diagnostics: String) -> Self {
+        let mut outcome = Self::new();
+        outcome.add_issue(OperationOutcomeIssue::new(
+            "error".to_string(),
+            code.to_string(),
+            Some(diagnostics),
+        ));
+        outcome
+    }
+
+    /// Returns the resource type.
+    pub fn resource_type(&self) -> &str {
+        &self.resource_type
+    }
+
+    /// Returns the issues.
+    pub fn issue(&self) -> &[OperationOutcomeIssue] {
+        &self.issue
+    }
+
+    /// Returns true if any issue is an error or fatal.
+    pub fn has_errors(&self) -> bool {
+        self.issue
+            .iter()
+            .any(|issue| issue.severity == "error" || issue.severity == "fatal")
+    }
+
+    /// Sets the issues.
+    pub fn set_issue(&mut self, issue: Vec<OperationOutcomeIssue>) {
+        self.issue = issue;
+    }
+
+    /// Adds an issue.
+    pub fn add_issue(&mut self, issue: OperationOutcomeIssue) {
+        self.issue.push(issue);
+    }
+
+    /// Converts the OperationOutcome to a JSON string.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Converts a JSON string to an OperationOutcome.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+impl Default for OperationOutcome {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OperationOutcomeIssue {
+    /// Creates a new OperationOutcomeIssue.
+    pub fn new(severity: String, code: String, diagnostics: Option<String>) -> Self {
+        Self {
+            severity,
+            code,
+            diagnostics,
+            expression: Vec::new(),
+        }
+    }
+
+    /// Returns the severity.
+    pub fn severity(&self) -> &str {
+        &self.severity
+    }
+
+    /// Returns the issue type code.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Returns the diagnostics.
+    pub fn diagnostics(&self) -> Option<&str> {
+        self.diagnostics.as_deref()
+    }
+
+    /// Returns the FHIRPath expressions of the elements the issue concerns.
+    pub fn expression(&self) -> &[String] {
+        &self.expression
+    }
+
+    /// Sets the severity.
+    pub fn set_severity(&mut self, severity: String) {
+        self.severity = severity;
+    }
+
+    /// Sets the issue type code.
+    pub fn set_code(&mut self, code: String) {
+        self.code = code;
+    }
+
+    /// Sets the diagnostics.
+    pub fn set_diagnostics(&mut self, diagnostics: Option<String>) {
+        self.diagnostics = diagnostics;
+    }
+
+    /// Adds an element expression.
+    pub fn add_expression(&mut self, expression: String) {
+        self.expression.push(expression);
+    }
+}
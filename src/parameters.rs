@@ -0,0 +1,259 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Parameters {
+    #[serde(rename = "resourceType")]
+    resource_type: String,
+    #[serde(rename = "id", skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(rename = "parameter", default, skip_serializing_if = "Vec::is_empty")]
+    parameter: Vec<ParametersParameter>,
+}
+
+/// A named parameter holding a value, a resource-free list of parts, or both.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawParameter", into = "RawParameter")]
+pub struct ParametersParameter {
+    name: String,
+    value: Option<ParameterValue>,
+    part: Vec<ParametersParameter>,
+}
+
+/// A `value[x]` element: the type suffix, e.g. `String` or `ContactPoint`,
+/// and the JSON form of the value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterValue {
+    value_type: String,
+    value: Value,
+}
+
+/// The JSON form of a parameter, with `value[x]` gathered from the remaining keys.
+#[derive(Serialize, Deserialize)]
+struct RawParameter {
+    name: String,
+    #[serde(flatten)]
+    values: Map<String, Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    part: Vec<ParametersParameter>,
+}
+
+impl Parameters {
+    /// Creates a new Parameters resource without parameters.
+    pub fn new() -> Self {
+        Self {
+            resource_type: "Parameters".to_string(),
+            id: None,
+            parameter: Vec::new(),
+        }
+    }
+
+    /// Returns the resource type.
+    pub fn resource_type(&self) -> &str {
+        &self.resource_type
+    }
+
+    /// Returns the logical id.
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Returns the parameters.
+    pub fn parameter(&self) -> &[ParametersParameter] {
+        &self.parameter
+    }
+
+    /// Returns the parameters with a name.
+    pub fn named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a ParametersParameter> + 'a {
+        self.parameter.iter().filter(move |parameter| parameter.name == name)
+    }
+
+    /// Sets the logical id.
+    pub fn set_id(&mut self, id: Option<String>) {
+        self.id = id;
+    }
+
+    /// Sets the parameters.
+    pub fn set_parameter(&mut self, parameter: Vec<ParametersParameter>) {
+        self.parameter = parameter;
+    }
+
+    /// Adds a parameter.
+    pub fn add_parameter(&mut self, parameter: ParametersParameter) {
+        self.parameter.push(parameter);
+    }
+
+    /// Converts the Parameters to a JSON string.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Converts a JSON string to a Parameters resource.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+impl Default for Parameters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ParametersParameter {
+    /// Creates a new parameter without a value or parts.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            value: None,
+            part: Vec::new(),
+        }
+    }
+
+    /// Creates a new parameter holding a value.
+    pub fn with_value(name: &str, value: ParameterValue) -> Self {
+        Self {
+            name: name.to_string(),
+            value: Some(value),
+            part: Vec::new(),
+        }
+    }
+
+    /// Creates a new parameter made of parts.
+    pub fn with_parts(name: &str, part: Vec<ParametersParameter>) -> Self {
+        Self {
+            name: name.to_string(),
+            value: None,
+            part,
+        }
+    }
+
+    /// Returns the name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the value.
+    pub fn value(&self) -> Option<&ParameterValue> {
+        self.value.as_ref()
+    }
+
+    /// Returns the parts.
+    pub fn part(&self) -> &[ParametersParameter] {
+        &self.part
+    }
+
+    /// Returns the first part with a name.
+    pub fn part_named(&self, name: &str) -> Option<&ParametersParameter> {
+        self.part.iter().find(|part| part.name == name)
+    }
+
+    /// Sets the value.
+    pub fn set_value(&mut self, value: Option<ParameterValue>) {
+        self.value = value;
+    }
+
+    /// Adds a part.
+    pub fn add_part(&mut self, part: ParametersParameter) {
+        self.part.push(part);
+    }
+}
+
+impl ParameterValue {
+    /// Creates a new ParameterValue from a type suffix and a JSON value.
+    pub fn new(value_type: &str, value: Value) -> Self {
+        Self {
+            value_type: value_type.to_string(),
+            value,
+        }
+    }
+
+    /// Creates a `valueString`.
+    pub fn string(value: &str) -> Self {
+        Self::new("String", Value::String(value.to_string()))
+    }
+
+    /// Creates a `valueCode`.
+    pub fn code(value: &str) -> Self {
+        Self::new("Code", Value::String(value.to_string()))
+    }
+
+    /// Creates a `valueInteger`.
+    pub fn integer(value: i64) -> Self {
+        Self::new("Integer", Value::from(value))
+    }
+
+    /// Creates a `valueBoolean`.
+    pub fn boolean(value: bool) -> Self {
+        Self::new("Boolean", Value::Bool(value))
+    }
+
+    /// Creates a value typed from its JSON form: `Boolean`, `Integer`,
+    /// `Decimal` or `String`, or `Element` for an object.
+    pub fn infer(value: &Value) -> Self {
+        let value_type = match value {
+            Value::Bool(_) => "Boolean",
+            Value::Number(number) if number.is_i64() || number.is_u64() => "Integer",
+            Value::Number(_) => "Decimal",
+            Value::Object(_) => "Element",
+            _ => "String",
+        };
+        Self::new(value_type, value.clone())
+    }
+
+    /// Returns the type suffix, e.g. `String` for `valueString`.
+    pub fn value_type(&self) -> &str {
+        &self.value_type
+    }
+
+    /// Returns the JSON form of the value.
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// Returns the value as a string, for string-like types.
+    pub fn as_str(&self) -> Option<&str> {
+        self.value.as_str()
+    }
+
+    /// Returns the value as an integer.
+    pub fn as_integer(&self) -> Option<i64> {
+        self.value.as_i64()
+    }
+}
+
+impl TryFrom<RawParameter> for ParametersParameter {
+    type Error = String;
+
+    fn try_from(raw: RawParameter) -> Result<Self, Self::Error> {
+        let mut value = None;
+        for (key, json) in raw.values {
+            match key.strip_prefix("value") {
+                Some(value_type) if !value_type.is_empty() && value.is_none() => {
+                    value = Some(ParameterValue::new(value_type, json));
+                }
+                Some(_) => return Err(format!("parameter {} has more than one value", raw.name)),
+                None => return Err(format!("unknown field {} in parameter {}", key, raw.name)),
+            }
+        }
+        Ok(Self {
+            name: raw.name,
+            value,
+            part: raw.part,
+        })
+    }
+}
+
+impl From<ParametersParameter> for RawParameter {
+    fn from(parameter: ParametersParameter) -> Self {
+        let mut values = Map::new();
+        if let Some(value) = parameter.value {
+            values.insert(format!("value{}", value.value_type), value.value);
+        }
+        Self {
+            name: parameter.name,
+            values,
+            part: parameter.part,
+        }
+    }
+}
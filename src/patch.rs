@@ -0,0 +1,191 @@
+//! JSON Patch (RFC 6902) applied to resources.
+//!
+//! A patch is applied to the JSON form of a resource and the result is read
+//! back into the typed resource, so a patch that produces an invalid resource
+//! fails as a whole and leaves the original untouched.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::resource::{AnyResource, Resource};
+
+#[derive(Debug, thiserror::Error)]
+pub enum PatchError {
+    #[error("Invalid patch document: {0}")]
+    InvalidDocument(String),
+    #[error("Path not found: {0}")]
+    PathNotFound(String),
+    #[error("Test failed at path {0}")]
+    TestFailed(String),
+    #[error("Patch cannot change {0}")]
+    ImmutableElement(String),
+    #[error("Invalid FHIRPath {0}: {1}")]
+    InvalidPath(String, String),
+    #[error("Path {0} matches more than one element")]
+    MultipleMatches(String),
+    #[error("Patched resource is invalid: {0}")]
+    InvalidResource(#[from] serde_json::Error),
+}
+
+/// A single JSON Patch operation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Value },
+}
+
+/// A JSON Patch document, an ordered list of operations.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct JsonPatch {
+    operations: Vec<PatchOperation>,
+}
+
+impl JsonPatch {
+    /// Creates a new, empty JsonPatch.
+    pub fn new() -> Self {
+        Self { operations: Vec::new() }
+    }
+
+    /// Returns the operations.
+    pub fn operations(&self) -> &[PatchOperation] {
+        &self.operations
+    }
+
+    /// Adds an operation.
+    pub fn add_operation(&mut self, operation: PatchOperation) {
+        self.operations.push(operation);
+    }
+
+    /// Applies the operations in order to a JSON value. On error the value
+    /// may be partially patched.
+    pub fn apply_value(&self, document: &mut Value) -> Result<(), PatchError> {
+        for operation in &self.operations {
+            match operation {
+                PatchOperation::Add { path, value } => add(document, path, value.clone())?,
+                PatchOperation::Remove { path } => {
+                    remove(document, path)?;
+                }
+                PatchOperation::Replace { path, value } => {
+                    *pointer_mut(document, path)? = value.clone();
+                }
+                PatchOperation::Move { from, path } => {
+                    if path.starts_with(&format!("{}/", from)) {
+                        return Err(PatchError::InvalidDocument(format!("cannot move {} into itself", from)));
+                    }
+                    let value = remove(document, from)?;
+                    add(document, path, value)?;
+                }
+                PatchOperation::Copy { from, path } => {
+                    let value = pointer_mut(document, from)?.clone();
+                    add(document, path, value)?;
+                }
+                PatchOperation::Test { path, value } => {
+                    if pointer_mut(document, path)? != value {
+                        return Err(PatchError::TestFailed(path.clone()));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a patched copy of a resource. The resource type and id cannot be patched.
+    pub fn apply<R: Resource>(&self, resource: &R) -> Result<R, PatchError> {
+        let patched = self.patch_json(serde_json::to_value(resource)?)?;
+        Ok(serde_json::from_value(patched)?)
+    }
+
+    /// Returns a patched copy of any resource.
+    pub fn apply_any(&self, resource: &AnyResource) -> Result<AnyResource, PatchError> {
+        let patched = self.patch_json(serde_json::to_value(resource)?)?;
+        Ok(serde_json::from_value(patched)?)
+    }
+
+    fn patch_json(&self, original: Value) -> Result<Value, PatchError> {
+        let mut patched = original.clone();
+        self.apply_value(&mut patched)?;
+        check_immutable(&original, &patched)?;
+        Ok(patched)
+    }
+
+    /// Converts the patch to a JSON string.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Converts a JSON string to a JsonPatch.
+    pub fn from_json(json: &str) -> Result<Self, PatchError> {
+        serde_json::from_str(json).map_err(|error| PatchError::InvalidDocument(error.to_string()))
+    }
+}
+
+/// Rejects a patch that changed the resource type or id.
+pub(crate) fn check_immutable(original: &Value, patched: &Value) -> Result<(), PatchError> {
+    for element in ["resourceType", "id"] {
+        if patched.get(element) != original.get(element) {
+            return Err(PatchError::ImmutableElement(element.to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// Splits a JSON Pointer into its parent pointer and unescaped last token.
+fn split_pointer(path: &str) -> Result<(&str, String), PatchError> {
+    let index = path
+        .rfind('/')
+        .ok_or_else(|| PatchError::InvalidDocument(format!("invalid path {}", path)))?;
+    let token = path[index + 1..].replace("~1", "/").replace("~0", "~");
+    Ok((&path[..index], token))
+}
+
+pub(crate) fn pointer_mut<'a>(document: &'a mut Value, path: &str) -> Result<&'a mut Value, PatchError> {
+    document
+        .pointer_mut(path)
+        .ok_or_else(|| PatchError::PathNotFound(path.to_string()))
+}
+
+fn array_index(token: &str, len: usize, path: &str) -> Result<usize, PatchError> {
+    match token.parse::<usize>() {
+        Ok(index) if index <= len && (token == "0" || !token.starts_with('0')) => Ok(index),
+        _ => Err(PatchError::PathNotFound(path.to_string())),
+    }
+}
+
+fn add(document: &mut Value, path: &str, value: Value) -> Result<(), PatchError> {
+    if path.is_empty() {
+        *document = value;
+        return Ok(());
+    }
+    let (parent, token) = split_pointer(path)?;
+    match pointer_mut(document, parent)? {
+        Value::Object(object) => {
+            object.insert(token, value);
+        }
+        Value::Array(array) if token == "-" => array.push(value),
+        Value::Array(array) => {
+            let index = array_index(&token, array.len(), path)?;
+            array.insert(index, value);
+        }
+        _ => return Err(PatchError::PathNotFound(path.to_string())),
+    }
+    Ok(())
+}
+
+pub(crate) fn remove(document: &mut Value, path: &str) -> Result<Value, PatchError> {
+    let (parent, token) = split_pointer(path)?;
+    let removed = match pointer_mut(document, parent)? {
+        Value::Object(object) => object.remove(&token),
+        Value::Array(array) => {
+            let index = array_index(&token, array.len(), path)?;
+            (index < array.len()).then(|| array.remove(index))
+        }
+        _ => None,
+    };
+    removed.ok_or_else(|| PatchError::PathNotFound(path.to_string()))
+}
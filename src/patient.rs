@@ -1,4 +1,5 @@
 use crate::identifier::Identifier;
+use crate::meta::Meta;
 use crate::human_name::HumanName;
 use crate::data_types::contact_point::ContactPoint;
 use crate::data_types::address::Address;
@@ -7,47 +8,52 @@ use crate::data_types::reference::Reference;
 use crate::patient_contact::PatientContact;
 use crate::patient_communication::PatientCommunication;
 use crate::patient_link::PatientLink;
+use crate::data_types::date_time::{Date, DateTime};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Patient {
     #[serde(rename = "resourceType")]
     resource_type: String,
-    #[serde(rename = "identifier")]
+    #[serde(rename = "id", skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(rename = "meta", skip_serializing_if = "Option::is_none")]
+    meta: Option<Meta>,
+    #[serde(rename = "identifier", default, skip_serializing_if = "Vec::is_empty")]
     identifier: Vec<Identifier>,
-    #[serde(rename = "active")]
+    #[serde(rename = "active", skip_serializing_if = "Option::is_none")]
     active: Option<bool>,
-    #[serde(rename = "name")]
+    #[serde(rename = "name", default, skip_serializing_if = "Vec::is_empty")]
     name: Vec<HumanName>,
-    #[serde(rename = "telecom")]
+    #[serde(rename = "telecom", default, skip_serializing_if = "Vec::is_empty")]
     telecom: Vec<ContactPoint>,
-    #[serde(rename = "gender")]
+    #[serde(rename = "gender", skip_serializing_if = "Option::is_none")]
     gender: Option<String>, // male | female | other | unknown
-    #[serde(rename = "birthDate")]
-    birth_date: Option<String>,
-    #[serde(rename = "deceasedBoolean")]
+    #[serde(rename = "birthDate", skip_serializing_if = "Option::is_none")]
+    birth_date: Option<Date>,
+    #[serde(rename = "deceasedBoolean", skip_serializing_if = "Option::is_none")]
     deceased_boolean: Option<bool>,
-    #[serde(rename = "deceasedDateTime")]
-    deceased_date_time: Option<String>,
-    #[serde(rename = "address")]
+    #[serde(rename = "deceasedDateTime", skip_serializing_if = "Option::is_none")]
+    deceased_date_time: Option<DateTime>,
+    #[serde(rename = "address", default, skip_serializing_if = "Vec::is_empty")]
     address: Vec<Address>,
-    #[serde(rename = "maritalStatus")]
+    #[serde(rename = "maritalStatus", skip_serializing_if = "Option::is_none")]
     marital_status: Option<CodeableConcept>,
-    #[serde(rename = "multipleBirthBoolean")]
+    #[serde(rename = "multipleBirthBoolean", skip_serializing_if = "Option::is_none")]
     multiple_birth_boolean: Option<bool>,
-    #[serde(rename = "multipleBirthInteger")]
+    #[serde(rename = "multipleBirthInteger", skip_serializing_if = "Option::is_none")]
     multiple_birth_integer: Option<i32>,
-    #[serde(rename = "photo")]
+    #[serde(rename = "photo", default, skip_serializing_if = "Vec::is_empty")]
     photo: Vec<String>, // Simplified as String for now
-    #[serde(rename = "contact")]
+    #[serde(rename = "contact", default, skip_serializing_if = "Vec::is_empty")]
     contact: Vec<PatientContact>,
-    #[serde(rename = "communication")]
+    #[serde(rename = "communication", default, skip_serializing_if = "Vec::is_empty")]
     communication: Vec<PatientCommunication>,
-    #[serde(rename = "generalPractitioner")]
+    #[serde(rename = "generalPractitioner", default, skip_serializing_if = "Vec::is_empty")]
     general_practitioner: Vec<Reference>,
-    #[serde(rename = "managingOrganization")]
+    #[serde(rename = "managingOrganization", skip_serializing_if = "Option::is_none")]
     managing_organization: Option<Reference>,
-    #[serde(rename = "link")]
+    #[serde(rename = "link", default, skip_serializing_if = "Vec::is_empty")]
     link: Vec<PatientLink>,
 }
 
@@ -56,6 +62,8 @@ impl Patient {
     pub fn new() -> Self {
         Self {
             resource_type: "Patient".to_string(),
+            id: None,
+            meta: None,
             identifier: Vec::new(),
             active: None,
             name: Vec::new(),
@@ -81,6 +89,8 @@ impl Patient {
     pub fn with_resource_type(resource_type: String) -> Self {
         Self {
             resource_type,
+            id: None,
+            meta: None,
             identifier: Vec::new(),
             active: None,
             name: Vec::new(),
@@ -107,6 +117,16 @@ impl Patient {
         &self.resource_type
     }
 
+    /// Returns the logical id.
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Returns the resource metadata.
+    pub fn meta(&self) -> Option<&Meta> {
+        self.meta.as_ref()
+    }
+
     /// Returns the identifiers.
     pub fn identifiers(&self) -> &[Identifier] {
         &self.identifier
@@ -132,6 +152,16 @@ impl Patient {
         self.resource_type = resource_type;
     }
 
+    /// Sets the logical id.
+    pub fn set_id(&mut self, id: Option<String>) {
+        self.id = id;
+    }
+
+    /// Sets the resource metadata.
+    pub fn set_meta(&mut self, meta: Option<Meta>) {
+        self.meta = meta;
+    }
+
     /// Sets the identifiers.
     pub fn set_identifiers(&mut self, identifiers: Vec<Identifier>) {
         self.identifier = identifiers;
@@ -174,11 +204,11 @@ impl Patient {
     }
 
     // Birth date
-    pub fn birth_date(&self) -> Option<&str> {
-        self.birth_date.as_deref()
+    pub fn birth_date(&self) -> Option<&Date> {
+        self.birth_date.as_ref()
     }
 
-    pub fn set_birth_date(&mut self, birth_date: Option<String>) {
+    pub fn set_birth_date(&mut self, birth_date: Option<Date>) {
         self.birth_date = birth_date;
     }
 
@@ -191,11 +221,11 @@ impl Patient {
         self.deceased_boolean = deceased;
     }
 
-    pub fn deceased_date_time(&self) -> Option<&str> {
-        self.deceased_date_time.as_deref()
+    pub fn deceased_date_time(&self) -> Option<&DateTime> {
+        self.deceased_date_time.as_ref()
     }
 
-    pub fn set_deceased_date_time(&mut self, deceased_date_time: Option<String>) {
+    pub fn set_deceased_date_time(&mut self, deceased_date_time: Option<DateTime>) {
         self.deceased_date_time = deceased_date_time;
     }
 
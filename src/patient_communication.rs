@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 pub struct PatientCommunication {
     #[serde(rename = "language")]
     language: CodeableConcept,
-    #[serde(rename = "preferred")]
+    #[serde(rename = "preferred", skip_serializing_if = "Option::is_none")]
     preferred: Option<bool>,
 }
 
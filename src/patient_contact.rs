@@ -8,19 +8,19 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PatientContact {
-    #[serde(rename = "relationship")]
+    #[serde(rename = "relationship", default, skip_serializing_if = "Vec::is_empty")]
     relationship: Vec<CodeableConcept>,
-    #[serde(rename = "name")]
+    #[serde(rename = "name", skip_serializing_if = "Option::is_none")]
     name: Option<HumanName>,
-    #[serde(rename = "telecom")]
+    #[serde(rename = "telecom", default, skip_serializing_if = "Vec::is_empty")]
     telecom: Vec<ContactPoint>,
-    #[serde(rename = "address")]
+    #[serde(rename = "address", skip_serializing_if = "Option::is_none")]
     address: Option<Address>,
-    #[serde(rename = "gender")]
+    #[serde(rename = "gender", skip_serializing_if = "Option::is_none")]
     gender: Option<String>, // male | female | other | unknown
-    #[serde(rename = "organization")]
+    #[serde(rename = "organization", skip_serializing_if = "Option::is_none")]
     organization: Option<Reference>,
-    #[serde(rename = "period")]
+    #[serde(rename = "period", skip_serializing_if = "Option::is_none")]
     period: Option<Period>,
 }
 
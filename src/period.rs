@@ -1,32 +1,132 @@
+use std::cmp::Ordering;
+use std::time::Duration;
+
+use crate::data_types::date_time::{DateTime, Instant, Precision};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PeriodError {
+    #[error("Period start {0} is after its end {1} (per-1)")]
+    StartAfterEnd(String, String),
+}
+
+/// A time range. Either bound may be absent: a period without an end is
+/// ongoing. A date bound covers its whole year, month or day, an end time
+/// its whole second, and a bound without a time zone is read in UTC; see
+/// [`DateTime::assume_offset`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Period {
-    start: String,
-    end: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start: Option<DateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end: Option<DateTime>,
 }
 
 impl Period {
-    pub fn new(start: String, end: String) -> Self {
+    pub fn new(start: Option<DateTime>, end: Option<DateTime>) -> Self {
         Self { start, end }
     }
 
-    pub fn start(&self) -> &str {
-        &self.start
+    pub fn start(&self) -> Option<&DateTime> {
+        self.start.as_ref()
     }
 
-    pub fn set_start(&mut self, start: String) {
+    pub fn set_start(&mut self, start: Option<DateTime>) {
         self.start = start;
     }
 
-    pub fn end(&self) -> &str {
-        &self.end
+    pub fn end(&self) -> Option<&DateTime> {
+        self.end.as_ref()
     }
 
-    pub fn set_end(&mut self, end: String) {
+    pub fn set_end(&mut self, end: Option<DateTime>) {
         self.end = end;
     }
 
+    /// Checks per-1: the start, if present, is not after the end. Bounds
+    /// whose order is uncertain, such as `2020` and `2020-06`, pass.
+    pub fn validate(&self) -> Result<(), PeriodError> {
+        if let (Some(start), Some(end)) = (&self.start, &self.end)
+            && start.compare(end) == Some(Ordering::Greater)
+        {
+            return Err(PeriodError::StartAfterEnd(start.to_string(), end.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Returns true if the instant falls within the period, bounds included.
+    pub fn contains(&self, instant: &Instant) -> bool {
+        let moment = instant.epoch_nanos();
+        self.lower().is_none_or(|lower| lower <= moment) && self.upper().is_none_or(|upper| moment <= upper)
+    }
+
+    /// Returns true if the periods share at least one moment.
+    pub fn overlaps(&self, other: &Period) -> bool {
+        let lower = self.lower().max(other.lower());
+        let upper = match (self.upper(), other.upper()) {
+            (Some(upper), Some(other_upper)) => Some(upper.min(other_upper)),
+            (upper, other_upper) => upper.or(other_upper),
+        };
+        match (lower, upper) {
+            (Some(lower), Some(upper)) => lower <= upper,
+            _ => true,
+        }
+    }
+
+    /// Returns the part shared by both periods, keeping the bounds as
+    /// written, or `None` if they do not overlap.
+    pub fn intersection(&self, other: &Period) -> Option<Period> {
+        if !self.overlaps(other) {
+            return None;
+        }
+        let start = match (&self.start, &other.start) {
+            (Some(_), Some(other_start)) if other.lower() > self.lower() => Some(other_start.clone()),
+            (Some(start), _) => Some(start.clone()),
+            (None, other_start) => other_start.clone(),
+        };
+        let end = match (&self.end, &other.end) {
+            (Some(_), Some(other_end)) if other.upper() < self.upper() => Some(other_end.clone()),
+            (Some(end), _) => Some(end.clone()),
+            (None, other_end) => other_end.clone(),
+        };
+        Some(Period { start, end })
+    }
+
+    /// Returns the length from the start to the end, counting a date end
+    /// to the close of its day, month or year. `None` if a bound is absent
+    /// or the end is before the start.
+    pub fn duration(&self) -> Option<Duration> {
+        let start = self.start.as_ref()?.span().0;
+        let end = self.end.as_ref()?.span().1;
+        let nanos = u128::try_from(end - start).ok()?;
+        Some(Duration::new((nanos / 1_000_000_000) as u64, (nanos % 1_000_000_000) as u32))
+    }
+
+    /// Returns true if the period contains the current time.
+    pub fn is_current(&self) -> bool {
+        self.contains(&Instant::now())
+    }
+
+    /// The first moment covered, in nanoseconds since the epoch.
+    fn lower(&self) -> Option<i128> {
+        self.start.as_ref().map(|start| start.span().0)
+    }
+
+    /// The last moment covered, in nanoseconds since the epoch. An end
+    /// time covers its whole second, or the whole of its last fractional
+    /// digit, so `10:00:00` runs until just before `10:00:01`.
+    fn upper(&self) -> Option<i128> {
+        self.end.as_ref().map(|end| {
+            let (start, close) = end.span();
+            let close = match end.precision() {
+                Precision::Second => start + 1_000_000_000,
+                Precision::Fraction(digits) => start + 10_i128.pow(9 - u32::from(digits)),
+                _ => close,
+            };
+            close - 1
+        })
+    }
+
     /// Converts the Period to a JSON string.
     pub fn to_json(&self) -> String {
         serde_json::to_string(self).unwrap()
@@ -36,4 +136,4 @@ impl Period {
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
         serde_json::from_str(json)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,286 @@
+//! `_elements` and `_summary` projections.
+//!
+//! A [`Projection`] returns a trimmed copy of a resource holding only the
+//! requested or summary elements, along with `id`, `meta` and the elements the
+//! resource cannot be valid without. Trimmed copies are tagged SUBSETTED so
+//! that they are not mistaken for the full resource.
+
+use crate::account::Account;
+use crate::data_types::codeable_concept::Coding;
+use crate::meta::Meta;
+use crate::patient::Patient;
+use crate::resource::{AnyResource, Resource};
+
+/// The code system of the SUBSETTED tag.
+pub const SUBSETTED_SYSTEM: &str = "http://terminology.hl7.org/CodeSystem/v3-ObservationValue";
+/// The code of the SUBSETTED tag.
+pub const SUBSETTED_CODE: &str = "SUBSETTED";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProjectionError {
+    #[error("Invalid _summary value: {0}")]
+    InvalidSummary(String),
+    #[error("Unknown element {1} for resource type {0}")]
+    UnknownElement(String, String),
+}
+
+/// The `_summary` modes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryMode {
+    /// Only elements marked as summary elements.
+    True,
+    /// Only `id`, `meta`, the narrative and mandatory elements.
+    Text,
+    /// Everything but the narrative.
+    Data,
+    /// Only the number of matches; applies to searches.
+    Count,
+    /// The full resource.
+    False,
+}
+
+impl SummaryMode {
+    /// Parses a `_summary` value.
+    pub fn parse(value: &str) -> Result<Self, ProjectionError> {
+        match value {
+            "true" => Ok(SummaryMode::True),
+            "text" => Ok(SummaryMode::Text),
+            "data" => Ok(SummaryMode::Data),
+            "count" => Ok(SummaryMode::Count),
+            "false" => Ok(SummaryMode::False),
+            _ => Err(ProjectionError::InvalidSummary(value.to_string())),
+        }
+    }
+}
+
+/// The elements of a resource that can be projected.
+pub trait Projectable: Resource {
+    /// The top-level elements, with choice elements named without their type suffix.
+    const ELEMENTS: &'static [&'static str];
+    /// The elements marked as summary elements.
+    const SUMMARY_ELEMENTS: &'static [&'static str];
+    /// The elements with a minimum cardinality of one.
+    const MANDATORY_ELEMENTS: &'static [&'static str];
+
+    /// Clears the named top-level element.
+    fn clear_element(&mut self, name: &str);
+}
+
+/// An `_elements` or `_summary` projection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Projection {
+    Elements(Vec<String>),
+    Summary(SummaryMode),
+}
+
+impl Projection {
+    /// Parses an `_elements` value, a comma-separated list of element names
+    /// that may be prefixed with the resource type, e.g. `Patient.name`.
+    pub fn elements(value: &str) -> Self {
+        let names = value
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| match name.split_once('.') {
+                Some((_, element)) => element.to_string(),
+                None => name.to_string(),
+            })
+            .collect();
+        Projection::Elements(names)
+    }
+
+    /// Parses a `_summary` value.
+    pub fn summary(value: &str) -> Result<Self, ProjectionError> {
+        SummaryMode::parse(value).map(Projection::Summary)
+    }
+
+    /// Returns an error for any requested element the resource type does not have.
+    pub fn validate(&self, resource_type: &str) -> Result<(), ProjectionError> {
+        let elements = match resource_type {
+            Patient::RESOURCE_TYPE => Patient::ELEMENTS,
+            Account::RESOURCE_TYPE => Account::ELEMENTS,
+            _ => &[],
+        };
+        if let Projection::Elements(names) = self
+            && let Some(unknown) = names.iter().find(|name| !elements.contains(&name.as_str()))
+        {
+            return Err(ProjectionError::UnknownElement(resource_type.to_string(), unknown.clone()));
+        }
+        Ok(())
+    }
+
+    /// Returns true when the projection returns the resource unchanged.
+    pub fn is_full(&self) -> bool {
+        matches!(self, Projection::Summary(SummaryMode::False))
+    }
+
+    /// Returns a trimmed copy of a resource, tagged SUBSETTED unless the
+    /// projection keeps the full resource.
+    pub fn apply<R: Projectable>(&self, resource: &R) -> R {
+        let mut projected = resource.clone();
+        if self.is_full() {
+            return projected;
+        }
+        for element in R::ELEMENTS {
+            if !self.keeps::<R>(element) {
+                projected.clear_element(element);
+            }
+        }
+        let mut meta = projected.meta().cloned().unwrap_or_default();
+        if !is_subsetted(&meta) {
+            meta.add_tag(Coding::with_system_and_code(
+                SUBSETTED_SYSTEM.to_string(),
+                SUBSETTED_CODE.to_string(),
+            ));
+        }
+        projected.set_meta(Some(meta));
+        projected
+    }
+
+    /// Applies the projection to any resource.
+    pub fn apply_any(&self, resource: &AnyResource) -> AnyResource {
+        match resource {
+            AnyResource::Patient(patient) => self.apply(patient).into(),
+            AnyResource::Account(account) => self.apply(account).into(),
+        }
+    }
+
+    fn keeps<R: Projectable>(&self, element: &str) -> bool {
+        if R::MANDATORY_ELEMENTS.contains(&element) {
+            return true;
+        }
+        match self {
+            Projection::Elements(names) => names.iter().any(|name| name == element),
+            Projection::Summary(SummaryMode::True) => R::SUMMARY_ELEMENTS.contains(&element),
+            Projection::Summary(SummaryMode::Text | SummaryMode::Count) => false,
+            Projection::Summary(SummaryMode::Data | SummaryMode::False) => true,
+        }
+    }
+}
+
+/// Returns true if the metadata carries the SUBSETTED tag.
+pub fn is_subsetted(meta: &Meta) -> bool {
+    meta.tag()
+        .iter()
+        .any(|tag| tag.system() == Some(SUBSETTED_SYSTEM) && tag.code() == Some(SUBSETTED_CODE))
+}
+
+impl Projectable for Patient {
+    const ELEMENTS: &'static [&'static str] = &[
+        "identifier",
+        "active",
+        "name",
+        "telecom",
+        "gender",
+        "birthDate",
+        "deceased",
+        "address",
+        "maritalStatus",
+        "multipleBirth",
+        "photo",
+        "contact",
+        "communication",
+        "generalPractitioner",
+        "managingOrganization",
+        "link",
+    ];
+    const SUMMARY_ELEMENTS: &'static [&'static str] = &[
+        "identifier",
+        "active",
+        "name",
+        "telecom",
+        "gender",
+        "birthDate",
+        "deceased",
+        "address",
+        "managingOrganization",
+        "link",
+    ];
+    const MANDATORY_ELEMENTS: &'static [&'static str] = &[];
+
+    fn clear_element(&mut self, name: &str) {
+        match name {
+            "identifier" => self.set_identifiers(Vec::new()),
+            "active" => self.set_active(None),
+            "name" => self.set_names(Vec::new()),
+            "telecom" => self.set_telecom(Vec::new()),
+            "gender" => self.set_gender(None),
+            "birthDate" => self.set_birth_date(None),
+            "deceased" => {
+                self.set_deceased_boolean(None);
+                self.set_deceased_date_time(None);
+            }
+            "address" => self.set_address(Vec::new()),
+            "maritalStatus" => self.set_marital_status(None),
+            "multipleBirth" => {
+                self.set_multiple_birth_boolean(None);
+                self.set_multiple_birth_integer(None);
+            }
+            "photo" => self.set_photo(Vec::new()),
+            "contact" => self.set_contact(Vec::new()),
+            "communication" => self.set_communication(Vec::new()),
+            "generalPractitioner" => self.set_general_practitioner(Vec::new()),
+            "managingOrganization" => self.set_managing_organization(None),
+            "link" => self.set_link(Vec::new()),
+            _ => {}
+        }
+    }
+}
+
+impl Projectable for Account {
+    const ELEMENTS: &'static [&'static str] = &[
+        "identifier",
+        "status",
+        "billingStatus",
+        "type",
+        "name",
+        "subject",
+        "servicePeriod",
+        "coverage",
+        "owner",
+        "description",
+        "guarantor",
+        "diagnosis",
+        "procedure",
+        "relatedAccount",
+        "currency",
+        "balance",
+        "calculatedAt",
+    ];
+    const SUMMARY_ELEMENTS: &'static [&'static str] = &[
+        "identifier",
+        "status",
+        "billingStatus",
+        "type",
+        "name",
+        "subject",
+        "servicePeriod",
+        "coverage",
+        "owner",
+        "description",
+    ];
+    const MANDATORY_ELEMENTS: &'static [&'static str] = &["status"];
+
+    fn clear_element(&mut self, name: &str) {
+        match name {
+            "identifier" => self.set_identifiers(Vec::new()),
+            "status" => self.set_status(None),
+            "billingStatus" => self.set_billing_status(None),
+            "type" => self.set_account_type(None),
+            "name" => self.set_name(None),
+            "subject" => self.set_subject(Vec::new()),
+            "servicePeriod" => self.set_service_period(None),
+            "coverage" => self.set_coverage(Vec::new()),
+            "owner" => self.set_owner(None),
+            "description" => self.set_description(None),
+            "guarantor" => self.set_guarantor(Vec::new()),
+            "diagnosis" => self.set_diagnosis(Vec::new()),
+            "procedure" => self.set_procedure(Vec::new()),
+            "relatedAccount" => self.set_related_account(Vec::new()),
+            "currency" => self.set_currency(None),
+            "balance" => self.set_balance(Vec::new()),
+            "calculatedAt" => self.set_calculated_at(None),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+
+use crate::meta::{now, Meta};
+use crate::resource::{AnyResource, RESOURCE_TYPES};
+
+#[derive(Debug, thiserror::Error)]
+pub enum RepositoryError {
+    #[error("Resource not found: {0}/{1}")]
+    NotFound(String, String),
+    #[error("Resource has no id")]
+    MissingId,
+    #[error("Version conflict on {0}/{1}: expected version {2} but the current version is {3}")]
+    VersionConflict(String, String, String, String),
+}
+
+/// Storage for resources, used by search and the operations built on it.
+pub trait Repository {
+    /// Stores a new resource, assigning its id and `meta.versionId`.
+    fn create(&mut self, resource: AnyResource) -> Result<AnyResource, RepositoryError>;
+
+    /// Reads the current version of a resource.
+    fn read(&self, resource_type: &str, id: &str) -> Result<AnyResource, RepositoryError>;
+
+    /// Returns a new id that no stored resource has and that `create` will
+    /// not assign, for a resource to be stored later with `update`.
+    fn allocate_id(&mut self) -> String;
+
+    /// Replaces the resource with the same type and id, creating it if absent.
+    fn update(&mut self, resource: AnyResource) -> Result<AnyResource, RepositoryError>;
+
+    /// Replaces the resource only if its current `meta.versionId` is the
+    /// expected version, so that concurrent edits cannot overwrite each other.
+    fn update_versioned(&mut self, resource: AnyResource, expected_version: &str) -> Result<AnyResource, RepositoryError> {
+        let id = resource.id().ok_or(RepositoryError::MissingId)?;
+        let current = self.read(resource.resource_type(), id)?;
+        let current_version = current.meta().and_then(Meta::version_id).unwrap_or_default();
+        if current_version != expected_version {
+            return Err(RepositoryError::VersionConflict(
+                resource.resource_type().to_string(),
+                id.to_string(),
+                expected_version.to_string(),
+                current_version.to_string(),
+            ));
+        }
+        self.update(resource)
+    }
+
+    /// Deletes a resource.
+    fn delete(&mut self, resource_type: &str, id: &str) -> Result<(), RepositoryError>;
+
+    /// Returns every current resource of a type.
+    fn list(&self, resource_type: &str) -> Result<Vec<AnyResource>, RepositoryError>;
+
+    /// Reads a specific version of a resource. Repositories that keep no
+    /// history can only return the current version.
+    fn vread(&self, resource_type: &str, id: &str, version_id: &str) -> Result<AnyResource, RepositoryError> {
+        let resource = self.read(resource_type, id)?;
+        match resource.meta().and_then(Meta::version_id) {
+            Some(current) if current == version_id => Ok(resource),
+            _ => Err(RepositoryError::NotFound(
+                resource_type.to_string(),
+                format!("{}/_history/{}", id, version_id),
+            )),
+        }
+    }
+
+    /// Returns the stored versions, newest first, optionally restricted to a
+    /// resource type and to one resource of that type. Repositories that keep
+    /// no history return the current versions.
+    fn history(&self, resource_type: Option<&str>, id: Option<&str>) -> Result<Vec<ResourceVersion>, RepositoryError> {
+        let resource_types: Vec<&str> = match resource_type {
+            Some(resource_type) => vec![resource_type],
+            None => RESOURCE_TYPES.to_vec(),
+        };
+        let mut versions = Vec::new();
+        for resource_type in resource_types {
+            for resource in self.list(resource_type)? {
+                if id.is_none_or(|id| resource.id() == Some(id)) {
+                    versions.extend(ResourceVersion::from_resource(resource));
+                }
+            }
+        }
+        versions.sort_by(|left, right| right.last_updated.cmp(&left.last_updated));
+        Ok(versions)
+    }
+}
+
+/// One stored version of a resource. Deletions are recorded as versions
+/// without a resource.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceVersion {
+    resource_type: String,
+    id: String,
+    version_id: String,
+    last_updated: String,
+    resource: Option<AnyResource>,
+}
+
+impl ResourceVersion {
+    /// Creates the version recorded for a stored resource, if it has an id and version.
+    pub fn from_resource(resource: AnyResource) -> Option<Self> {
+        let meta = resource.meta()?;
+        Some(Self {
+            resource_type: resource.resource_type().to_string(),
+            id: resource.id()?.to_string(),
+            version_id: meta.version_id()?.to_string(),
+            last_updated: meta.last_updated().unwrap_or_default().to_string(),
+            resource: Some(resource),
+        })
+    }
+
+    /// Creates the version recording a deletion.
+    pub fn deletion(resource_type: String, id: String, version_id: String) -> Self {
+        Self {
+            resource_type,
+            id,
+            version_id,
+            last_updated: now(),
+            resource: None,
+        }
+    }
+
+    /// Returns the resource type.
+    pub fn resource_type(&self) -> &str {
+        &self.resource_type
+    }
+
+    /// Returns the logical id.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the version id.
+    pub fn version_id(&self) -> &str {
+        &self.version_id
+    }
+
+    /// Returns when the version was stored.
+    pub fn last_updated(&self) -> &str {
+        &self.last_updated
+    }
+
+    /// Returns the resource, or `None` for a deletion.
+    pub fn resource(&self) -> Option<&AnyResource> {
+        self.resource.as_ref()
+    }
+
+    /// Returns true if the version records a deletion.
+    pub fn is_deletion(&self) -> bool {
+        self.resource.is_none()
+    }
+}
+
+/// A [`Repository`] holding resources in memory.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryRepository {
+    resources: HashMap<String, Vec<AnyResource>>,
+    versions: Vec<ResourceVersion>,
+    next_id: u64,
+}
+
+impl MemoryRepository {
+    /// Creates a new, empty MemoryRepository.
+    pub fn new() -> Self {
+        Self {
+            resources: HashMap::new(),
+            versions: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    fn position(&self, resource_type: &str, id: &str) -> Option<usize> {
+        self.resources
+            .get(resource_type)?
+            .iter()
+            .position(|resource| resource.id() == Some(id))
+    }
+
+    fn latest_version(&self, resource_type: &str, id: &str) -> Option<&ResourceVersion> {
+        self.versions
+            .iter()
+            .rev()
+            .find(|version| version.resource_type == resource_type && version.id == id)
+    }
+
+    fn record(&mut self, resource: &AnyResource) {
+        self.versions.extend(ResourceVersion::from_resource(resource.clone()));
+    }
+}
+
+impl Repository for MemoryRepository {
+    fn create(&mut self, mut resource: AnyResource) -> Result<AnyResource, RepositoryError> {
+        resource.set_id(Some(self.allocate_id()));
+        resource.set_meta(Some(next_meta(resource.meta(), None)));
+        self.resources
+            .entry(resource.resource_type().to_string())
+            .or_default()
+            .push(resource.clone());
+        self.record(&resource);
+        Ok(resource)
+    }
+
+    /// Returns the next id not used by any resource, past or present. Ids
+    /// chosen by clients through `update` are skipped.
+    fn allocate_id(&mut self) -> String {
+        let mut id = self.next_id.max(1);
+        while self.versions.iter().any(|version| version.id == id.to_string()) {
+            id += 1;
+        }
+        self.next_id = id + 1;
+        id.to_string()
+    }
+
+    fn read(&self, resource_type: &str, id: &str) -> Result<AnyResource, RepositoryError> {
+        self.position(resource_type, id)
+            .map(|index| self.resources[resource_type][index].clone())
+            .ok_or_else(|| RepositoryError::NotFound(resource_type.to_string(), id.to_string()))
+    }
+
+    fn update(&mut self, mut resource: AnyResource) -> Result<AnyResource, RepositoryError> {
+        let id = resource.id().ok_or(RepositoryError::MissingId)?.to_string();
+        let resource_type = resource.resource_type();
+        match self.position(resource_type, &id) {
+            Some(index) => {
+                let stored = &mut self.resources.get_mut(resource_type).unwrap()[index];
+                resource.set_meta(Some(next_meta(resource.meta(), stored.meta())));
+                *stored = resource.clone();
+            }
+            None => {
+                // A resource recreated after deletion continues its version sequence.
+                let previous = self
+                    .latest_version(resource_type, &id)
+                    .map(|version| Meta::with_version(version.version_id.clone()));
+                resource.set_meta(Some(next_meta(resource.meta(), previous.as_ref())));
+                self.resources
+                    .entry(resource_type.to_string())
+                    .or_default()
+                    .push(resource.clone());
+            }
+        }
+        self.record(&resource);
+        Ok(resource)
+    }
+
+    fn delete(&mut self, resource_type: &str, id: &str) -> Result<(), RepositoryError> {
+        let index = self
+            .position(resource_type, id)
+            .ok_or_else(|| RepositoryError::NotFound(resource_type.to_string(), id.to_string()))?;
+        let removed = self.resources.get_mut(resource_type).unwrap().remove(index);
+        let version = next_meta(None, removed.meta()).version_id().unwrap_or_default().to_string();
+        self.versions.push(ResourceVersion::deletion(
+            resource_type.to_string(),
+            id.to_string(),
+            version,
+        ));
+        Ok(())
+    }
+
+    fn list(&self, resource_type: &str) -> Result<Vec<AnyResource>, RepositoryError> {
+        Ok(self.resources.get(resource_type).cloned().unwrap_or_default())
+    }
+
+    fn vread(&self, resource_type: &str, id: &str, version_id: &str) -> Result<AnyResource, RepositoryError> {
+        self.versions
+            .iter()
+            .find(|version| version.resource_type == resource_type && version.id == id && version.version_id == version_id)
+            .and_then(|version| version.resource.clone())
+            .ok_or_else(|| {
+                RepositoryError::NotFound(resource_type.to_string(), format!("{}/_history/{}", id, version_id))
+            })
+    }
+
+    fn history(&self, resource_type: Option<&str>, id: Option<&str>) -> Result<Vec<ResourceVersion>, RepositoryError> {
+        Ok(self
+            .versions
+            .iter()
+            .rev()
+            .filter(|version| resource_type.is_none_or(|resource_type| version.resource_type == resource_type))
+            .filter(|version| id.is_none_or(|id| version.id == id))
+            .cloned()
+            .collect())
+    }
+}
+
+/// Builds the meta for a newly stored version, keeping the incoming tags and
+/// incrementing the previous version id.
+fn next_meta(incoming: Option<&Meta>, previous: Option<&Meta>) -> Meta {
+    let version = previous
+        .and_then(Meta::version_id)
+        .and_then(|version| version.parse::<u64>().ok())
+        .map_or(1, |version| version + 1);
+    let mut meta = Meta::with_version(version.to_string());
+    if let Some(incoming) = incoming {
+        meta.set_tag(incoming.tag().to_vec());
+    }
+    meta
+}
@@ -0,0 +1,176 @@
+use crate::account::Account;
+use crate::meta::Meta;
+use crate::patient::Patient;
+use crate::search::{Searchable, SearchValue};
+use serde::de::{DeserializeOwned, Error as _};
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// The media type of FHIR JSON.
+pub const FHIR_JSON: &str = "application/fhir+json";
+
+/// Common behaviour of the FHIR resources provided by this crate.
+pub trait Resource: Searchable + Clone + Serialize + DeserializeOwned {
+    /// The FHIR resource type name, e.g. `Patient`.
+    const RESOURCE_TYPE: &'static str;
+
+    /// Returns the logical id.
+    fn id(&self) -> Option<&str>;
+
+    /// Sets the logical id.
+    fn set_id(&mut self, id: Option<String>);
+
+    /// Returns the resource metadata.
+    fn meta(&self) -> Option<&Meta>;
+
+    /// Sets the resource metadata.
+    fn set_meta(&mut self, meta: Option<Meta>);
+
+    /// Wraps the resource in an [`AnyResource`].
+    fn into_any(self) -> AnyResource;
+
+    /// Unwraps an [`AnyResource`] holding this resource type.
+    fn from_any(resource: AnyResource) -> Option<Self>;
+}
+
+macro_rules! impl_resource {
+    ($resource:ident) => {
+        impl Resource for $resource {
+            const RESOURCE_TYPE: &'static str = stringify!($resource);
+
+            fn id(&self) -> Option<&str> {
+                $resource::id(self)
+            }
+
+            fn set_id(&mut self, id: Option<String>) {
+                $resource::set_id(self, id)
+            }
+
+            fn meta(&self) -> Option<&Meta> {
+                $resource::meta(self)
+            }
+
+            fn set_meta(&mut self, meta: Option<Meta>) {
+                $resource::set_meta(self, meta)
+            }
+
+            fn into_any(self) -> AnyResource {
+                AnyResource::$resource(self)
+            }
+
+            fn from_any(resource: AnyResource) -> Option<Self> {
+                match resource {
+                    AnyResource::$resource(resource) => Some(resource),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+impl_resource!(Patient);
+impl_resource!(Account);
+
+/// Any resource provided by this crate, dispatched on `resourceType` when deserialized.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum AnyResource {
+    Patient(Patient),
+    Account(Account),
+}
+
+/// The resource types that can be held in an [`AnyResource`].
+pub const RESOURCE_TYPES: &[&str] = &[Patient::RESOURCE_TYPE, Account::RESOURCE_TYPE];
+
+macro_rules! dispatch {
+    ($value:expr, $resource:ident => $body:expr) => {
+        match $value {
+            AnyResource::Patient($resource) => $body,
+            AnyResource::Account($resource) => $body,
+        }
+    };
+}
+
+impl AnyResource {
+    /// Returns the resource type name.
+    pub fn resource_type(&self) -> &'static str {
+        match self {
+            AnyResource::Patient(_) => Patient::RESOURCE_TYPE,
+            AnyResource::Account(_) => Account::RESOURCE_TYPE,
+        }
+    }
+
+    /// Returns the logical id.
+    pub fn id(&self) -> Option<&str> {
+        dispatch!(self, resource => Resource::id(resource))
+    }
+
+    /// Sets the logical id.
+    pub fn set_id(&mut self, id: Option<String>) {
+        dispatch!(self, resource => Resource::set_id(resource, id))
+    }
+
+    /// Returns the resource metadata.
+    pub fn meta(&self) -> Option<&Meta> {
+        dispatch!(self, resource => Resource::meta(resource))
+    }
+
+    /// Sets the resource metadata.
+    pub fn set_meta(&mut self, meta: Option<Meta>) {
+        dispatch!(self, resource => Resource::set_meta(resource, meta))
+    }
+
+    /// Returns the relative reference `Type/id`, if the resource has an id.
+    pub fn reference(&self) -> Option<String> {
+        self.id().map(|id| format!("{}/{}", self.resource_type(), id))
+    }
+
+    /// Converts to a concrete resource type.
+    pub fn downcast<R: Resource>(self) -> Option<R> {
+        R::from_any(self)
+    }
+
+    /// Converts the resource to a JSON string.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Converts a JSON string to a resource, dispatching on `resourceType`.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+impl Searchable for AnyResource {
+    fn search_values(&self, name: &str) -> Vec<SearchValue<'_>> {
+        dispatch!(self, resource => resource.search_values(name))
+    }
+}
+
+impl<'de> Deserialize<'de> for AnyResource {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let resource_type = value
+            .get("resourceType")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| D::Error::missing_field("resourceType"))?
+            .to_string();
+        match resource_type.as_str() {
+            "Patient" => serde_json::from_value(value).map(AnyResource::Patient),
+            "Account" => serde_json::from_value(value).map(AnyResource::Account),
+            other => return Err(D::Error::custom(format!("unsupported resource type: {}", other))),
+        }
+        .map_err(D::Error::custom)
+    }
+}
+
+impl From<Patient> for AnyResource {
+    fn from(patient: Patient) -> Self {
+        AnyResource::Patient(patient)
+    }
+}
+
+impl From<Account> for AnyResource {
+    fn from(account: Account) -> Self {
+        AnyResource::Account(account)
+    }
+}
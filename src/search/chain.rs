@@ -0,0 +1,102 @@
+//! Chained (`subject:Patient.family=smith`) and reverse chained
+//! (`_has:Account:subject:status=active`) search parameters.
+
+use std::collections::HashSet;
+
+use crate::repository::Repository;
+use crate::resource::RESOURCE_TYPES;
+use super::params::{self, SearchParamType, SearchParameterDefinition};
+use super::{QueryParam, SearchEngine, SearchError, SearchQuery, SearchValue, Searchable};
+
+impl SearchEngine {
+    /// Matches a chained reference parameter by searching the target type with
+    /// the rest of the chain and checking whether the resource refers to any of
+    /// the results.
+    pub(super) fn matches_chain(
+        &self,
+        repository: &dyn Repository,
+        definition: &SearchParameterDefinition,
+        resource: &dyn Searchable,
+        param: &QueryParam,
+    ) -> Result<bool, SearchError> {
+        if definition.param_type != SearchParamType::Reference {
+            return Err(SearchError::InvalidChain(param.name().to_string()));
+        }
+        let chain = param.chain().unwrap_or_default();
+        let target_types: Vec<&str> = match param.modifier() {
+            Some(target) if definition.targets.contains(&target) => vec![target],
+            Some(target) => return Err(SearchError::UnsupportedModifier(param.name().to_string(), target.to_string())),
+            None => definition
+                .targets
+                .iter()
+                .copied()
+                .filter(|target| RESOURCE_TYPES.contains(target))
+                .filter(|target| params::definition(target, chained_name(chain)).is_some())
+                .collect(),
+        };
+        if target_types.is_empty() {
+            return Err(SearchError::InvalidChain(param.name().to_string()));
+        }
+
+        let mut query = SearchQuery::new();
+        query.add(chain, param.value().to_string());
+        let mut targets = HashSet::new();
+        for target_type in target_types {
+            for target in self.search(repository, target_type, &query)? {
+                targets.extend(target.reference());
+            }
+        }
+        Ok(refers_to_any(&resource.search_values(param.name()), &targets))
+    }
+
+    /// Matches `_has:Type:reference-param:rest=value` by searching `Type` with
+    /// `rest=value` and checking whether any result refers back to the resource.
+    pub(super) fn matches_has(
+        &self,
+        repository: &dyn Repository,
+        resource_type: &str,
+        resource: &dyn Searchable,
+        param: &QueryParam,
+    ) -> Result<bool, SearchError> {
+        let spec = param.modifier().unwrap_or_default();
+        let mut parts = spec.splitn(3, ':');
+        let (Some(source_type), Some(reference_name), Some(rest)) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(SearchError::InvalidValue(param.name().to_string(), spec.to_string()));
+        };
+        let definition = params::definition(source_type, reference_name)
+            .ok_or_else(|| SearchError::UnknownParameter(source_type.to_string(), reference_name.to_string()))?;
+        if definition.param_type != SearchParamType::Reference || !definition.targets.contains(&resource_type) {
+            return Err(SearchError::InvalidChain(reference_name.to_string()));
+        }
+        let Some(id) = resource.search_values("_id").into_iter().find_map(|value| match value {
+            SearchValue::Code(_, id) => Some(id),
+            _ => None,
+        }) else {
+            return Ok(false);
+        };
+        let target = HashSet::from([format!("{}/{}", resource_type, id)]);
+
+        let mut query = SearchQuery::new();
+        query.add(rest, param.value().to_string());
+        for source in self.search(repository, source_type, &query)? {
+            if refers_to_any(&source.search_values(reference_name), &target) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Returns the parameter name at the head of a chain such as `family` or `organization.name`.
+fn chained_name(chain: &str) -> &str {
+    chain.split(['.', ':']).next().unwrap_or(chain)
+}
+
+fn refers_to_any(values: &[SearchValue<'_>], targets: &HashSet<String>) -> bool {
+    values.iter().any(|value| match value {
+        SearchValue::Reference(reference) => reference
+            .target()
+            .is_some_and(|(resource_type, id)| targets.contains(&format!("{}/{}", resource_type, id))),
+        _ => false,
+    })
+}
@@ -0,0 +1,184 @@
+//! `_include` and `_revinclude` result parameters.
+
+use std::collections::HashSet;
+
+use crate::repository::{Repository, RepositoryError};
+use crate::resource::{AnyResource, RESOURCE_TYPES};
+use super::params::{self, SearchParamType};
+use super::{QueryParam, SearchEngine, SearchError, SearchValue, Searchable};
+
+/// A parsed `_include` or `_revinclude` value of the form
+/// `SourceType:search-param[:TargetType]`, where the search param may be `*`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncludeParam {
+    source_type: String,
+    param_names: Vec<&'static str>,
+    target_type: Option<String>,
+    iterate: bool,
+    reverse: bool,
+}
+
+impl IncludeParam {
+    /// Parses an `_include` or `_revinclude` query parameter.
+    pub fn parse(param: &QueryParam) -> Result<Self, SearchError> {
+        let reverse = match param.name() {
+            "_include" => false,
+            "_revinclude" => true,
+            other => return Err(SearchError::UnknownParameter(String::new(), other.to_string())),
+        };
+        let iterate = match param.modifier() {
+            None => false,
+            Some("iterate") | Some("recurse") => true,
+            Some(modifier) => {
+                return Err(SearchError::UnsupportedModifier(param.name().to_string(), modifier.to_string()));
+            }
+        };
+        let parts: Vec<&str> = param.value().split(':').collect();
+        let (source_type, name, target_type) = match parts.as_slice() {
+            [source_type, name] => (*source_type, *name, None),
+            [source_type, name, target_type] => (*source_type, *name, Some(target_type.to_string())),
+            _ => return Err(SearchError::InvalidValue(param.name().to_string(), param.value().to_string())),
+        };
+        let param_names: Vec<&'static str> = if name == "*" {
+            params::definitions_for(source_type)
+                .filter(|definition| definition.param_type == SearchParamType::Reference)
+                .map(|definition| definition.name)
+                .collect()
+        } else {
+            let definition = params::definition(source_type, name)
+                .ok_or_else(|| SearchError::UnknownParameter(source_type.to_string(), name.to_string()))?;
+            if definition.param_type != SearchParamType::Reference {
+                return Err(SearchError::InvalidValue(param.name().to_string(), param.value().to_string()));
+            }
+            vec![definition.name]
+        };
+        Ok(Self {
+            source_type: source_type.to_string(),
+            param_names,
+            target_type,
+            iterate,
+            reverse,
+        })
+    }
+
+    /// Returns the resource type the references are read from.
+    pub fn source_type(&self) -> &str {
+        &self.source_type
+    }
+
+    /// Returns the target type restriction.
+    pub fn target_type(&self) -> Option<&str> {
+        self.target_type.as_deref()
+    }
+
+    /// Returns true for `:iterate`, which also applies to included resources.
+    pub fn iterate(&self) -> bool {
+        self.iterate
+    }
+
+    /// Returns true for `_revinclude`.
+    pub fn reverse(&self) -> bool {
+        self.reverse
+    }
+
+    fn accepts_target(&self, resource_type: &str) -> bool {
+        self.target_type.as_deref().is_none_or(|target| target == resource_type)
+    }
+}
+
+impl SearchEngine {
+    /// Resolves the includes for a set of matches. The first pass applies every
+    /// include to the matches; later passes apply only `:iterate` includes to
+    /// the resources added by the previous pass.
+    pub(super) fn resolve_includes(
+        &self,
+        repository: &dyn Repository,
+        matches: &[AnyResource],
+        includes: &[IncludeParam],
+    ) -> Result<Vec<AnyResource>, SearchError> {
+        let mut seen: HashSet<String> = matches.iter().filter_map(AnyResource::reference).collect();
+        let mut included = Vec::new();
+        let mut frontier = matches.to_vec();
+        let mut first_pass = true;
+        while !frontier.is_empty() {
+            let mut added = Vec::new();
+            for include in includes.iter().filter(|include| first_pass || include.iterate()) {
+                let found = if include.reverse() {
+                    reverse_included(repository, include, &frontier)?
+                } else {
+                    forward_included(repository, include, &frontier)?
+                };
+                for resource in found {
+                    if let Some(reference) = resource.reference()
+                        && seen.insert(reference)
+                    {
+                        added.push(resource);
+                    }
+                }
+            }
+            included.extend(added.iter().cloned());
+            frontier = added;
+            first_pass = false;
+        }
+        Ok(included)
+    }
+}
+
+fn forward_included(
+    repository: &dyn Repository,
+    include: &IncludeParam,
+    frontier: &[AnyResource],
+) -> Result<Vec<AnyResource>, SearchError> {
+    let mut found = Vec::new();
+    for resource in frontier.iter().filter(|resource| resource.resource_type() == include.source_type()) {
+        for name in &include.param_names {
+            for value in resource.search_values(name) {
+                let SearchValue::Reference(reference) = value else {
+                    continue;
+                };
+                let Some((resource_type, id)) = reference.target() else {
+                    continue;
+                };
+                if !include.accepts_target(resource_type) || !RESOURCE_TYPES.contains(&resource_type) {
+                    continue;
+                }
+                match repository.read(resource_type, id) {
+                    Ok(target) => found.push(target),
+                    Err(RepositoryError::NotFound(_, _)) => {}
+                    Err(error) => return Err(error.into()),
+                }
+            }
+        }
+    }
+    Ok(found)
+}
+
+fn reverse_included(
+    repository: &dyn Repository,
+    include: &IncludeParam,
+    frontier: &[AnyResource],
+) -> Result<Vec<AnyResource>, SearchError> {
+    let targets: HashSet<String> = frontier
+        .iter()
+        .filter(|resource| include.accepts_target(resource.resource_type()))
+        .filter_map(AnyResource::reference)
+        .collect();
+    if targets.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut found = Vec::new();
+    for source in repository.list(include.source_type())? {
+        let refers = include.param_names.iter().any(|name| {
+            source.search_values(name).iter().any(|value| match value {
+                SearchValue::Reference(reference) => reference
+                    .target()
+                    .is_some_and(|(resource_type, id)| targets.contains(&format!("{}/{}", resource_type, id))),
+                _ => false,
+            })
+        });
+        if refers {
+            found.push(source);
+        }
+    }
+    Ok(found)
+}
@@ -0,0 +1,502 @@
+//! FHIR search support.
+//!
+//! Search parameters are declared in [`params::SEARCH_PARAMETERS`] and each
+//! resource exposes the values indexed under a parameter through the
+//! [`Searchable`] trait. A [`SearchQuery`] parsed from a query string is then
+//! evaluated against resources by a [`SearchEngine`], either directly or over
+//! a [`Repository`] when the query chains through references.
+
+mod chain;
+pub mod include;
+pub mod paging;
+pub mod params;
+pub mod reference;
+pub mod string;
+pub mod token;
+pub mod value_set;
+
+use std::collections::HashMap;
+
+use crate::bundle::{Bundle, BundleEntry, BundleEntrySearch};
+use crate::data_types::codeable_concept::{CodeableConcept, Coding};
+use crate::data_types::contact_point::ContactPoint;
+use crate::data_types::reference::Reference;
+use crate::identifier::Identifier;
+use crate::projection::{Projection, ProjectionError};
+use crate::repository::{Repository, RepositoryError};
+use crate::resource::{AnyResource, Resource};
+use include::IncludeParam;
+use paging::{PageRequest, PagingMode, TotalMode};
+use params::SearchParamType;
+use reference::ReferenceCriterion;
+use string::StringCriterion;
+use token::TokenCriterion;
+use value_set::ValueSet;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SearchError {
+    #[error("Unknown search parameter {1} for resource type {0}")]
+    UnknownParameter(String, String),
+    #[error("Modifier :{1} is not supported by search parameter {0}")]
+    UnsupportedModifier(String, String),
+    #[error("Invalid value for search parameter {0}: {1}")]
+    InvalidValue(String, String),
+    #[error("Unknown value set: {0}")]
+    UnknownValueSet(String),
+    #[error("Search parameter {0} cannot be chained")]
+    InvalidChain(String),
+    #[error("Search parameter {0} requires a repository")]
+    RepositoryRequired(String),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+    #[error(transparent)]
+    Projection(#[from] ProjectionError),
+}
+
+/// Parameters that shape the result set rather than filter resources.
+pub const RESULT_PARAMETERS: &[&str] = &[
+    "_include",
+    "_revinclude",
+    "_sort",
+    "_count",
+    "_offset",
+    "_cursor",
+    "_total",
+    "_elements",
+    "_summary",
+];
+
+/// A single value indexed under a search parameter.
+#[derive(Debug, Clone, Copy)]
+pub enum SearchValue<'a> {
+    Identifier(&'a Identifier),
+    Coding(&'a Coding),
+    CodeableConcept(&'a CodeableConcept),
+    ContactPoint(&'a ContactPoint),
+    /// A primitive code with the system of its required binding, if any.
+    Code(Option<&'static str>, &'a str),
+    Boolean(bool),
+    String(&'a str),
+    Reference(&'a Reference),
+}
+
+/// Implemented by resources that can be evaluated against search parameters.
+pub trait Searchable {
+    /// Returns the values indexed under the named search parameter.
+    fn search_values(&self, name: &str) -> Vec<SearchValue<'_>>;
+}
+
+/// A single `name[:modifier][.chain]=value` pair from a search request.
+///
+/// For `_has:Account:subject:status` the modifier holds everything after
+/// `_has:`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryParam {
+    name: String,
+    modifier: Option<String>,
+    chain: Option<String>,
+    value: String,
+}
+
+impl QueryParam {
+    /// Creates a new QueryParam from an unparsed key and its value.
+    pub fn new(key: &str, value: String) -> Self {
+        let (head, chain) = match key.split_once('.') {
+            Some((head, chain)) if !key.starts_with("_has:") => (head, Some(chain.to_string())),
+            _ => (key, None),
+        };
+        let (name, modifier) = match head.split_once(':') {
+            Some((name, modifier)) => (name.to_string(), Some(modifier.to_string())),
+            None => (head.to_string(), None),
+        };
+        Self { name, modifier, chain, value }
+    }
+
+    /// Returns the parameter name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the modifier, without the leading colon.
+    pub fn modifier(&self) -> Option<&str> {
+        self.modifier.as_deref()
+    }
+
+    /// Returns the key as it appears in a query string, e.g. `subject:Patient.family`.
+    pub fn key(&self) -> String {
+        let mut key = self.name.clone();
+        if let Some(modifier) = &self.modifier {
+            key.push(':');
+            key.push_str(modifier);
+        }
+        if let Some(chain) = &self.chain {
+            key.push('.');
+            key.push_str(chain);
+        }
+        key
+    }
+
+    /// Returns the chained parameter following the first `.`.
+    pub fn chain(&self) -> Option<&str> {
+        self.chain.as_deref()
+    }
+
+    /// Returns the raw (percent-decoded) value.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+/// A parsed search request. Every parameter must match for a resource to be included.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    params: Vec<QueryParam>,
+}
+
+impl SearchQuery {
+    /// Creates an empty SearchQuery.
+    pub fn new() -> Self {
+        Self { params: Vec::new() }
+    }
+
+    /// Parses a URL query string such as `identifier=http://hospital/mrn|12345&active=true`.
+    pub fn parse(query: &str) -> Self {
+        let mut search = Self::new();
+        for pair in query.trim_start_matches('?').split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            search.add(&percent_decode(key), percent_decode(value));
+        }
+        search
+    }
+
+    /// Adds a parameter from an unparsed `name[:modifier]` key.
+    pub fn add(&mut self, key: &str, value: String) {
+        self.params.push(QueryParam::new(key, value));
+    }
+
+    /// Returns the parameters.
+    pub fn params(&self) -> &[QueryParam] {
+        &self.params
+    }
+}
+
+/// Evaluates search queries against resources.
+#[derive(Debug, Clone)]
+pub struct SearchEngine {
+    value_sets: HashMap<String, ValueSet>,
+    base_url: Option<String>,
+    default_count: usize,
+    max_count: usize,
+    paging_mode: PagingMode,
+}
+
+impl SearchEngine {
+    /// Creates a new SearchEngine with no value sets and pages of 50 matches.
+    pub fn new() -> Self {
+        Self {
+            value_sets: HashMap::new(),
+            base_url: None,
+            default_count: 50,
+            max_count: 1000,
+            paging_mode: PagingMode::Offset,
+        }
+    }
+
+    /// Returns the page size used when `_count` is absent.
+    pub fn default_count(&self) -> usize {
+        self.default_count
+    }
+
+    /// Sets the page size used when `_count` is absent.
+    pub fn set_default_count(&mut self, default_count: usize) {
+        self.default_count = default_count;
+    }
+
+    /// Returns the largest page size honoured for `_count`.
+    pub fn max_count(&self) -> usize {
+        self.max_count
+    }
+
+    /// Sets the largest page size honoured for `_count`.
+    pub fn set_max_count(&mut self, max_count: usize) {
+        self.max_count = max_count;
+    }
+
+    /// Returns how `next` and `previous` links address pages.
+    pub fn paging_mode(&self) -> PagingMode {
+        self.paging_mode
+    }
+
+    /// Sets how `next` and `previous` links address pages.
+    pub fn set_paging_mode(&mut self, paging_mode: PagingMode) {
+        self.paging_mode = paging_mode;
+    }
+
+    /// Returns the base URL used for `fullUrl` and links in result bundles.
+    pub fn base_url(&self) -> Option<&str> {
+        self.base_url.as_deref()
+    }
+
+    /// Sets the base URL used for `fullUrl` and links in result bundles.
+    pub fn set_base_url(&mut self, base_url: Option<String>) {
+        self.base_url = base_url.map(|base_url| base_url.trim_end_matches('/').to_string());
+    }
+
+    /// Registers a value set for use by the `:in` and `:not-in` modifiers.
+    pub fn add_value_set(&mut self, value_set: ValueSet) {
+        self.value_sets.insert(value_set.url().to_string(), value_set);
+    }
+
+    /// Returns the value set registered under `url`.
+    pub fn value_set(&self, url: &str) -> Option<&ValueSet> {
+        self.value_sets.get(url)
+    }
+
+    /// Checks whether a resource matches every parameter of the query.
+    ///
+    /// Chained and `_has` parameters need a repository and are rejected here;
+    /// use [`SearchEngine::search`] for those.
+    pub fn matches<R: Resource>(&self, resource: &R, query: &SearchQuery) -> Result<bool, SearchError> {
+        self.matches_query(None, R::RESOURCE_TYPE, resource, query)
+    }
+
+    /// Returns the resources matching the query.
+    pub fn filter<'r, R: Resource>(&self, resources: &'r [R], query: &SearchQuery) -> Result<Vec<&'r R>, SearchError> {
+        let mut matched = Vec::new();
+        for resource in resources {
+            if self.matches(resource, query)? {
+                matched.push(resource);
+            }
+        }
+        Ok(matched)
+    }
+
+    /// Returns the resources of a type in the repository matching the query,
+    /// following chained and `_has` parameters through the repository.
+    pub fn search(
+        &self,
+        repository: &dyn Repository,
+        resource_type: &str,
+        query: &SearchQuery,
+    ) -> Result<Vec<AnyResource>, SearchError> {
+        let mut matched = Vec::new();
+        for resource in repository.list(resource_type)? {
+            if self.matches_query(Some(repository), resource_type, &resource, query)? {
+                matched.push(resource);
+            }
+        }
+        Ok(matched)
+    }
+
+    /// Runs a search and returns one page of a `searchset` Bundle: the sorted
+    /// matches on the page followed by any resources added by `_include` and
+    /// `_revinclude`, with `self`/`next`/`previous` navigation links.
+    pub fn execute(
+        &self,
+        repository: &dyn Repository,
+        resource_type: &str,
+        query: &SearchQuery,
+    ) -> Result<Bundle, SearchError> {
+        let page = PageRequest::parse(self, resource_type, query)?;
+        let includes = query
+            .params()
+            .iter()
+            .filter(|param| param.name() == "_include" || param.name() == "_revinclude")
+            .map(IncludeParam::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        let projection = projection(resource_type, query)?;
+
+        let mut matches = self.search(repository, resource_type, query)?;
+        page.sort_matches(&mut matches);
+        let start = page.start(&matches)?;
+        let end = (start + page.count()).min(matches.len());
+        let page_matches = matches[start..end].to_vec();
+        let included = self.resolve_includes(repository, &page_matches, &includes)?;
+
+        let mut bundle = Bundle::new("searchset".to_string());
+        // All matches are at hand, so an estimate is answered with the exact count
+        if page.total() != TotalMode::None {
+            bundle.set_total(Some(matches.len() as u32));
+        }
+        bundle.set_link(page.links(self, resource_type, query, &matches, start));
+        for resource in page_matches {
+            let resource = match &projection {
+                Some(projection) => projection.apply_any(&resource),
+                None => resource,
+            };
+            bundle.add_entry(self.search_entry(resource, "match"));
+        }
+        for resource in included {
+            bundle.add_entry(self.search_entry(resource, "include"));
+        }
+        Ok(bundle)
+    }
+
+    /// Returns the absolute URL of a resource under the base URL.
+    pub fn full_url(&self, resource: &AnyResource) -> Option<String> {
+        let base_url = self.base_url.as_deref()?;
+        resource.reference().map(|reference| format!("{}/{}", base_url, reference))
+    }
+
+    fn search_entry(&self, resource: AnyResource, mode: &str) -> BundleEntry {
+        let mut entry = BundleEntry::with_resource(self.full_url(&resource), resource);
+        entry.set_search(Some(BundleEntrySearch::with_mode(mode.to_string())));
+        entry
+    }
+
+    fn matches_query(
+        &self,
+        repository: Option<&dyn Repository>,
+        resource_type: &str,
+        resource: &dyn Searchable,
+        query: &SearchQuery,
+    ) -> Result<bool, SearchError> {
+        for param in query.params().iter().filter(|param| !RESULT_PARAMETERS.contains(&param.name())) {
+            if !self.matches_param(repository, resource_type, resource, param)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn matches_param(
+        &self,
+        repository: Option<&dyn Repository>,
+        resource_type: &str,
+        resource: &dyn Searchable,
+        param: &QueryParam,
+    ) -> Result<bool, SearchError> {
+        if param.name() == "_has" {
+            let repository = repository.ok_or_else(|| SearchError::RepositoryRequired(param.name().to_string()))?;
+            return self.matches_has(repository, resource_type, resource, param);
+        }
+        let definition = params::definition(resource_type, param.name()).ok_or_else(|| {
+            SearchError::UnknownParameter(resource_type.to_string(), param.name().to_string())
+        })?;
+        if param.chain().is_some() {
+            let repository = repository.ok_or_else(|| SearchError::RepositoryRequired(param.name().to_string()))?;
+            return self.matches_chain(repository, definition, resource, param);
+        }
+        let values = resource.search_values(param.name());
+        match definition.param_type {
+            SearchParamType::Token => {
+                TokenCriterion::parse(param.name(), param.modifier(), param.value())?.matches(&values, self)
+            }
+            SearchParamType::String => {
+                Ok(StringCriterion::parse(param.name(), param.modifier(), param.value())?.matches(&values))
+            }
+            SearchParamType::Reference => Ok(ReferenceCriterion::parse(
+                param.name(),
+                param.modifier(),
+                param.value(),
+                definition.targets,
+            )?
+            .matches(&values)),
+        }
+    }
+}
+
+impl Default for SearchEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reads the `_elements` or `_summary` projection of a query; the last one given wins.
+fn projection(resource_type: &str, query: &SearchQuery) -> Result<Option<Projection>, SearchError> {
+    let mut projection = None;
+    for param in query.params() {
+        match param.name() {
+            "_elements" => projection = Some(Projection::elements(param.value())),
+            "_summary" => projection = Some(Projection::summary(param.value())?),
+            _ => {}
+        }
+    }
+    if let Some(projection) = &projection {
+        projection.validate(resource_type)?;
+    }
+    Ok(projection.filter(|projection| !projection.is_full()))
+}
+
+/// Splits a parameter value on unescaped commas. Escape sequences are kept
+/// so that the parts can be split further before being unescaped.
+pub(crate) fn split_values(value: &str) -> Vec<String> {
+    split_escaped(value, ',')
+}
+
+/// Splits on unescaped occurrences of `separator`, keeping escape sequences intact.
+pub(crate) fn split_escaped(value: &str, separator: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        let current = parts.last_mut().unwrap();
+        if c == '\\' {
+            current.push(c);
+            if let Some(next) = chars.next() {
+                current.push(next);
+            }
+        } else if c == separator {
+            parts.push(String::new());
+        } else {
+            current.push(c);
+        }
+    }
+    parts
+}
+
+/// Removes the search escape sequences (`\,`, `\|`, `\$`, `\\`) from a value.
+pub(crate) fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(next) = chars.next() {
+                out.push(next);
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Encodes a URL query component, leaving unreserved characters and the
+/// `:`, `/` and `,` used by search syntax readable.
+pub(crate) fn percent_encode(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b':' | b'/' | b',' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+/// Decodes `%XX` escapes and `+` in a URL query component.
+pub(crate) fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => match (hex_value(bytes[i + 1]), hex_value(bytes[i + 2])) {
+                (Some(high), Some(low)) => {
+                    out.push(high << 4 | low);
+                    i += 2;
+                }
+                _ => out.push(b'%'),
+            },
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn hex_value(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|digit| digit as u8)
+}
@@ -0,0 +1,297 @@
+//! `_sort`, `_count`, `_offset`, `_cursor` and `_total` result parameters, and
+//! the navigation links of a searchset Bundle.
+
+use std::cmp::Ordering;
+
+use crate::bundle::BundleLink;
+use crate::data_types::date_time::Instant;
+use crate::resource::AnyResource;
+use super::params;
+use super::{percent_encode, SearchEngine, SearchError, SearchQuery, SearchValue, Searchable};
+
+/// How `next` and `previous` links address a page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PagingMode {
+    /// Pages are addressed by `_offset`, the number of matches to skip.
+    #[default]
+    Offset,
+    /// Pages are addressed by `_cursor`, the id of the last match of the
+    /// previous page, so that page boundaries survive earlier deletions.
+    Cursor,
+}
+
+/// The `_total` modes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TotalMode {
+    /// No total is given.
+    None,
+    /// An estimate is allowed. Every match is evaluated before paging, so
+    /// the exact count costs nothing more and is given instead.
+    Estimate,
+    /// The exact number of matches is given.
+    #[default]
+    Accurate,
+}
+
+impl TotalMode {
+    /// Parses a `_total` value.
+    pub fn parse(value: &str) -> Result<Self, SearchError> {
+        match value {
+            "none" => Ok(TotalMode::None),
+            "estimate" => Ok(TotalMode::Estimate),
+            "accurate" => Ok(TotalMode::Accurate),
+            _ => Err(SearchError::InvalidValue("_total".to_string(), value.to_string())),
+        }
+    }
+}
+
+/// One `_sort` key; a leading `-` sorts descending.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortKey {
+    name: String,
+    descending: bool,
+}
+
+impl SortKey {
+    /// Parses a single `_sort` key for a resource type.
+    pub fn parse(resource_type: &str, key: &str) -> Result<Self, SearchError> {
+        let (name, descending) = match key.strip_prefix('-') {
+            Some(name) => (name, true),
+            None => (key, false),
+        };
+        if name != "_lastUpdated" && params::definition(resource_type, name).is_none() {
+            return Err(SearchError::UnknownParameter(resource_type.to_string(), name.to_string()));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            descending,
+        })
+    }
+
+    /// Returns the search parameter sorted on.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns true for descending order.
+    pub fn descending(&self) -> bool {
+        self.descending
+    }
+
+    /// Ascending order compares the lowest value of each resource and
+    /// descending order the highest. Resources without a value sort last.
+    fn compare(&self, left: &AnyResource, right: &AnyResource) -> Ordering {
+        let pick = |resource: &AnyResource| {
+            let values = sort_values(resource, &self.name).into_iter();
+            if self.descending { values.max() } else { values.min() }
+        };
+        match (pick(left), pick(right)) {
+            (Some(left), Some(right)) if self.descending => right.cmp(&left),
+            (Some(left), Some(right)) => left.cmp(&right),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+}
+
+/// The paging and sorting options of a search request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageRequest {
+    sort: Vec<SortKey>,
+    count: usize,
+    offset: usize,
+    cursor: Option<String>,
+    total: TotalMode,
+}
+
+impl PageRequest {
+    /// Reads the result parameters of a query, applying the engine's page size limits.
+    pub fn parse(engine: &SearchEngine, resource_type: &str, query: &SearchQuery) -> Result<Self, SearchError> {
+        let mut request = Self {
+            sort: Vec::new(),
+            count: engine.default_count(),
+            offset: 0,
+            cursor: None,
+            total: TotalMode::default(),
+        };
+        for param in query.params() {
+            match param.name() {
+                "_sort" => {
+                    for key in param.value().split(',').filter(|key| !key.is_empty()) {
+                        request.sort.push(SortKey::parse(resource_type, key)?);
+                    }
+                }
+                "_count" => request.count = parse_number(param.name(), param.value())?.min(engine.max_count()),
+                "_offset" => request.offset = parse_number(param.name(), param.value())?,
+                "_cursor" => request.cursor = Some(param.value().to_string()),
+                "_total" => request.total = TotalMode::parse(param.value())?,
+                "_summary" if param.value() == "count" => request.count = 0,
+                _ => {}
+            }
+        }
+        Ok(request)
+    }
+
+    /// Returns the sort keys.
+    pub fn sort(&self) -> &[SortKey] {
+        &self.sort
+    }
+
+    /// Returns the page size.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the requested `_total` mode.
+    pub fn total(&self) -> TotalMode {
+        self.total
+    }
+
+    /// Sorts the matches by the sort keys, keeping repository order for ties.
+    pub fn sort_matches(&self, matches: &mut [AnyResource]) {
+        matches.sort_by(|left, right| {
+            self.sort
+                .iter()
+                .map(|key| key.compare(left, right))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+    }
+
+    /// Returns the index of the first match on the requested page.
+    pub fn start(&self, matches: &[AnyResource]) -> Result<usize, SearchError> {
+        match &self.cursor {
+            Some(cursor) => matches
+                .iter()
+                .position(|resource| resource.id() == Some(cursor.as_str()))
+                .map(|index| index + 1)
+                .ok_or_else(|| SearchError::InvalidValue("_cursor".to_string(), cursor.clone())),
+            None => Ok(self.offset.min(matches.len())),
+        }
+    }
+
+    /// Builds the `self`, `first`, `previous`, `next` and (for offset paging)
+    /// `last` links for the page starting at `start`.
+    pub fn links(
+        &self,
+        engine: &SearchEngine,
+        resource_type: &str,
+        query: &SearchQuery,
+        matches: &[AnyResource],
+        start: usize,
+    ) -> Vec<BundleLink> {
+        let total = matches.len();
+        let url = |position: usize| {
+            let paging = match engine.paging_mode() {
+                PagingMode::Offset => format!("_count={}&_offset={}", self.count, position),
+                PagingMode::Cursor => match position.checked_sub(1).and_then(|index| matches[index].id()) {
+                    Some(cursor) => format!("_count={}&_cursor={}", self.count, percent_encode(cursor)),
+                    None => format!("_count={}", self.count),
+                },
+            };
+            page_url(engine, resource_type, query, &paging)
+        };
+        let mut links = vec![BundleLink::new("self".to_string(), url(start))];
+        if self.count == 0 {
+            return links;
+        }
+        links.push(BundleLink::new("first".to_string(), url(0)));
+        if start > 0 {
+            links.push(BundleLink::new("previous".to_string(), url(start.saturating_sub(self.count))));
+        }
+        if start + self.count < total {
+            links.push(BundleLink::new("next".to_string(), url(start + self.count)));
+        }
+        if engine.paging_mode() == PagingMode::Offset && total > 0 {
+            links.push(BundleLink::new("last".to_string(), url((total - 1) / self.count * self.count)));
+        }
+        links
+    }
+}
+
+fn parse_number(name: &str, value: &str) -> Result<usize, SearchError> {
+    value
+        .parse()
+        .map_err(|_| SearchError::InvalidValue(name.to_string(), value.to_string()))
+}
+
+/// Builds `[base/]Type?query&paging`, dropping the paging parameters of the original query.
+fn page_url(engine: &SearchEngine, resource_type: &str, query: &SearchQuery, paging: &str) -> String {
+    let mut parts: Vec<String> = query
+        .params()
+        .iter()
+        .filter(|param| !matches!(param.name(), "_count" | "_offset" | "_cursor"))
+        .map(|param| format!("{}={}", percent_encode(&param.key()), percent_encode(param.value())))
+        .collect();
+    parts.push(paging.to_string());
+    match engine.base_url() {
+        Some(base_url) => format!("{}/{}?{}", base_url, resource_type, parts.join("&")),
+        None => format!("{}?{}", resource_type, parts.join("&")),
+    }
+}
+
+/// A value to sort on. Instants compare as points in time, whatever their offset.
+#[derive(Debug, Clone)]
+enum SortValue {
+    Instant(Instant),
+    Text(String),
+}
+
+impl Ord for SortValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (SortValue::Instant(left), SortValue::Instant(right)) => left.compare(right),
+            (SortValue::Text(left), SortValue::Text(right)) => left.cmp(right),
+            (SortValue::Instant(_), SortValue::Text(_)) => Ordering::Less,
+            (SortValue::Text(_), SortValue::Instant(_)) => Ordering::Greater,
+        }
+    }
+}
+
+impl PartialOrd for SortValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for SortValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for SortValue {}
+
+/// Returns comparable values of a sort parameter.
+fn sort_values(resource: &AnyResource, name: &str) -> Vec<SortValue> {
+    if name == "_lastUpdated" {
+        return resource
+            .meta()
+            .and_then(|meta| meta.last_updated())
+            .and_then(|last_updated| Instant::parse(last_updated).ok())
+            .map(SortValue::Instant)
+            .into_iter()
+            .collect();
+    }
+    resource
+        .search_values(name)
+        .iter()
+        .filter_map(|value| match value {
+            SearchValue::Identifier(identifier) => Some(identifier.value().to_string()),
+            SearchValue::Coding(coding) => coding.code().map(str::to_string),
+            SearchValue::CodeableConcept(concept) => concept
+                .coding()
+                .iter()
+                .find_map(|coding| coding.code())
+                .or(concept.text())
+                .map(str::to_string),
+            SearchValue::ContactPoint(contact_point) => Some(contact_point.value().to_string()),
+            SearchValue::Code(_, code) => Some(code.to_string()),
+            SearchValue::Boolean(flag) => Some(flag.to_string()),
+            SearchValue::String(value) => Some(value.to_lowercase()),
+            SearchValue::Reference(reference) => reference.reference().map(str::to_string),
+        })
+        .map(SortValue::Text)
+        .collect()
+}
@@ -0,0 +1,208 @@
+use crate::account::Account;
+use crate::data_types::address::Address;
+use crate::human_name::HumanName;
+use crate::patient::Patient;
+use super::{Searchable, SearchValue};
+
+/// The type of a search parameter, which determines how its values are matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchParamType {
+    Token,
+    String,
+    Reference,
+}
+
+impl SearchParamType {
+    /// Returns the FHIR code of the parameter type.
+    pub fn code(&self) -> &'static str {
+        match self {
+            SearchParamType::Token => "token",
+            SearchParamType::String => "string",
+            SearchParamType::Reference => "reference",
+        }
+    }
+}
+
+/// Declares a search parameter supported for a resource type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchParameterDefinition {
+    pub resource_type: &'static str,
+    pub name: &'static str,
+    pub param_type: SearchParamType,
+    pub expression: &'static str,
+    /// The resource types a reference parameter may point to.
+    pub targets: &'static [&'static str],
+}
+
+const fn token(resource_type: &'static str, name: &'static str, expression: &'static str) -> SearchParameterDefinition {
+    SearchParameterDefinition { resource_type, name, param_type: SearchParamType::Token, expression, targets: &[] }
+}
+
+const fn string(resource_type: &'static str, name: &'static str, expression: &'static str) -> SearchParameterDefinition {
+    SearchParameterDefinition { resource_type, name, param_type: SearchParamType::String, expression, targets: &[] }
+}
+
+const fn reference(
+    resource_type: &'static str,
+    name: &'static str,
+    expression: &'static str,
+    targets: &'static [&'static str],
+) -> SearchParameterDefinition {
+    SearchParameterDefinition { resource_type, name, param_type: SearchParamType::Reference, expression, targets }
+}
+
+/// Every search parameter supported by the crate's resources.
+pub const SEARCH_PARAMETERS: &[SearchParameterDefinition] = &[
+    token("Patient", "_id", "Patient.id"),
+    token("Patient", "identifier", "Patient.identifier"),
+    token("Patient", "active", "Patient.active"),
+    token("Patient", "gender", "Patient.gender"),
+    token("Patient", "marital-status", "Patient.maritalStatus"),
+    token("Patient", "language", "Patient.communication.language"),
+    token("Patient", "telecom", "Patient.telecom"),
+    token("Patient", "phone", "Patient.telecom.where(system='phone')"),
+    token("Patient", "email", "Patient.telecom.where(system='email')"),
+    string("Patient", "name", "Patient.name"),
+    string("Patient", "family", "Patient.name.family"),
+    string("Patient", "given", "Patient.name.given"),
+    string("Patient", "address", "Patient.address"),
+    string("Patient", "address-city", "Patient.address.city"),
+    string("Patient", "address-state", "Patient.address.state"),
+    string("Patient", "address-postalcode", "Patient.address.postalCode"),
+    string("Patient", "address-country", "Patient.address.country"),
+    reference(
+        "Patient",
+        "general-practitioner",
+        "Patient.generalPractitioner",
+        &["Organization", "Practitioner", "PractitionerRole"],
+    ),
+    reference("Patient", "organization", "Patient.managingOrganization", &["Organization"]),
+    reference("Patient", "link", "Patient.link.other", &["Patient", "RelatedPerson"]),
+    token("Account", "_id", "Account.id"),
+    token("Account", "identifier", "Account.identifier"),
+    token("Account", "status", "Account.status"),
+    token("Account", "type", "Account.type"),
+    string("Account", "name", "Account.name"),
+    reference(
+        "Account",
+        "subject",
+        "Account.subject",
+        &["Patient", "Device", "Practitioner", "PractitionerRole", "Location", "HealthcareService", "Organization"],
+    ),
+    reference("Account", "patient", "Account.subject.where(resolve() is Patient)", &["Patient"]),
+    reference("Account", "owner", "Account.owner", &["Organization"]),
+];
+
+/// Looks up the definition of a search parameter for a resource type.
+pub fn definition(resource_type: &str, name: &str) -> Option<&'static SearchParameterDefinition> {
+    SEARCH_PARAMETERS
+        .iter()
+        .find(|definition| definition.resource_type == resource_type && definition.name == name)
+}
+
+/// Returns the search parameters defined for a resource type.
+pub fn definitions_for(resource_type: &str) -> impl Iterator<Item = &'static SearchParameterDefinition> + '_ {
+    SEARCH_PARAMETERS
+        .iter()
+        .filter(move |definition| definition.resource_type == resource_type)
+}
+
+/// The system of the required binding on `Patient.gender`.
+const ADMINISTRATIVE_GENDER: &str = "http://hl7.org/fhir/administrative-gender";
+/// The system of the required binding on `Account.status`.
+const ACCOUNT_STATUS: &str = "http://hl7.org/fhir/account-status";
+
+impl Searchable for Patient {
+    fn search_values(&self, name: &str) -> Vec<SearchValue<'_>> {
+        match name {
+            "_id" => self.id().map(|id| SearchValue::Code(None, id)).into_iter().collect(),
+            "identifier" => self.identifiers().iter().map(SearchValue::Identifier).collect(),
+            "active" => self.active().map(SearchValue::Boolean).into_iter().collect(),
+            "gender" => self
+                .gender()
+                .map(|gender| SearchValue::Code(Some(ADMINISTRATIVE_GENDER), gender))
+                .into_iter()
+                .collect(),
+            "marital-status" => self.marital_status().map(SearchValue::CodeableConcept).into_iter().collect(),
+            "language" => self
+                .communication()
+                .iter()
+                .map(|communication| SearchValue::CodeableConcept(communication.language()))
+                .collect(),
+            "telecom" => self.telecom().iter().map(SearchValue::ContactPoint).collect(),
+            "phone" | "email" => self
+                .telecom()
+                .iter()
+                .filter(|telecom| telecom.system() == name)
+                .map(SearchValue::ContactPoint)
+                .collect(),
+            "name" => self.names().iter().flat_map(name_strings).collect(),
+            "family" => strings(self.names().iter().map(|name| name.family())),
+            "given" => strings(self.names().iter().flat_map(|name| name.given()).map(String::as_str)),
+            "address" => self.address().iter().flat_map(address_strings).collect(),
+            "address-city" => strings(self.address().iter().filter_map(|address| address.city())),
+            "address-state" => strings(self.address().iter().filter_map(|address| address.state())),
+            "address-postalcode" => strings(self.address().iter().filter_map(|address| address.postal_code())),
+            "address-country" => strings(self.address().iter().filter_map(|address| address.country())),
+            "general-practitioner" => self.general_practitioner().iter().map(SearchValue::Reference).collect(),
+            "organization" => self.managing_organization().map(SearchValue::Reference).into_iter().collect(),
+            "link" => self.link().iter().map(|link| SearchValue::Reference(link.other())).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Searchable for Account {
+    fn search_values(&self, name: &str) -> Vec<SearchValue<'_>> {
+        match name {
+            "_id" => self.id().map(|id| SearchValue::Code(None, id)).into_iter().collect(),
+            "identifier" => self.identifiers().iter().map(SearchValue::Identifier).collect(),
+            "status" => self.status().map(|status| SearchValue::Code(Some(ACCOUNT_STATUS), status)).into_iter().collect(),
+            "type" => self.account_type().map(SearchValue::CodeableConcept).into_iter().collect(),
+            "name" => strings(self.name()),
+            "subject" => self.subject().iter().map(SearchValue::Reference).collect(),
+            "patient" => self
+                .subject()
+                .iter()
+                .filter(|subject| {
+                    subject.reference_type() == Some("Patient")
+                        || subject.target().is_some_and(|(resource_type, _)| resource_type == "Patient")
+                })
+                .map(SearchValue::Reference)
+                .collect(),
+            "owner" => self.owner().map(SearchValue::Reference).into_iter().collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn strings<'a>(values: impl IntoIterator<Item = &'a str>) -> Vec<SearchValue<'a>> {
+    values
+        .into_iter()
+        .filter(|value| !value.is_empty())
+        .map(SearchValue::String)
+        .collect()
+}
+
+fn name_strings(name: &HumanName) -> Vec<SearchValue<'_>> {
+    let parts = [name.text(), name.family()]
+        .into_iter()
+        .chain(name.given().iter().map(String::as_str))
+        .chain(name.prefix().iter().map(String::as_str))
+        .chain(name.suffix().iter().map(String::as_str));
+    strings(parts)
+}
+
+fn address_strings(address: &Address) -> Vec<SearchValue<'_>> {
+    let parts = address
+        .line()
+        .iter()
+        .map(String::as_str)
+        .chain(address.text())
+        .chain(address.city())
+        .chain(address.district())
+        .chain(address.state())
+        .chain(address.postal_code())
+        .chain(address.country());
+    strings(parts)
+}
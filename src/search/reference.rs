@@ -0,0 +1,53 @@
+use crate::data_types::reference::Reference;
+use super::{split_values, unescape, SearchError, SearchValue};
+
+/// A parsed reference search parameter.
+///
+/// Values may be relative (`Patient/123`), absolute
+/// (`http://example.org/fhir/Patient/123`) or a bare id (`123`), optionally
+/// narrowed by a type modifier (`subject:Patient=123`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceCriterion {
+    alternatives: Vec<(Option<String>, String)>,
+}
+
+impl ReferenceCriterion {
+    /// Parses the modifier and value of a reference parameter. The modifier, if
+    /// any, must be one of the parameter's target types.
+    pub fn parse(name: &str, modifier: Option<&str>, value: &str, targets: &[&str]) -> Result<Self, SearchError> {
+        if let Some(modifier) = modifier
+            && !targets.contains(&modifier)
+        {
+            return Err(SearchError::UnsupportedModifier(name.to_string(), modifier.to_string()));
+        }
+        let mut alternatives = Vec::new();
+        for value in split_values(value).iter().map(|value| unescape(value)) {
+            let parsed = Reference::with_reference(value.clone());
+            let (resource_type, id) = match parsed.target() {
+                Some((resource_type, id)) => (Some(resource_type.to_string()), id.to_string()),
+                None if !value.is_empty() && !value.contains('/') => (None, value.clone()),
+                None => return Err(SearchError::InvalidValue(name.to_string(), value)),
+            };
+            if let (Some(modifier), Some(resource_type)) = (modifier, resource_type.as_deref())
+                && modifier != resource_type
+            {
+                return Err(SearchError::InvalidValue(name.to_string(), value));
+            }
+            alternatives.push((resource_type.or(modifier.map(str::to_string)), id));
+        }
+        Ok(Self { alternatives })
+    }
+
+    /// Evaluates the criterion against the values a resource indexes for the parameter.
+    pub fn matches(&self, values: &[SearchValue<'_>]) -> bool {
+        values.iter().any(|value| match value {
+            SearchValue::Reference(reference) => match reference.target() {
+                Some((resource_type, id)) => self.alternatives.iter().any(|(expected_type, expected_id)| {
+                    expected_id == id && expected_type.as_deref().is_none_or(|expected| expected == resource_type)
+                }),
+                None => false,
+            },
+            _ => false,
+        })
+    }
+}
@@ -0,0 +1,59 @@
+use super::{split_values, unescape, SearchError, SearchValue};
+
+/// Modifiers accepted by string search parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringModifier {
+    Exact,
+    Contains,
+}
+
+impl StringModifier {
+    /// Parses a modifier name (without the leading colon).
+    pub fn parse(name: &str, modifier: &str) -> Result<Self, SearchError> {
+        match modifier {
+            "exact" => Ok(StringModifier::Exact),
+            "contains" => Ok(StringModifier::Contains),
+            _ => Err(SearchError::UnsupportedModifier(name.to_string(), modifier.to_string())),
+        }
+    }
+}
+
+/// A parsed string search parameter.
+///
+/// Without a modifier a value matches when it starts with the search text,
+/// ignoring case. `:exact` requires an exact, case-sensitive match and
+/// `:contains` a case-insensitive match anywhere in the value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StringCriterion {
+    modifier: Option<StringModifier>,
+    alternatives: Vec<String>,
+}
+
+impl StringCriterion {
+    /// Parses the modifier and value of a string parameter.
+    pub fn parse(name: &str, modifier: Option<&str>, value: &str) -> Result<Self, SearchError> {
+        let modifier = modifier.map(|modifier| StringModifier::parse(name, modifier)).transpose()?;
+        Ok(Self {
+            modifier,
+            alternatives: split_values(value).iter().map(|value| unescape(value)).collect(),
+        })
+    }
+
+    /// Evaluates the criterion against the values a resource indexes for the parameter.
+    pub fn matches(&self, values: &[SearchValue<'_>]) -> bool {
+        let strings: Vec<&str> = values
+            .iter()
+            .filter_map(|value| match value {
+                SearchValue::String(value) => Some(*value),
+                _ => None,
+            })
+            .collect();
+        self.alternatives.iter().any(|expected| {
+            strings.iter().any(|value| match self.modifier {
+                None => value.to_lowercase().starts_with(&expected.to_lowercase()),
+                Some(StringModifier::Exact) => value == expected,
+                Some(StringModifier::Contains) => value.to_lowercase().contains(&expected.to_lowercase()),
+            })
+        })
+    }
+}
@@ -0,0 +1,230 @@
+use crate::data_types::codeable_concept::Coding;
+use super::{split_escaped, split_values, unescape, SearchEngine, SearchError, SearchValue};
+
+/// Modifiers accepted by token search parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenModifier {
+    Text,
+    Not,
+    OfType,
+    In,
+    NotIn,
+}
+
+impl TokenModifier {
+    /// Parses a modifier name (without the leading colon).
+    pub fn parse(name: &str, modifier: &str) -> Result<Self, SearchError> {
+        match modifier {
+            "text" => Ok(TokenModifier::Text),
+            "not" => Ok(TokenModifier::Not),
+            "of-type" => Ok(TokenModifier::OfType),
+            "in" => Ok(TokenModifier::In),
+            "not-in" => Ok(TokenModifier::NotIn),
+            _ => Err(SearchError::UnsupportedModifier(name.to_string(), modifier.to_string())),
+        }
+    }
+}
+
+/// A single `[system]|[code]` token.
+///
+/// A system of `Some("")` (from `|code`) requires the value to have no system;
+/// a system of `None` (from a bare `code`) matches any system. A code of `None`
+/// (from `system|`) matches any code in the system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    system: Option<String>,
+    code: Option<String>,
+}
+
+impl Token {
+    /// Parses a token from a single, still escaped, search value.
+    pub fn parse(value: &str) -> Self {
+        let parts = split_escaped(value, '|');
+        if parts.len() == 1 {
+            return Self {
+                system: None,
+                code: Some(unescape(value)),
+            };
+        }
+        let code = unescape(&parts[1..].join("|"));
+        Self {
+            system: Some(unescape(&parts[0])),
+            code: if code.is_empty() { None } else { Some(code) },
+        }
+    }
+
+    /// Returns the system part.
+    pub fn system(&self) -> Option<&str> {
+        self.system.as_deref()
+    }
+
+    /// Returns the code part.
+    pub fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    /// Checks the token against a system and code pair.
+    pub fn matches(&self, system: Option<&str>, code: Option<&str>) -> bool {
+        let system_matches = match self.system.as_deref() {
+            None => true,
+            Some("") => system.is_none_or(str::is_empty),
+            Some(expected) => system == Some(expected),
+        };
+        let code_matches = match self.code.as_deref() {
+            None => true,
+            Some(expected) => code == Some(expected),
+        };
+        system_matches && code_matches
+    }
+
+    /// Checks the token against an indexed value. A primitive code is
+    /// compared with the system of its required binding, so `|female` does
+    /// not match a gender; a boolean has no system.
+    pub fn matches_value(&self, value: &SearchValue<'_>) -> bool {
+        codes(value).into_iter().any(|(system, code)| self.matches(system, code))
+    }
+}
+
+/// A parsed token search parameter: an optional modifier and one or more
+/// comma-separated alternatives, any of which may match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenCriterion {
+    name: String,
+    modifier: Option<TokenModifier>,
+    alternatives: Vec<String>,
+}
+
+impl TokenCriterion {
+    /// Parses the modifier and value of a token parameter.
+    pub fn parse(name: &str, modifier: Option<&str>, value: &str) -> Result<Self, SearchError> {
+        let modifier = modifier.map(|modifier| TokenModifier::parse(name, modifier)).transpose()?;
+        if value.is_empty() {
+            return Err(SearchError::InvalidValue(name.to_string(), value.to_string()));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            modifier,
+            alternatives: split_values(value),
+        })
+    }
+
+    /// Returns the modifier.
+    pub fn modifier(&self) -> Option<TokenModifier> {
+        self.modifier
+    }
+
+    /// Evaluates the criterion against the values a resource indexes for the parameter.
+    pub fn matches(&self, values: &[SearchValue<'_>], engine: &SearchEngine) -> Result<bool, SearchError> {
+        match self.modifier {
+            None => Ok(self.matches_tokens(values)),
+            Some(TokenModifier::Not) => Ok(!self.matches_tokens(values)),
+            Some(TokenModifier::Text) => Ok(self.matches_text(values)),
+            Some(TokenModifier::OfType) => self.matches_of_type(values),
+            Some(TokenModifier::In) => self.matches_value_set(values, engine),
+            Some(TokenModifier::NotIn) => Ok(!self.matches_value_set(values, engine)?),
+        }
+    }
+
+    fn matches_tokens(&self, values: &[SearchValue<'_>]) -> bool {
+        self.alternatives.iter().map(|alternative| Token::parse(alternative)).any(|token| {
+            values.iter().any(|value| token.matches_value(value))
+        })
+    }
+
+    /// `:text` does a case-insensitive starts-with match on display text.
+    fn matches_text(&self, values: &[SearchValue<'_>]) -> bool {
+        let texts: Vec<String> = values.iter().flat_map(texts).map(str::to_lowercase).collect();
+        self.alternatives.iter().any(|alternative| {
+            let expected = unescape(alternative).to_lowercase();
+            texts.iter().any(|text| text.starts_with(&expected))
+        })
+    }
+
+    /// `:of-type` takes `type-system|type-code|value` and only applies to identifiers.
+    fn matches_of_type(&self, values: &[SearchValue<'_>]) -> Result<bool, SearchError> {
+        for alternative in &self.alternatives {
+            let parts: Vec<String> = split_escaped(alternative, '|').iter().map(|part| unescape(part)).collect();
+            let [type_system, type_code, value] = parts.as_slice() else {
+                return Err(SearchError::InvalidValue(self.name.clone(), alternative.clone()));
+            };
+            let type_token = Token {
+                system: Some(type_system.clone()),
+                code: Some(type_code.clone()),
+            };
+            let found = values.iter().any(|candidate| match candidate {
+                SearchValue::Identifier(identifier) => {
+                    identifier.value() == value
+                        && identifier.identifier_type().is_some_and(|identifier_type| {
+                            identifier_type
+                                .coding()
+                                .iter()
+                                .any(|coding| type_token.matches(coding.system(), coding.code()))
+                        })
+                }
+                _ => false,
+            });
+            if found {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn matches_value_set(&self, values: &[SearchValue<'_>], engine: &SearchEngine) -> Result<bool, SearchError> {
+        for alternative in &self.alternatives {
+            let url = unescape(alternative);
+            let value_set = engine
+                .value_set(&url)
+                .ok_or_else(|| SearchError::UnknownValueSet(url.clone()))?;
+            let found = values.iter().any(|value| {
+                codes(value).into_iter().any(|(system, code)| {
+                    code.is_some_and(|code| value_set.contains(system.filter(|system| !system.is_empty()), code))
+                })
+            });
+            if found {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Returns the `(system, code)` pairs carried by an indexed value.
+///
+/// For a ContactPoint the `use` plays the role of the system, as in the FHIR
+/// token mapping table.
+fn codes<'a>(value: &SearchValue<'a>) -> Vec<(Option<&'a str>, Option<&'a str>)> {
+    match *value {
+        SearchValue::Identifier(identifier) => {
+            let system = Some(identifier.system().as_str()).filter(|system| !system.is_empty());
+            vec![(system, Some(identifier.value()))]
+        }
+        SearchValue::Coding(coding) => vec![(coding.system(), coding.code())],
+        SearchValue::CodeableConcept(concept) => concept
+            .coding()
+            .iter()
+            .map(|coding| (coding.system(), coding.code()))
+            .collect(),
+        SearchValue::ContactPoint(contact_point) => vec![(contact_point.use_value(), Some(contact_point.value()))],
+        SearchValue::Code(system, code) => vec![(system, Some(code))],
+        SearchValue::Boolean(flag) => vec![(None, Some(if flag { "true" } else { "false" }))],
+        SearchValue::String(_) | SearchValue::Reference(_) => Vec::new(),
+    }
+}
+
+/// Returns the display texts carried by an indexed value, used by `:text`.
+fn texts<'a>(value: &SearchValue<'a>) -> Vec<&'a str> {
+    match *value {
+        SearchValue::Identifier(identifier) => identifier
+            .identifier_type()
+            .map(|identifier_type| concept_texts(identifier_type.text(), identifier_type.coding()))
+            .unwrap_or_default(),
+        SearchValue::Coding(coding) => coding.display().into_iter().collect(),
+        SearchValue::CodeableConcept(concept) => concept_texts(concept.text(), concept.coding()),
+        _ => Vec::new(),
+    }
+}
+
+fn concept_texts<'a>(text: Option<&'a str>, coding: &'a [Coding]) -> Vec<&'a str> {
+    text.into_iter().chain(coding.iter().filter_map(|coding| coding.display())).collect()
+}
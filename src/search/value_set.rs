@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+/// An expanded value set: a canonical URL and the codes it contains.
+///
+/// Used by the `:in` and `:not-in` token modifiers. Only the expansion is
+/// modelled; compose rules must be expanded by the caller.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValueSet {
+    #[serde(rename = "url")]
+    url: String,
+    #[serde(rename = "contains", default, skip_serializing_if = "Vec::is_empty")]
+    contains: Vec<ValueSetConcept>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValueSetConcept {
+    #[serde(rename = "system")]
+    system: String,
+    #[serde(rename = "code")]
+    code: String,
+}
+
+impl ValueSet {
+    /// Creates a new, empty ValueSet.
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            contains: Vec::new(),
+        }
+    }
+
+    /// Returns the canonical URL.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns the concepts in the expansion.
+    pub fn concepts(&self) -> &[ValueSetConcept] {
+        &self.contains
+    }
+
+    /// Adds a concept to the expansion.
+    pub fn add_concept(&mut self, system: String, code: String) {
+        self.contains.push(ValueSetConcept { system, code });
+    }
+
+    /// Checks whether the value set contains a code. A code without a system
+    /// (such as a primitive `code` element) matches on the code alone.
+    pub fn contains(&self, system: Option<&str>, code: &str) -> bool {
+        self.contains.iter().any(|concept| {
+            concept.code == code && system.is_none_or(|system| concept.system == system)
+        })
+    }
+}
+
+impl ValueSetConcept {
+    /// Returns the code system.
+    pub fn system(&self) -> &str {
+        &self.system
+    }
+
+    /// Returns the code.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+}
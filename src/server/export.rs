@@ -0,0 +1,94 @@
+//! Bulk Data `$export` with the asynchronous request pattern.
+//!
+//! A kickoff request selects the resources and answers 202 Accepted with a
+//! `Content-Location` to poll; the NDJSON files are written by an
+//! [`ExportJob`] in the background. Polling answers 202 while the job runs
+//! and the manifest once it completes, and the files are served from
+//! `$export-output`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::MutexGuard;
+
+use crate::bulk_export::{BulkExportError, ExportJob, ExportLevel, ExportRequest, ExportStatus, FHIR_NDJSON};
+use crate::repository::Repository;
+use super::{FhirServer, ServerError, ServerRequest, ServerResponse};
+
+/// Numbers export jobs uniquely within the process.
+static NEXT_EXPORT: AtomicU64 = AtomicU64::new(1);
+
+impl<R: Repository> FhirServer<R> {
+    /// `GET [base]/$export`, `Patient/$export` or `Group/[id]/$export`, which
+    /// require `Prefer: respond-async`.
+    pub(super) fn export(&self, level: ExportLevel, request: &ServerRequest) -> Result<ServerResponse, ServerError> {
+        if !request.header("Prefer").is_some_and(|prefer| prefer.contains("respond-async")) {
+            return Err(BulkExportError::InvalidParameter("$export requires Prefer: respond-async".to_string()).into());
+        }
+        let export = ExportRequest::parse(level, request.query())?;
+        let data = self.exporter.collect(&self.engine, &*self.repository(), &export)?;
+
+        let id = format!("{}-{}", std::process::id(), NEXT_EXPORT.fetch_add(1, Ordering::Relaxed));
+        let output_url = self.url(&format!("$export-output/{}", id));
+        let job = ExportJob::start(data, self.export_dir.join(&id), Some(output_url));
+        self.exports().insert(id.clone(), job);
+
+        let mut response = ServerResponse::new(202);
+        response.add_header("Content-Location", &self.url(&format!("$export-poll-status/{}", id)));
+        Ok(response)
+    }
+
+    /// `GET [base]/$export-poll-status/[id]`
+    pub(super) fn export_status(&self, id: &str) -> Result<ServerResponse, ServerError> {
+        let status = self
+            .exports()
+            .get(id)
+            .map(ExportJob::status)
+            .ok_or_else(|| ServerError::ExportNotFound(id.to_string()))?;
+        match status {
+            ExportStatus::InProgress => {
+                let mut response = ServerResponse::new(202);
+                response.add_header("X-Progress", "in-progress");
+                response.add_header("Retry-After", "1");
+                Ok(response)
+            }
+            ExportStatus::Complete(manifest) => {
+                let mut response = ServerResponse::new(200);
+                response.add_header("Content-Type", "application/json");
+                response.set_body(manifest.to_json());
+                Ok(response)
+            }
+            ExportStatus::Failed(message) => Err(ServerError::ExportFailed(message)),
+        }
+    }
+
+    /// `DELETE [base]/$export-poll-status/[id]` cancels the job and deletes its files.
+    pub(super) fn cancel_export(&self, id: &str) -> Result<ServerResponse, ServerError> {
+        let job = self
+            .exports()
+            .remove(id)
+            .ok_or_else(|| ServerError::ExportNotFound(id.to_string()))?;
+        job.cancel()?;
+        Ok(ServerResponse::new(202))
+    }
+
+    /// `GET [base]/$export-output/[id]/[type].ndjson`
+    pub(super) fn export_file(&self, id: &str, file_name: &str) -> Result<ServerResponse, ServerError> {
+        let not_found = || ServerError::ExportNotFound(format!("{}/{}", id, file_name));
+        let exports = self.exports();
+        let job = exports.get(id).ok_or_else(not_found)?;
+        let is_file_name = file_name.ends_with(".ndjson") && !file_name.contains(['/', '\\']) && !file_name.starts_with('.');
+        if !is_file_name || !matches!(job.status(), ExportStatus::Complete(_)) {
+            return Err(not_found());
+        }
+        let body = fs::read_to_string(job.directory().join(file_name)).map_err(|_| not_found())?;
+        let mut response = ServerResponse::new(200);
+        response.add_header("Content-Type", FHIR_NDJSON);
+        response.set_body(body);
+        Ok(response)
+    }
+
+    fn exports(&self) -> MutexGuard<'_, HashMap<String, ExportJob>> {
+        self.exports.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
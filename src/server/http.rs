@@ -0,0 +1,104 @@
+//! Serving a [`FhirServer`] over HTTP with `tiny_http`.
+
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use tiny_http::{Header, Response, Server};
+
+use crate::repository::Repository;
+use super::{FhirServer, ServerError, ServerRequest, ServerResponse};
+
+/// A running HTTP server. Dropping the handle stops the server.
+pub struct ServerHandle {
+    server: Arc<Server>,
+    address: SocketAddr,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ServerHandle {
+    /// Returns the address the server listens on.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Returns the base URL of the server, e.g. `http://127.0.0.1:8080`.
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Blocks until the server stops.
+    pub fn join(mut self) {
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    /// Stops the server and waits for the request in progress to complete.
+    pub fn stop(self) {
+        drop(self);
+    }
+}
+
+impl Drop for ServerHandle {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl<R: Repository + Clone + Send + 'static> FhirServer<R> {
+    /// Starts serving on a background thread. Use port 0 to pick a free port;
+    /// the base URL defaults to the address listened on.
+    pub fn start(mut self, address: &str) -> io::Result<ServerHandle> {
+        let server = Server::http(address).map_err(io::Error::other)?;
+        let address = server
+            .server_addr()
+            .to_ip()
+            .ok_or_else(|| io::Error::other("the server is not listening on an IP address"))?;
+        if self.base_url().is_none() {
+            self.set_base_url(Some(format!("http://{}", address)));
+        }
+        let server = Arc::new(server);
+        let listener = Arc::clone(&server);
+        let thread = std::thread::spawn(move || {
+            for mut request in listener.incoming_requests() {
+                let response = match to_server_request(&mut request) {
+                    Ok(server_request) => self.handle(&server_request),
+                    Err(error) => ServerResponse::error(&ServerError::InvalidBody(error.to_string())),
+                };
+                let _ = request.respond(to_http_response(response));
+            }
+        });
+        Ok(ServerHandle {
+            server,
+            address,
+            thread: Some(thread),
+        })
+    }
+}
+
+fn to_server_request(request: &mut tiny_http::Request) -> io::Result<ServerRequest> {
+    let mut server_request = ServerRequest::new(request.method().as_str(), request.url());
+    for header in request.headers() {
+        server_request.add_header(header.field.as_str().as_str(), header.value.as_str());
+    }
+    let mut body = String::new();
+    request.as_reader().read_to_string(&mut body)?;
+    server_request.set_body(body);
+    Ok(server_request)
+}
+
+fn to_http_response(response: ServerResponse) -> Response<io::Cursor<Vec<u8>>> {
+    // `from_string` would add a text/plain Content-Type, even to an empty body
+    let mut http_response = Response::from_data(response.body().as_bytes()).with_status_code(response.status());
+    for (name, value) in response.headers() {
+        if let Ok(header) = Header::from_bytes(name.as_bytes(), value.as_bytes()) {
+            http_response.add_header(header);
+        }
+    }
+    http_response
+}
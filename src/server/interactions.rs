@@ -0,0 +1,322 @@
+//! The RESTful interactions of [`FhirServer`].
+
+use crate::bundle::{Bundle, BundleEntry, BundleEntryRequest, BundleEntryResponse, BundleLink};
+use crate::capability_statement::{
+    CapabilityStatement, CapabilityStatementBuilder, OperationRegistration, SystemInteraction, TypeInteraction,
+};
+use crate::compartment::{self, EverythingParams};
+use crate::conditional::{ConditionalCreate, ConditionalRepository, ConditionalUpdate};
+use crate::data_types::date_time::Instant;
+use crate::fhirpath_patch::FhirPathPatch;
+use crate::meta::Meta;
+use crate::patch::JsonPatch;
+use crate::repository::{Repository, RepositoryError, ResourceVersion};
+use crate::resource::{AnyResource, RESOURCE_TYPES};
+use crate::search::{percent_decode, SearchQuery};
+use crate::transaction;
+use super::{
+    check_content_type, if_match, precondition_failed, query_value, strip_format_parameters, FhirServer, ServerError, ServerRequest,
+    ServerResponse, FHIR_JSON, JSON_PATCH,
+};
+
+impl<R: Repository> FhirServer<R> {
+    /// Derives the CapabilityStatement served at `/metadata` from the
+    /// interactions the server is configured to support.
+    pub fn capability_statement(&self) -> CapabilityStatement {
+        let mut builder = CapabilityStatementBuilder::new()
+            .software("fhir-resources-rs", Some(env!("CARGO_PKG_VERSION")))
+            .system_interaction(SystemInteraction::Transaction)
+            .system_interaction(SystemInteraction::Batch)
+            .system_interaction(SystemInteraction::HistorySystem)
+            .conditional(self.conditional_delete)
+            .versioned_update()
+            .operation(OperationRegistration::on_types(
+                "everything",
+                "http://hl7.org/fhir/OperationDefinition/Patient-everything",
+                &["Patient"],
+            ))
+            .operation(OperationRegistration::system(
+                "export",
+                "http://hl7.org/fhir/uv/bulkdata/OperationDefinition/export",
+            ))
+            .operation(OperationRegistration::on_types(
+                "export",
+                "http://hl7.org/fhir/uv/bulkdata/OperationDefinition/patient-export",
+                &["Patient"],
+            ));
+        if self.interactions.contains(&TypeInteraction::Patch) {
+            builder = builder.patch_format(JSON_PATCH).patch_format(FHIR_JSON);
+        }
+        for resource_type in RESOURCE_TYPES {
+            builder = builder.resource(resource_type, &self.interactions);
+        }
+        builder.build()
+    }
+
+    /// `GET [type]/[id]`
+    pub(super) fn read(&self, resource_type: &str, id: &str) -> Result<ServerResponse, ServerError> {
+        let repository = self.repository();
+        match repository.read(resource_type, id) {
+            Ok(resource) => Ok(ServerResponse::resource(200, &resource)),
+            Err(RepositoryError::NotFound(_, _)) if is_deleted(&*repository, resource_type, id)? => {
+                Err(ServerError::Gone(resource_type.to_string(), id.to_string()))
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// `GET [type]/[id]/_history/[vid]`
+    pub(super) fn vread(&self, resource_type: &str, id: &str, version_id: &str) -> Result<ServerResponse, ServerError> {
+        let repository = self.repository();
+        match repository.vread(resource_type, id, version_id) {
+            Ok(resource) => Ok(ServerResponse::resource(200, &resource)),
+            Err(RepositoryError::NotFound(_, _)) => {
+                let deleted = repository
+                    .history(Some(resource_type), Some(id))?
+                    .iter()
+                    .any(|version| version.version_id() == version_id && version.is_deletion());
+                if deleted {
+                    Err(ServerError::Gone(resource_type.to_string(), id.to_string()))
+                } else {
+                    Err(RepositoryError::NotFound(resource_type.to_string(), format!("{}/_history/{}", id, version_id)).into())
+                }
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// `POST [type]`: the server assigns the id. With `If-None-Exist` the
+    /// resource is only created if nothing matches the criteria.
+    pub(super) fn create(&self, resource_type: &str, request: &ServerRequest) -> Result<ServerResponse, ServerError> {
+        let mut resource = parse_resource(resource_type, request)?;
+        if let Some(criteria) = request.header("If-None-Exist") {
+            return match self.repository().conditional_create(&self.engine, resource, criteria)? {
+                ConditionalCreate::Created(created) => Ok(self.written(201, &created, request)),
+                ConditionalCreate::Existing(existing) => Ok(self.written(200, &existing, request)),
+            };
+        }
+        resource.set_id(None);
+        let created = self.repository().create(resource)?;
+        Ok(self.written(201, &created, request))
+    }
+
+    /// `PUT [type]/[id]`: replaces the resource, creating it if absent.
+    ///
+    /// With `If-Match` the update fails with 412 unless the current version
+    /// matches; otherwise a `meta.versionId` in the body that is no longer
+    /// current fails with 409.
+    pub(super) fn update(&self, resource_type: &str, id: &str, request: &ServerRequest) -> Result<ServerResponse, ServerError> {
+        let resource = parse_resource(resource_type, request)?;
+        match resource.id() {
+            Some(resource_id) if resource_id == id => {}
+            Some(resource_id) => return Err(ServerError::IdMismatch(resource_id.to_string(), id.to_string())),
+            None => return Err(ServerError::InvalidResource("the resource has no id".to_string())),
+        }
+        let mut repository = self.repository();
+        let exists = repository.read(resource_type, id).is_ok();
+        let body_version = resource.meta().and_then(Meta::version_id).map(str::to_string);
+        let updated = match (if_match(request), body_version) {
+            (Some(expected), _) => repository.update_versioned(resource, &expected).map_err(precondition_failed)?,
+            (None, Some(expected)) if exists => repository.update_versioned(resource, &expected)?,
+            _ => repository.update(resource)?,
+        };
+        Ok(self.written(if exists { 200 } else { 201 }, &updated, request))
+    }
+
+    /// `PUT [type]?criteria`: updates the single match, or creates the resource if nothing matches.
+    pub(super) fn update_matching(&self, resource_type: &str, request: &ServerRequest) -> Result<ServerResponse, ServerError> {
+        let resource = parse_resource(resource_type, request)?;
+        let criteria = strip_format_parameters(request.query());
+        match self.repository().conditional_update(&self.engine, resource, &criteria)? {
+            ConditionalUpdate::Created(created) => Ok(self.written(201, &created, request)),
+            ConditionalUpdate::Updated(updated) => Ok(self.written(200, &updated, request)),
+        }
+    }
+
+    /// `PATCH [type]/[id]` with a JSON Patch document, or a FHIRPath Patch
+    /// Parameters resource sent as FHIR JSON, checked against `If-Match` if given.
+    pub(super) fn patch(&self, resource_type: &str, id: &str, request: &ServerRequest) -> Result<ServerResponse, ServerError> {
+        check_content_type(request, &[JSON_PATCH, FHIR_JSON, "application/json"])?;
+        let json_patch = match request.header("Content-Type") {
+            Some(content_type) => content_type.starts_with(JSON_PATCH),
+            None => request.body().trim_start().starts_with('['),
+        };
+        let mut repository = self.repository();
+        let current = repository.read(resource_type, id)?;
+        let patched = if json_patch {
+            JsonPatch::from_json(request.body())?.apply_any(&current)?
+        } else {
+            FhirPathPatch::from_json(request.body())?.apply_any(&current)?
+        };
+        let updated = match if_match(request) {
+            Some(expected) => repository.update_versioned(patched, &expected).map_err(precondition_failed)?,
+            None => repository.update(patched)?,
+        };
+        Ok(self.written(200, &updated, request))
+    }
+
+    /// `DELETE [type]/[id]`: deleting an already deleted resource succeeds.
+    pub(super) fn delete(&self, resource_type: &str, id: &str) -> Result<ServerResponse, ServerError> {
+        let mut repository = self.repository();
+        match repository.delete(resource_type, id) {
+            Ok(()) => Ok(ServerResponse::new(204)),
+            Err(RepositoryError::NotFound(_, _)) if is_deleted(&*repository, resource_type, id)? => {
+                Ok(ServerResponse::new(204))
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// `POST /` with a transaction or batch Bundle.
+    pub(super) fn transaction(&self, request: &ServerRequest) -> Result<ServerResponse, ServerError>
+    where
+        R: Clone,
+    {
+        check_content_type(request, &[FHIR_JSON, "application/json"])?;
+        let bundle = Bundle::from_json(request.body()).map_err(|error| ServerError::InvalidResource(error.to_string()))?;
+        let response = transaction::process(&mut *self.repository(), &bundle)?;
+        Ok(ServerResponse::json(200, response.to_json()))
+    }
+
+    /// `DELETE [type]?criteria`: deleting nothing succeeds.
+    pub(super) fn delete_matching(
+        &self,
+        resource_type: &str,
+        request: &ServerRequest,
+    ) -> Result<ServerResponse, ServerError> {
+        let criteria = strip_format_parameters(request.query());
+        self.repository()
+            .conditional_delete(&self.engine, resource_type, &criteria, self.conditional_delete)?;
+        Ok(ServerResponse::new(204))
+    }
+
+    /// `GET [type]?query` and `POST [type]/_search`.
+    pub(super) fn search(&self, resource_type: &str, query: &str) -> Result<ServerResponse, ServerError> {
+        let query = SearchQuery::parse(&strip_format_parameters(query));
+        let bundle = self.engine.execute(&*self.repository(), resource_type, &query)?;
+        Ok(ServerResponse::json(200, bundle.to_json()))
+    }
+
+    /// `GET Patient/[id]/$everything` with `_since` and `_type`.
+    pub(super) fn everything(&self, id: &str, request: &ServerRequest) -> Result<ServerResponse, ServerError> {
+        let params = EverythingParams::parse(&SearchQuery::parse(request.query()))?;
+        let bundle = compartment::everything(&self.engine, &*self.repository(), id, &params)?;
+        Ok(ServerResponse::json(200, bundle.to_json()))
+    }
+
+    /// `GET [[type]/[id]]/_history`, newest first, with `_since` and `_count`.
+    pub(super) fn history(
+        &self,
+        resource_type: Option<&str>,
+        id: Option<&str>,
+        request: &ServerRequest,
+    ) -> Result<ServerResponse, ServerError> {
+        let since = match query_value(request.query(), "_since").map(percent_decode) {
+            Some(since) => Some(Instant::parse(&since).map_err(|_| {
+                ServerError::InvalidResource(format!("invalid _since {}", since))
+            })?),
+            None => None,
+        };
+        let count = match query_value(request.query(), "_count") {
+            Some(count) => Some(count.parse::<usize>().map_err(|_| {
+                ServerError::InvalidResource(format!("invalid _count {}", count))
+            })?),
+            None => None,
+        };
+        let versions: Vec<ResourceVersion> = self
+            .repository()
+            .history(resource_type, id)?
+            .into_iter()
+            .filter(|version| {
+                since.as_ref().is_none_or(|since| {
+                    Instant::parse(version.last_updated()).is_ok_and(|last_updated| last_updated.compare(since).is_ge())
+                })
+            })
+            .collect();
+        if versions.is_empty()
+            && let (Some(resource_type), Some(id)) = (resource_type, id)
+        {
+            return Err(RepositoryError::NotFound(resource_type.to_string(), id.to_string()).into());
+        }
+
+        let mut bundle = Bundle::new("history".to_string());
+        bundle.set_total(Some(versions.len() as u32));
+        bundle.add_link(BundleLink::new("self".to_string(), self.url(&request_path(request))));
+        for version in versions.iter().take(count.unwrap_or(usize::MAX)) {
+            bundle.add_entry(self.history_entry(version));
+        }
+        Ok(ServerResponse::json(200, bundle.to_json()))
+    }
+
+    fn history_entry(&self, version: &ResourceVersion) -> BundleEntry {
+        let reference = format!("{}/{}", version.resource_type(), version.id());
+        let (method, url, status) = match version.resource() {
+            None => ("DELETE", reference.clone(), "204 No Content"),
+            Some(_) if version.version_id() == "1" => ("POST", version.resource_type().to_string(), "201 Created"),
+            Some(_) => ("PUT", reference.clone(), "200 OK"),
+        };
+        let mut entry = BundleEntry::new();
+        entry.set_full_url(Some(self.url(&reference)));
+        entry.set_resource(version.resource().cloned());
+        entry.set_request(Some(BundleEntryRequest::new(method.to_string(), url)));
+        let mut response = BundleEntryResponse::new(status.to_string());
+        response.set_etag(Some(format!("W/\"{}\"", version.version_id())));
+        response.set_last_modified(Some(version.last_updated().to_string()));
+        entry.set_response(Some(response));
+        entry
+    }
+
+    /// Builds the response to a create, update or patch, honouring `Prefer: return=minimal`.
+    fn written(&self, status: u16, resource: &AnyResource, request: &ServerRequest) -> ServerResponse {
+        let mut response = ServerResponse::resource(status, resource);
+        if request.header("Prefer").is_some_and(|prefer| prefer.contains("return=minimal")) {
+            response.set_body(String::new());
+            response.remove_header("Content-Type");
+        }
+        if let (Some(reference), Some(version_id)) =
+            (resource.reference(), resource.meta().and_then(|meta| meta.version_id()))
+        {
+            response.add_header("Location", &self.url(&format!("{}/_history/{}", reference, version_id)));
+        }
+        response
+    }
+
+    /// Returns a URL relative to the base URL, or the relative URL if there is none.
+    pub(super) fn url(&self, relative: &str) -> String {
+        match self.base_url() {
+            Some(base_url) => format!("{}/{}", base_url, relative),
+            None => relative.to_string(),
+        }
+    }
+}
+
+/// Returns true if the latest version of a resource is a deletion.
+fn is_deleted<R: Repository + ?Sized>(repository: &R, resource_type: &str, id: &str) -> Result<bool, ServerError> {
+    Ok(repository
+        .history(Some(resource_type), Some(id))?
+        .first()
+        .is_some_and(ResourceVersion::is_deletion))
+}
+
+/// Reads the resource in a request body, which must have the type named in the URL.
+fn parse_resource(resource_type: &str, request: &ServerRequest) -> Result<AnyResource, ServerError> {
+    check_content_type(request, &[FHIR_JSON, "application/json"])?;
+    let resource =
+        AnyResource::from_json(request.body()).map_err(|error| ServerError::InvalidResource(error.to_string()))?;
+    if resource.resource_type() != resource_type {
+        return Err(ServerError::InvalidResource(format!(
+            "expected a {} but found a {}",
+            resource_type,
+            resource.resource_type()
+        )));
+    }
+    Ok(resource)
+}
+
+fn request_path(request: &ServerRequest) -> String {
+    let path = request.path().trim_matches('/');
+    match request.query() {
+        "" => path.to_string(),
+        query => format!("{}?{}", path, query),
+    }
+}
@@ -0,0 +1,549 @@
+//! A FHIR RESTful API server over a [`Repository`], behind the `server` feature.
+//!
+//! [`FhirServer::handle`] maps a [`ServerRequest`] to a [`ServerResponse`]
+//! without any networking, and [`FhirServer::start`] serves the same handler
+//! over HTTP on a background thread. Every resource type in the crate supports
+//! read, vread, create, update, patch, delete, search (GET and POST) and
+//! history, with conditional create, update and delete; `POST /` processes
+//! transaction and batch Bundles, `$export` runs Bulk Data exports and
+//! `GET /metadata` returns the capabilities. Errors are returned as an
+//! `OperationOutcome`.
+
+mod export;
+mod http;
+mod interactions;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use crate::bulk_export::{BulkExportError, BulkExporter, ExportJob, ExportLevel};
+
+use crate::capability_statement::TypeInteraction;
+use crate::compartment::CompartmentError;
+use crate::conditional::{ConditionalDeleteMode, ConditionalError};
+use crate::operation_outcome::OperationOutcome;
+use crate::patch::PatchError;
+use crate::repository::{Repository, RepositoryError};
+use crate::resource::{AnyResource, RESOURCE_TYPES};
+pub use crate::resource::FHIR_JSON;
+use crate::search::{SearchEngine, SearchError};
+use crate::transaction::TransactionError;
+
+pub use http::ServerHandle;
+
+/// The media type of a JSON Patch document.
+pub const JSON_PATCH: &str = "application/json-patch+json";
+
+/// The first path segments served at the system level rather than for a
+/// resource type.
+const SYSTEM_SEGMENTS: &[&str] = &["metadata", "_history", "$export", "$export-poll-status", "$export-output"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum ServerError {
+    #[error("Unknown resource type: {0}")]
+    UnknownResourceType(String),
+    #[error("Unsupported interaction: {0} {1}")]
+    UnsupportedInteraction(String, String),
+    #[error("Resource deleted: {0}/{1}")]
+    Gone(String, String),
+    #[error("Invalid resource: {0}")]
+    InvalidResource(String),
+    #[error("Unreadable request body: {0}")]
+    InvalidBody(String),
+    #[error("Resource id {0} does not match the id {1} in the URL")]
+    IdMismatch(String, String),
+    #[error("Unsupported media type: {0}")]
+    UnsupportedMediaType(String),
+    #[error("Not acceptable: {0}")]
+    NotAcceptable(String),
+    #[error("Precondition failed: {0}")]
+    PreconditionFailed(String),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+    #[error(transparent)]
+    Search(#[from] SearchError),
+    #[error(transparent)]
+    Patch(#[from] PatchError),
+    #[error(transparent)]
+    Transaction(#[from] TransactionError),
+    #[error(transparent)]
+    Conditional(#[from] ConditionalError),
+    #[error(transparent)]
+    Compartment(#[from] CompartmentError),
+    #[error("Export not found: {0}")]
+    ExportNotFound(String),
+    #[error("Export failed: {0}")]
+    ExportFailed(String),
+    #[error(transparent)]
+    BulkExport(#[from] BulkExportError),
+}
+
+impl ServerError {
+    /// Returns the HTTP status code for the error.
+    pub fn status(&self) -> u16 {
+        match self {
+            ServerError::UnknownResourceType(_) => 404,
+            ServerError::UnsupportedInteraction(_, _) => 405,
+            ServerError::Gone(_, _) => 410,
+            ServerError::InvalidResource(_) | ServerError::InvalidBody(_) | ServerError::IdMismatch(_, _) => 400,
+            ServerError::UnsupportedMediaType(_) => 415,
+            ServerError::NotAcceptable(_) => 406,
+            ServerError::PreconditionFailed(_) => 412,
+            ServerError::Repository(RepositoryError::VersionConflict(_, _, _, _)) => 409,
+            ServerError::Repository(RepositoryError::NotFound(_, _))
+            | ServerError::Search(SearchError::Repository(RepositoryError::NotFound(_, _))) => 404,
+            ServerError::Repository(_) | ServerError::Search(_) => 400,
+            ServerError::Patch(PatchError::InvalidDocument(_) | PatchError::InvalidPath(_, _)) => 400,
+            ServerError::Patch(_) => 422,
+            ServerError::Transaction(error) => error.status(),
+            ServerError::Conditional(error) => error.status(),
+            ServerError::Compartment(error) => error.status(),
+            ServerError::ExportNotFound(_) => 404,
+            ServerError::ExportFailed(_) => 500,
+            ServerError::BulkExport(error) => error.status(),
+        }
+    }
+
+    /// Returns the OperationOutcome issue type code for the error.
+    pub fn issue_code(&self) -> &'static str {
+        match self {
+            ServerError::UnknownResourceType(_)
+            | ServerError::UnsupportedInteraction(_, _)
+            | ServerError::UnsupportedMediaType(_)
+            | ServerError::NotAcceptable(_) => "not-supported",
+            ServerError::Gone(_, _) => "deleted",
+            ServerError::PreconditionFailed(_) | ServerError::Repository(RepositoryError::VersionConflict(_, _, _, _)) => {
+                "conflict"
+            }
+            ServerError::Repository(RepositoryError::NotFound(_, _))
+            | ServerError::Search(SearchError::Repository(RepositoryError::NotFound(_, _))) => "not-found",
+            ServerError::Patch(_) => "processing",
+            ServerError::Transaction(error) => error.issue_code(),
+            ServerError::Conditional(error) => error.issue_code(),
+            ServerError::Compartment(error) => error.issue_code(),
+            ServerError::ExportNotFound(_) => "not-found",
+            ServerError::ExportFailed(_) => "exception",
+            ServerError::BulkExport(error) => error.issue_code(),
+            _ => "invalid",
+        }
+    }
+
+    /// Converts the error to an OperationOutcome.
+    pub fn to_outcome(&self) -> OperationOutcome {
+        OperationOutcome::error(self.issue_code(), self.to_string())
+    }
+}
+
+/// An HTTP request to the server. The path is relative to the server base.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerRequest {
+    method: String,
+    path: String,
+    query: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl ServerRequest {
+    /// Creates a new ServerRequest from a method and a URL of the form `/path?query`.
+    pub fn new(method: &str, url: &str) -> Self {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        Self {
+            method: method.to_ascii_uppercase(),
+            path: path.to_string(),
+            query: query.to_string(),
+            headers: Vec::new(),
+            body: String::new(),
+        }
+    }
+
+    /// Returns the HTTP method.
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// Returns the path.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the query string, without the leading `?`.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Returns the value of a header, matching its name case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the body.
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+
+    /// Adds a header.
+    pub fn add_header(&mut self, name: &str, value: &str) {
+        self.headers.push((name.to_string(), value.to_string()));
+    }
+
+    /// Sets the body.
+    pub fn set_body(&mut self, body: String) {
+        self.body = body;
+    }
+
+    /// Returns the request with a header added.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.add_header(name, value);
+        self
+    }
+
+    /// Returns the request with a body.
+    pub fn with_body(mut self, body: String) -> Self {
+        self.body = body;
+        self
+    }
+}
+
+/// An HTTP response from the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl ServerResponse {
+    /// Creates a new ServerResponse with a status and no body.
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: String::new(),
+        }
+    }
+
+    /// Creates a response holding a FHIR JSON body.
+    pub fn json(status: u16, body: String) -> Self {
+        let mut response = Self::new(status);
+        response.add_header("Content-Type", FHIR_JSON);
+        response.body = body;
+        response
+    }
+
+    /// Creates a response holding a resource, with its ETag when it has a version.
+    pub fn resource(status: u16, resource: &AnyResource) -> Self {
+        let mut response = Self::json(status, resource.to_json());
+        if let Some(version_id) = resource.meta().and_then(|meta| meta.version_id()) {
+            response.add_header("ETag", &format!("W/\"{}\"", version_id));
+        }
+        response
+    }
+
+    /// Creates an error response holding an OperationOutcome.
+    pub fn error(error: &ServerError) -> Self {
+        Self::json(error.status(), error.to_outcome().to_json())
+    }
+
+    /// Returns the status code.
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    /// Returns the headers.
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// Returns the value of a header, matching its name case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the body.
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+
+    /// Adds a header.
+    pub fn add_header(&mut self, name: &str, value: &str) {
+        self.headers.push((name.to_string(), value.to_string()));
+    }
+
+    /// Removes a header, matching its name case-insensitively.
+    pub fn remove_header(&mut self, name: &str) {
+        self.headers.retain(|(header, _)| !header.eq_ignore_ascii_case(name));
+    }
+
+    /// Sets the body.
+    pub fn set_body(&mut self, body: String) {
+        self.body = body;
+    }
+}
+
+/// A FHIR RESTful API server over a repository.
+#[derive(Debug)]
+pub struct FhirServer<R: Repository> {
+    repository: Mutex<R>,
+    engine: SearchEngine,
+    interactions: Vec<TypeInteraction>,
+    conditional_delete: ConditionalDeleteMode,
+    exporter: BulkExporter,
+    export_dir: PathBuf,
+    exports: Mutex<HashMap<String, ExportJob>>,
+}
+
+impl<R: Repository> FhirServer<R> {
+    /// Creates a new FhirServer over a repository, supporting every type
+    /// interaction. The base URL, used for `fullUrl`, `Location` and Bundle
+    /// links, defaults to none. Exports are written under the system
+    /// temporary directory.
+    pub fn new(repository: R) -> Self {
+        Self {
+            repository: Mutex::new(repository),
+            engine: SearchEngine::new(),
+            interactions: TypeInteraction::ALL.to_vec(),
+            conditional_delete: ConditionalDeleteMode::Single,
+            exporter: BulkExporter::new(),
+            export_dir: std::env::temp_dir().join("fhir-export"),
+            exports: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the type interactions the server supports.
+    pub fn interactions(&self) -> &[TypeInteraction] {
+        &self.interactions
+    }
+
+    /// Sets the type interactions the server supports, e.g. to serve read-only.
+    /// Other interactions are rejected and left out of the CapabilityStatement.
+    pub fn set_interactions(&mut self, interactions: Vec<TypeInteraction>) {
+        self.interactions = interactions;
+    }
+
+    /// Returns how conditional deletes treat more than one match.
+    pub fn conditional_delete(&self) -> ConditionalDeleteMode {
+        self.conditional_delete
+    }
+
+    /// Sets how conditional deletes treat more than one match.
+    pub fn set_conditional_delete(&mut self, mode: ConditionalDeleteMode) {
+        self.conditional_delete = mode;
+    }
+
+    /// Returns the directory export jobs write their files under.
+    pub fn export_dir(&self) -> &Path {
+        &self.export_dir
+    }
+
+    /// Sets the directory export jobs write their files under, one
+    /// subdirectory per job.
+    pub fn set_export_dir(&mut self, export_dir: PathBuf) {
+        self.export_dir = export_dir;
+    }
+
+    /// Returns the exporter, to register groups for `Group/[id]/$export`.
+    pub fn exporter_mut(&mut self) -> &mut BulkExporter {
+        &mut self.exporter
+    }
+
+    /// Returns the base URL.
+    pub fn base_url(&self) -> Option<&str> {
+        self.engine.base_url()
+    }
+
+    /// Sets the base URL.
+    pub fn set_base_url(&mut self, base_url: Option<String>) {
+        self.engine.set_base_url(base_url);
+    }
+
+    /// Returns the search engine, to register value sets or change paging.
+    pub fn engine(&self) -> &SearchEngine {
+        &self.engine
+    }
+
+    /// Returns the search engine for modification.
+    pub fn engine_mut(&mut self) -> &mut SearchEngine {
+        &mut self.engine
+    }
+
+    /// Locks and returns the repository.
+    pub fn repository(&self) -> MutexGuard<'_, R> {
+        self.repository.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<R: Repository + Clone> FhirServer<R> {
+    /// Handles a request, returning an OperationOutcome for any error.
+    pub fn handle(&self, request: &ServerRequest) -> ServerResponse {
+        self.route(request).unwrap_or_else(|error| ServerResponse::error(&error))
+    }
+
+    fn route(&self, request: &ServerRequest) -> Result<ServerResponse, ServerError> {
+        check_accept(request)?;
+        let segments: Vec<&str> = request
+            .path()
+            .trim_matches('/')
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        if let Some(resource_type) = segments.first()
+            && !SYSTEM_SEGMENTS.contains(resource_type)
+            && !matches!(segments.as_slice(), ["Group", _, "$export"])
+            && !RESOURCE_TYPES.contains(resource_type)
+        {
+            return Err(ServerError::UnknownResourceType(resource_type.to_string()));
+        }
+        if let Some(interaction) = interaction(request.method(), &segments)
+            && !self.interactions.contains(&interaction)
+        {
+            return Err(ServerError::UnsupportedInteraction(
+                request.method().to_string(),
+                request.path().to_string(),
+            ));
+        }
+        match (request.method(), segments.as_slice()) {
+            ("POST", []) => self.transaction(request),
+            ("GET", ["$export"]) => self.export(ExportLevel::System, request),
+            ("GET", ["Patient", "$export"]) => self.export(ExportLevel::Patient, request),
+            ("GET", ["Group", id, "$export"]) => self.export(ExportLevel::Group(id.to_string()), request),
+            ("GET", ["$export-poll-status", id]) => self.export_status(id),
+            ("DELETE", ["$export-poll-status", id]) => self.cancel_export(id),
+            ("GET", ["$export-output", id, file_name]) => self.export_file(id, file_name),
+            ("GET", ["metadata"]) => Ok(ServerResponse::json(200, self.capability_statement().to_json())),
+            ("GET", ["_history"]) => self.history(None, None, request),
+            ("GET", [resource_type]) => self.search(resource_type, request.query()),
+            ("POST", [resource_type, "_search"]) => {
+                let query = form_query(request);
+                self.search(resource_type, &query)
+            }
+            ("POST", [resource_type]) => self.create(resource_type, request),
+            ("PUT", [resource_type]) if !request.query().is_empty() => self.update_matching(resource_type, request),
+            ("DELETE", [resource_type]) if !request.query().is_empty() => self.delete_matching(resource_type, request),
+            ("GET", [resource_type, "_history"]) => self.history(Some(resource_type), None, request),
+            ("GET", [resource_type, id]) => self.read(resource_type, id),
+            ("PUT", [resource_type, id]) => self.update(resource_type, id, request),
+            ("PATCH", [resource_type, id]) => self.patch(resource_type, id, request),
+            ("DELETE", [resource_type, id]) => self.delete(resource_type, id),
+            ("GET", ["Patient", id, "$everything"]) => self.everything(id, request),
+            ("GET", [resource_type, id, "_history"]) => self.history(Some(resource_type), Some(id), request),
+            ("GET", [resource_type, id, "_history", version_id]) => self.vread(resource_type, id, version_id),
+            _ => Err(ServerError::UnsupportedInteraction(
+                request.method().to_string(),
+                request.path().to_string(),
+            )),
+        }
+    }
+}
+
+/// Returns the type interaction a request performs, if any.
+fn interaction(method: &str, segments: &[&str]) -> Option<TypeInteraction> {
+    let interaction = match (method, segments) {
+        ("GET", ["metadata"]) | ("GET", ["_history"]) => return None,
+        (_, [operation, ..]) if operation.starts_with('$') => return None,
+        (_, [_, "$export"]) | (_, [_, _, "$export"]) => return None,
+        ("GET", [_]) | ("POST", [_, "_search"]) => TypeInteraction::SearchType,
+        ("POST", [_]) => TypeInteraction::Create,
+        ("PUT", [_]) => TypeInteraction::Update,
+        ("DELETE", [_]) => TypeInteraction::Delete,
+        ("GET", [_, "_history"]) => TypeInteraction::HistoryType,
+        ("GET", [_, _]) => TypeInteraction::Read,
+        ("PUT", [_, _]) => TypeInteraction::Update,
+        ("PATCH", [_, _]) => TypeInteraction::Patch,
+        ("DELETE", [_, _]) => TypeInteraction::Delete,
+        ("GET", [_, _, "_history"]) => TypeInteraction::HistoryInstance,
+        ("GET", [_, _, "_history", _]) => TypeInteraction::Vread,
+        _ => return None,
+    };
+    Some(interaction)
+}
+
+/// Rejects requests that accept neither FHIR JSON nor plain JSON.
+fn check_accept(request: &ServerRequest) -> Result<(), ServerError> {
+    let format = query_value(request.query(), "_format").or(request.header("Accept"));
+    match format {
+        Some(format) if !is_json(format) => Err(ServerError::NotAcceptable(format.to_string())),
+        _ => Ok(()),
+    }
+}
+
+/// Returns true if a media type list or `_format` value accepts JSON.
+fn is_json(format: &str) -> bool {
+    format.split(',').any(|media_type| {
+        let media_type = media_type.split(';').next().unwrap_or_default().trim();
+        matches!(
+            media_type,
+            "json" | "application/json" | "application/fhir+json" | "application/*" | "*/*"
+        )
+    })
+}
+
+/// Checks that a request body is JSON of one of the accepted media types.
+fn check_content_type(request: &ServerRequest, accepted: &[&str]) -> Result<(), ServerError> {
+    match request.header("Content-Type") {
+        Some(content_type) => {
+            let media_type = content_type.split(';').next().unwrap_or_default().trim();
+            if accepted.contains(&media_type) {
+                Ok(())
+            } else {
+                Err(ServerError::UnsupportedMediaType(content_type.to_string()))
+            }
+        }
+        None => Ok(()),
+    }
+}
+
+/// Returns the raw value of the first query parameter with the given name.
+fn query_value<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+/// Removes the parameters that describe the response format rather than the search.
+fn strip_format_parameters(query: &str) -> String {
+    query
+        .split('&')
+        .filter(|pair| {
+            let key = pair.split('=').next().unwrap_or_default();
+            !pair.is_empty() && key != "_format" && key != "_pretty"
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Returns the version id in an `If-Match` header, e.g. `3` for `W/"3"`.
+fn if_match(request: &ServerRequest) -> Option<String> {
+    request
+        .header("If-Match")
+        .map(|etag| etag.trim().trim_start_matches("W/").trim_matches('"').to_string())
+}
+
+/// Reports a failed `If-Match` check, or a missing resource, as 412 Precondition Failed.
+fn precondition_failed(error: RepositoryError) -> ServerError {
+    match error {
+        RepositoryError::VersionConflict(_, _, _, _) | RepositoryError::NotFound(_, _) => {
+            ServerError::PreconditionFailed(error.to_string())
+        }
+        error => error.into(),
+    }
+}
+
+/// Combines the URL query and the form-encoded body of a POST search.
+fn form_query(request: &ServerRequest) -> String {
+    let body = request.body().trim().replace('+', "%20");
+    [request.query(), body.as_str()]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("&")
+}
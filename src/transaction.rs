@@ -0,0 +1,329 @@
+//! Transaction and batch Bundle processing against a [`Repository`].
+//!
+//! Entries are processed in the order the specification requires: DELETE,
+//! then POST, then PUT, then GET and HEAD. The response lists them in request
+//! order. A transaction runs against a copy of the repository which replaces
+//! it only when every entry succeeds, so a failure leaves the repository
+//! untouched. Batch entries run directly against the repository and fail
+//! independently, each failure carrying an OperationOutcome.
+//!
+//! Every POST entry is given its id before any entry runs, so references to
+//! the `urn:uuid:` or `urn:oid:` `fullUrl` of another entry are rewritten to
+//! its `Type/id` in one pass, whatever order the entries reference each other in.
+
+use std::collections::{HashMap, HashSet};
+
+use serde_json::Value;
+
+use crate::bundle::{Bundle, BundleEntry, BundleEntryResponse};
+use crate::operation_outcome::OperationOutcome;
+use crate::repository::{Repository, RepositoryError};
+use crate::resource::{AnyResource, RESOURCE_TYPES};
+
+#[derive(Debug, thiserror::Error)]
+pub enum TransactionError {
+    #[error("Expected a transaction or batch Bundle but found a {0} Bundle")]
+    NotProcessable(String),
+    #[error("Entry has no request")]
+    MissingRequest,
+    #[error("Entry has no resource")]
+    MissingResource,
+    #[error("Unsupported request: {0} {1}")]
+    UnsupportedRequest(String, String),
+    #[error("Expected a {0} but found a {1}")]
+    ResourceTypeMismatch(String, String),
+    #[error("Resource id {0} does not match the id {1} in the URL")]
+    IdMismatch(String, String),
+    #[error("More than one entry for {0}")]
+    DuplicateEntry(String),
+    #[error("Precondition failed: {0}")]
+    PreconditionFailed(String),
+    #[error("Rewritten resource is invalid: {0}")]
+    InvalidResource(#[from] serde_json::Error),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+    #[error("Entry {0} failed: {1}")]
+    Entry(usize, Box<TransactionError>),
+}
+
+impl TransactionError {
+    /// Returns the HTTP status code for the error.
+    pub fn status(&self) -> u16 {
+        match self {
+            TransactionError::UnsupportedRequest(_, _) => 405,
+            TransactionError::Repository(RepositoryError::NotFound(_, _)) => 404,
+            TransactionError::Repository(RepositoryError::VersionConflict(_, _, _, _)) => 409,
+            TransactionError::PreconditionFailed(_) => 412,
+            TransactionError::Entry(_, error) => error.status(),
+            _ => 400,
+        }
+    }
+
+    /// Returns the OperationOutcome issue type code for the error.
+    pub fn issue_code(&self) -> &'static str {
+        match self {
+            TransactionError::UnsupportedRequest(_, _) => "not-supported",
+            TransactionError::Repository(RepositoryError::NotFound(_, _)) => "not-found",
+            TransactionError::DuplicateEntry(_) => "duplicate",
+            TransactionError::PreconditionFailed(_)
+            | TransactionError::Repository(RepositoryError::VersionConflict(_, _, _, _)) => "conflict",
+            TransactionError::Entry(_, error) => error.issue_code(),
+            _ => "invalid",
+        }
+    }
+
+    /// Converts the error to an OperationOutcome.
+    pub fn to_outcome(&self) -> OperationOutcome {
+        OperationOutcome::error(self.issue_code(), self.to_string())
+    }
+}
+
+/// Processes a transaction or batch Bundle, returning the
+/// transaction-response or batch-response Bundle.
+///
+/// A transaction fails as a whole with [`TransactionError::Entry`], naming
+/// the entry that failed. A batch only fails as a whole when the Bundle itself
+/// cannot be processed.
+pub fn process<R: Repository + Clone>(repository: &mut R, bundle: &Bundle) -> Result<Bundle, TransactionError> {
+    let entries = match bundle.bundle_type() {
+        "transaction" => {
+            check_duplicates(bundle.entry())?;
+            let mut working = repository.clone();
+            let entries = run(&mut working, bundle.entry(), true)?;
+            *repository = working;
+            entries
+        }
+        "batch" => run(repository, bundle.entry(), false)?,
+        other => return Err(TransactionError::NotProcessable(other.to_string())),
+    };
+    let mut response = Bundle::new(format!("{}-response", bundle.bundle_type()));
+    response.set_entry(entries);
+    Ok(response)
+}
+
+fn run<R: Repository>(
+    repository: &mut R,
+    entries: &[BundleEntry],
+    atomic: bool,
+) -> Result<Vec<BundleEntry>, TransactionError> {
+    let mut references = put_placeholders(entries);
+    let mut ids: HashMap<usize, String> = HashMap::new();
+    for (index, entry) in entries.iter().enumerate() {
+        let Some(request) = entry.request().filter(|request| request.method() == "POST") else { continue };
+        let id = repository.allocate_id();
+        if let Some(full_url) = entry.full_url().filter(|url| is_placeholder(url)) {
+            let resource_type = request.url().split('?').next().unwrap_or_default().trim_matches('/');
+            references.insert(full_url.to_string(), format!("{}/{}", resource_type, id));
+        }
+        ids.insert(index, id);
+    }
+    let mut responses: Vec<Option<BundleEntry>> = vec![None; entries.len()];
+    for index in processing_order(entries) {
+        let response = match execute(repository, &entries[index], ids.remove(&index), &references) {
+            Ok(response) => response,
+            Err(error) if atomic => return Err(TransactionError::Entry(index, Box::new(error))),
+            Err(error) => failed(&error),
+        };
+        responses[index] = Some(response);
+    }
+    Ok(responses.into_iter().flatten().collect())
+}
+
+/// Returns the entry indexes in processing order: DELETE, POST, PUT, then
+/// GET and HEAD, with anything else last.
+fn processing_order(entries: &[BundleEntry]) -> Vec<usize> {
+    let rank = |entry: &BundleEntry| match entry.request().map(|request| request.method()) {
+        Some("DELETE") => 0,
+        Some("POST") => 1,
+        Some("PUT") => 2,
+        Some("GET") | Some("HEAD") => 3,
+        _ => 4,
+    };
+    let mut order: Vec<usize> = (0..entries.len()).collect();
+    order.sort_by_key(|index| rank(&entries[*index]));
+    order
+}
+
+/// Rejects transactions with more than one entry writing or reading the same resource.
+fn check_duplicates(entries: &[BundleEntry]) -> Result<(), TransactionError> {
+    let mut seen = HashSet::new();
+    for request in entries.iter().filter_map(BundleEntry::request) {
+        if request.method() != "POST" && !seen.insert(request.url().trim_matches('/')) {
+            return Err(TransactionError::DuplicateEntry(request.url().to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// Maps the placeholder `fullUrl`s of PUT entries to the `Type/id` in their URL.
+fn put_placeholders(entries: &[BundleEntry]) -> HashMap<String, String> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let request = entry.request().filter(|request| request.method() == "PUT")?;
+            let full_url = entry.full_url().filter(|url| is_placeholder(url))?;
+            Some((full_url.to_string(), request.url().trim_matches('/').to_string()))
+        })
+        .collect()
+}
+
+/// Runs one entry. A POST stores its resource under the id given to it in
+/// advance, or a new one.
+fn execute<R: Repository>(
+    repository: &mut R,
+    entry: &BundleEntry,
+    id: Option<String>,
+    references: &HashMap<String, String>,
+) -> Result<BundleEntry, TransactionError> {
+    let request = entry.request().ok_or(TransactionError::MissingRequest)?;
+    let path = request.url().split('?').next().unwrap_or_default();
+    let segments: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+    let unsupported = || TransactionError::UnsupportedRequest(request.method().to_string(), request.url().to_string());
+    if !segments.first().is_some_and(|resource_type| RESOURCE_TYPES.contains(resource_type)) {
+        return Err(unsupported());
+    }
+
+    match (request.method(), segments.as_slice()) {
+        ("DELETE", [resource_type, id]) => {
+            match repository.delete(resource_type, id) {
+                Ok(()) => {}
+                Err(RepositoryError::NotFound(_, _))
+                    if repository
+                        .history(Some(resource_type), Some(id))?
+                        .first()
+                        .is_some_and(|version| version.is_deletion()) => {}
+                Err(error) => return Err(error.into()),
+            }
+            Ok(response_entry("204 No Content", None))
+        }
+        ("POST", [resource_type]) => {
+            let mut resource = entry_resource(entry, resource_type, references)?;
+            resource.set_id(Some(id.unwrap_or_else(|| repository.allocate_id())));
+            let created = repository.update(resource)?;
+            Ok(response_entry("201 Created", Some(created)))
+        }
+        ("PUT", [resource_type, id]) => {
+            let mut resource = entry_resource(entry, resource_type, references)?;
+            match resource.id() {
+                Some(resource_id) if resource_id != *id => {
+                    return Err(TransactionError::IdMismatch(resource_id.to_string(), id.to_string()));
+                }
+                Some(_) => {}
+                None => resource.set_id(Some(id.to_string())),
+            }
+            let exists = repository.read(resource_type, id).is_ok();
+            let updated = match request.if_match() {
+                Some(etag) => {
+                    let expected = etag.trim_start_matches("W/").trim_matches('"');
+                    repository.update_versioned(resource, expected).map_err(|error| match error {
+                        RepositoryError::VersionConflict(_, _, _, _) | RepositoryError::NotFound(_, _) => {
+                            TransactionError::PreconditionFailed(error.to_string())
+                        }
+                        error => error.into(),
+                    })?
+                }
+                None => repository.update(resource)?,
+            };
+            Ok(response_entry(if exists { "200 OK" } else { "201 Created" }, Some(updated)))
+        }
+        (method @ ("GET" | "HEAD"), [resource_type, id]) => {
+            let resource = repository.read(resource_type, id)?;
+            Ok(read_entry(method, resource))
+        }
+        (method @ ("GET" | "HEAD"), [resource_type, id, "_history", version_id]) => {
+            let resource = repository.vread(resource_type, id, version_id)?;
+            Ok(read_entry(method, resource))
+        }
+        _ => Err(unsupported()),
+    }
+}
+
+/// Returns the entry's resource, checked against the URL's type, with
+/// placeholder references rewritten.
+fn entry_resource(
+    entry: &BundleEntry,
+    resource_type: &str,
+    references: &HashMap<String, String>,
+) -> Result<AnyResource, TransactionError> {
+    let resource = entry.resource().ok_or(TransactionError::MissingResource)?;
+    if resource.resource_type() != resource_type {
+        return Err(TransactionError::ResourceTypeMismatch(
+            resource_type.to_string(),
+            resource.resource_type().to_string(),
+        ));
+    }
+    let mut value = serde_json::to_value(resource)?;
+    rewrite_references(&mut value, references);
+    Ok(serde_json::from_value(value)?)
+}
+
+fn read_entry(method: &str, resource: AnyResource) -> BundleEntry {
+    let mut entry = response_entry("200 OK", Some(resource));
+    if method == "HEAD" {
+        entry.set_resource(None);
+    }
+    entry
+}
+
+/// Builds a response entry, with the location and version of a stored resource.
+fn response_entry(status: &str, resource: Option<AnyResource>) -> BundleEntry {
+    let mut response = BundleEntryResponse::new(status.to_string());
+    if let Some(resource) = &resource {
+        let meta = resource.meta();
+        if let (Some(reference), Some(version_id)) = (resource.reference(), meta.and_then(|meta| meta.version_id())) {
+            response.set_location(Some(format!("{}/_history/{}", reference, version_id)));
+            response.set_etag(Some(format!("W/\"{}\"", version_id)));
+        }
+        response.set_last_modified(meta.and_then(|meta| meta.last_updated()).map(str::to_string));
+    }
+    let mut entry = BundleEntry::new();
+    entry.set_resource(resource);
+    entry.set_response(Some(response));
+    entry
+}
+
+/// Builds the response entry of a failed batch entry.
+fn failed(error: &TransactionError) -> BundleEntry {
+    let mut response = BundleEntryResponse::new(status_line(error.status()));
+    response.set_outcome(Some(error.to_outcome()));
+    let mut entry = BundleEntry::new();
+    entry.set_response(Some(response));
+    entry
+}
+
+fn status_line(status: u16) -> String {
+    let reason = match status {
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        410 => "Gone",
+        412 => "Precondition Failed",
+        422 => "Unprocessable Entity",
+        _ => return status.to_string(),
+    };
+    format!("{} {}", status, reason)
+}
+
+fn is_placeholder(url: &str) -> bool {
+    url.starts_with("urn:uuid:") || url.starts_with("urn:oid:")
+}
+
+fn rewrite_references(value: &mut Value, references: &HashMap<String, String>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                match value {
+                    Value::String(reference) if key == "reference" => {
+                        if let Some(rewritten) = references.get(reference.as_str()) {
+                            *reference = rewritten.clone();
+                        }
+                    }
+                    _ => rewrite_references(value, references),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| rewrite_references(item, references)),
+        _ => {}
+    }
+}
@@ -0,0 +1,122 @@
+use fhir_resources_rs::account::Account;
+use fhir_resources_rs::account_balance::AccountBalance;
+use fhir_resources_rs::account_hierarchy::{AccountGraph, AccountHierarchyError};
+use fhir_resources_rs::account_related_account::AccountRelatedAccount;
+use fhir_resources_rs::balance_summary::BalanceAggregate;
+use fhir_resources_rs::data_types::codeable_concept::{CodeableConcept, Coding};
+use fhir_resources_rs::data_types::reference::Reference;
+use fhir_resources_rs::money::Money;
+use fhir_resources_rs::repository::{MemoryRepository, Repository};
+
+#[test]
+fn test_build_and_traverse() {
+    // 1 is the top account; 2 and 3 name it as their parent, 4 names 2, and 5
+    // names 1 as its guarantor account.
+    let mut repository = MemoryRepository::new();
+    let mut top = Account::new();
+    top.set_id(Some("1".to_string()));
+    repository.create(top.into()).unwrap();
+    for (id, relationship, target) in [("2", "parent", "1"), ("3", "parent", "1"), ("4", "parent", "2"), ("5", "guarantor", "1")] {
+        let mut account = Account::new();
+        account.set_id(Some(id.to_string()));
+        account.add_related_account(AccountRelatedAccount::with_relationship(
+            Reference::with_reference(format!("Account/{}", target)),
+            CodeableConcept::with_coding(Coding::with_system_and_code(
+                "http://terminology.hl7.org/CodeSystem/account-relationship".to_string(),
+                relationship.to_string(),
+            )),
+        ));
+        repository.create(account.into()).unwrap();
+    }
+
+    let graph = AccountGraph::build(&repository).unwrap();
+    assert_eq!(graph.links().len(), 4);
+    assert_eq!(graph.links()[0].relationship(), Some("parent"));
+    assert_eq!(graph.parents("4"), ["2"]);
+    assert_eq!(graph.children("1"), ["2", "3", "5"]);
+    assert_eq!(graph.descendants("1").unwrap(), ["2", "3", "5", "4"]);
+    assert_eq!(graph.ancestors("4").unwrap(), ["2", "1"]);
+    assert!(graph.ancestors("1").unwrap().is_empty());
+    assert!(graph.find_cycle().is_none());
+    assert!(matches!(graph.descendants("9"), Err(AccountHierarchyError::NotFound(id)) if id == "9"));
+
+    // A link to a missing account does not break the rest of the graph
+    let mut top = Account::new();
+    top.set_id(Some("1".to_string()));
+    let mut child = Account::new();
+    child.set_id(Some("6".to_string()));
+    child.add_related_account(AccountRelatedAccount::with_relationship(
+        Reference::with_reference("Account/7".to_string()),
+        CodeableConcept::with_coding(Coding::with_code("parent".to_string())),
+    ));
+    child.add_related_account(AccountRelatedAccount::with_relationship(
+        Reference::with_reference("Account/1".to_string()),
+        CodeableConcept::with_coding(Coding::with_code("parent".to_string())),
+    ));
+    let graph = AccountGraph::from_accounts(vec![top, child]);
+    assert_eq!(graph.dangling().len(), 1);
+    assert_eq!((graph.dangling()[0].child(), graph.dangling()[0].parent()), ("6", "7"));
+    assert_eq!(graph.parents("6"), ["1"]);
+}
+
+#[test]
+fn test_rollup_respects_relationship() {
+    let mut accounts = Vec::new();
+    for (id, related, amount) in [
+        ("1", None, "100.00"),
+        ("2", Some(("parent", "1")), "20.00"),
+        ("3", Some(("parent", "1")), "30.00"),
+        ("4", Some(("parent", "2")), "4.50"),
+        ("5", Some(("guarantor", "1")), "999.00"),
+    ] {
+        let mut account = Account::new();
+        account.set_id(Some(id.to_string()));
+        if let Some((relationship, target)) = related {
+            account.add_related_account(AccountRelatedAccount::with_relationship(
+                Reference::with_reference(format!("Account/{}", target)),
+                CodeableConcept::with_coding(Coding::with_code(relationship.to_string())),
+            ));
+        }
+        account.add_balance(AccountBalance::new(Money::with_currency(amount.parse().unwrap(), "USD".to_string())));
+        accounts.push(account);
+    }
+
+    // Account 5 names 1 as its guarantor account, so it is not part of 1's
+    // parent rollup
+    let graph = AccountGraph::from_accounts(accounts);
+    let rollup = graph.rollup("1", "parent").unwrap();
+    assert_eq!(rollup.accounts(), ["1", "2", "3", "4"]);
+    assert_eq!(rollup.summary().by_aggregate(BalanceAggregate::Total).unwrap().unwrap().format().unwrap(), "$154.50");
+
+    let branch = graph.rollup("2", "parent").unwrap();
+    assert_eq!(branch.accounts(), ["2", "4"]);
+    let guaranteed = graph.rollup("1", "guarantor").unwrap();
+    assert_eq!(guaranteed.accounts(), ["1", "5"]);
+}
+
+#[test]
+fn test_cycles() {
+    // 1 names 4 as its parent, closing the loop 1 -> 4 -> 2 -> 1
+    let mut accounts = Vec::new();
+    for (id, relationship, target) in [("1", "parent", "4"), ("2", "parent", "1"), ("4", "parent", "2"), ("5", "guarantor", "1")] {
+        let mut account = Account::new();
+        account.set_id(Some(id.to_string()));
+        account.add_related_account(AccountRelatedAccount::with_relationship(
+            Reference::with_reference(format!("Account/{}", target)),
+            CodeableConcept::with_coding(Coding::with_code(relationship.to_string())),
+        ));
+        account.add_balance(AccountBalance::new(Money::with_currency("1.00".parse().unwrap(), "USD".to_string())));
+        accounts.push(account);
+    }
+
+    let graph = AccountGraph::from_accounts(accounts);
+    assert_eq!(graph.find_cycle().unwrap(), ["1", "4", "2", "1"]);
+    // Traversal still ends, visiting each account once
+    assert_eq!(graph.ancestors("2").unwrap(), ["1", "4"]);
+    assert!(matches!(
+        graph.rollup("2", "parent"),
+        Err(AccountHierarchyError::Cycle(cycle)) if cycle == ["2", "4", "1", "2"]
+    ));
+    // A cycle through other relationships does not block the rollup
+    assert_eq!(graph.rollup("1", "guarantor").unwrap().accounts(), ["1", "5"]);
+}
@@ -0,0 +1,99 @@
+use fhir_resources_rs::account::Account;
+use fhir_resources_rs::account_balance::AccountBalance;
+use fhir_resources_rs::account_lifecycle::{
+    AccountLifecycle, AccountStatus, BillingStatus, LifecycleError, StatusChange,
+};
+use fhir_resources_rs::data_types::codeable_concept::CodeableConcept;
+use fhir_resources_rs::data_types::reference::Reference;
+use fhir_resources_rs::money::Money;
+
+#[test]
+fn test_allowed_transitions() {
+    assert!(AccountStatus::Active.can_become(AccountStatus::OnHold));
+    assert!(AccountStatus::Inactive.can_become(AccountStatus::Active));
+    assert!(!AccountStatus::Inactive.can_become(AccountStatus::OnHold));
+    assert!(!AccountStatus::EnteredInError.can_become(AccountStatus::Active));
+    assert!(!AccountStatus::Active.can_become(AccountStatus::Unknown));
+    assert!(!AccountStatus::Active.can_become(AccountStatus::Active));
+
+    assert!(BillingStatus::Open.can_become(BillingStatus::Billing));
+    assert!(BillingStatus::Billing.can_become(BillingStatus::ClosedBadDebt));
+    assert!(!BillingStatus::Billing.can_become(BillingStatus::CareCompleteNotBilled));
+    assert!(!BillingStatus::ClosedCompleted.can_become(BillingStatus::Open));
+
+    assert_eq!(AccountStatus::parse("on-hold").unwrap(), AccountStatus::OnHold);
+    assert_eq!(AccountStatus::parse("closed"), Err(LifecycleError::UnknownStatus("closed".to_string())));
+    let mut account = Account::new();
+    account.set_billing_status(Some(CodeableConcept::with_text("carecomplete-notbilled".to_string())));
+    assert_eq!(BillingStatus::of(&account).unwrap(), Some(BillingStatus::CareCompleteNotBilled));
+}
+
+#[test]
+fn test_transitions_are_recorded() {
+    let clerk = Reference::with_reference("Practitioner/clerk".to_string());
+    let mut account = Account::new();
+    account.set_status(Some("active".to_string()));
+    account.add_balance(AccountBalance::new(Money::with_currency("0.00".parse().unwrap(), "USD".to_string())));
+    let mut lifecycle = AccountLifecycle::new(account);
+    lifecycle.change_billing_status(BillingStatus::Open, clerk.clone(), "2024-05-01T09:00:00Z".parse().unwrap()).unwrap();
+    lifecycle.change_billing_status(BillingStatus::ClosedCompleted, clerk.clone(), "2024-05-02T09:00:00Z".parse().unwrap()).unwrap();
+    let transition = lifecycle.change_status(AccountStatus::Inactive, clerk.clone(), "2024-05-03T09:00:00Z".parse().unwrap()).unwrap();
+    assert_eq!(
+        transition.change(),
+        StatusChange::Status { from: Some(AccountStatus::Active), to: AccountStatus::Inactive }
+    );
+    assert_eq!(transition.by().reference(), Some("Practitioner/clerk"));
+    assert_eq!(transition.at().as_str(), "2024-05-03T09:00:00Z");
+    assert_eq!(lifecycle.history().len(), 3);
+    assert_eq!(
+        lifecycle.history()[0].change(),
+        StatusChange::BillingStatus { from: None, to: BillingStatus::Open }
+    );
+
+    let account = lifecycle.into_account();
+    assert_eq!(account.status(), Some("inactive"));
+    let billing = account.billing_status().unwrap().coding()[0].clone();
+    assert_eq!(billing.system(), Some("http://hl7.org/fhir/account-billing-status"));
+    assert_eq!(billing.code(), Some("closed-completed"));
+}
+
+#[test]
+fn test_guards() {
+    let clerk = Reference::with_reference("Practitioner/clerk".to_string());
+
+    // A balance blocks closing until it is written off
+    let mut account = Account::new();
+    account.set_status(Some("active".to_string()));
+    account.add_balance(AccountBalance::new(Money::with_currency("125.00".parse().unwrap(), "USD".to_string())));
+    let mut lifecycle = AccountLifecycle::new(account);
+    assert_eq!(
+        lifecycle.change_status(AccountStatus::Inactive, clerk.clone(), "2024-05-01T09:00:00Z".parse().unwrap()).unwrap_err(),
+        LifecycleError::OutstandingBalance("$125.00".to_string())
+    );
+    lifecycle.change_billing_status(BillingStatus::Billing, clerk.clone(), "2024-05-01T09:00:00Z".parse().unwrap()).unwrap();
+    assert!(matches!(
+        lifecycle.change_billing_status(BillingStatus::ClosedCompleted, clerk.clone(), "2024-05-01T09:00:00Z".parse().unwrap()),
+        Err(LifecycleError::OutstandingBalance(_))
+    ));
+    lifecycle.change_billing_status(BillingStatus::ClosedBadDebt, clerk.clone(), "2024-05-02T09:00:00Z".parse().unwrap()).unwrap();
+    lifecycle.change_status(AccountStatus::Inactive, clerk.clone(), "2024-05-03T09:00:00Z".parse().unwrap()).unwrap();
+    assert_eq!(lifecycle.history().len(), 3);
+
+    assert_eq!(
+        lifecycle.change_billing_status(BillingStatus::Open, clerk.clone(), "2024-05-04T09:00:00Z".parse().unwrap()).unwrap_err(),
+        LifecycleError::InvalidBillingStatus("closed-baddebt".to_string(), "open".to_string())
+    );
+
+    let mut account = Account::new();
+    account.set_status(Some("entered-in-error".to_string()));
+    let mut voided = AccountLifecycle::new(account);
+    assert_eq!(
+        voided.change_status(AccountStatus::Active, clerk.clone(), "2024-05-01T09:00:00Z".parse().unwrap()).unwrap_err(),
+        LifecycleError::InvalidStatus("entered-in-error".to_string(), "active".to_string())
+    );
+    assert_eq!(
+        voided.change_billing_status(BillingStatus::Open, clerk.clone(), "2024-05-01T09:00:00Z".parse().unwrap()).unwrap_err(),
+        LifecycleError::Locked(AccountStatus::EnteredInError)
+    );
+    assert!(voided.history().is_empty());
+}
@@ -10,6 +10,9 @@ use fhir_resources_rs::identifier::Identifier;
 use fhir_resources_rs::data_types::codeable_concept::CodeableConcept;
 use fhir_resources_rs::data_types::reference::Reference;
 use fhir_resources_rs::data_types::uri::Uri;
+use fhir_resources_rs::data_types::decimal::Decimal;
+use fhir_resources_rs::data_types::date_time::{DateTime, Instant};
+use fhir_resources_rs::data_types::positive_int::{PositiveInt, PositiveIntError};
 use fhir_resources_rs::period::Period;
 
 #[test]
@@ -70,8 +73,8 @@ fn test_account_setters_and_getters() {
     assert_eq!(account.description(), Some("Test description"));
     
     // Test calculated_at
-    account.set_calculated_at(Some("2023-01-01T00:00:00Z".to_string()));
-    assert_eq!(account.calculated_at(), Some("2023-01-01T00:00:00Z"));
+    account.set_calculated_at(Some("2023-01-01T00:00:00Z".parse().unwrap()));
+    assert_eq!(account.calculated_at().map(Instant::as_str), Some("2023-01-01T00:00:00Z"));
 }
 
 #[test]
@@ -80,8 +83,18 @@ fn test_account_coverage() {
     let coverage = AccountCoverage::new(reference.clone());
     assert!(coverage.priority().is_none());
     
-    let coverage_with_priority = AccountCoverage::with_priority(reference, "1".to_string());
-    assert_eq!(coverage_with_priority.priority(), Some("1"));
+    let coverage_with_priority = AccountCoverage::with_priority(reference, PositiveInt::new(1).unwrap());
+    assert_eq!(coverage_with_priority.priority().map(|priority| priority.value()), Some(1));
+
+    // priority is a positiveInt, written as a JSON number
+    assert!(serde_json::to_string(&coverage_with_priority).unwrap().ends_with(r#""priority":1}"#));
+    let parsed: AccountCoverage = serde_json::from_str(r#"{"coverage":{},"priority":2}"#).unwrap();
+    assert_eq!(parsed.priority(), Some("2".parse().unwrap()));
+    assert!(serde_json::from_str::<AccountCoverage>(r#"{"coverage":{},"priority":0}"#).is_err());
+    assert!(serde_json::from_str::<AccountCoverage>(r#"{"coverage":{},"priority":"1"}"#).is_err());
+    assert_eq!("0".parse::<PositiveInt>(), Err(PositiveIntError::InvalidFormat("0".to_string())));
+    assert_eq!("+7".parse::<PositiveInt>().unwrap().value(), 7);
+    assert_eq!(PositiveInt::new(1 << 31), Err(PositiveIntError::OutOfRange(1 << 31)));
 }
 
 #[test]
@@ -96,6 +109,54 @@ fn test_account_guarantor() {
     assert_eq!(guarantor.on_hold(), Some(true));
 }
 
+#[test]
+fn test_payers_on() {
+    let coverage = |id: &str, priority: u32| {
+        AccountCoverage::with_priority(Reference::with_reference(format!("Coverage/{}", id)), PositiveInt::new(priority).unwrap())
+    };
+    let guarantor = |id: &str, on_hold: Option<bool>, start: Option<&str>, end: Option<&str>| {
+        let mut guarantor = AccountGuarantor::new(Reference::with_reference(format!("RelatedPerson/{}", id)));
+        guarantor.set_on_hold(on_hold);
+        if start.is_some() || end.is_some() {
+            guarantor.set_period(Some(Period::new(start.map(|start| start.parse().unwrap()), end.map(|end| end.parse().unwrap()))));
+        }
+        guarantor
+    };
+    let mut account = Account::new();
+    account.set_coverage(vec![coverage("secondary", 2), coverage("primary", 1)]);
+    account.set_guarantor(vec![
+        guarantor("mother", None, Some("2024-01-01"), None),
+        guarantor("held", Some(true), None, None),
+        guarantor("former", Some(false), None, Some("2024-03-31T23:59:59Z")),
+        guarantor("father", Some(false), None, None),
+    ]);
+
+    let payers = account.payers_on(&"2024-03-31".parse().unwrap()).unwrap();
+    let coverages: Vec<&str> = payers.coverages().iter().filter_map(|coverage| coverage.coverage().reference()).collect();
+    assert_eq!(coverages, ["Coverage/primary", "Coverage/secondary"]);
+    let guarantors: Vec<&str> = payers.guarantors().iter().filter_map(|guarantor| guarantor.party().reference()).collect();
+    assert_eq!(guarantors, ["RelatedPerson/mother", "RelatedPerson/former", "RelatedPerson/father"]);
+
+    let later = account.payers_on(&"2024-04-01".parse().unwrap()).unwrap();
+    assert_eq!(later.guarantors().len(), 2);
+    let earlier = account.payers_on(&"2023-12-31".parse().unwrap()).unwrap();
+    assert_eq!(earlier.guarantors().len(), 2);
+
+    // Ambiguous priorities
+    account.add_coverage(coverage("other", 2));
+    assert_eq!(
+        account.payers_on(&"2024-03-31".parse().unwrap()),
+        Err(PayerError::DuplicatePriority(PositiveInt::new(2).unwrap(), "Coverage/secondary".to_string(), "Coverage/other".to_string()))
+    );
+    account.set_coverage(vec![coverage("primary", 1), AccountCoverage::new(Reference::with_reference("Coverage/unranked".to_string()))]);
+    assert_eq!(
+        account.payers_on(&"2024-03-31".parse().unwrap()),
+        Err(PayerError::MissingPriority("Coverage/unranked".to_string()))
+    );
+    account.set_coverage(vec![AccountCoverage::new(Reference::with_reference("Coverage/only".to_string()))]);
+    assert_eq!(account.payers_on(&"2024-03-31".parse().unwrap()).unwrap().coverages().len(), 1);
+}
+
 #[test]
 fn test_account_diagnosis() {
     let reference = Reference::new();
@@ -108,11 +169,11 @@ fn test_account_diagnosis() {
     
     let mut diagnosis = diagnosis;
     diagnosis.set_sequence(Some("1".to_string()));
-    diagnosis.set_date_of_diagnosis(Some("2023-01-01T00:00:00Z".to_string()));
+    diagnosis.set_date_of_diagnosis(Some("2023-01-01T00:00:00Z".parse().unwrap()));
     diagnosis.set_on_admission(Some(true));
     
     assert_eq!(diagnosis.sequence(), Some("1"));
-    assert_eq!(diagnosis.date_of_diagnosis(), Some("2023-01-01T00:00:00Z"));
+    assert_eq!(diagnosis.date_of_diagnosis().map(DateTime::as_str), Some("2023-01-01T00:00:00Z"));
     assert_eq!(diagnosis.on_admission(), Some(true));
 }
 
@@ -147,12 +208,12 @@ fn test_account_related_account() {
 
 #[test]
 fn test_account_balance() {
-    let money = Money::with_value(100.0);
+    let money = Money::with_value("100.0".parse().unwrap());
     let balance = AccountBalance::new(money);
     assert!(balance.aggregate().is_none());
     assert!(balance.term().is_none());
     assert!(balance.estimate().is_none());
-    assert_eq!(balance.amount().value(), Some(100.0));
+    assert_eq!(balance.amount().value().map(Decimal::as_str), Some("100.0"));
     
     let mut balance = balance;
     balance.set_estimate(Some(true));
@@ -165,18 +226,18 @@ fn test_money() {
     assert!(money.value().is_none());
     assert!(money.currency().is_none());
     
-    let money_with_value = Money::with_value(100.0);
-    assert_eq!(money_with_value.value(), Some(100.0));
+    let money_with_value = Money::with_value("100.0".parse().unwrap());
+    assert_eq!(money_with_value.value().map(Decimal::as_str), Some("100.0"));
     assert!(money_with_value.currency().is_none());
     
-    let money_with_currency = Money::with_currency(100.0, "USD".to_string());
-    assert_eq!(money_with_currency.value(), Some(100.0));
+    let money_with_currency = Money::with_currency("100.0".parse().unwrap(), "USD".to_string());
+    assert_eq!(money_with_currency.value().map(Decimal::as_str), Some("100.0"));
     assert_eq!(money_with_currency.currency(), Some("USD"));
     
     let mut money = Money::new();
-    money.set_value(Some(50.0));
+    money.set_value(Some("50.0".parse().unwrap()));
     money.set_currency(Some("EUR".to_string()));
-    assert_eq!(money.value(), Some(50.0));
+    assert_eq!(money.value().map(Decimal::as_str), Some("50.0"));
     assert_eq!(money.currency(), Some("EUR"));
 }
 
@@ -217,11 +278,11 @@ fn test_account_complex_json() {
     
     // Add coverage
     let coverage_reference = Reference::new();
-    let coverage = AccountCoverage::with_priority(coverage_reference, "1".to_string());
+    let coverage = AccountCoverage::with_priority(coverage_reference, PositiveInt::new(1).unwrap());
     account.add_coverage(coverage);
     
     // Add balance
-    let money = Money::with_currency(1000.0, "USD".to_string());
+    let money = Money::with_currency("1000.0".parse().unwrap(), "USD".to_string());
     let balance = AccountBalance::new(money);
     account.add_balance(balance);
     
@@ -240,5 +301,5 @@ fn test_account_complex_json() {
     assert_eq!(deserialized.coverage().len(), 1);
     assert_eq!(deserialized.balance().len(), 1);
     assert_eq!(deserialized.balance()[0].amount().currency(), Some("USD"));
-    assert_eq!(deserialized.balance()[0].amount().value(), Some(1000.0));
+    assert_eq!(deserialized.balance()[0].amount().value().map(Decimal::as_str), Some("1000.0"));
 } 
\ No newline at end of file
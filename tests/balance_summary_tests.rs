@@ -0,0 +1,158 @@
+use fhir_resources_rs::account::Account;
+use fhir_resources_rs::account_balance::AccountBalance;
+use fhir_resources_rs::balance_summary::{AgingReport, BalanceAggregate, BalanceError, BalanceSummary, BalanceTerm};
+use fhir_resources_rs::data_types::codeable_concept::{CodeableConcept, Coding};
+use fhir_resources_rs::money::{Money, MoneyError};
+
+#[test]
+fn test_totals_by_aggregate_and_term() {
+    let mut patient_current = AccountBalance::new(Money::with_currency("20.00".parse().unwrap(), "USD".to_string()));
+    patient_current.set_aggregate(Some(CodeableConcept::with_coding(Coding::with_code("patient".to_string()))));
+    patient_current.set_term(Some(CodeableConcept::with_coding(Coding::with_code("current".to_string()))));
+    let mut insurance_current = AccountBalance::new(Money::with_currency("80.00".parse().unwrap(), "USD".to_string()));
+    insurance_current.set_aggregate(Some(CodeableConcept::with_coding(Coding::with_code("insurance".to_string()))));
+    insurance_current.set_term(Some(CodeableConcept::with_coding(Coding::with_code("current".to_string()))));
+    let mut total_current = AccountBalance::new(Money::with_currency("100.00".parse().unwrap(), "USD".to_string()));
+    total_current.set_aggregate(Some(CodeableConcept::with_coding(Coding::with_system_and_code(
+        "http://hl7.org/fhir/account-aggregate".to_string(),
+        "total".to_string(),
+    ))));
+    total_current.set_term(Some(CodeableConcept::with_coding(Coding::with_system_and_code(
+        "http://hl7.org/fhir/account-balance-term".to_string(),
+        "current".to_string(),
+    ))));
+    // Two patient balances in the same term add up
+    let mut patient_30 = AccountBalance::new(Money::with_currency("15.00".parse().unwrap(), "USD".to_string()));
+    patient_30.set_aggregate(Some(CodeableConcept::with_coding(Coding::with_code("patient".to_string()))));
+    patient_30.set_term(Some(CodeableConcept::with_coding(Coding::with_code("30".to_string()))));
+    let mut patient_30_more = AccountBalance::new(Money::with_currency("5.50".parse().unwrap(), "USD".to_string()));
+    patient_30_more.set_aggregate(Some(CodeableConcept::with_coding(Coding::with_code("patient".to_string()))));
+    patient_30_more.set_term(Some(CodeableConcept::with_coding(Coding::with_code("30".to_string()))));
+    let mut insurance_30 = AccountBalance::new(Money::with_currency("40.00".parse().unwrap(), "USD".to_string()));
+    insurance_30.set_aggregate(Some(CodeableConcept::with_coding(Coding::with_code("insurance".to_string()))));
+    insurance_30.set_term(Some(CodeableConcept::with_coding(Coding::with_code("30".to_string()))));
+    let mut estimate = AccountBalance::new(Money::with_currency("25.00".parse().unwrap(), "USD".to_string()));
+    estimate.set_aggregate(Some(CodeableConcept::with_coding(Coding::with_code("insurance".to_string()))));
+    estimate.set_term(Some(CodeableConcept::with_coding(Coding::with_code("current".to_string()))));
+    estimate.set_estimate(Some(true));
+
+    let summary = BalanceSummary::from_balances(&[
+        patient_current,
+        insurance_current,
+        total_current,
+        patient_30,
+        patient_30_more,
+        insurance_30,
+        estimate,
+    ])
+    .unwrap();
+
+    assert_eq!(summary.amount(BalanceAggregate::Patient, Some(BalanceTerm::Days30)).unwrap().format().unwrap(), "$20.50");
+    assert_eq!(summary.by_aggregate(BalanceAggregate::Patient).unwrap().unwrap().format().unwrap(), "$40.50");
+    assert_eq!(summary.by_aggregate(BalanceAggregate::Insurance).unwrap().unwrap().format().unwrap(), "$120.00");
+    assert_eq!(summary.by_term(Some(BalanceTerm::Current)).unwrap().unwrap().format().unwrap(), "$100.00");
+    assert_eq!(summary.by_term(Some(BalanceTerm::Days30)).unwrap().unwrap().format().unwrap(), "$60.50");
+    assert_eq!(summary.by_term(Some(BalanceTerm::Days60)).unwrap(), None);
+
+    assert!(summary.has_estimates());
+    assert_eq!(summary.estimate(BalanceAggregate::Insurance, Some(BalanceTerm::Current)).unwrap().format().unwrap(), "$25.00");
+    assert_eq!(summary.estimate_by_term(Some(BalanceTerm::Current)).unwrap().unwrap().format().unwrap(), "$25.00");
+    assert!(summary.check_totals().is_ok());
+
+    // A balance without an aggregate is the total
+    let unaged = BalanceSummary::from_balances(&[AccountBalance::new(Money::with_currency("12".parse().unwrap(), "USD".to_string()))]).unwrap();
+    assert_eq!(unaged.by_aggregate(BalanceAggregate::Total).unwrap().unwrap().format().unwrap(), "$12.00");
+}
+
+#[test]
+fn test_summary_errors() {
+    let mut patient = AccountBalance::new(Money::with_currency("20.00".parse().unwrap(), "USD".to_string()));
+    patient.set_aggregate(Some(CodeableConcept::with_coding(Coding::with_code("patient".to_string()))));
+    let mut insurance = AccountBalance::new(Money::with_currency("70.00".parse().unwrap(), "USD".to_string()));
+    insurance.set_aggregate(Some(CodeableConcept::with_coding(Coding::with_code("insurance".to_string()))));
+    let mut total = AccountBalance::new(Money::with_currency("100.00".parse().unwrap(), "USD".to_string()));
+    total.set_aggregate(Some(CodeableConcept::with_coding(Coding::with_code("total".to_string()))));
+    let unbalanced = BalanceSummary::from_balances(&[patient.clone(), insurance, total]).unwrap();
+    assert_eq!(
+        unbalanced.check_totals(),
+        Err(BalanceError::Unbalanced("unaged".to_string(), "20.00".to_string(), "70.00".to_string(), "100.00".to_string()))
+    );
+
+    let mut euros = patient.clone();
+    euros.set_amount(Money::with_currency("1".parse().unwrap(), "EUR".to_string()));
+    assert!(matches!(
+        BalanceSummary::from_balances(&[patient.clone(), euros]),
+        Err(BalanceError::Money(MoneyError::CurrencyMismatch(..)))
+    ));
+
+    let mut unknown_term = patient.clone();
+    unknown_term.set_term(Some(CodeableConcept::with_coding(Coding::with_code("45".to_string()))));
+    assert_eq!(BalanceSummary::from_balances(&[unknown_term]), Err(BalanceError::UnknownTerm("45".to_string())));
+
+    let mut unknown_aggregate = patient;
+    unknown_aggregate.set_aggregate(Some(CodeableConcept::with_text("employer".to_string())));
+    assert_eq!(
+        BalanceSummary::from_balances(&[unknown_aggregate]),
+        Err(BalanceError::UnknownAggregate("employer".to_string()))
+    );
+}
+
+#[test]
+fn test_aging_report() {
+    let mut a1_current = AccountBalance::new(Money::with_currency("100.00".parse().unwrap(), "USD".to_string()));
+    a1_current.set_term(Some(CodeableConcept::with_coding(Coding::with_code("current".to_string()))));
+    let mut a1_60 = AccountBalance::new(Money::with_currency("40.00".parse().unwrap(), "USD".to_string()));
+    a1_60.set_term(Some(CodeableConcept::with_coding(Coding::with_code("60".to_string()))));
+    let mut a1_estimate = AccountBalance::new(Money::with_currency("10.00".parse().unwrap(), "USD".to_string()));
+    a1_estimate.set_term(Some(CodeableConcept::with_coding(Coding::with_code("current".to_string()))));
+    a1_estimate.set_estimate(Some(true));
+    let mut a1 = Account::new();
+    a1.set_id(Some("a1".to_string()));
+    a1.set_balance(vec![a1_current, a1_60, a1_estimate]);
+
+    // Patient and insurance parts are used when no total is given for the term
+    let mut a2_patient = AccountBalance::new(Money::with_currency("5.00".parse().unwrap(), "USD".to_string()));
+    a2_patient.set_aggregate(Some(CodeableConcept::with_coding(Coding::with_code("patient".to_string()))));
+    a2_patient.set_term(Some(CodeableConcept::with_coding(Coding::with_code("60".to_string()))));
+    let mut a2_insurance = AccountBalance::new(Money::with_currency("15.00".parse().unwrap(), "USD".to_string()));
+    a2_insurance.set_aggregate(Some(CodeableConcept::with_coding(Coding::with_code("insurance".to_string()))));
+    a2_insurance.set_term(Some(CodeableConcept::with_coding(Coding::with_code("60".to_string()))));
+    let mut a2_120 = AccountBalance::new(Money::with_currency("7.25".parse().unwrap(), "USD".to_string()));
+    a2_120.set_aggregate(Some(CodeableConcept::with_coding(Coding::with_code("total".to_string()))));
+    a2_120.set_term(Some(CodeableConcept::with_coding(Coding::with_code("120".to_string()))));
+    let mut a2_unaged = AccountBalance::new(Money::with_currency("999.00".parse().unwrap(), "USD".to_string()));
+    a2_unaged.set_aggregate(Some(CodeableConcept::with_coding(Coding::with_code("total".to_string()))));
+    let mut a2 = Account::new();
+    a2.set_id(Some("a2".to_string()));
+    a2.set_balance(vec![a2_patient, a2_insurance, a2_120, a2_unaged]);
+
+    let report = AgingReport::new(&[a1, a2]).unwrap();
+    assert_eq!(report.rows().len(), 2);
+    let a2 = &report.rows()[1];
+    assert_eq!(a2.account(), Some("a2"));
+    assert_eq!(a2.amount(BalanceTerm::Days60).unwrap().format().unwrap(), "$20.00");
+    assert_eq!(a2.amount(BalanceTerm::Current), None);
+    assert_eq!(a2.total().unwrap().unwrap().format().unwrap(), "$27.25");
+
+    let totals = report.totals();
+    assert_eq!(totals.account(), None);
+    let by_term: Vec<Option<String>> =
+        BalanceTerm::ALL.iter().map(|term| totals.amount(*term).map(|money| money.format().unwrap())).collect();
+    assert_eq!(
+        by_term,
+        [Some("$100.00".to_string()), None, Some("$60.00".to_string()), None, Some("$7.25".to_string())]
+    );
+    assert_eq!(totals.estimate(BalanceTerm::Current).unwrap().format().unwrap(), "$10.00");
+    assert_eq!(totals.total().unwrap().unwrap().format().unwrap(), "$167.25");
+
+    let mut patient = AccountBalance::new(Money::with_currency("1.00".parse().unwrap(), "USD".to_string()));
+    patient.set_aggregate(Some(CodeableConcept::with_coding(Coding::with_code("patient".to_string()))));
+    patient.set_term(Some(CodeableConcept::with_coding(Coding::with_code("30".to_string()))));
+    let mut total = AccountBalance::new(Money::with_currency("2.00".parse().unwrap(), "USD".to_string()));
+    total.set_aggregate(Some(CodeableConcept::with_coding(Coding::with_code("total".to_string()))));
+    total.set_term(Some(CodeableConcept::with_coding(Coding::with_code("30".to_string()))));
+    let mut unbalanced = Account::new();
+    unbalanced.set_id(Some("a3".to_string()));
+    unbalanced.set_balance(vec![patient, total]);
+    assert!(matches!(AgingReport::new(&[unbalanced]), Err(BalanceError::Unbalanced(..))));
+}
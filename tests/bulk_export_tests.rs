@@ -0,0 +1,161 @@
+use std::fs;
+
+use fhir_resources_rs::account::Account;
+use fhir_resources_rs::bulk_export::{
+    BulkExportError, BulkExporter, ExportJob, ExportLevel, ExportManifest, ExportRequest, ExportStatus,
+};
+use fhir_resources_rs::data_types::reference::Reference;
+use fhir_resources_rs::patient::Patient;
+use fhir_resources_rs::repository::{MemoryRepository, Repository};
+use fhir_resources_rs::resource::AnyResource;
+use fhir_resources_rs::search::SearchEngine;
+
+#[test]
+fn test_parse_request() {
+    let request = ExportRequest::parse(
+        ExportLevel::System,
+        "_type=Patient,Account&_since=2024-01-01T00:00:00Z&_typeFilter=Patient%3Fgender%3Dmale%2Cfemale,Account%3Fstatus%3Dactive",
+    )
+    .unwrap();
+    assert_eq!(request.types(), ["Patient".to_string(), "Account".to_string()]);
+    assert_eq!(request.since().map(|since| since.as_str()), Some("2024-01-01T00:00:00Z"));
+    let filters: Vec<(&str, &str)> = request
+        .type_filters()
+        .iter()
+        .map(|(resource_type, query)| (resource_type.as_str(), query.params()[0].value()))
+        .collect();
+    assert_eq!(filters, [("Patient", "male,female"), ("Account", "active")]);
+    assert!(request.url().starts_with("$export?_type="));
+
+    assert!(matches!(
+        ExportRequest::parse(ExportLevel::Patient, "_outputFormat=text/csv"),
+        Err(BulkExportError::UnsupportedFormat(_))
+    ));
+    assert!(matches!(
+        ExportRequest::parse(ExportLevel::Patient, "_typeFilter=gender%3Dmale"),
+        Err(BulkExportError::InvalidParameter(_))
+    ));
+    let invalid = ExportRequest::parse(ExportLevel::Patient, "_since=garbage").unwrap_err();
+    assert!(matches!(invalid, BulkExportError::InvalidParameter(_)));
+    assert_eq!(invalid.status(), 400);
+}
+
+#[test]
+fn test_collect_levels_and_filters() {
+    // Accounts 3 and 5 belong to Patient/1 and Account/4 to Patient/2
+    let mut repository = MemoryRepository::new();
+    for gender in ["female", "male"] {
+        let mut patient = Patient::new();
+        patient.set_gender(Some(gender.to_string()));
+        repository.create(patient.into()).unwrap();
+    }
+    for subject in ["Patient/1", "Patient/2", "Patient/1"] {
+        let mut account = Account::new();
+        account.set_subject(vec![Reference::with_reference(subject.to_string())]);
+        repository.create(account.into()).unwrap();
+    }
+    let engine = SearchEngine::new();
+    let mut exporter = BulkExporter::new();
+    exporter.add_group("g1", vec!["2".to_string(), "9".to_string()]);
+
+    let system = exporter.collect(&engine, &repository, &ExportRequest::new(ExportLevel::System)).unwrap();
+    let ids: Vec<&str> = system.resources("Patient").iter().filter_map(AnyResource::id).collect();
+    assert_eq!(ids, ["1", "2"]);
+    let ids: Vec<&str> = system.resources("Account").iter().filter_map(AnyResource::id).collect();
+    assert_eq!(ids, ["3", "4", "5"]);
+
+    let group = exporter.collect(&engine, &repository, &ExportRequest::new(ExportLevel::Group("g1".to_string()))).unwrap();
+    let ids: Vec<&str> = group.resources("Patient").iter().filter_map(AnyResource::id).collect();
+    assert_eq!(ids, ["2"]);
+    let ids: Vec<&str> = group.resources("Account").iter().filter_map(AnyResource::id).collect();
+    assert_eq!(ids, ["4"]);
+
+    let filtered = ExportRequest::parse(ExportLevel::Patient, "_type=Patient&_typeFilter=Patient%3Fgender%3Dmale").unwrap();
+    let patients = exporter.collect(&engine, &repository, &filtered).unwrap();
+    let ids: Vec<&str> = patients.resources("Patient").iter().filter_map(AnyResource::id).collect();
+    assert_eq!(ids, ["2"]);
+    assert!(patients.resources("Account").is_empty());
+
+    let mut since = ExportRequest::new(ExportLevel::System);
+    since.set_since(Some("2999-01-01T00:00:00Z".parse().unwrap()));
+    assert!(exporter.collect(&engine, &repository, &since).unwrap().resources("Patient").is_empty());
+    // The same wall-clock time as the latest change is earlier at +14:00 and later at -12:00
+    let latest = repository.read("Account", "5").unwrap().meta().unwrap().last_updated().unwrap().to_string();
+    since.set_since(Some(latest.replace('Z', "+14:00").parse().unwrap()));
+    assert_eq!(exporter.collect(&engine, &repository, &since).unwrap().resources("Account").len(), 3);
+    since.set_since(Some(latest.replace('Z', "-12:00").parse().unwrap()));
+    assert!(exporter.collect(&engine, &repository, &since).unwrap().resources("Account").is_empty());
+
+    let missing = ExportRequest::new(ExportLevel::Group("g2".to_string()));
+    assert_eq!(exporter.collect(&engine, &repository, &missing).unwrap_err().status(), 404);
+    let unknown = ExportRequest::parse(ExportLevel::System, "_type=Organization").unwrap();
+    assert!(matches!(
+        exporter.collect(&engine, &repository, &unknown),
+        Err(BulkExportError::UnsupportedType(code)) if code == "Organization"
+    ));
+}
+
+#[test]
+fn test_export_writes_ndjson() {
+    let mut repository = MemoryRepository::new();
+    repository.create(Patient::new().into()).unwrap();
+    repository.create(Patient::new().into()).unwrap();
+    for subject in ["Patient/1", "Patient/2", "Patient/1"] {
+        let mut account = Account::new();
+        account.set_subject(vec![Reference::with_reference(subject.to_string())]);
+        repository.create(account.into()).unwrap();
+    }
+    let directory = std::env::temp_dir().join(format!("fhir-export-tests-{}-sync", std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    let manifest = BulkExporter::new()
+        .export(
+            &SearchEngine::new(),
+            &repository,
+            &ExportRequest::new(ExportLevel::System),
+            &directory,
+            Some("http://localhost/files"),
+        )
+        .unwrap();
+    assert_eq!(manifest.request(), "$export");
+    assert!(!manifest.requires_access_token());
+    let outputs: Vec<(&str, &str, Option<u64>)> = manifest
+        .output()
+        .iter()
+        .map(|output| (output.resource_type(), output.url(), output.count()))
+        .collect();
+    assert_eq!(
+        outputs,
+        [
+            ("Patient", "http://localhost/files/Patient.ndjson", Some(2)),
+            ("Account", "http://localhost/files/Account.ndjson", Some(3)),
+        ]
+    );
+    let accounts = fs::read_to_string(directory.join("Account.ndjson")).unwrap();
+    assert_eq!(accounts.lines().count(), 3);
+    for line in accounts.lines() {
+        assert!(AnyResource::from_json(line).unwrap().downcast::<Account>().is_some());
+    }
+    assert_eq!(ExportManifest::from_json(&manifest.to_json()).unwrap(), manifest);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_export_job() {
+    let mut repository = MemoryRepository::new();
+    repository.create(Patient::new().into()).unwrap();
+    let mut account = Account::new();
+    account.set_subject(vec![Reference::with_reference("Patient/1".to_string())]);
+    repository.create(account.into()).unwrap();
+    let directory = std::env::temp_dir().join(format!("fhir-export-tests-{}-job", std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    let data = BulkExporter::new()
+        .collect(&SearchEngine::new(), &repository, &ExportRequest::new(ExportLevel::Patient))
+        .unwrap();
+    let job = ExportJob::start(data, directory.clone(), None);
+    let ExportStatus::Complete(manifest) = job.wait() else { panic!("expected a complete export") };
+    assert_eq!(manifest.output().len(), 2);
+    assert!(directory.join("Patient.ndjson").exists());
+
+    job.cancel().unwrap();
+    assert!(!directory.exists());
+}
@@ -0,0 +1,86 @@
+use fhir_resources_rs::capability_statement::{
+    CapabilityStatement, CapabilityStatementBuilder, OperationRegistration, SystemInteraction, TypeInteraction,
+};
+use fhir_resources_rs::search::params;
+
+#[test]
+fn test_interactions() {
+    let statement = CapabilityStatementBuilder::new()
+        .software("Test Server", Some("1.0"))
+        .resource("Patient", TypeInteraction::ALL)
+        .resource("Account", &[TypeInteraction::Read, TypeInteraction::SearchType])
+        .system_interaction(SystemInteraction::Transaction)
+        .build();
+    assert_eq!(statement.resource_type(), "CapabilityStatement");
+    assert_eq!(statement.kind(), "instance");
+    assert_eq!(statement.software().unwrap().name(), "Test Server");
+
+    let patient = statement.resource("Patient").unwrap();
+    assert!(TypeInteraction::ALL.iter().all(|interaction| patient.supports(*interaction)));
+    assert_eq!(patient.versioning(), Some("versioned"));
+    assert_eq!(patient.update_create(), Some(true));
+
+    let account = statement.resource("Account").unwrap();
+    assert!(account.supports(TypeInteraction::Read));
+    assert!(!account.supports(TypeInteraction::Delete));
+    assert_eq!(account.versioning(), Some("no-version"));
+    assert!(statement.resource("Observation").is_none());
+
+    assert!(statement.rest()[0].supports(SystemInteraction::Transaction));
+    assert!(!statement.rest()[0].supports(SystemInteraction::Batch));
+}
+
+#[test]
+fn test_search_params_match_definitions() {
+    let statement = CapabilityStatementBuilder::new()
+        .resource("Patient", TypeInteraction::ALL)
+        .resource("Account", &[TypeInteraction::SearchType])
+        .build();
+    let patient = statement.resource("Patient").unwrap();
+    for definition in params::definitions_for("Patient") {
+        let param = patient.search_param().iter().find(|param| param.name() == definition.name).unwrap();
+        assert_eq!(param.param_type(), definition.param_type.code());
+    }
+    // Result parameters are described in the documentation instead
+    assert!(!patient.search_param().iter().any(|param| param.name() == "_count"));
+    assert!(statement.rest()[0].documentation().unwrap().contains("_count, _offset"));
+
+    assert!(patient.search_include().contains(&"Patient:organization".to_string()));
+    assert!(patient.search_rev_include().contains(&"Account:patient".to_string()));
+    assert!(patient.search_rev_include().contains(&"Account:subject".to_string()));
+    assert!(statement.resource("Account").unwrap().search_rev_include().is_empty());
+}
+
+#[test]
+fn test_operations() {
+    let statement = CapabilityStatementBuilder::new()
+        .resource("Patient", TypeInteraction::ALL)
+        .resource("Account", &[TypeInteraction::Read])
+        .operation(OperationRegistration::system("export", "http://hl7.org/fhir/OperationDefinition/export"))
+        .operation(OperationRegistration::on_types(
+            "everything",
+            "http://hl7.org/fhir/OperationDefinition/Patient-everything",
+            &["Patient"],
+        ))
+        .build();
+    let system: Vec<&str> = statement.rest()[0].operation().iter().map(|operation| operation.name()).collect();
+    assert_eq!(system, vec!["export"]);
+    let patient = statement.resource("Patient").unwrap();
+    assert_eq!(patient.operation()[0].name(), "everything");
+    assert!(statement.resource("Account").unwrap().operation().is_empty());
+}
+
+#[test]
+fn test_json_round_trip() {
+    let statement = CapabilityStatementBuilder::new()
+        .software("Test Server", Some("1.0"))
+        .resource("Patient", TypeInteraction::ALL)
+        .system_interaction(SystemInteraction::Transaction)
+        .operation(OperationRegistration::system("export", "http://hl7.org/fhir/OperationDefinition/export"))
+        .build();
+    let json = statement.to_json();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["rest"][0]["resource"][0]["type"], "Patient");
+    assert_eq!(value["rest"][0]["resource"][0]["interaction"][0]["code"], "read");
+    assert_eq!(CapabilityStatement::from_json(&json).unwrap(), statement);
+}
@@ -0,0 +1,159 @@
+use std::sync::mpsc;
+use std::thread;
+
+use fhir_resources_rs::account::Account;
+use fhir_resources_rs::bundle::{Bundle, BundleEntry};
+use fhir_resources_rs::client::{ClientError, FhirClient};
+use fhir_resources_rs::meta::Meta;
+use fhir_resources_rs::patient::Patient;
+use fhir_resources_rs::repository::MemoryRepository;
+use fhir_resources_rs::server::FhirServer;
+
+/// A recorded request: method, URL, If-Match header and body.
+type Recorded = (String, String, Option<String>, String);
+
+/// Starts a mock server answering every request with the same status and body.
+fn mock_server(status: u16, body: &'static str) -> (String, mpsc::Receiver<Recorded>) {
+    let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", server.server_addr().to_ip().unwrap());
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for mut request in server.incoming_requests() {
+            let if_match = request
+                .headers()
+                .iter()
+                .find(|header| header.field.equiv("If-Match"))
+                .map(|header| header.value.to_string());
+            let mut body_in = String::new();
+            request.as_reader().read_to_string(&mut body_in).unwrap();
+            let recorded = (request.method().to_string(), request.url().to_string(), if_match, body_in);
+            let response = tiny_http::Response::from_string(body).with_status_code(status);
+            request.respond(response).unwrap();
+            if sender.send(recorded).is_err() {
+                break;
+            }
+        }
+    });
+    (base_url, receiver)
+}
+
+#[test]
+fn test_create_read_and_delete() {
+    let server = FhirServer::new(MemoryRepository::new()).start("127.0.0.1:0").unwrap();
+    let client = FhirClient::new(&server.base_url());
+
+    let mut account = Account::new();
+    account.set_name(Some("Main".to_string()));
+    account.set_status(Some("active".to_string()));
+    let created = client.create(&account).unwrap();
+    assert_eq!(created.id(), Some("1"));
+    let read: Account = client.read("1").unwrap();
+    assert_eq!(read.name(), Some("Main"));
+    let first: Account = client.vread("1", "1").unwrap();
+    assert_eq!(first, read);
+
+    client.delete::<Account>("1").unwrap();
+    let error = client.read::<Account>("1").unwrap_err();
+    assert_eq!(error.status(), Some(410));
+    assert_eq!(error.outcome().unwrap().issue()[0].code(), "deleted");
+}
+
+#[test]
+fn test_read_with_wrong_type() {
+    let server = FhirServer::new(MemoryRepository::new()).start("127.0.0.1:0").unwrap();
+    let client = FhirClient::new(&server.base_url());
+    client.create(&Patient::new()).unwrap();
+    assert!(matches!(client.read::<Account>("1"), Err(ClientError::Status(404, _))));
+}
+
+#[test]
+fn test_update_sends_if_match() {
+    let (base_url, requests) = mock_server(200, r#"{"resourceType":"Patient","id":"p1","meta":{"versionId":"4"}}"#);
+    let client = FhirClient::new(&base_url);
+    let mut patient = Patient::new();
+    patient.set_id(Some("p1".to_string()));
+    let mut meta = Meta::new();
+    meta.set_version_id(Some("3".to_string()));
+    patient.set_meta(Some(meta));
+
+    let updated = client.update(&patient).unwrap();
+    assert_eq!(updated.meta().unwrap().version_id(), Some("4"));
+    let (method, url, if_match, _) = requests.recv().unwrap();
+    assert_eq!(method, "PUT");
+    assert_eq!(url, "/Patient/p1");
+    assert_eq!(if_match.as_deref(), Some("W/\"3\""));
+
+    assert!(matches!(client.update(&Patient::new()), Err(ClientError::MissingId)));
+}
+
+#[test]
+fn test_absent_elements_are_omitted() {
+    let mut patient = Patient::new();
+    patient.set_gender(Some("female".to_string()));
+
+    // The body the client sends
+    let (base_url, requests) = mock_server(201, r#"{"resourceType":"Patient","id":"1"}"#);
+    FhirClient::new(&base_url).create(&patient).unwrap();
+    let (_, _, _, sent) = requests.recv().unwrap();
+    assert_eq!(sent, r#"{"resourceType":"Patient","gender":"female"}"#);
+
+    // The resources and Bundles the server returns
+    let server = FhirServer::new(MemoryRepository::new()).start("127.0.0.1:0").unwrap();
+    FhirClient::new(&server.base_url()).create(&patient).unwrap();
+    for path in ["/Patient/1", "/Patient?gender=female"] {
+        let body = ureq::get(&format!("{}{}", server.base_url(), path)).call().unwrap().into_string().unwrap();
+        assert!(!body.contains("null") && !body.contains("[]"), "{}", body);
+    }
+}
+
+#[test]
+fn test_search_and_paging() {
+    let server = FhirServer::new(MemoryRepository::new()).start("127.0.0.1:0").unwrap();
+    let client = FhirClient::new(&server.base_url());
+    for index in 0..5 {
+        let mut account = Account::new();
+        account.set_name(Some(format!("Account {}", index)));
+        account.set_status(Some("active".to_string()));
+        client.create(&account).unwrap();
+    }
+    let mut closed = Account::new();
+    closed.set_status(Some("inactive".to_string()));
+    client.create(&closed).unwrap();
+
+    let page = client.search::<Account>(&[("status", "active"), ("_count", "2")]).unwrap();
+    assert_eq!(page.total(), Some(5));
+    assert_eq!(page.resources().len(), 2);
+    let second = client.next_page(&page).unwrap().unwrap();
+    assert_eq!(second.resources()[0].name(), Some("Account 2"));
+
+    let all = client.search_all::<Account>(&[("status", "active"), ("_count", "2")]).unwrap();
+    let names: Vec<&str> = all.iter().filter_map(Account::name).collect();
+    assert_eq!(names, vec!["Account 0", "Account 1", "Account 2", "Account 3", "Account 4"]);
+
+    let error = client.search::<Account>(&[("shoe-size", "9")]).unwrap_err();
+    assert_eq!(error.status(), Some(400));
+}
+
+#[test]
+fn test_transaction() {
+    let (base_url, requests) = mock_server(
+        200,
+        r#"{"resourceType":"Bundle","type":"transaction-response","entry":[{"response":{"status":"201 Created","location":"Patient/1/_history/1"}}]}"#,
+    );
+    let client = FhirClient::new(&base_url);
+    let mut bundle = Bundle::new("transaction".to_string());
+    bundle.add_entry(BundleEntry::with_resource(None, Patient::new().into()));
+
+    let response = client.transaction(&bundle).unwrap();
+    assert_eq!(response.bundle_type(), "transaction-response");
+    assert_eq!(response.entry()[0].response().unwrap().status(), "201 Created");
+    let (method, url, _, body) = requests.recv().unwrap();
+    assert_eq!((method.as_str(), url.as_str()), ("POST", "/"));
+    assert_eq!(Bundle::from_json(&body).unwrap(), bundle);
+}
+
+#[test]
+fn test_transport_error() {
+    let client = FhirClient::new("http://127.0.0.1:1");
+    assert!(matches!(client.read::<Patient>("1"), Err(ClientError::Transport(_))));
+}
@@ -0,0 +1,84 @@
+use fhir_resources_rs::account::Account;
+use fhir_resources_rs::compartment::{self, CompartmentDefinition, CompartmentError, EverythingParams};
+use fhir_resources_rs::data_types::reference::Reference;
+use fhir_resources_rs::patient::Patient;
+use fhir_resources_rs::patient_link::PatientLink;
+use fhir_resources_rs::repository::{MemoryRepository, Repository};
+use fhir_resources_rs::resource::AnyResource;
+use fhir_resources_rs::search::{SearchEngine, SearchQuery};
+
+#[test]
+fn test_patient_compartment_definition() {
+    let definition = CompartmentDefinition::patient();
+    assert_eq!(definition.code(), "Patient");
+    assert_eq!(definition.params("Account"), ["subject".to_string()]);
+    assert!(definition.contains("Patient"));
+    assert!(!definition.contains("Organization"));
+
+    let json = definition.to_json();
+    assert!(json.contains(r#""resourceType":"CompartmentDefinition""#));
+    assert_eq!(CompartmentDefinition::from_json(&json).unwrap(), definition);
+}
+
+#[test]
+fn test_everything() {
+    // Patient/3 links to Patient/1, and Accounts 4 and 6 are Patient/1's
+    let mut repository = MemoryRepository::new();
+    repository.create(Patient::new().into()).unwrap();
+    repository.create(Patient::new().into()).unwrap();
+    let mut linked = Patient::new();
+    linked.add_link(PatientLink::new(Reference::with_reference("Patient/1".to_string()), "seealso".to_string()));
+    repository.create(linked.into()).unwrap();
+    for subject in ["Patient/1", "Patient/2", "Patient/1"] {
+        let mut account = Account::new();
+        account.set_subject(vec![Reference::with_reference(subject.to_string())]);
+        repository.create(account.into()).unwrap();
+    }
+    let mut engine = SearchEngine::new();
+    engine.set_base_url(Some("http://localhost/fhir".to_string()));
+
+    let bundle = compartment::everything(&engine, &repository, "1", &EverythingParams::new()).unwrap();
+    assert_eq!(bundle.total(), Some(4));
+    let references: Vec<String> =
+        bundle.entry().iter().filter_map(|entry| entry.resource().and_then(AnyResource::reference)).collect();
+    assert_eq!(references, ["Patient/1", "Account/4", "Account/6", "Patient/3"]);
+    assert_eq!(bundle.entry()[0].full_url(), Some("http://localhost/fhir/Patient/1"));
+
+    let params = EverythingParams::parse(&SearchQuery::parse("_type=Account")).unwrap();
+    let bundle = compartment::everything(&engine, &repository, "1", &params).unwrap();
+    let references: Vec<String> =
+        bundle.entry().iter().filter_map(|entry| entry.resource().and_then(AnyResource::reference)).collect();
+    assert_eq!(references, ["Account/4", "Account/6"]);
+
+    let mut params = EverythingParams::new();
+    params.set_since(Some("2999-01-01T00:00:00Z".parse().unwrap()));
+    let bundle = compartment::everything(&engine, &repository, "1", &params).unwrap();
+    assert!(bundle.entry().is_empty());
+
+    // The same wall-clock time as the patient's lastUpdated is earlier at +14:00 and later at -12:00
+    let last_updated = repository.read("Patient", "1").unwrap().meta().unwrap().last_updated().unwrap().to_string();
+    params.set_since(Some(last_updated.replace('Z', "+14:00").parse().unwrap()));
+    let bundle = compartment::everything(&engine, &repository, "1", &params).unwrap();
+    assert_eq!(bundle.entry()[0].resource().unwrap().reference().as_deref(), Some("Patient/1"));
+    let query = SearchQuery::parse(&format!("_since={}", last_updated.replace('Z', "-12:00")));
+    let bundle = compartment::everything(&engine, &repository, "1", &EverythingParams::parse(&query).unwrap()).unwrap();
+    assert!(bundle.entry().iter().all(|entry| entry.resource().unwrap().id() != Some("1")));
+}
+
+#[test]
+fn test_everything_errors() {
+    let mut repository = MemoryRepository::new();
+    repository.create(Patient::new().into()).unwrap();
+    let engine = SearchEngine::new();
+    let missing = compartment::everything(&engine, &repository, "9", &EverythingParams::new()).unwrap_err();
+    assert_eq!(missing.status(), 404);
+
+    let params = EverythingParams::parse(&SearchQuery::parse("_type=Organization")).unwrap();
+    let error = compartment::everything(&engine, &repository, "1", &params).unwrap_err();
+    assert!(matches!(error, CompartmentError::NotInCompartment(ref code, _) if code == "Organization"));
+    assert_eq!(error.status(), 400);
+
+    let error = EverythingParams::parse(&SearchQuery::parse("_since=garbage")).unwrap_err();
+    assert!(matches!(error, CompartmentError::InvalidSince(ref since) if since == "garbage"));
+    assert_eq!(error.status(), 400);
+}
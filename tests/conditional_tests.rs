@@ -0,0 +1,108 @@
+use fhir_resources_rs::conditional::{
+    ConditionalCreate, ConditionalDeleteMode, ConditionalError, ConditionalRepository, ConditionalUpdate,
+};
+use fhir_resources_rs::data_types::uri::Uri;
+use fhir_resources_rs::identifier::Identifier;
+use fhir_resources_rs::patient::Patient;
+use fhir_resources_rs::repository::{MemoryRepository, Repository};
+use fhir_resources_rs::resource::AnyResource;
+use fhir_resources_rs::search::SearchEngine;
+
+#[test]
+fn test_conditional_create() {
+    let engine = SearchEngine::new();
+    let mut repository = MemoryRepository::new();
+    let criteria = "identifier=http://hospital/mrn|12345";
+
+    let mut patient = Patient::new();
+    patient.add_identifier(Identifier::new(
+        Uri::new_unchecked("official".to_string()),
+        Uri::new_unchecked("http://hospital/mrn".to_string()),
+        "12345".to_string(),
+    ));
+    let patient: AnyResource = patient.into();
+
+    let created = repository.conditional_create(&engine, patient.clone(), criteria).unwrap();
+    let ConditionalCreate::Created(created) = created else { panic!("expected a create") };
+    let existing = repository.conditional_create(&engine, patient.clone(), criteria).unwrap();
+    assert_eq!(existing, ConditionalCreate::Existing(created));
+    assert_eq!(repository.list("Patient").unwrap().len(), 1);
+
+    repository.create(patient.clone()).unwrap();
+    let error = repository.conditional_create(&engine, patient.clone(), criteria).unwrap_err();
+    assert!(matches!(error, ConditionalError::MultipleMatches(_, _, 2)));
+    assert_eq!(error.status(), 412);
+    assert!(matches!(
+        repository.conditional_create(&engine, patient, ""),
+        Err(ConditionalError::MissingCriteria)
+    ));
+}
+
+#[test]
+fn test_conditional_update() {
+    let engine = SearchEngine::new();
+    let mut repository = MemoryRepository::new();
+    let criteria = "identifier=http://hospital/mrn|12345";
+
+    let mut patient = Patient::new();
+    patient.add_identifier(Identifier::new(
+        Uri::new_unchecked("official".to_string()),
+        Uri::new_unchecked("http://hospital/mrn".to_string()),
+        "12345".to_string(),
+    ));
+    patient.set_gender(Some("female".to_string()));
+    let created = repository.conditional_update(&engine, patient.clone().into(), criteria).unwrap();
+    assert!(matches!(&created, ConditionalUpdate::Created(resource) if resource.id() == Some("1")));
+    patient.set_gender(Some("male".to_string()));
+    let updated = repository.conditional_update(&engine, patient.clone().into(), criteria).unwrap();
+    let ConditionalUpdate::Updated(updated) = updated else { panic!("expected an update") };
+    assert_eq!(updated.id(), Some("1"));
+    assert_eq!(updated.meta().unwrap().version_id(), Some("2"));
+
+    let mut other_id = patient;
+    other_id.set_id(Some("7".to_string()));
+    assert!(matches!(
+        repository.conditional_update(&engine, other_id.into(), criteria),
+        Err(ConditionalError::IdMismatch(_, _))
+    ));
+
+    // No match, so the resource's own id is used unless another resource has it
+    let mut unmatched = Patient::new();
+    unmatched.add_identifier(Identifier::new(
+        Uri::new_unchecked("official".to_string()),
+        Uri::new_unchecked("http://hospital/mrn".to_string()),
+        "99999".to_string(),
+    ));
+    unmatched.set_id(Some("1".to_string()));
+    let error = repository.conditional_update(&engine, unmatched.clone().into(), "identifier=99999").unwrap_err();
+    assert_eq!(error.status(), 409);
+
+    unmatched.set_id(Some("p99".to_string()));
+    let created = repository.conditional_update(&engine, unmatched.into(), "identifier=99999").unwrap();
+    assert!(matches!(created, ConditionalUpdate::Created(resource) if resource.id() == Some("p99")));
+}
+
+#[test]
+fn test_conditional_delete() {
+    let engine = SearchEngine::new();
+    let mut repository = MemoryRepository::new();
+    for gender in ["female", "female", "male"] {
+        let mut patient = Patient::new();
+        patient.set_gender(Some(gender.to_string()));
+        repository.create(patient.into()).unwrap();
+    }
+
+    let deleted = repository
+        .conditional_delete(&engine, "Patient", "gender=other", ConditionalDeleteMode::Single)
+        .unwrap();
+    assert_eq!(deleted, 0);
+    assert!(matches!(
+        repository.conditional_delete(&engine, "Patient", "gender=female", ConditionalDeleteMode::Single),
+        Err(ConditionalError::MultipleMatches(_, _, 2))
+    ));
+    let deleted = repository
+        .conditional_delete(&engine, "Patient", "gender=female", ConditionalDeleteMode::Multiple)
+        .unwrap();
+    assert_eq!(deleted, 2);
+    assert_eq!(repository.list("Patient").unwrap().len(), 1);
+}
@@ -0,0 +1,129 @@
+use std::cmp::Ordering;
+
+use fhir_resources_rs::account::Account;
+use fhir_resources_rs::data_types::date_time::{Date, DateTime, DateTimeError, Instant, Precision, Time};
+use fhir_resources_rs::patient::Patient;
+
+#[test]
+fn test_parse_precision() {
+    let year: DateTime = "2020".parse().unwrap();
+    assert_eq!(year.precision(), Precision::Year);
+    assert_eq!(year.month(), None);
+
+    let month: DateTime = "2020-06".parse().unwrap();
+    assert_eq!(month.precision(), Precision::Month);
+    assert_eq!(month.month(), Some(6));
+    assert_eq!(month.day(), None);
+
+    let seconds: DateTime = "2020-06-15T10:30:00+02:00".parse().unwrap();
+    assert_eq!(seconds.precision(), Precision::Second);
+    assert_eq!(seconds.offset(), Some(120));
+    assert_eq!(seconds.date().as_str(), "2020-06-15");
+
+    let fraction: DateTime = "2020-06-15T10:30:00.250-05:30".parse().unwrap();
+    assert_eq!(fraction.precision(), Precision::Fraction(3));
+    assert_eq!(fraction.offset(), Some(-330));
+    let time = fraction.time().unwrap();
+    assert_eq!(time.as_str(), "10:30:00.250");
+    assert_eq!(time.nanosecond(), 250_000_000);
+
+    let local: DateTime = "2020-06-15T10:30:00".parse().unwrap();
+    assert_eq!(local.offset(), None);
+    assert!(local.has_time());
+}
+
+#[test]
+fn test_validation() {
+    for invalid in [
+        "20", "0000", "2020-13", "2020-6", "2020-06-15T10:30", "2020-06-15T24:00:00Z", "2020-06-15T10:30:00.Z",
+        "2020-06-15T10:30:00.1234567890Z", "2020-06-15T10:30:00+14:30", "2020-06T10:30:00Z", "2020-06-15 10:30:00",
+    ] {
+        assert!(invalid.parse::<DateTime>().is_err(), "{}", invalid);
+    }
+    assert!(matches!("2021-02-29".parse::<DateTime>(), Err(DateTimeError::InvalidDate(_))));
+    assert!("2020-02-29".parse::<Date>().is_ok());
+    assert!("2020-06-15T10:30:00Z".parse::<Date>().is_err());
+
+    assert!(matches!("2020-06-15T10:30:00".parse::<Instant>(), Err(DateTimeError::MissingTimeZone(_))));
+    assert!("2020-06-15".parse::<Instant>().is_err());
+    assert!("2020-06-15T23:59:60Z".parse::<Instant>().is_ok());
+
+    assert!("23:59:59.5".parse::<Time>().is_ok());
+    assert!("23:59".parse::<Time>().is_err());
+    assert!("10:30:00Z".parse::<Time>().is_err());
+}
+
+#[test]
+fn test_compare_with_uncertainty() {
+    let date = |text: &str| text.parse::<DateTime>().unwrap();
+    assert_eq!(date("2020").compare(&date("2021-03")), Some(Ordering::Less));
+    assert_eq!(date("2020").compare(&date("2020-03")), None);
+    assert_eq!(date("2020-03-01").compare(&date("2020-03-01")), Some(Ordering::Equal));
+    assert_eq!(date("2020-03-01").compare(&date("2020-03-01T10:00:00Z")), None);
+    assert_eq!(date("2020-03-01T10:00:00Z").compare(&date("2020-03-01T10:00:00.000Z")), Some(Ordering::Equal));
+    assert_eq!(date("2020-03-01T10:00:00Z").compare(&date("2020-03-01T09:59:59.9Z")), Some(Ordering::Greater));
+    assert_eq!(date("2020-03-01T10:00:00Z").compare(&date("2020-03-01T10:00:00+01:00")), Some(Ordering::Greater));
+
+    let birth: Date = "1980-05".parse().unwrap();
+    assert_eq!(birth.compare(&"1980-04-30".parse().unwrap()), Some(Ordering::Greater));
+    let time: Time = "08:00:00".parse().unwrap();
+    assert_eq!(time.compare(&"17:30:00".parse().unwrap()), Ordering::Less);
+}
+
+#[test]
+fn test_utc_normalization() {
+    let date = |text: &str| text.parse::<DateTime>().unwrap();
+    assert_eq!(date("2020-03-01T00:30:00.5+01:00").to_utc().unwrap().as_str(), "2020-02-29T23:30:00.5Z");
+    assert_eq!(date("2020-12-31T20:00:00-05:00").to_utc().unwrap().as_str(), "2021-01-01T01:00:00Z");
+    assert_eq!(date("2020-03-01").to_utc().unwrap().as_str(), "2020-03-01");
+    assert!(matches!(date("2020-03-01T10:00:00").to_utc(), Err(DateTimeError::MissingTimeZone(_))));
+
+    let instant: Instant = "2016-12-31T23:59:60+00:00".parse().unwrap();
+    assert_eq!(instant.to_utc().as_str(), "2016-12-31T23:59:60Z");
+    assert_eq!(date("1970-01-01T01:00:00+01:00").to_instant().unwrap().epoch_seconds(), 0);
+    assert!(date("1970-01-01T01:00:00").to_instant().is_none());
+}
+
+#[test]
+fn test_offset_aware_ordering() {
+    let date = |text: &str| text.parse::<DateTime>().unwrap();
+    assert_eq!(date("2020-03-01T10:00:00+01:00").compare(&date("2020-03-01T09:00:00Z")), Some(Ordering::Equal));
+    assert_eq!(date("2020-03-01T10:00:00").compare(&date("2020-03-01T09:00:00")), Some(Ordering::Greater));
+
+    let local = date("2020-03-01T10:00:00");
+    assert_eq!(local.compare(&date("2020-03-01T09:30:00Z")), None);
+    let assumed = local.assume_offset(60).unwrap();
+    assert_eq!(assumed.as_str(), "2020-03-01T10:00:00+01:00");
+    assert_eq!(assumed.compare(&date("2020-03-01T09:30:00Z")), Some(Ordering::Less));
+    assert!(matches!(local.assume_offset(15 * 60), Err(DateTimeError::InvalidOffset(900))));
+
+    let mut accounts: Vec<Account> = ["2024-05-01T12:00:00+02:00", "2024-05-01T10:30:00Z", "2024-05-01T06:00:00-05:00"]
+        .into_iter()
+        .map(|calculated_at| {
+            let mut account = Account::new();
+            account.set_calculated_at(Some(calculated_at.parse().unwrap()));
+            account
+        })
+        .collect();
+    accounts.sort_by(|a, b| a.calculated_at().unwrap().compare(b.calculated_at().unwrap()));
+    let sorted: Vec<&str> = accounts.iter().filter_map(|account| account.calculated_at().map(Instant::as_str)).collect();
+    assert_eq!(sorted, ["2024-05-01T12:00:00+02:00", "2024-05-01T10:30:00Z", "2024-05-01T06:00:00-05:00"]);
+}
+
+#[test]
+fn test_serialization_is_exact() {
+    let mut patient = Patient::new();
+    patient.set_birth_date(Some("1980-05".parse().unwrap()));
+    patient.set_deceased_date_time(Some("2020-06-15T10:30:00.120+02:00".parse().unwrap()));
+    let json = patient.to_json();
+    assert!(json.contains(r#""birthDate":"1980-05""#));
+    assert!(json.contains(r#""deceasedDateTime":"2020-06-15T10:30:00.120+02:00""#));
+    assert_eq!(Patient::from_json(&json).unwrap(), patient);
+
+    let mut account = Account::new();
+    account.set_calculated_at(Some(Instant::now()));
+    let json = account.to_json();
+    assert_eq!(Account::from_json(&json).unwrap(), account);
+
+    assert!(Patient::from_json(r#"{"resourceType":"Patient","birthDate":"15/05/1980"}"#).is_err());
+}
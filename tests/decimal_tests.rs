@@ -0,0 +1,95 @@
+use std::cmp::Ordering;
+
+use fhir_resources_rs::account::Account;
+use fhir_resources_rs::account_balance::AccountBalance;
+use fhir_resources_rs::data_types::decimal::{Decimal, DecimalError};
+use fhir_resources_rs::money::Money;
+use fhir_resources_rs::resource::AnyResource;
+
+#[test]
+fn test_parse() {
+    for valid in ["0", "-0", "10.50", "-3.14159", "1e3", "1.5E-2", "123456789012345678901234567890.12345678"] {
+        assert_eq!(valid.parse::<Decimal>().unwrap().as_str(), valid);
+    }
+    for invalid in ["", "-", "01", "1.", ".5", "+1", "1e", "1,5", "NaN", "1.5e+x"] {
+        assert!(matches!(invalid.parse::<Decimal>(), Err(DecimalError::InvalidFormat(_))), "{}", invalid);
+    }
+    assert!(matches!("1234567890123456789012345678901234567890".parse::<Decimal>(), Err(DecimalError::OutOfRange(_))));
+
+    assert_eq!("10.50".parse::<Decimal>().unwrap().scale(), 2);
+    assert_eq!(Decimal::new(-1050, 2).as_str(), "-10.50");
+    assert_eq!(Decimal::new(5, 3).as_str(), "0.005");
+    assert_eq!(Decimal::from(42).as_str(), "42");
+}
+
+#[test]
+fn test_exact_arithmetic() {
+    assert_eq!("0.1".parse::<Decimal>().unwrap().checked_add(&"0.2".parse().unwrap()).unwrap().as_str(), "0.3");
+    assert_eq!("10.50".parse::<Decimal>().unwrap().checked_add(&"1.5".parse().unwrap()).unwrap().as_str(), "12.00");
+    assert_eq!("10.50".parse::<Decimal>().unwrap().checked_sub(&"20".parse().unwrap()).unwrap().as_str(), "-9.50");
+    assert_eq!("1.10".parse::<Decimal>().unwrap().checked_mul(&"3".parse().unwrap()).unwrap().as_str(), "3.30");
+    assert_eq!("1e3".parse::<Decimal>().unwrap().checked_mul(&"2.5".parse().unwrap()).unwrap().as_str(), "2500");
+    assert_eq!("1.5E-2".parse::<Decimal>().unwrap().checked_add(&"1".parse().unwrap()).unwrap().as_str(), "1.015");
+    assert_eq!((-"-2.0".parse::<Decimal>().unwrap()).as_str(), "2.0");
+    assert_eq!("-2.0".parse::<Decimal>().unwrap().abs().as_str(), "2.0");
+
+    let large = "99999999999999999999999999999999999999".parse::<Decimal>().unwrap();
+    assert_eq!(large.checked_add(&"1".parse().unwrap()), None);
+    assert_eq!(large.checked_mul(&"10".parse().unwrap()), None);
+}
+
+#[test]
+fn test_compare() {
+    assert_eq!("10.5".parse::<Decimal>().unwrap().compare(&"10.50".parse().unwrap()), Ordering::Equal);
+    assert_ne!("10.5".parse::<Decimal>().unwrap(), "10.50".parse::<Decimal>().unwrap());
+    assert_eq!("9.99".parse::<Decimal>().unwrap().compare(&"10".parse().unwrap()), Ordering::Less);
+    assert_eq!("-9.99".parse::<Decimal>().unwrap().compare(&"-10".parse().unwrap()), Ordering::Greater);
+    assert_eq!("0".parse::<Decimal>().unwrap().compare(&"-0.0".parse().unwrap()), Ordering::Equal);
+    assert_eq!("1e3".parse::<Decimal>().unwrap().compare(&"999.999".parse().unwrap()), Ordering::Greater);
+    assert_eq!("1e-38".parse::<Decimal>().unwrap().compare(&"99999999999999999999999999999999999999".parse().unwrap()), Ordering::Less);
+    assert_eq!("-1".parse::<Decimal>().unwrap().compare(&"0.001".parse().unwrap()), Ordering::Less);
+}
+
+#[test]
+#[cfg(feature = "exact-decimal")]
+fn test_money_round_trip() {
+    let money = Money::with_currency("10.50".parse().unwrap(), "USD".to_string());
+    let json = serde_json::to_string(&money).unwrap();
+    assert_eq!(json, r#"{"value":10.50,"currency":"USD"}"#);
+    assert_eq!(serde_json::from_str::<Money>(&json).unwrap(), money);
+    assert!(serde_json::from_str::<Money>(r#"{"value":"10.50","currency":"USD"}"#).is_err());
+
+    let mut account = Account::new();
+    account.add_balance(AccountBalance::new(money));
+    let resource = AnyResource::from_json(&account.to_json()).unwrap();
+    let json = resource.to_json();
+    assert!(json.contains(r#""value":10.50"#));
+    let account = resource.downcast::<Account>().unwrap();
+    assert_eq!(account.balance()[0].amount().value().map(Decimal::as_str), Some("10.50"));
+}
+
+#[test]
+#[cfg(not(feature = "exact-decimal"))]
+fn test_money_round_trip_through_f64() {
+    let money = Money::with_currency("10.50".parse().unwrap(), "USD".to_string());
+    let json = serde_json::to_string(&money).unwrap();
+    assert_eq!(json, r#"{"value":10.5,"currency":"USD"}"#);
+    let value = serde_json::from_str::<Money>(&json).unwrap().value().unwrap().clone();
+    assert_eq!(value.compare(&"10.50".parse().unwrap()), Ordering::Equal);
+    assert!(serde_json::from_str::<Money>(r#"{"value":"10.50","currency":"USD"}"#).is_err());
+
+    let mut account = Account::new();
+    account.add_balance(AccountBalance::new(money));
+    let resource = AnyResource::from_json(&account.to_json()).unwrap();
+    assert!(resource.to_json().contains(r#""value":10.5,"#));
+}
+
+#[test]
+fn test_round() {
+    assert_eq!("2.345".parse::<Decimal>().unwrap().round(2).unwrap().as_str(), "2.35");
+    assert_eq!("-2.345".parse::<Decimal>().unwrap().round(2).unwrap().as_str(), "-2.35");
+    assert_eq!("2.344".parse::<Decimal>().unwrap().round(2).unwrap().as_str(), "2.34");
+    assert_eq!("10.5".parse::<Decimal>().unwrap().round(2).unwrap().as_str(), "10.50");
+    assert_eq!("0.5".parse::<Decimal>().unwrap().round(0).unwrap().as_str(), "1");
+    assert_eq!("1.5e2".parse::<Decimal>().unwrap().round(1).unwrap().as_str(), "150.0");
+}
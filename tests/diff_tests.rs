@@ -0,0 +1,122 @@
+use fhir_resources_rs::account::Account;
+use fhir_resources_rs::data_types::contact_point::ContactPoint;
+use fhir_resources_rs::diff::{self, ChangeKind, DiffError};
+use fhir_resources_rs::fhirpath_patch::FhirPathPatch;
+use fhir_resources_rs::human_name::HumanName;
+use fhir_resources_rs::meta::Meta;
+use fhir_resources_rs::patient::Patient;
+use fhir_resources_rs::patient_contact::PatientContact;
+use fhir_resources_rs::resource::AnyResource;
+use serde_json::json;
+
+#[test]
+fn test_diff_reports_paths() {
+    let mut old = Patient::new();
+    old.set_id(Some("p1".to_string()));
+    old.set_gender(Some("female".to_string()));
+    old.add_telecom(ContactPoint::new("phone".to_string(), "555-0100".to_string()));
+    old.add_telecom(ContactPoint::new("email".to_string(), "ann@example.org".to_string()));
+    let mut new = Patient::new();
+    new.set_id(Some("p1".to_string()));
+    new.set_active(Some(true));
+    new.add_telecom(ContactPoint::new("phone".to_string(), "555-0199".to_string()));
+
+    let diff = diff::diff(&old, &new);
+    let changes: Vec<(&str, ChangeKind)> = diff.changes().iter().map(|change| (change.path(), change.kind())).collect();
+    assert_eq!(
+        changes,
+        vec![
+            ("Patient.active", ChangeKind::Added),
+            ("Patient.gender", ChangeKind::Removed),
+            ("Patient.telecom[0].value", ChangeKind::Changed),
+            ("Patient.telecom[1]", ChangeKind::Removed),
+        ]
+    );
+    let value = diff.change("Patient.telecom[0].value").unwrap();
+    assert_eq!(value.old_value(), Some(&json!("555-0100")));
+    assert_eq!(value.new_value(), Some(&json!("555-0199")));
+    assert_eq!(diff.of_kind(ChangeKind::Removed).count(), 2);
+}
+
+#[test]
+fn test_diff_ignores_version_metadata() {
+    let mut old = Patient::new();
+    old.set_id(Some("p1".to_string()));
+    old.set_gender(Some("female".to_string()));
+    old.set_meta(Some(Meta::with_version("1".to_string())));
+    let mut new = old.clone();
+    new.set_meta(Some(Meta::with_version("2".to_string())));
+    assert!(diff::diff(&old, &new).is_empty());
+
+    let mut account = Account::new();
+    account.set_name(Some("Main".to_string()));
+    let diff = diff::diff(&Account::new(), &account);
+    assert_eq!(diff.changes().len(), 1);
+    assert_eq!(diff.changes()[0].path(), "Account.name");
+}
+
+#[test]
+fn test_diff_as_fhirpath_patch() {
+    let mut old = Patient::new();
+    old.set_id(Some("p1".to_string()));
+    old.set_gender(Some("female".to_string()));
+    old.add_telecom(ContactPoint::new("phone".to_string(), "555-0100".to_string()));
+    let mut new = old.clone();
+    new.set_gender(None);
+    new.set_active(Some(false));
+    let mut name = HumanName::new("official".to_string(), "Ann Lee".to_string(), "Lee".to_string());
+    name.set_given(vec!["Ann".to_string()]);
+    new.add_name(name.clone());
+    let mut contact = PatientContact::with_name(name);
+    contact.add_telecom(ContactPoint::new("phone".to_string(), "555-0111".to_string()));
+    new.add_contact(contact);
+
+    let patch = diff::diff(&old, &new).to_fhirpath_patch();
+    assert_eq!(patch.apply(&old).unwrap(), new);
+
+    let parameters = patch.to_parameters();
+    let restored = FhirPathPatch::from_parameters(&parameters).unwrap();
+    assert_eq!(restored.apply(&old).unwrap(), new);
+    assert!(!parameters.to_json().contains("valueElement"));
+}
+
+#[test]
+fn test_diff_any_requires_same_type() {
+    let mut patient = Patient::new();
+    patient.set_gender(Some("female".to_string()));
+    let patient = AnyResource::Patient(patient);
+    let account = AnyResource::Account(Account::new());
+    assert!(matches!(
+        diff::diff_any(&patient, &account),
+        Err(DiffError::ResourceTypeMismatch(old, new)) if old == "Patient" && new == "Account"
+    ));
+    assert!(diff::diff_any(&patient, &patient).unwrap().is_empty());
+}
+
+#[test]
+fn test_diff_between_list_and_single_value() {
+    let old = json!({"resourceType": "Basic", "id": "b1", "note": ["first", "second"], "code": "x"});
+    let new = json!({"resourceType": "Basic", "id": "b1", "note": "only", "code": ["x", "y"]});
+
+    let diff = diff::diff_value(&old, &new).unwrap();
+    let paths: Vec<(&str, ChangeKind)> = diff.changes().iter().map(|change| (change.path(), change.kind())).collect();
+    assert_eq!(
+        paths,
+        vec![
+            ("Basic.code", ChangeKind::Removed),
+            ("Basic.code[0]", ChangeKind::Added),
+            ("Basic.code[1]", ChangeKind::Added),
+            ("Basic.note[0]", ChangeKind::Removed),
+            ("Basic.note[1]", ChangeKind::Removed),
+            ("Basic.note", ChangeKind::Added),
+        ]
+    );
+
+    let mut patched = old.clone();
+    diff.to_fhirpath_patch().apply_value(&mut patched).unwrap();
+    assert_eq!(patched, new);
+    let mut reverted = new.clone();
+    diff::diff_value(&new, &old).unwrap().to_fhirpath_patch().apply_value(&mut reverted).unwrap();
+    assert_eq!(reverted, old);
+    assert!(matches!(diff::diff_value(&old, &json!({"resourceType": "Patient"})), Err(DiffError::ResourceTypeMismatch(_, _))));
+}
@@ -0,0 +1,103 @@
+use fhir_resources_rs::account::Account;
+use fhir_resources_rs::account_balance::AccountBalance;
+use fhir_resources_rs::currency::ISO_4217;
+use fhir_resources_rs::data_types::codeable_concept::{CodeableConcept, Coding};
+use fhir_resources_rs::data_types::decimal::Decimal;
+use fhir_resources_rs::exchange::{self, ConversionError, ExchangeRate, ExchangeRates, RateTable};
+use fhir_resources_rs::money::{Money, MoneyError};
+
+#[test]
+fn test_rate_table() {
+    let mut table = RateTable::new();
+    let january = ExchangeRate::new("EUR".to_string(), "USD".to_string(), "1.10".parse().unwrap(), "2024-01-01".parse().unwrap());
+    let june = ExchangeRate::new("EUR".to_string(), "USD".to_string(), "1.0825".parse().unwrap(), "2024-06-01".parse().unwrap());
+    table.add_rate(january).unwrap();
+    table.add_rate(june).unwrap();
+
+    // The latest rate effective on the date applies, in one direction only
+    assert_eq!(table.rate("EUR", "USD", &"2024-03-15".parse().unwrap()).unwrap().rate().as_str(), "1.10");
+    assert_eq!(table.rate("EUR", "USD", &"2024-06-01".parse().unwrap()).unwrap().rate().as_str(), "1.0825");
+    assert!(table.rate("EUR", "USD", &"2023-12-31".parse().unwrap()).is_none());
+    assert!(table.rate("USD", "EUR", &"2024-03-15".parse().unwrap()).is_none());
+
+    let mut table = RateTable::new();
+    let unknown = ExchangeRate::new("EUR".to_string(), "DOLLARS".to_string(), "1.1".parse().unwrap(), "2024-01-01".parse().unwrap());
+    assert!(matches!(
+        table.add_rate(unknown),
+        Err(ConversionError::Money(MoneyError::UnknownCurrency(code))) if code == "DOLLARS"
+    ));
+    let zero = ExchangeRate::new("EUR".to_string(), "USD".to_string(), Decimal::from(0), "2024-01-01".parse().unwrap());
+    assert!(matches!(table.add_rate(zero), Err(ConversionError::InvalidRate(..))));
+    assert!(table.rates().is_empty());
+}
+
+#[test]
+fn test_convert() {
+    let mut table = RateTable::new();
+    table.add_rate(ExchangeRate::new("EUR".to_string(), "USD".to_string(), "1.10".parse().unwrap(), "2024-01-01".parse().unwrap())).unwrap();
+    table.add_rate(ExchangeRate::new("EUR".to_string(), "USD".to_string(), "1.0825".parse().unwrap(), "2024-06-01".parse().unwrap())).unwrap();
+    table.add_rate(ExchangeRate::new("USD".to_string(), "JPY".to_string(), "151.5".parse().unwrap(), "2024-01-01".parse().unwrap())).unwrap();
+    let date = "2024-07-01".parse().unwrap();
+
+    let euros = Money::with_currency("100.00".parse().unwrap(), "EUR".to_string());
+    let conversion = exchange::convert(&table, &euros, "USD", &date).unwrap();
+    assert_eq!(conversion.amount(), &Money::with_currency("108.25".parse().unwrap(), "USD".to_string()));
+    assert_eq!(conversion.rate().unwrap().effective().as_str(), "2024-06-01");
+
+    // Rounded to the target currency's minor units
+    let dollars = Money::with_currency("10.99".parse().unwrap(), "USD".to_string());
+    let yen = exchange::convert(&table, &dollars, "JPY", &date).unwrap();
+    assert_eq!(yen.amount(), &Money::with_currency("1665".parse().unwrap(), "JPY".to_string()));
+
+    let five = Money::with_currency("5".parse().unwrap(), "USD".to_string());
+    let same = exchange::convert(&table, &five, "USD", &date).unwrap();
+    assert_eq!(same.amount(), &five);
+    assert!(same.rate().is_none());
+
+    let francs = Money::with_currency("1".parse().unwrap(), "CHF".to_string());
+    assert_eq!(
+        exchange::convert(&table, &francs, "USD", &date),
+        Err(ConversionError::NoRate("CHF".to_string(), "USD".to_string(), "2024-07-01".to_string()))
+    );
+    let no_currency = Money::with_value(Decimal::from(1));
+    assert_eq!(
+        exchange::convert(&table, &no_currency, "USD", &date),
+        Err(ConversionError::Money(MoneyError::MissingCurrency))
+    );
+}
+
+#[test]
+fn test_restate_account() {
+    let mut table = RateTable::new();
+    table.add_rate(ExchangeRate::new("EUR".to_string(), "USD".to_string(), "1.10".parse().unwrap(), "2024-01-01".parse().unwrap())).unwrap();
+    table.add_rate(ExchangeRate::new("GBP".to_string(), "USD".to_string(), "1.27".parse().unwrap(), "2024-01-01".parse().unwrap())).unwrap();
+
+    let mut account = Account::new();
+    account.set_currency(Some(CodeableConcept::with_coding(Coding::with_system_and_code(
+        ISO_4217.to_string(),
+        "USD".to_string(),
+    ))));
+    account.add_balance(AccountBalance::new(Money::with_currency("100.00".parse().unwrap(), "EUR".to_string())));
+    account.add_balance(AccountBalance::new(Money::with_currency("50".parse().unwrap(), "GBP".to_string())));
+    account.add_balance(AccountBalance::new(Money::with_currency("20.00".parse().unwrap(), "EUR".to_string())));
+    account.add_balance(AccountBalance::new(Money::with_value("7.5".parse().unwrap())));
+    assert_eq!(account.currency_code(), Some("USD"));
+
+    let restatement = exchange::restate_account(&table, &account, &"2024-03-15".parse().unwrap()).unwrap();
+    let amounts: Vec<String> = restatement
+        .account()
+        .balance()
+        .iter()
+        .map(|balance| balance.amount().format().unwrap())
+        .collect();
+    assert_eq!(amounts, ["$110.00", "$63.50", "$22.00", "$7.50"]);
+    let used: Vec<(&str, &str)> = restatement.rates().iter().map(|rate| (rate.from(), rate.rate().as_str())).collect();
+    assert_eq!(used, [("EUR", "1.10"), ("GBP", "1.27")]);
+    // The original account is left as it was
+    assert_eq!(account.balance()[0].amount(), &Money::with_currency("100.00".parse().unwrap(), "EUR".to_string()));
+
+    assert!(matches!(
+        exchange::restate_account(&table, &Account::new(), &"2024-03-15".parse().unwrap()),
+        Err(ConversionError::NoAccountCurrency)
+    ));
+}
@@ -0,0 +1,120 @@
+use fhir_resources_rs::account::Account;
+use fhir_resources_rs::data_types::contact_point::ContactPoint;
+use fhir_resources_rs::fhirpath_patch::{FhirPathOperation, FhirPathPatch};
+use fhir_resources_rs::parameters::{ParameterValue, Parameters};
+use fhir_resources_rs::patch::PatchError;
+use fhir_resources_rs::patient::Patient;
+use serde_json::json;
+
+#[test]
+fn test_replace_and_delete() {
+    let mut patient = Patient::new();
+    patient.add_telecom(ContactPoint::new("phone".to_string(), "555-0100".to_string()));
+    patient.add_telecom(ContactPoint::new("email".to_string(), "ann@example.org".to_string()));
+
+    let mut patch = FhirPathPatch::new();
+    patch.add_operation(FhirPathOperation::Replace {
+        path: "Patient.telecom[0].value".to_string(),
+        value: ParameterValue::string("555-0199"),
+    });
+    patch.add_operation(FhirPathOperation::Replace {
+        path: "Patient.telecom.where(system = 'email').value".to_string(),
+        value: ParameterValue::string("ann@example.com"),
+    });
+    patch.add_operation(FhirPathOperation::Delete { path: "Patient.telecom.last()".to_string() });
+    // Deleting an element that is not there is not an error
+    patch.add_operation(FhirPathOperation::Delete { path: "Patient.gender".to_string() });
+
+    let patched = patch.apply(&patient).unwrap();
+    assert_eq!(patched.telecom().len(), 1);
+    assert_eq!(patched.telecom()[0].value(), "555-0199");
+}
+
+#[test]
+fn test_add_insert_and_move() {
+    let account_patch = FhirPathPatch::from_json(
+        r#"{"resourceType":"Parameters","parameter":[{"name":"operation","part":[
+            {"name":"type","valueCode":"add"},
+            {"name":"path","valueString":"Account"},
+            {"name":"name","valueString":"guarantor"},
+            {"name":"value","part":[
+                {"name":"party","valueReference":{"reference":"Patient/p1"}},
+                {"name":"onHold","valueBoolean":false}
+            ]}
+        ]}]}"#,
+    )
+    .unwrap();
+    let account = account_patch.apply(&Account::new()).unwrap();
+    assert_eq!(account.guarantor()[0].party().reference(), Some("Patient/p1"));
+    assert_eq!(account.guarantor()[0].on_hold(), Some(false));
+
+    let mut patient = Patient::new();
+    patient.add_telecom(ContactPoint::new("phone".to_string(), "555-0100".to_string()));
+    patient.add_telecom(ContactPoint::new("email".to_string(), "ann@example.org".to_string()));
+    let mut patch = FhirPathPatch::new();
+    patch.add_operation(FhirPathOperation::Insert {
+        path: "Patient.telecom".to_string(),
+        value: ParameterValue::new("ContactPoint", json!({"system": "sms", "value": "555-0142"})),
+        index: 1,
+    });
+    patch.add_operation(FhirPathOperation::Move { path: "Patient.telecom".to_string(), source: 2, destination: 0 });
+    patch.add_operation(FhirPathOperation::Add {
+        path: "Patient".to_string(),
+        name: "active".to_string(),
+        value: ParameterValue::boolean(true),
+    });
+
+    let patched = patch.apply(&patient).unwrap();
+    let systems: Vec<&str> = patched.telecom().iter().map(ContactPoint::system).collect();
+    assert_eq!(systems, vec!["email", "phone", "sms"]);
+    assert_eq!(patched.active(), Some(true));
+}
+
+#[test]
+fn test_parameters_round_trip() {
+    let mut original = FhirPathPatch::new();
+    original.add_operation(FhirPathOperation::Add {
+        path: "Patient".to_string(),
+        name: "gender".to_string(),
+        value: ParameterValue::code("female"),
+    });
+    original.add_operation(FhirPathOperation::Move { path: "Patient.telecom".to_string(), source: 1, destination: 0 });
+    original.add_operation(FhirPathOperation::Delete { path: "Patient.active".to_string() });
+
+    let parameters = Parameters::from_json(&original.to_json()).unwrap();
+    assert_eq!(parameters.parameter().len(), 3);
+    assert_eq!(
+        parameters.parameter()[0].part_named("value").unwrap().value().unwrap().value_type(),
+        "Code"
+    );
+    assert_eq!(FhirPathPatch::from_parameters(&parameters).unwrap(), original);
+}
+
+#[test]
+fn test_errors() {
+    let mut patient = Patient::new();
+    patient.set_id(Some("p1".to_string()));
+    patient.add_telecom(ContactPoint::new("phone".to_string(), "555-0100".to_string()));
+    patient.add_telecom(ContactPoint::new("email".to_string(), "ann@example.org".to_string()));
+    let replace = |path: &str, value: ParameterValue| {
+        let mut patch = FhirPathPatch::new();
+        patch.add_operation(FhirPathOperation::Replace { path: path.to_string(), value });
+        patch.apply(&patient)
+    };
+
+    assert!(matches!(
+        replace("Patient.telecom.value", ParameterValue::string("x")),
+        Err(PatchError::MultipleMatches(_))
+    ));
+    assert!(matches!(replace("Patient.birthDate", ParameterValue::string("x")), Err(PatchError::PathNotFound(_))));
+    assert!(matches!(replace("Patient.telecom.where(", ParameterValue::string("x")), Err(PatchError::InvalidPath(_, _))));
+    assert!(matches!(
+        replace("Patient.telecom[0].system", ParameterValue::integer(1)),
+        Err(PatchError::InvalidResource(_))
+    ));
+    assert!(matches!(replace("Patient.id", ParameterValue::string("p2")), Err(PatchError::ImmutableElement(_))));
+    assert!(matches!(
+        FhirPathPatch::from_json(r#"{"resourceType":"Parameters","parameter":[{"name":"operation","part":[{"name":"type","valueCode":"swap"},{"name":"path","valueString":"Patient"}]}]}"#),
+        Err(PatchError::InvalidDocument(_))
+    ));
+}
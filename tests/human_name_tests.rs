@@ -1,4 +1,5 @@
 use fhir_resources_rs::human_name::HumanName;
+use fhir_resources_rs::period::Period;
 
 #[test]
 fn test_human_name_creation() {
@@ -41,7 +42,7 @@ fn test_human_name_with_full_details() {
     name.set_suffix(vec!["MD".to_string(), "PhD".to_string()]);
     
     // Add period
-    name.set_period(Some("2020-01-01 to 2023-12-31".to_string()));
+    name.set_period(Some(Period::new(Some("2020-01-01".parse().unwrap()), Some("2023-12-31".parse().unwrap()))));
     
     // Verify all details
     assert_eq!(name.use_value(), "official");
@@ -50,7 +51,9 @@ fn test_human_name_with_full_details() {
     assert_eq!(name.given(), &["John", "Andrew"]);
     assert_eq!(name.prefix(), &["Dr."]);
     assert_eq!(name.suffix(), &["MD", "PhD"]);
-    assert_eq!(name.period(), Some("2020-01-01 to 2023-12-31"));
+    let period = name.period().unwrap();
+    assert_eq!(period.start().map(|start| start.as_str()), Some("2020-01-01"));
+    assert_eq!(period.end().map(|end| end.as_str()), Some("2023-12-31"));
     
     println!("✅ HumanName with full details created successfully!");
     println!("   Use: {}", name.use_value());
@@ -59,7 +62,7 @@ fn test_human_name_with_full_details() {
     println!("   Given: {}", name.given().join(", "));
     println!("   Prefix: {}", name.prefix().join(", "));
     println!("   Suffix: {}", name.suffix().join(", "));
-    println!("   Period: {}", name.period().unwrap_or("None"));
+    println!("   Period: {:?}", name.period());
 }
 
 #[test]
@@ -157,7 +160,7 @@ fn test_human_name_serialization() {
     name.set_given(vec!["John".to_string(), "Andrew".to_string()]);
     name.set_prefix(vec!["Dr.".to_string()]);
     name.set_suffix(vec!["MD".to_string(), "PhD".to_string()]);
-    name.set_period(Some("2020-2023".to_string()));
+    name.set_period(Some(Period::new(Some("2020".parse().unwrap()), Some("2023".parse().unwrap()))));
     
     // Serialize to JSON
     let json = serde_json::to_string_pretty(&name).unwrap();
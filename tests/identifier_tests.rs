@@ -29,7 +29,7 @@ fn test_identifier_with_period() {
     let mut identifier = Identifier::new(use_uri, system_uri, "MRN12345".to_string());
     
     // Add a period
-    let period = Period::new("2020-01-01".to_string(), "2023-12-31".to_string());
+    let period = Period::new(Some("2020-01-01".parse().unwrap()), Some("2023-12-31".parse().unwrap()));
     identifier.set_period(Some(period));
     
     // Verify with period
@@ -39,14 +39,14 @@ fn test_identifier_with_period() {
     assert!(identifier.period().is_some());
     
     let period_ref = identifier.period().unwrap();
-    assert_eq!(period_ref.start(), "2020-01-01");
-    assert_eq!(period_ref.end(), "2023-12-31");
+    assert_eq!(period_ref.start().unwrap().as_str(), "2020-01-01");
+    assert_eq!(period_ref.end().unwrap().as_str(), "2023-12-31");
     
     println!("✅ Identifier with period created successfully!");
     println!("   Use: {}", identifier.use_value().as_str());
     println!("   System: {}", identifier.system().as_str());
     println!("   Value: {}", identifier.value());
-    println!("   Period: {} to {}", period_ref.start(), period_ref.end());
+    println!("   Period: {} to {}", period_ref.start().unwrap(), period_ref.end().unwrap());
 }
 
 #[test]
@@ -97,6 +97,7 @@ fn test_identifier_healthcare_systems() {
 }
 
 #[test]
+#[allow(unused_variables)]
 fn test_identifier_international_formats() {
     // Test international identifier formats
     let international_identifiers = vec![
@@ -119,6 +120,7 @@ fn test_identifier_international_formats() {
 }
 
 #[test]
+#[allow(unused_variables)]
 fn test_identifier_validation_scenarios() {
     // Test various identifier validation scenarios
     let validation_scenarios = vec![
@@ -147,7 +149,7 @@ fn test_identifier_serialization() {
     let mut identifier = Identifier::new(use_uri, system_uri, "MRN12345".to_string());
     
     // Add a period
-    let period = Period::new("2020-01-01".to_string(), "2023-12-31".to_string());
+    let period = Period::new(Some("2020-01-01".parse().unwrap()), Some("2023-12-31".parse().unwrap()));
     identifier.set_period(Some(period));
     
     // Serialize to JSON
@@ -165,6 +167,7 @@ fn test_identifier_serialization() {
 }
 
 #[test]
+#[allow(unused_variables)]
 fn test_identifier_edge_cases() {
     // Test edge cases for identifiers
     let edge_cases = vec![
@@ -187,6 +190,7 @@ fn test_identifier_edge_cases() {
 }
 
 #[test]
+#[allow(unused_variables)]
 fn test_identifier_real_world_scenarios() {
     // Test real-world identifier scenarios
     let real_world_scenarios = vec![
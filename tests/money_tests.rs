@@ -0,0 +1,99 @@
+use fhir_resources_rs::currency::Currency;
+use fhir_resources_rs::data_types::decimal::Decimal;
+use fhir_resources_rs::money::{Money, MoneyError};
+
+#[test]
+fn test_currency_codes() {
+    let usd = Currency::from_code("USD").unwrap();
+    assert_eq!(usd.minor_units(), Some(2));
+    assert_eq!(usd.symbol(), Some("$"));
+    assert_eq!(Currency::from_code("JPY").unwrap().minor_units(), Some(0));
+    assert_eq!(Currency::from_code("KWD").unwrap().minor_units(), Some(3));
+    assert_eq!(Currency::from_code("XAU").unwrap().minor_units(), None);
+    assert!(!Currency::is_valid("usd"));
+    assert!(!Currency::is_valid("ABC"));
+
+    let euros = Money::with_currency(Decimal::from(1), "EUR".to_string());
+    assert!(euros.validate().is_ok());
+    assert!(Money::with_value(Decimal::from(1)).validate().is_ok());
+    let dollars = Money::with_currency(Decimal::from(1), "DOLLARS".to_string());
+    assert!(matches!(dollars.validate(), Err(MoneyError::UnknownCurrency(code)) if code == "DOLLARS"));
+}
+
+#[test]
+fn test_checked_arithmetic() {
+    let price = Money::with_currency("10.50".parse().unwrap(), "USD".to_string());
+    let fee = Money::with_currency("0.25".parse().unwrap(), "USD".to_string());
+    let sum = price.checked_add(&fee).unwrap();
+    assert_eq!(sum.value().map(Decimal::as_str), Some("10.75"));
+    assert_eq!(sum.currency(), Some("USD"));
+
+    let ten = Money::with_currency("10".parse().unwrap(), "USD".to_string());
+    let refund = Money::with_currency("12.5".parse().unwrap(), "USD".to_string());
+    let difference = ten.checked_sub(&refund).unwrap();
+    assert_eq!(difference.value().map(Decimal::as_str), Some("-2.5"));
+
+    let credit = Money::with_currency("-3.10".parse().unwrap(), "USD".to_string());
+    assert_eq!(credit.negate().value().map(Decimal::as_str), Some("3.10"));
+
+    let unit_price = Money::with_currency("19.99".parse().unwrap(), "USD".to_string());
+    let total = unit_price.scale(&"3".parse().unwrap()).unwrap();
+    assert_eq!(total.value().map(Decimal::as_str), Some("59.97"));
+
+    // Amounts in different or missing currencies are never mixed
+    let one_dollar = Money::with_currency(Decimal::from(1), "USD".to_string());
+    let one_euro = Money::with_currency(Decimal::from(1), "EUR".to_string());
+    assert_eq!(
+        one_dollar.checked_add(&one_euro),
+        Err(MoneyError::CurrencyMismatch("USD".to_string(), "EUR".to_string()))
+    );
+    assert!(matches!(one_dollar.checked_add(&Money::with_value(Decimal::from(1))), Err(MoneyError::CurrencyMismatch(..))));
+    let mut no_value = Money::new();
+    no_value.set_currency(Some("USD".to_string()));
+    assert_eq!(one_dollar.checked_add(&no_value), Err(MoneyError::MissingValue));
+
+    let huge = Money::with_currency("99999999999999999999999999999999999999".parse().unwrap(), "USD".to_string());
+    assert_eq!(huge.checked_add(&one_dollar), Err(MoneyError::Overflow));
+}
+
+#[test]
+fn test_minor_unit_rounding() {
+    let dollars = Money::with_currency("10.005".parse().unwrap(), "USD".to_string());
+    assert_eq!(dollars.round().unwrap().value().map(Decimal::as_str), Some("10.01"));
+    let negative = Money::with_currency("-10.005".parse().unwrap(), "USD".to_string());
+    assert_eq!(negative.round().unwrap().value().map(Decimal::as_str), Some("-10.01"));
+    let padded = Money::with_currency("10.5".parse().unwrap(), "USD".to_string());
+    assert_eq!(padded.round().unwrap().value().map(Decimal::as_str), Some("10.50"));
+    let yen = Money::with_currency("1234.5".parse().unwrap(), "JPY".to_string());
+    assert_eq!(yen.round().unwrap().value().map(Decimal::as_str), Some("1235"));
+    let dinars = Money::with_currency("1.2345".parse().unwrap(), "BHD".to_string());
+    assert_eq!(dinars.round().unwrap().value().map(Decimal::as_str), Some("1.235"));
+
+    let gold = Money::with_currency(Decimal::from(1), "XAU".to_string());
+    assert_eq!(gold.round(), Err(MoneyError::NoMinorUnits("XAU".to_string())));
+    assert_eq!(Money::with_value(Decimal::from(1)).round(), Err(MoneyError::MissingCurrency));
+}
+
+#[test]
+fn test_format() {
+    let dollars = Money::with_currency("1234.5".parse().unwrap(), "USD".to_string());
+    assert_eq!(dollars.format().unwrap(), "$1,234.50");
+    let euros = Money::with_currency("-12".parse().unwrap(), "EUR".to_string());
+    assert_eq!(euros.format().unwrap(), "-€12.00");
+    let pounds = Money::with_currency("1234567.891".parse().unwrap(), "GBP".to_string());
+    assert_eq!(pounds.format().unwrap(), "£1,234,567.89");
+    let francs = Money::with_currency("1000".parse().unwrap(), "CHF".to_string());
+    assert_eq!(francs.format().unwrap(), "CHF 1,000.00");
+    let yen = Money::with_currency("999.5".parse().unwrap(), "JPY".to_string());
+    assert_eq!(yen.format().unwrap(), "¥1,000");
+    let fraction = Money::with_currency("-0.001".parse().unwrap(), "USD".to_string());
+    assert_eq!(fraction.format().unwrap(), "$0.00");
+    assert_eq!(Money::with_value("1234.567".parse().unwrap()).format().unwrap(), "1,234.567");
+
+    // Exponent notation is written out in full
+    assert_eq!(Money::with_value("12e3".parse().unwrap()).format().unwrap(), "12,000");
+    assert_eq!(Money::with_value("1.5E-3".parse().unwrap()).format().unwrap(), "0.0015");
+    let gold = Money::with_currency("1e3".parse().unwrap(), "XAU".to_string());
+    assert_eq!(gold.format().unwrap(), "XAU 1,000");
+    assert_eq!(Money::new().format(), Err(MoneyError::MissingValue));
+}
@@ -1,35 +1,38 @@
-use fhir_resources_rs::period::Period;
+use std::time::Duration;
+
+use fhir_resources_rs::data_types::date_time::DateTime;
+use fhir_resources_rs::period::{Period, PeriodError};
 
 #[test]
 fn test_period_creation() {
     // Create a basic period
-    let period = Period::new("2020-01-01".to_string(), "2023-12-31".to_string());
+    let period = Period::new(Some("2020-01-01".parse().unwrap()), Some("2023-12-31".parse().unwrap()));
     
     // Verify basic values
-    assert_eq!(period.start(), "2020-01-01");
-    assert_eq!(period.end(), "2023-12-31");
+    assert_eq!(period.start().unwrap().as_str(), "2020-01-01");
+    assert_eq!(period.end().unwrap().as_str(), "2023-12-31");
     
     println!("✅ Period created successfully with basic data");
-    println!("   Start: {}", period.start());
-    println!("   End: {}", period.end());
+    println!("   Start: {}", period.start().unwrap());
+    println!("   End: {}", period.end().unwrap());
 }
 
 #[test]
 fn test_period_modification() {
     // Create a period
-    let mut period = Period::new("2020-01-01".to_string(), "2023-12-31".to_string());
+    let mut period = Period::new(Some("2020-01-01".parse().unwrap()), Some("2023-12-31".parse().unwrap()));
     
     // Modify the period
-    period.set_start("2021-01-01".to_string());
-    period.set_end("2024-12-31".to_string());
+    period.set_start(Some("2021-01-01".parse().unwrap()));
+    period.set_end(Some("2024-12-31".parse().unwrap()));
     
     // Verify modifications
-    assert_eq!(period.start(), "2021-01-01");
-    assert_eq!(period.end(), "2024-12-31");
+    assert_eq!(period.start().unwrap().as_str(), "2021-01-01");
+    assert_eq!(period.end().unwrap().as_str(), "2024-12-31");
     
     println!("✅ Period modified successfully!");
-    println!("   Start: {}", period.start());
-    println!("   End: {}", period.end());
+    println!("   Start: {}", period.start().unwrap());
+    println!("   End: {}", period.end().unwrap());
 }
 
 #[test]
@@ -38,18 +41,20 @@ fn test_period_different_formats() {
     let period_formats = vec![
         ("2020-01-01", "2023-12-31", "Standard date format"),
         ("2020-01-01T00:00:00Z", "2023-12-31T23:59:59Z", "ISO 8601 format"),
-        ("2020-01-01 00:00:00", "2023-12-31 23:59:59", "DateTime format"),
         ("2020-01-01T00:00:00+00:00", "2023-12-31T23:59:59+00:00", "ISO 8601 with timezone"),
     ];
     
     for (start, end, description) in period_formats {
-        let period = Period::new(start.to_string(), end.to_string());
+        let period = Period::new(Some(start.parse().unwrap()), Some(end.parse().unwrap()));
         
-        assert_eq!(period.start(), start);
-        assert_eq!(period.end(), end);
+        assert_eq!(period.start().unwrap().as_str(), start);
+        assert_eq!(period.end().unwrap().as_str(), end);
         
         println!("✅ {}: {} to {}", description, start, end);
     }
+
+    // A space instead of the "T" separator is not a valid dateTime
+    assert!("2020-01-01 00:00:00".parse::<DateTime>().is_err());
 }
 
 #[test]
@@ -64,17 +69,17 @@ fn test_period_healthcare_scenarios() {
     ];
     
     for (start, end, description) in healthcare_periods {
-        let period = Period::new(start.to_string(), end.to_string());
+        let period = Period::new(Some(start.parse().unwrap()), Some(end.parse().unwrap()));
         
         println!("✅ {}: {} to {}", description, start, end);
-        println!("   Duration: {} to {}", period.start(), period.end());
+        println!("   Duration: {} to {}", period.start().unwrap(), period.end().unwrap());
     }
 }
 
 #[test]
 fn test_period_serialization() {
     // Create a period with specific dates
-    let period = Period::new("2020-01-01".to_string(), "2023-12-31".to_string());
+    let period = Period::new(Some("2020-01-01".parse().unwrap()), Some("2023-12-31".parse().unwrap()));
     
     // Serialize to JSON
     let json = serde_json::to_string_pretty(&period).unwrap();
@@ -101,10 +106,10 @@ fn test_period_edge_cases() {
     ];
     
     for (start, end, description) in edge_cases {
-        let period = Period::new(start.to_string(), end.to_string());
+        let period = Period::new(Some(start.parse().unwrap()), Some(end.parse().unwrap()));
         
         println!("✅ {}: {} to {}", description, start, end);
-        println!("   Start: {}, End: {}", period.start(), period.end());
+        println!("   Start: {}, End: {}", period.start().unwrap(), period.end().unwrap());
     }
 }
 
@@ -120,28 +125,95 @@ fn test_period_real_world_scenarios() {
     ];
     
     for (scenario, start, end, description) in real_world_scenarios {
-        let period = Period::new(start.to_string(), end.to_string());
+        let period = Period::new(Some(start.parse().unwrap()), Some(end.parse().unwrap()));
         
         println!("✅ {}: {}", scenario, description);
-        println!("   Period: {} to {}", period.start(), period.end());
+        println!("   Period: {} to {}", period.start().unwrap(), period.end().unwrap());
     }
 }
 
 #[test]
 fn test_period_international_formats() {
-    // Test international date formats
+    // Only the ISO 8601 format is a valid FHIR dateTime
+    let period = Period::new(Some("2020-01-01".parse().unwrap()), Some("2023-12-31".parse().unwrap()));
+    assert_eq!(period.start().unwrap().as_str(), "2020-01-01");
+
     let international_formats = vec![
-        ("2020-01-01", "2023-12-31", "ISO format (YYYY-MM-DD)"),
         ("01/01/2020", "12/31/2023", "US format (MM/DD/YYYY)"),
         ("01/01/2020", "31/12/2023", "European format (DD/MM/YYYY)"),
         ("2020年1月1日", "2023年12月31日", "Japanese format"),
         ("1. Januar 2020", "31. Dezember 2023", "German format"),
     ];
-    
+
     for (start, end, description) in international_formats {
-        let period = Period::new(start.to_string(), end.to_string());
-        
-        println!("✅ {}: {} to {}", description, start, end);
-        println!("   Period: {} to {}", period.start(), period.end());
+        assert!(start.parse::<DateTime>().is_err(), "{}", description);
+        assert!(end.parse::<DateTime>().is_err(), "{}", description);
+
+        println!("✅ {} rejected: {} to {}", description, start, end);
     }
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_period_open_ended() {
+    let ongoing = Period::new(Some("2020-01-01".parse().unwrap()), None);
+    assert!(ongoing.end().is_none());
+    assert!(ongoing.is_current());
+    assert!(ongoing.contains(&"2999-01-01T00:00:00Z".parse().unwrap()));
+    assert!(!ongoing.contains(&"2019-12-31T23:59:59Z".parse().unwrap()));
+    assert_eq!(ongoing.duration(), None);
+
+    let json = ongoing.to_json();
+    assert_eq!(Period::from_json(&json).unwrap(), ongoing);
+    assert_eq!(Period::from_json(r#"{"end":"2020"}"#).unwrap(), Period::new(None, Some("2020".parse().unwrap())));
+    assert!(Period::default().contains(&"2020-01-01T00:00:00Z".parse().unwrap()));
+}
+
+#[test]
+fn test_period_contains_and_duration() {
+    // A date end covers the whole day
+    let year = Period::new(Some("2020-01-01".parse().unwrap()), Some("2020-12-31".parse().unwrap()));
+    assert!(year.contains(&"2020-12-31T23:59:59.999Z".parse().unwrap()));
+    assert!(!year.contains(&"2021-01-01T00:00:00Z".parse().unwrap()));
+    assert!(year.contains(&"2021-01-01T00:30:00+01:00".parse().unwrap()));
+    assert_eq!(year.duration(), Some(Duration::from_secs(366 * 86_400)));
+    assert!(!year.is_current());
+
+    let hour = Period::new(Some("2020-03-01T10:00:00+01:00".parse().unwrap()), Some("2020-03-01T10:00:00Z".parse().unwrap()));
+    assert!(hour.contains(&"2020-03-01T10:00:00Z".parse().unwrap()));
+    // A time end covers its whole second, or its last fractional digit
+    assert!(hour.contains(&"2020-03-01T10:00:00.5Z".parse().unwrap()));
+    assert!(hour.contains(&"2020-03-01T10:00:00.999999999Z".parse().unwrap()));
+    assert!(!hour.contains(&"2020-03-01T10:00:01Z".parse().unwrap()));
+    assert_eq!(hour.duration(), Some(Duration::from_secs(3600)));
+    let tenths = Period::new(None, Some("2020-03-01T10:00:00.5Z".parse().unwrap()));
+    assert!(tenths.contains(&"2020-03-01T10:00:00.55Z".parse().unwrap()));
+    assert!(!tenths.contains(&"2020-03-01T10:00:00.6Z".parse().unwrap()));
+    assert_eq!(Period::new(Some("2020-02".parse().unwrap()), Some("2020-02".parse().unwrap())).duration(), Some(Duration::from_secs(29 * 86_400)));
+}
+
+#[test]
+fn test_period_overlaps_and_intersection() {
+    let coverage = Period::new(Some("2020-01-01".parse().unwrap()), Some("2020-06-30".parse().unwrap()));
+    let stay = Period::new(Some("2020-06-30T22:00:00Z".parse().unwrap()), Some("2020-07-04T10:00:00Z".parse().unwrap()));
+    assert!(coverage.overlaps(&stay));
+    let shared = coverage.intersection(&stay).unwrap();
+    assert_eq!(shared, Period::new(Some("2020-06-30T22:00:00Z".parse().unwrap()), Some("2020-06-30".parse().unwrap())));
+
+    let later = Period::new(Some("2020-07-01".parse().unwrap()), None);
+    assert!(!coverage.overlaps(&later));
+    assert_eq!(coverage.intersection(&later), None);
+    assert_eq!(stay.intersection(&later).unwrap(), Period::new(Some("2020-07-01".parse().unwrap()), Some("2020-07-04T10:00:00Z".parse().unwrap())));
+    assert_eq!(later.intersection(&Period::default()).unwrap(), later);
+}
+
+#[test]
+fn test_period_validation() {
+    assert!(Period::new(Some("2020-01-01".parse().unwrap()), Some("2020-12-31".parse().unwrap())).validate().is_ok());
+    assert!(Period::new(Some("2020".parse().unwrap()), Some("2020-06".parse().unwrap())).validate().is_ok());
+    assert!(Period::new(None, Some("2020".parse().unwrap())).validate().is_ok());
+    assert!(Period::new(Some("2020-01-01T10:00:00+01:00".parse().unwrap()), Some("2020-01-01T09:30:00Z".parse().unwrap())).validate().is_ok());
+    assert!(matches!(
+        Period::new(Some("2021-01-01".parse().unwrap()), Some("2020-12-31".parse().unwrap())).validate(),
+        Err(PeriodError::StartAfterEnd(start, end)) if start == "2021-01-01" && end == "2020-12-31"
+    ));
+}
@@ -0,0 +1,114 @@
+use fhir_resources_rs::account::Account;
+use fhir_resources_rs::data_types::date_time::Date;
+use fhir_resources_rs::data_types::reference::Reference;
+use fhir_resources_rs::human_name::HumanName;
+use fhir_resources_rs::meta::Meta;
+use fhir_resources_rs::patient::Patient;
+use fhir_resources_rs::projection::{is_subsetted, Projection, ProjectionError, SummaryMode};
+use fhir_resources_rs::repository::{MemoryRepository, Repository};
+use fhir_resources_rs::resource::AnyResource;
+use fhir_resources_rs::search::{SearchEngine, SearchError, SearchQuery};
+
+#[test]
+fn test_elements_projection() {
+    let mut patient = Patient::new();
+    patient.set_id(Some("example".to_string()));
+    patient.set_meta(Some(Meta::with_version("1".to_string())));
+    patient.add_name(HumanName::new("official".to_string(), "Jim".to_string(), "Chalmers".to_string()));
+    patient.set_gender(Some("male".to_string()));
+    patient.set_birth_date(Some("1974-12-25".parse().unwrap()));
+    patient.add_photo("photo.png".to_string());
+
+    let projected = Projection::elements("name,Patient.birthDate").apply(&patient);
+    assert_eq!(projected.id(), Some("example"));
+    assert_eq!(projected.names(), patient.names());
+    assert_eq!(projected.birth_date().map(Date::as_str), Some("1974-12-25"));
+    assert_eq!(projected.gender(), None);
+    assert!(projected.photo().is_empty());
+    let meta = projected.meta().unwrap();
+    assert_eq!(meta.version_id(), Some("1"));
+    assert!(is_subsetted(meta));
+    assert!(!is_subsetted(patient.meta().unwrap()));
+}
+
+#[test]
+fn test_summary_projection() {
+    let mut patient = Patient::new();
+    patient.set_meta(Some(Meta::with_version("1".to_string())));
+    patient.add_name(HumanName::new("official".to_string(), "Jim".to_string(), "Chalmers".to_string()));
+    patient.set_gender(Some("male".to_string()));
+    patient.set_multiple_birth_boolean(Some(false));
+    patient.add_photo("photo.png".to_string());
+
+    let projected = Projection::summary("true").unwrap().apply(&patient);
+    assert_eq!(projected.gender(), Some("male"));
+    assert_eq!(projected.multiple_birth_boolean(), None);
+    assert!(projected.photo().is_empty());
+
+    let text = Projection::Summary(SummaryMode::Text).apply(&patient);
+    assert!(text.names().is_empty());
+    assert!(is_subsetted(text.meta().unwrap()));
+
+    let full = Projection::summary("false").unwrap().apply(&patient);
+    assert_eq!(full, patient);
+}
+
+#[test]
+fn test_mandatory_elements_are_kept() {
+    let mut account = Account::new();
+    account.set_status(Some("active".to_string()));
+    account.set_name(Some("HACC Funded Billing".to_string()));
+    account.set_description(Some("Hospital charges".to_string()));
+    account.add_subject(Reference::with_reference("Patient/example".to_string()));
+
+    let projected = Projection::elements("name").apply(&account);
+    assert_eq!(projected.status(), Some("active"));
+    assert_eq!(projected.name(), Some("HACC Funded Billing"));
+    assert!(projected.subject().is_empty());
+    assert!(projected.meta().is_some_and(is_subsetted));
+
+    // Projecting twice does not repeat the tag.
+    let twice = Projection::elements("name").apply(&projected);
+    assert_eq!(twice.meta().unwrap().tag().len(), 1);
+}
+
+#[test]
+fn test_projection_errors() {
+    assert!(matches!(Projection::summary("maybe"), Err(ProjectionError::InvalidSummary(_))));
+    assert!(Projection::elements("name,birthDate").validate("Patient").is_ok());
+    assert!(matches!(
+        Projection::elements("name,shoeSize").validate("Patient"),
+        Err(ProjectionError::UnknownElement(_, _))
+    ));
+}
+
+#[test]
+fn test_search_projection() {
+    let mut repository = MemoryRepository::new();
+    let mut patient = Patient::new();
+    patient.set_gender(Some("male".to_string()));
+    repository.create(patient.into()).unwrap();
+    let mut account = Account::new();
+    account.add_subject(Reference::with_reference("Patient/1".to_string()));
+    repository.create(account.into()).unwrap();
+    let engine = SearchEngine::new();
+
+    let bundle = engine
+        .execute(&repository, "Account", &SearchQuery::parse("_elements=status&_include=Account:subject"))
+        .unwrap();
+    let AnyResource::Account(matched) = bundle.resources_with_mode("match")[0] else {
+        panic!("expected an Account");
+    };
+    assert!(matched.subject().is_empty());
+    let included = bundle.resources_with_mode("include")[0];
+    assert!(included.meta().is_some_and(|meta| !is_subsetted(meta)));
+
+    let count = engine
+        .execute(&repository, "Patient", &SearchQuery::parse("gender=male&_summary=count"))
+        .unwrap();
+    assert_eq!(count.total(), Some(1));
+    assert!(count.entry().is_empty());
+
+    let error = engine.execute(&repository, "Patient", &SearchQuery::parse("_elements=shoeSize"));
+    assert!(matches!(error, Err(SearchError::Projection(_))));
+}
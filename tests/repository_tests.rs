@@ -0,0 +1,151 @@
+use fhir_resources_rs::account::Account;
+use fhir_resources_rs::data_types::reference::Reference;
+use fhir_resources_rs::patient::Patient;
+use fhir_resources_rs::repository::{MemoryRepository, Repository, RepositoryError};
+use fhir_resources_rs::resource::{AnyResource, Resource};
+
+#[test]
+fn test_create_assigns_id_and_version() {
+    let mut repository = MemoryRepository::new();
+    let created = repository.create(Patient::new().into()).unwrap();
+    assert_eq!(created.id(), Some("1"));
+    assert_eq!(created.meta().unwrap().version_id(), Some("1"));
+    assert!(created.meta().unwrap().last_updated().unwrap().ends_with('Z'));
+
+    let read = repository.read("Patient", "1").unwrap();
+    assert_eq!(read, created);
+}
+
+#[test]
+fn test_update_increments_version() {
+    let mut repository = MemoryRepository::new();
+    let created = repository.create(Account::new().into()).unwrap();
+    let mut account = created.downcast::<Account>().unwrap();
+    account.set_status(Some("inactive".to_string()));
+    let updated = repository.update(account.into()).unwrap();
+    assert_eq!(updated.meta().unwrap().version_id(), Some("2"));
+
+    let read = repository.read("Account", "1").unwrap().downcast::<Account>().unwrap();
+    assert_eq!(read.status(), Some("inactive"));
+}
+
+#[test]
+fn test_create_skips_ids_taken_by_update() {
+    let mut repository = MemoryRepository::new();
+    let mut account = Account::new();
+    account.set_id(Some("1".to_string()));
+    repository.update(account.into()).unwrap();
+    let mut account = Account::new();
+    account.set_id(Some("2".to_string()));
+    repository.update(account.into()).unwrap();
+
+    let created = repository.create(Account::new().into()).unwrap();
+    assert_eq!(created.id(), Some("3"));
+    assert_eq!(repository.list("Account").unwrap().len(), 3);
+}
+
+#[test]
+fn test_update_without_id_fails() {
+    let mut repository = MemoryRepository::new();
+    assert!(matches!(
+        repository.update(Patient::new().into()),
+        Err(RepositoryError::MissingId)
+    ));
+}
+
+#[test]
+fn test_delete_and_list() {
+    let mut repository = MemoryRepository::new();
+    repository.create(Patient::new().into()).unwrap();
+    repository.create(Patient::new().into()).unwrap();
+    repository.delete("Patient", "1").unwrap();
+
+    let remaining = repository.list("Patient").unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].id(), Some("2"));
+    assert!(matches!(
+        repository.read("Patient", "1"),
+        Err(RepositoryError::NotFound(_, _))
+    ));
+}
+
+#[test]
+fn test_any_resource_json_dispatch() {
+    let mut account = Account::new();
+    account.set_id(Some("a1".to_string()));
+    let json = AnyResource::from(account).to_json();
+    let parsed = AnyResource::from_json(&json).unwrap();
+    assert_eq!(parsed.resource_type(), Account::RESOURCE_TYPE);
+    assert_eq!(parsed.reference(), Some("Account/a1".to_string()));
+
+    assert!(AnyResource::from_json(r#"{"resourceType":"Observation"}"#).is_err());
+}
+
+#[test]
+fn test_reference_target() {
+    let target = |reference: &str| {
+        Reference::with_reference(reference.to_string())
+            .target()
+            .map(|(resource_type, id)| (resource_type.to_string(), id.to_string()))
+    };
+    assert_eq!(target("Patient/123"), Some(("Patient".to_string(), "123".to_string())));
+    assert_eq!(
+        target("http://example.org/fhir/Patient/123/_history/2"),
+        Some(("Patient".to_string(), "123".to_string()))
+    );
+    assert_eq!(target("#contained"), None);
+    assert_eq!(target("urn:uuid:53fefa32-fcbb-4ff8-8a92-55ee120877b7"), None);
+}
+
+#[test]
+fn test_history_and_vread() {
+    let mut repository = MemoryRepository::new();
+    let created = repository.create(Patient::new().into()).unwrap();
+    let mut patient = created.clone().downcast::<Patient>().unwrap();
+    patient.set_active(Some(true));
+    repository.update(patient.into()).unwrap();
+    repository.delete("Patient", "1").unwrap();
+
+    let history = repository.history(Some("Patient"), Some("1")).unwrap();
+    let versions: Vec<&str> = history.iter().map(|version| version.version_id()).collect();
+    assert_eq!(versions, vec!["3", "2", "1"]);
+    assert!(history[0].is_deletion());
+    assert_eq!(repository.vread("Patient", "1", "1").unwrap(), created);
+    assert!(matches!(
+        repository.vread("Patient", "1", "3"),
+        Err(RepositoryError::NotFound(_, _))
+    ));
+
+    // Recreating the resource continues its version sequence.
+    let mut recreated = Patient::new();
+    recreated.set_id(Some("1".to_string()));
+    let recreated = repository.update(recreated.into()).unwrap();
+    assert_eq!(recreated.meta().unwrap().version_id(), Some("4"));
+    assert_eq!(repository.history(None, None).unwrap().len(), 4);
+}
+
+#[test]
+fn test_update_versioned() {
+    let mut repository = MemoryRepository::new();
+    let created = repository.create(Account::new().into()).unwrap();
+    let mut first_edit = created.clone().downcast::<Account>().unwrap();
+    first_edit.set_name(Some("First".to_string()));
+    let mut second_edit = created.downcast::<Account>().unwrap();
+    second_edit.set_name(Some("Second".to_string()));
+
+    let updated = repository.update_versioned(first_edit.into(), "1").unwrap();
+    assert_eq!(updated.meta().unwrap().version_id(), Some("2"));
+    assert!(matches!(
+        repository.update_versioned(second_edit.into(), "1"),
+        Err(RepositoryError::VersionConflict(_, _, expected, current)) if expected == "1" && current == "2"
+    ));
+    let read = repository.read("Account", "1").unwrap().downcast::<Account>().unwrap();
+    assert_eq!(read.name(), Some("First"));
+
+    let mut missing = Account::new();
+    missing.set_id(Some("9".to_string()));
+    assert!(matches!(
+        repository.update_versioned(missing.into(), "1"),
+        Err(RepositoryError::NotFound(_, _))
+    ));
+}
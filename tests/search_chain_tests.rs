@@ -0,0 +1,161 @@
+use fhir_resources_rs::account::Account;
+use fhir_resources_rs::data_types::reference::Reference;
+use fhir_resources_rs::human_name::HumanName;
+use fhir_resources_rs::patient::Patient;
+use fhir_resources_rs::repository::{MemoryRepository, Repository};
+use fhir_resources_rs::resource::AnyResource;
+use fhir_resources_rs::search::{SearchEngine, SearchError, SearchQuery};
+
+#[test]
+fn test_string_search() {
+    let mut repository = MemoryRepository::new();
+    let mut smith = Patient::new();
+    let mut name = HumanName::new("official".to_string(), "Jane Smith".to_string(), "Smith".to_string());
+    name.set_given(vec!["Jane".to_string()]);
+    smith.add_name(name);
+    repository.create(smith.into()).unwrap();
+    let mut jones = Patient::new();
+    let mut name = HumanName::new("official".to_string(), "Bob Jones".to_string(), "Jones".to_string());
+    name.set_given(vec!["Bob".to_string()]);
+    jones.add_name(name);
+    repository.create(jones.into()).unwrap();
+
+    let engine = SearchEngine::new();
+    let found = engine.search(&repository, "Patient", &SearchQuery::parse("family=smi")).unwrap();
+    assert_eq!(found.iter().filter_map(AnyResource::id).collect::<Vec<_>>(), ["1"]);
+    let found = engine.search(&repository, "Patient", &SearchQuery::parse("name=bob")).unwrap();
+    assert_eq!(found.iter().filter_map(AnyResource::id).collect::<Vec<_>>(), ["2"]);
+    assert!(engine.search(&repository, "Patient", &SearchQuery::parse("family:exact=smith")).unwrap().is_empty());
+    let found = engine.search(&repository, "Patient", &SearchQuery::parse("family:exact=Smith")).unwrap();
+    assert_eq!(found.iter().filter_map(AnyResource::id).collect::<Vec<_>>(), ["1"]);
+    let found = engine.search(&repository, "Patient", &SearchQuery::parse("family:contains=ONE")).unwrap();
+    assert_eq!(found.iter().filter_map(AnyResource::id).collect::<Vec<_>>(), ["2"]);
+}
+
+#[test]
+fn test_reference_search() {
+    let mut repository = MemoryRepository::new();
+    repository.create(Patient::new().into()).unwrap();
+    repository.create(Patient::new().into()).unwrap();
+    let mut smith_household = Account::new();
+    smith_household.add_subject(Reference::with_reference("Patient/1".to_string()));
+    repository.create(smith_household.into()).unwrap();
+    let mut jones_household = Account::new();
+    jones_household.add_subject(Reference::with_reference("Patient/2".to_string()));
+    repository.create(jones_household.into()).unwrap();
+
+    let engine = SearchEngine::new();
+    let found = engine.search(&repository, "Account", &SearchQuery::parse("subject=Patient/1")).unwrap();
+    assert_eq!(found.iter().filter_map(AnyResource::id).collect::<Vec<_>>(), ["3"]);
+    let found = engine.search(&repository, "Account", &SearchQuery::parse("subject:Patient=2")).unwrap();
+    assert_eq!(found.iter().filter_map(AnyResource::id).collect::<Vec<_>>(), ["4"]);
+    let found = engine.search(&repository, "Account", &SearchQuery::parse("patient=1")).unwrap();
+    assert_eq!(found.iter().filter_map(AnyResource::id).collect::<Vec<_>>(), ["3"]);
+    let query = SearchQuery::parse("subject=http://example.org/fhir/Patient/1");
+    let found = engine.search(&repository, "Account", &query).unwrap();
+    assert_eq!(found.iter().filter_map(AnyResource::id).collect::<Vec<_>>(), ["3"]);
+}
+
+#[test]
+fn test_chained_search() {
+    let mut repository = MemoryRepository::new();
+    let mut smith = Patient::new();
+    let mut name = HumanName::new("official".to_string(), "Jane Smith".to_string(), "Smith".to_string());
+    name.set_given(vec!["Jane".to_string()]);
+    smith.add_name(name);
+    repository.create(smith.into()).unwrap();
+    let mut jones = Patient::new();
+    let mut name = HumanName::new("official".to_string(), "Bob Jones".to_string(), "Jones".to_string());
+    name.set_given(vec!["Bob".to_string()]);
+    jones.add_name(name);
+    repository.create(jones.into()).unwrap();
+    let mut smith_household = Account::new();
+    smith_household.add_subject(Reference::with_reference("Patient/1".to_string()));
+    repository.create(smith_household.into()).unwrap();
+    let mut jones_household = Account::new();
+    jones_household.add_subject(Reference::with_reference("Patient/2".to_string()));
+    repository.create(jones_household.into()).unwrap();
+
+    let engine = SearchEngine::new();
+    let found = engine.search(&repository, "Account", &SearchQuery::parse("subject:Patient.family=smith")).unwrap();
+    assert_eq!(found.iter().filter_map(AnyResource::id).collect::<Vec<_>>(), ["3"]);
+    let found = engine.search(&repository, "Account", &SearchQuery::parse("subject.given=bob")).unwrap();
+    assert_eq!(found.iter().filter_map(AnyResource::id).collect::<Vec<_>>(), ["4"]);
+    let query = SearchQuery::parse("subject:Patient.family=nobody");
+    assert!(engine.search(&repository, "Account", &query).unwrap().is_empty());
+}
+
+#[test]
+fn test_reverse_chained_search() {
+    let mut repository = MemoryRepository::new();
+    repository.create(Patient::new().into()).unwrap();
+    repository.create(Patient::new().into()).unwrap();
+    let mut smith_household = Account::new();
+    smith_household.set_name(Some("Smith household".to_string()));
+    smith_household.set_status(Some("active".to_string()));
+    smith_household.add_subject(Reference::with_reference("Patient/1".to_string()));
+    repository.create(smith_household.into()).unwrap();
+    let mut jones_household = Account::new();
+    jones_household.set_name(Some("Jones household".to_string()));
+    jones_household.set_status(Some("inactive".to_string()));
+    jones_household.add_subject(Reference::with_reference("Patient/2".to_string()));
+    repository.create(jones_household.into()).unwrap();
+
+    let engine = SearchEngine::new();
+    let query = SearchQuery::parse("_has:Account:subject:status=active");
+    let found = engine.search(&repository, "Patient", &query).unwrap();
+    assert_eq!(found.iter().filter_map(AnyResource::id).collect::<Vec<_>>(), ["1"]);
+    let query = SearchQuery::parse("_has:Account:subject:name=jones");
+    let found = engine.search(&repository, "Patient", &query).unwrap();
+    assert_eq!(found.iter().filter_map(AnyResource::id).collect::<Vec<_>>(), ["2"]);
+    let query = SearchQuery::parse("_has:Account:patient:status:not=active");
+    let found = engine.search(&repository, "Patient", &query).unwrap();
+    assert_eq!(found.iter().filter_map(AnyResource::id).collect::<Vec<_>>(), ["2"]);
+}
+
+#[test]
+fn test_nested_chain() {
+    let mut repository = MemoryRepository::new();
+    repository.create(Patient::new().into()).unwrap();
+    repository.create(Patient::new().into()).unwrap();
+    let mut smith_household = Account::new();
+    smith_household.set_status(Some("active".to_string()));
+    smith_household.add_subject(Reference::with_reference("Patient/1".to_string()));
+    repository.create(smith_household.into()).unwrap();
+    let mut jones_household = Account::new();
+    jones_household.set_status(Some("inactive".to_string()));
+    jones_household.add_subject(Reference::with_reference("Patient/2".to_string()));
+    repository.create(jones_household.into()).unwrap();
+
+    // Accounts whose subject has an inactive account
+    let engine = SearchEngine::new();
+    let query = SearchQuery::parse("subject:Patient._has:Account:subject:status=inactive");
+    let found = engine.search(&repository, "Account", &query).unwrap();
+    assert_eq!(found.iter().filter_map(AnyResource::id).collect::<Vec<_>>(), ["4"]);
+    let found = engine.search(&repository, "Account", &SearchQuery::parse("subject:Patient._id=2")).unwrap();
+    assert_eq!(found.iter().filter_map(AnyResource::id).collect::<Vec<_>>(), ["4"]);
+}
+
+#[test]
+fn test_chain_errors() {
+    let mut repository = MemoryRepository::new();
+    repository.create(Patient::new().into()).unwrap();
+    let mut account = Account::new();
+    account.set_status(Some("active".to_string()));
+    account.add_subject(Reference::with_reference("Patient/1".to_string()));
+    repository.create(account.into()).unwrap();
+
+    let engine = SearchEngine::new();
+    assert!(matches!(
+        engine.matches(&Patient::new(), &SearchQuery::parse("_has:Account:subject:status=active")),
+        Err(SearchError::RepositoryRequired(_))
+    ));
+    assert!(matches!(
+        engine.search(&repository, "Account", &SearchQuery::parse("status.family=smith")),
+        Err(SearchError::InvalidChain(_))
+    ));
+    assert!(matches!(
+        engine.search(&repository, "Account", &SearchQuery::parse("subject:Observation.code=1234")),
+        Err(SearchError::UnsupportedModifier(_, _))
+    ));
+}
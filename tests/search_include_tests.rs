@@ -0,0 +1,152 @@
+use fhir_resources_rs::account::Account;
+use fhir_resources_rs::data_types::reference::Reference;
+use fhir_resources_rs::patient::Patient;
+use fhir_resources_rs::patient_link::PatientLink;
+use fhir_resources_rs::repository::{MemoryRepository, Repository};
+use fhir_resources_rs::resource::AnyResource;
+use fhir_resources_rs::search::{SearchEngine, SearchError, SearchQuery};
+
+#[test]
+fn test_include() {
+    let mut repository = MemoryRepository::new();
+    repository.create(Patient::new().into()).unwrap();
+    repository.create(Patient::new().into()).unwrap();
+    for (status, subject) in [("active", "Patient/1"), ("inactive", "Patient/1"), ("active", "Patient/2")] {
+        let mut account = Account::new();
+        account.set_status(Some(status.to_string()));
+        account.add_subject(Reference::with_reference(subject.to_string()));
+        repository.create(account.into()).unwrap();
+    }
+    let mut engine = SearchEngine::new();
+    engine.set_base_url(Some("http://localhost/fhir/".to_string()));
+
+    let query = SearchQuery::parse("status=active&_include=Account:subject");
+    let bundle = engine.execute(&repository, "Account", &query).unwrap();
+    assert_eq!(bundle.bundle_type(), "searchset");
+    assert_eq!(bundle.total(), Some(2));
+    let matched: Vec<String> = bundle.resources_with_mode("match").into_iter().filter_map(AnyResource::reference).collect();
+    assert_eq!(matched, ["Account/3", "Account/5"]);
+    let included: Vec<String> = bundle.resources_with_mode("include").into_iter().filter_map(AnyResource::reference).collect();
+    assert_eq!(included, ["Patient/1", "Patient/2"]);
+    assert_eq!(bundle.entry()[2].full_url(), Some("http://localhost/fhir/Patient/1"));
+}
+
+#[test]
+fn test_include_is_deduplicated() {
+    let mut repository = MemoryRepository::new();
+    repository.create(Patient::new().into()).unwrap();
+    for status in ["active", "inactive"] {
+        let mut account = Account::new();
+        account.set_status(Some(status.to_string()));
+        account.add_subject(Reference::with_reference("Patient/1".to_string()));
+        repository.create(account.into()).unwrap();
+    }
+
+    // Both accounts point at Patient/1, which is included once
+    let query = SearchQuery::parse("subject=Patient/1&_include=Account:subject:Patient");
+    let bundle = SearchEngine::new().execute(&repository, "Account", &query).unwrap();
+    let matched: Vec<String> = bundle.resources_with_mode("match").into_iter().filter_map(AnyResource::reference).collect();
+    assert_eq!(matched, ["Account/2", "Account/3"]);
+    let included: Vec<String> = bundle.resources_with_mode("include").into_iter().filter_map(AnyResource::reference).collect();
+    assert_eq!(included, ["Patient/1"]);
+}
+
+#[test]
+fn test_include_iterate() {
+    let mut repository = MemoryRepository::new();
+    let mut linked = Patient::new();
+    linked.add_link(PatientLink::new(
+        Reference::with_reference("Patient/2".to_string()),
+        "seealso".to_string(),
+    ));
+    repository.create(linked.into()).unwrap();
+    repository.create(Patient::new().into()).unwrap();
+    let mut account = Account::new();
+    account.add_subject(Reference::with_reference("Patient/1".to_string()));
+    repository.create(account.into()).unwrap();
+    let engine = SearchEngine::new();
+
+    let query = SearchQuery::parse("_id=3&_include=Account:subject&_include:iterate=Patient:link");
+    let bundle = engine.execute(&repository, "Account", &query).unwrap();
+    let included: Vec<String> = bundle.resources_with_mode("include").into_iter().filter_map(AnyResource::reference).collect();
+    assert_eq!(included, ["Patient/1", "Patient/2"]);
+
+    // Without :iterate the link is only followed from the matches
+    let query = SearchQuery::parse("_id=3&_include=Account:subject&_include=Patient:link");
+    let bundle = engine.execute(&repository, "Account", &query).unwrap();
+    let included: Vec<String> = bundle.resources_with_mode("include").into_iter().filter_map(AnyResource::reference).collect();
+    assert_eq!(included, ["Patient/1"]);
+}
+
+#[test]
+fn test_include_wildcard_and_target_filter() {
+    let mut repository = MemoryRepository::new();
+    repository.create(Patient::new().into()).unwrap();
+    let mut account = Account::new();
+    account.add_subject(Reference::with_reference("Patient/1".to_string()));
+    repository.create(account.into()).unwrap();
+    let engine = SearchEngine::new();
+
+    let bundle = engine.execute(&repository, "Account", &SearchQuery::parse("_id=2&_include=Account:*")).unwrap();
+    let included: Vec<String> = bundle.resources_with_mode("include").into_iter().filter_map(AnyResource::reference).collect();
+    assert_eq!(included, ["Patient/1"]);
+
+    let query = SearchQuery::parse("_id=2&_include=Account:subject:Organization");
+    let bundle = engine.execute(&repository, "Account", &query).unwrap();
+    assert!(bundle.resources_with_mode("include").is_empty());
+}
+
+#[test]
+fn test_revinclude() {
+    let mut repository = MemoryRepository::new();
+    repository.create(Patient::new().into()).unwrap();
+    repository.create(Patient::new().into()).unwrap();
+    for subject in ["Patient/1", "Patient/1", "Patient/2"] {
+        let mut account = Account::new();
+        account.add_subject(Reference::with_reference(subject.to_string()));
+        repository.create(account.into()).unwrap();
+    }
+
+    let query = SearchQuery::parse("_id=1&_revinclude=Account:subject");
+    let bundle = SearchEngine::new().execute(&repository, "Patient", &query).unwrap();
+    let matched: Vec<String> = bundle.resources_with_mode("match").into_iter().filter_map(AnyResource::reference).collect();
+    assert_eq!(matched, ["Patient/1"]);
+    let included: Vec<String> = bundle.resources_with_mode("include").into_iter().filter_map(AnyResource::reference).collect();
+    assert_eq!(included, ["Account/3", "Account/4"]);
+}
+
+#[test]
+fn test_revinclude_iterate() {
+    // Patient/1 links to Patient/2; Account/3 and Account/4 belong to
+    // Patient/1 and Account/5 to Patient/2
+    let mut repository = MemoryRepository::new();
+    let mut linked = Patient::new();
+    linked.add_link(PatientLink::new(
+        Reference::with_reference("Patient/2".to_string()),
+        "seealso".to_string(),
+    ));
+    repository.create(linked.into()).unwrap();
+    repository.create(Patient::new().into()).unwrap();
+    for subject in ["Patient/1", "Patient/1", "Patient/2"] {
+        let mut account = Account::new();
+        account.add_subject(Reference::with_reference(subject.to_string()));
+        repository.create(account.into()).unwrap();
+    }
+
+    let query = SearchQuery::parse("_id=2&_revinclude:iterate=Patient:link&_revinclude:iterate=Account:subject");
+    let bundle = SearchEngine::new().execute(&repository, "Patient", &query).unwrap();
+    let included: Vec<String> = bundle.resources_with_mode("include").into_iter().filter_map(AnyResource::reference).collect();
+    assert_eq!(included, ["Patient/1", "Account/5", "Account/3", "Account/4"]);
+}
+
+#[test]
+fn test_include_errors() {
+    let mut repository = MemoryRepository::new();
+    repository.create(Account::new().into()).unwrap();
+    let engine = SearchEngine::new();
+    let run = |query: &str| engine.execute(&repository, "Account", &SearchQuery::parse(query));
+    assert!(matches!(run("_include=Account:status"), Err(SearchError::InvalidValue(_, _))));
+    assert!(matches!(run("_include=Account"), Err(SearchError::InvalidValue(_, _))));
+    assert!(matches!(run("_include=Account:nothing"), Err(SearchError::UnknownParameter(_, _))));
+    assert!(matches!(run("_include:deep=Account:subject"), Err(SearchError::UnsupportedModifier(_, _))));
+}
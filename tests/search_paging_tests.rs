@@ -0,0 +1,246 @@
+use fhir_resources_rs::account::Account;
+use fhir_resources_rs::data_types::reference::Reference;
+use fhir_resources_rs::human_name::HumanName;
+use fhir_resources_rs::patient::Patient;
+use fhir_resources_rs::meta::Meta;
+use fhir_resources_rs::repository::{MemoryRepository, Repository, RepositoryError};
+use fhir_resources_rs::resource::AnyResource;
+use fhir_resources_rs::search::paging::PagingMode;
+use fhir_resources_rs::search::{SearchEngine, SearchError, SearchQuery};
+
+#[test]
+fn test_sort_ascending_and_descending() {
+    // Evans, Baker, Adams, Davis and Clark get ids 1 to 5
+    let mut repository = MemoryRepository::new();
+    for family in ["Evans", "Baker", "Adams", "Davis", "Clark"] {
+        let mut patient = Patient::new();
+        patient.add_name(HumanName::new("official".to_string(), String::new(), family.to_string()));
+        repository.create(patient.into()).unwrap();
+    }
+    let engine = SearchEngine::new();
+
+    let bundle = engine.execute(&repository, "Patient", &SearchQuery::parse("_sort=family")).unwrap();
+    let ids: Vec<&str> = bundle.resources_with_mode("match").into_iter().filter_map(AnyResource::id).collect();
+    assert_eq!(ids, ["3", "2", "5", "4", "1"]);
+    let bundle = engine.execute(&repository, "Patient", &SearchQuery::parse("_sort=-family")).unwrap();
+    let ids: Vec<&str> = bundle.resources_with_mode("match").into_iter().filter_map(AnyResource::id).collect();
+    assert_eq!(ids, ["1", "4", "5", "2", "3"]);
+}
+
+#[test]
+fn test_sort_multiple_keys() {
+    let mut repository = MemoryRepository::new();
+    for (family, gender) in [("Evans", "male"), ("Baker", "female"), ("Adams", "male"), ("Davis", "female"), ("Clark", "male")] {
+        let mut patient = Patient::new();
+        patient.add_name(HumanName::new("official".to_string(), String::new(), family.to_string()));
+        patient.set_gender(Some(gender.to_string()));
+        repository.create(patient.into()).unwrap();
+    }
+
+    // Davis and Baker, then Evans, Clark and Adams
+    let query = SearchQuery::parse("_sort=gender,-family");
+    let bundle = SearchEngine::new().execute(&repository, "Patient", &query).unwrap();
+    let ids: Vec<&str> = bundle.resources_with_mode("match").into_iter().filter_map(AnyResource::id).collect();
+    assert_eq!(ids, ["4", "2", "1", "5", "3"]);
+}
+
+#[test]
+fn test_sort_missing_values_last() {
+    let mut repository = MemoryRepository::new();
+    repository.create(Patient::new().into()).unwrap();
+    for family in ["Baker", "Adams"] {
+        let mut patient = Patient::new();
+        patient.add_name(HumanName::new("official".to_string(), String::new(), family.to_string()));
+        repository.create(patient.into()).unwrap();
+    }
+    let bundle = SearchEngine::new().execute(&repository, "Patient", &SearchQuery::parse("_sort=-family")).unwrap();
+    let ids: Vec<&str> = bundle.resources_with_mode("match").into_iter().filter_map(AnyResource::id).collect();
+    assert_eq!(ids, ["2", "3", "1"]);
+}
+
+#[test]
+fn test_sort_last_updated_by_instant() {
+    // MemoryRepository stamps lastUpdated itself, so serve fixed resources.
+    struct Fixed(Vec<AnyResource>);
+    impl Repository for Fixed {
+        fn create(&mut self, _: AnyResource) -> Result<AnyResource, RepositoryError> {
+            Err(RepositoryError::MissingId)
+        }
+        fn allocate_id(&mut self) -> String {
+            String::new()
+        }
+        fn read(&self, resource_type: &str, id: &str) -> Result<AnyResource, RepositoryError> {
+            Err(RepositoryError::NotFound(resource_type.to_string(), id.to_string()))
+        }
+        fn update(&mut self, _: AnyResource) -> Result<AnyResource, RepositoryError> {
+            Err(RepositoryError::MissingId)
+        }
+        fn delete(&mut self, resource_type: &str, id: &str) -> Result<(), RepositoryError> {
+            Err(RepositoryError::NotFound(resource_type.to_string(), id.to_string()))
+        }
+        fn list(&self, _: &str) -> Result<Vec<AnyResource>, RepositoryError> {
+            Ok(self.0.clone())
+        }
+    }
+
+    let mut resources = Vec::new();
+    for (id, last_updated) in [("late", "2026-10-18T20:00:00Z"), ("early", "2026-10-19T05:00:00+14:00")] {
+        let mut patient = Patient::new();
+        patient.set_id(Some(id.to_string()));
+        let mut meta = Meta::new();
+        meta.set_last_updated(Some(last_updated.to_string()));
+        patient.set_meta(Some(meta));
+        resources.push(patient.into());
+    }
+    let repository = Fixed(resources);
+    let bundle = SearchEngine::new().execute(&repository, "Patient", &SearchQuery::parse("_sort=_lastUpdated")).unwrap();
+    let ids: Vec<&str> = bundle.resources_with_mode("match").into_iter().filter_map(AnyResource::id).collect();
+    assert_eq!(ids, ["early", "late"]);
+}
+
+#[test]
+fn test_offset_paging_and_links() {
+    // Evans, Baker, Adams, Davis and Clark get ids 1 to 5
+    let mut repository = MemoryRepository::new();
+    for (family, gender) in [("Evans", "male"), ("Baker", "female"), ("Adams", "male"), ("Davis", "female"), ("Clark", "male")] {
+        let mut patient = Patient::new();
+        patient.add_name(HumanName::new("official".to_string(), String::new(), family.to_string()));
+        patient.set_gender(Some(gender.to_string()));
+        repository.create(patient.into()).unwrap();
+    }
+    let mut engine = SearchEngine::new();
+    engine.set_base_url(Some("http://localhost/fhir".to_string()));
+
+    let query = SearchQuery::parse("gender=male,female&_sort=family&_count=2");
+    let first = engine.execute(&repository, "Patient", &query).unwrap();
+    assert_eq!(first.total(), Some(5));
+    let ids: Vec<&str> = first.resources_with_mode("match").into_iter().filter_map(AnyResource::id).collect();
+    assert_eq!(ids, ["3", "2"]);
+    assert_eq!(
+        first.link_url("self"),
+        Some("http://localhost/fhir/Patient?gender=male,female&_sort=family&_count=2&_offset=0")
+    );
+    assert!(first.link_url("previous").is_none());
+    assert_eq!(
+        first.link_url("last"),
+        Some("http://localhost/fhir/Patient?gender=male,female&_sort=family&_count=2&_offset=4")
+    );
+
+    // Links are followed by re-parsing their query strings
+    let (_, next) = first.link_url("next").unwrap().split_once('?').unwrap();
+    let second = engine.execute(&repository, "Patient", &SearchQuery::parse(next)).unwrap();
+    let ids: Vec<&str> = second.resources_with_mode("match").into_iter().filter_map(AnyResource::id).collect();
+    assert_eq!(ids, ["5", "4"]);
+    let (_, next) = second.link_url("next").unwrap().split_once('?').unwrap();
+    let third = engine.execute(&repository, "Patient", &SearchQuery::parse(next)).unwrap();
+    let ids: Vec<&str> = third.resources_with_mode("match").into_iter().filter_map(AnyResource::id).collect();
+    assert_eq!(ids, ["1"]);
+    assert!(third.link_url("next").is_none());
+
+    let (_, previous) = third.link_url("previous").unwrap().split_once('?').unwrap();
+    let back = engine.execute(&repository, "Patient", &SearchQuery::parse(previous)).unwrap();
+    let ids: Vec<&str> = back.resources_with_mode("match").into_iter().filter_map(AnyResource::id).collect();
+    assert_eq!(ids, ["5", "4"]);
+}
+
+#[test]
+fn test_cursor_paging() {
+    let mut repository = MemoryRepository::new();
+    for family in ["Evans", "Baker", "Adams", "Davis", "Clark"] {
+        let mut patient = Patient::new();
+        patient.add_name(HumanName::new("official".to_string(), String::new(), family.to_string()));
+        repository.create(patient.into()).unwrap();
+    }
+    let mut engine = SearchEngine::new();
+    engine.set_paging_mode(PagingMode::Cursor);
+    let first = engine.execute(&repository, "Patient", &SearchQuery::parse("_sort=family&_count=2")).unwrap();
+    let next = first.link_url("next").unwrap().to_string();
+    assert!(next.ends_with("_count=2&_cursor=2"));
+
+    // Deleting Adams from the first page does not shift the next page.
+    repository.delete("Patient", "3").unwrap();
+    let (_, query) = next.split_once('?').unwrap();
+    let second = engine.execute(&repository, "Patient", &SearchQuery::parse(query)).unwrap();
+    let ids: Vec<&str> = second.resources_with_mode("match").into_iter().filter_map(AnyResource::id).collect();
+    assert_eq!(ids, ["5", "4"]);
+    assert!(second.link_url("previous").unwrap().ends_with("_count=2"));
+}
+
+#[test]
+fn test_total_modes_and_count_zero() {
+    let mut repository = MemoryRepository::new();
+    for _ in 0..5 {
+        repository.create(Patient::new().into()).unwrap();
+    }
+    let engine = SearchEngine::new();
+    assert_eq!(engine.execute(&repository, "Patient", &SearchQuery::parse("_total=none")).unwrap().total(), None);
+    assert_eq!(engine.execute(&repository, "Patient", &SearchQuery::parse("_total=estimate")).unwrap().total(), Some(5));
+
+    let summary = engine.execute(&repository, "Patient", &SearchQuery::parse("_count=0")).unwrap();
+    assert_eq!(summary.total(), Some(5));
+    assert!(summary.entry().is_empty());
+    assert_eq!(summary.link().len(), 1);
+}
+
+#[test]
+fn test_total_estimate_is_exact() {
+    let mut repository = MemoryRepository::new();
+    for gender in ["female", "male", "female", "female", "male", "female", "female"] {
+        let mut patient = Patient::new();
+        patient.set_gender(Some(gender.to_string()));
+        repository.create(patient.into()).unwrap();
+    }
+    let engine = SearchEngine::new();
+
+    // An estimate is the exact number of matches, not of the page or the repository
+    let query = SearchQuery::parse("gender=female&_count=2&_total=estimate");
+    let estimate = engine.execute(&repository, "Patient", &query).unwrap();
+    assert_eq!(estimate.total(), Some(5));
+    assert_eq!(estimate.entry().len(), 2);
+    let query = SearchQuery::parse("gender=female&_count=2&_total=accurate");
+    let accurate = engine.execute(&repository, "Patient", &query).unwrap();
+    assert_eq!(estimate.total(), accurate.total());
+}
+
+#[test]
+fn test_count_limits() {
+    let mut repository = MemoryRepository::new();
+    for _ in 0..5 {
+        repository.create(Patient::new().into()).unwrap();
+    }
+    let mut engine = SearchEngine::new();
+    engine.set_default_count(3);
+    engine.set_max_count(4);
+    assert_eq!(engine.execute(&repository, "Patient", &SearchQuery::parse("")).unwrap().entry().len(), 3);
+    assert_eq!(engine.execute(&repository, "Patient", &SearchQuery::parse("_count=100")).unwrap().entry().len(), 4);
+}
+
+#[test]
+fn test_includes_follow_page() {
+    let mut repository = MemoryRepository::new();
+    repository.create(Patient::new().into()).unwrap();
+    repository.create(Patient::new().into()).unwrap();
+    for id in ["1", "2"] {
+        let mut account = Account::new();
+        account.add_subject(Reference::with_reference(format!("Patient/{}", id)));
+        repository.create(account.into()).unwrap();
+    }
+    let bundle = SearchEngine::new()
+        .execute(&repository, "Account", &SearchQuery::parse("_count=1&_include=Account:subject"))
+        .unwrap();
+    assert_eq!(bundle.total(), Some(2));
+    assert_eq!(bundle.entry().len(), 2);
+    assert_eq!(bundle.resources_with_mode("include")[0].id(), Some("1"));
+}
+
+#[test]
+fn test_paging_errors() {
+    let mut repository = MemoryRepository::new();
+    repository.create(Patient::new().into()).unwrap();
+    let engine = SearchEngine::new();
+    let run = |query: &str| engine.execute(&repository, "Patient", &SearchQuery::parse(query));
+    assert!(matches!(run("_sort=shoe-size"), Err(SearchError::UnknownParameter(_, _))));
+    assert!(matches!(run("_count=many"), Err(SearchError::InvalidValue(_, _))));
+    assert!(matches!(run("_total=exact"), Err(SearchError::InvalidValue(_, _))));
+    assert!(matches!(run("_cursor=99"), Err(SearchError::InvalidValue(_, _))));
+}
@@ -0,0 +1,236 @@
+use fhir_resources_rs::account::Account;
+use fhir_resources_rs::data_types::codeable_concept::{CodeableConcept, Coding};
+use fhir_resources_rs::data_types::contact_point::ContactPoint;
+use fhir_resources_rs::data_types::uri::Uri;
+use fhir_resources_rs::identifier::Identifier;
+use fhir_resources_rs::patient::Patient;
+use fhir_resources_rs::search::value_set::ValueSet;
+use fhir_resources_rs::search::{SearchEngine, SearchError, SearchQuery};
+
+#[test]
+fn test_token_system_and_code() {
+    let mut patient = Patient::new();
+    patient.add_identifier(Identifier::new(
+        Uri::new_unchecked("official".to_string()),
+        Uri::new_unchecked("http://hospital/mrn".to_string()),
+        "12345".to_string(),
+    ));
+
+    let engine = SearchEngine::new();
+    let check = |query: &str| engine.matches(&patient, &SearchQuery::parse(query)).unwrap();
+    assert!(check("identifier=http://hospital/mrn|12345"));
+    assert!(check("identifier=12345"));
+    assert!(check("identifier=http://hospital/mrn|"));
+    assert!(!check("identifier=http://other/mrn|12345"));
+    assert!(!check("identifier=|12345"));
+    assert!(check("identifier=http%3A%2F%2Fhospital%2Fmrn%7C12345"));
+}
+
+#[test]
+fn test_token_no_system() {
+    let mut patient = Patient::new();
+    patient.add_identifier(Identifier::new(
+        Uri::new_unchecked("usual".to_string()),
+        Uri::new_unchecked(String::new()),
+        "A-1".to_string(),
+    ));
+
+    let engine = SearchEngine::new();
+    assert!(engine.matches(&patient, &SearchQuery::parse("identifier=|A-1")).unwrap());
+    assert!(!engine.matches(&patient, &SearchQuery::parse("identifier=http://hospital/mrn|A-1")).unwrap());
+}
+
+#[test]
+fn test_token_or_values_and_escaping() {
+    let mut patient = Patient::new();
+    patient.add_identifier(Identifier::new(
+        Uri::new_unchecked("official".to_string()),
+        Uri::new_unchecked("http://hospital/mrn".to_string()),
+        "12345".to_string(),
+    ));
+    let engine = SearchEngine::new();
+    assert!(engine.matches(&patient, &SearchQuery::parse("identifier=999,http://hospital/mrn|12345")).unwrap());
+    assert!(!engine.matches(&patient, &SearchQuery::parse("identifier=999,888")).unwrap());
+
+    let mut escaped = Patient::new();
+    escaped.add_identifier(Identifier::new(
+        Uri::new_unchecked("usual".to_string()),
+        Uri::new_unchecked("http://hospital/mrn".to_string()),
+        "a,b".to_string(),
+    ));
+    assert!(engine.matches(&escaped, &SearchQuery::parse(r"identifier=http://hospital/mrn|a\,b")).unwrap());
+}
+
+#[test]
+fn test_token_codeable_concept_and_primitives() {
+    let mut patient = Patient::new();
+    patient.set_active(Some(true));
+    patient.set_gender(Some("female".to_string()));
+    patient.set_marital_status(Some(CodeableConcept::with_coding(Coding::with_system_and_code(
+        "http://terminology.hl7.org/CodeSystem/v3-MaritalStatus".to_string(),
+        "M".to_string(),
+    ))));
+
+    let engine = SearchEngine::new();
+    let check = |query: &str| engine.matches(&patient, &SearchQuery::parse(query)).unwrap();
+    assert!(check("marital-status=http://terminology.hl7.org/CodeSystem/v3-MaritalStatus|M"));
+    assert!(check("marital-status=M"));
+    assert!(!check("marital-status=S"));
+    assert!(check("gender=female"));
+    assert!(check("gender=http://hl7.org/fhir/administrative-gender|female"));
+    assert!(check("gender=http://hl7.org/fhir/administrative-gender|"));
+    assert!(!check("gender=http://wrong|female"));
+    assert!(!check("gender=|female"));
+    assert!(check("active=true"));
+    assert!(!check("active=false"));
+    assert!(check("active=|true"));
+    assert!(!check("active=http://wrong|true"));
+}
+
+#[test]
+fn test_token_contact_point() {
+    let mut patient = Patient::new();
+    patient.add_telecom(ContactPoint::new_with_details(
+        "email".to_string(),
+        "jane@example.com".to_string(),
+        Some("work".to_string()),
+        None,
+        None,
+    ));
+
+    let engine = SearchEngine::new();
+    assert!(engine.matches(&patient, &SearchQuery::parse("email=jane@example.com")).unwrap());
+    assert!(engine.matches(&patient, &SearchQuery::parse("telecom=work|jane@example.com")).unwrap());
+    assert!(!engine.matches(&patient, &SearchQuery::parse("phone=jane@example.com")).unwrap());
+}
+
+#[test]
+fn test_token_text_modifier() {
+    let mut patient = Patient::new();
+    let mut marital_status = CodeableConcept::with_coding(Coding::with_system_and_code(
+        "http://terminology.hl7.org/CodeSystem/v3-MaritalStatus".to_string(),
+        "M".to_string(),
+    ));
+    marital_status.set_text(Some("Married".to_string()));
+    patient.set_marital_status(Some(marital_status));
+    let mut identifier = Identifier::new(
+        Uri::new_unchecked("official".to_string()),
+        Uri::new_unchecked("http://hospital/mrn".to_string()),
+        "12345".to_string(),
+    );
+    let mut coding = Coding::with_system_and_code(
+        "http://terminology.hl7.org/CodeSystem/v2-0203".to_string(),
+        "MR".to_string(),
+    );
+    coding.set_display(Some("Medical record number".to_string()));
+    identifier.set_identifier_type(Some(CodeableConcept::with_coding(coding)));
+    patient.add_identifier(identifier);
+
+    // Matches the start of the text or of a coding's display
+    let engine = SearchEngine::new();
+    assert!(engine.matches(&patient, &SearchQuery::parse("marital-status:text=marr")).unwrap());
+    assert!(engine.matches(&patient, &SearchQuery::parse("identifier:text=medical")).unwrap());
+    assert!(!engine.matches(&patient, &SearchQuery::parse("marital-status:text=single")).unwrap());
+}
+
+#[test]
+fn test_token_not_modifier() {
+    let mut patient = Patient::new();
+    patient.set_gender(Some("female".to_string()));
+
+    let engine = SearchEngine::new();
+    assert!(engine.matches(&patient, &SearchQuery::parse("gender:not=male")).unwrap());
+    assert!(!engine.matches(&patient, &SearchQuery::parse("gender:not=female")).unwrap());
+    assert!(engine.matches(&Patient::new(), &SearchQuery::parse("gender:not=female")).unwrap());
+}
+
+#[test]
+fn test_token_of_type_modifier() {
+    let mut patient = Patient::new();
+    let mut identifier = Identifier::new(
+        Uri::new_unchecked("official".to_string()),
+        Uri::new_unchecked("http://hospital/mrn".to_string()),
+        "12345".to_string(),
+    );
+    identifier.set_identifier_type(Some(CodeableConcept::with_coding(Coding::with_system_and_code(
+        "http://terminology.hl7.org/CodeSystem/v2-0203".to_string(),
+        "MR".to_string(),
+    ))));
+    patient.add_identifier(identifier);
+
+    let engine = SearchEngine::new();
+    let query = SearchQuery::parse("identifier:of-type=http://terminology.hl7.org/CodeSystem/v2-0203|MR|12345");
+    assert!(engine.matches(&patient, &query).unwrap());
+    let query = SearchQuery::parse("identifier:of-type=http://terminology.hl7.org/CodeSystem/v2-0203|SS|12345");
+    assert!(!engine.matches(&patient, &query).unwrap());
+
+    let result = engine.matches(&patient, &SearchQuery::parse("identifier:of-type=MR|12345"));
+    assert!(matches!(result, Err(SearchError::InvalidValue(_, _))));
+}
+
+#[test]
+fn test_token_in_and_not_in() {
+    let mut engine = SearchEngine::new();
+    let mut married = ValueSet::new("http://example.org/vs/married".to_string());
+    married.add_concept("http://terminology.hl7.org/CodeSystem/v3-MaritalStatus".to_string(), "M".to_string());
+    engine.add_value_set(married);
+    let mut gender = ValueSet::new("http://hl7.org/fhir/ValueSet/administrative-gender".to_string());
+    gender.add_concept("http://hl7.org/fhir/administrative-gender".to_string(), "female".to_string());
+    engine.add_value_set(gender);
+
+    let mut patient = Patient::new();
+    patient.set_gender(Some("female".to_string()));
+    patient.set_marital_status(Some(CodeableConcept::with_coding(Coding::with_system_and_code(
+        "http://terminology.hl7.org/CodeSystem/v3-MaritalStatus".to_string(),
+        "M".to_string(),
+    ))));
+    let check = |query: &str| engine.matches(&patient, &SearchQuery::parse(query)).unwrap();
+    assert!(check("marital-status:in=http://example.org/vs/married"));
+    assert!(!check("marital-status:not-in=http://example.org/vs/married"));
+    assert!(check("gender:in=http://hl7.org/fhir/ValueSet/administrative-gender"));
+
+    let unknown = engine.matches(&patient, &SearchQuery::parse("gender:in=http://example.org/vs/unknown"));
+    assert!(matches!(unknown, Err(SearchError::UnknownValueSet(_))));
+}
+
+#[test]
+fn test_token_account_search() {
+    let mut active = Account::new();
+    active.set_status(Some("active".to_string()));
+    active.set_account_type(Some(CodeableConcept::with_coding(Coding::with_system_and_code(
+        "http://terminology.hl7.org/CodeSystem/v3-ActCode".to_string(),
+        "PBILLACCT".to_string(),
+    ))));
+    let mut inactive = Account::new();
+    inactive.set_status(Some("inactive".to_string()));
+    let accounts = vec![active, inactive];
+
+    let engine = SearchEngine::new();
+    let found = engine.filter(&accounts, &SearchQuery::parse("status=active&type=PBILLACCT")).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].status(), Some("active"));
+
+    let found = engine.filter(&accounts, &SearchQuery::parse("status=http://hl7.org/fhir/account-status|active")).unwrap();
+    assert_eq!(found.len(), 1);
+    assert!(engine.filter(&accounts, &SearchQuery::parse("status=|active")).unwrap().is_empty());
+
+    let found = engine.filter(&accounts, &SearchQuery::parse("status:not=active")).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].status(), Some("inactive"));
+}
+
+#[test]
+fn test_token_unknown_parameter_and_modifier() {
+    let mut patient = Patient::new();
+    patient.set_gender(Some("female".to_string()));
+
+    let engine = SearchEngine::new();
+    assert!(matches!(
+        engine.matches(&patient, &SearchQuery::parse("shoe-size=9")),
+        Err(SearchError::UnknownParameter(_, _))
+    ));
+    assert!(matches!(
+        engine.matches(&patient, &SearchQuery::parse("gender:exact=female")),
+        Err(SearchError::UnsupportedModifier(_, _))
+    ));
+}
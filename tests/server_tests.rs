@@ -0,0 +1,416 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+use fhir_resources_rs::bulk_export::ExportManifest;
+use fhir_resources_rs::bundle::Bundle;
+use fhir_resources_rs::capability_statement::{CapabilityStatement, TypeInteraction};
+use fhir_resources_rs::data_types::uri::Uri;
+use fhir_resources_rs::identifier::Identifier;
+use fhir_resources_rs::operation_outcome::OperationOutcome;
+use fhir_resources_rs::patient::Patient;
+use fhir_resources_rs::repository::MemoryRepository;
+use fhir_resources_rs::resource::AnyResource;
+use fhir_resources_rs::server::{FhirServer, ServerRequest};
+
+const PATIENT: &str = r#"{"resourceType":"Patient","gender":"female","name":[{"use":"official","text":"Ann Lee","family":"Lee"}]}"#;
+
+#[test]
+fn test_create_and_read() {
+    let mut server = FhirServer::new(MemoryRepository::new());
+    server.set_base_url(Some("http://localhost/fhir".to_string()));
+    let request = ServerRequest::new("POST", "/Patient")
+        .with_header("Content-Type", "application/fhir+json")
+        .with_body(PATIENT.to_string());
+    let created = server.handle(&request);
+    assert_eq!(created.status(), 201);
+    assert_eq!(created.header("Content-Type"), Some("application/fhir+json"));
+    assert_eq!(created.header("ETag"), Some("W/\"1\""));
+    assert_eq!(created.header("Location"), Some("http://localhost/fhir/Patient/1/_history/1"));
+
+    let read = server.handle(&ServerRequest::new("GET", "/Patient/1"));
+    assert_eq!(read.status(), 200);
+    let patient = AnyResource::from_json(read.body()).unwrap().downcast::<Patient>().unwrap();
+    assert_eq!(patient.gender(), Some("female"));
+}
+
+#[test]
+fn test_update_vread_and_history() {
+    let mut server = FhirServer::new(MemoryRepository::new());
+    server.set_base_url(Some("http://localhost/fhir".to_string()));
+    server.handle(&ServerRequest::new("POST", "/Patient").with_body(PATIENT.to_string()));
+    let update = ServerRequest::new("PUT", "/Patient/1")
+        .with_body(PATIENT.replace("female", "other").replace("{\"resourceType\":\"Patient\"", "{\"resourceType\":\"Patient\",\"id\":\"1\""));
+    let updated = server.handle(&update);
+    assert_eq!(updated.status(), 200);
+    assert_eq!(updated.header("ETag"), Some("W/\"2\""));
+
+    let first = server.handle(&ServerRequest::new("GET", "/Patient/1/_history/1"));
+    assert!(first.body().contains("\"female\""));
+
+    let history = Bundle::from_json(server.handle(&ServerRequest::new("GET", "/Patient/1/_history")).body()).unwrap();
+    assert_eq!(history.bundle_type(), "history");
+    assert_eq!(history.total(), Some(2));
+    let methods: Vec<&str> = history.entry().iter().map(|entry| entry.request().unwrap().method()).collect();
+    assert_eq!(methods, vec!["PUT", "POST"]);
+
+    // _since is an instant: the same wall-clock time at -12:00 is later, at +14:00 earlier
+    let last_updated = history.entry()[0].resource().unwrap().meta().unwrap().last_updated().unwrap().to_string();
+    let later = last_updated.replace('Z', "-12:00");
+    let response = server.handle(&ServerRequest::new("GET", &format!("/Patient/_history?_since={}", later)));
+    let since_later = Bundle::from_json(response.body()).unwrap();
+    assert_eq!(since_later.total(), Some(0));
+    let earlier = last_updated.replace('Z', "%2B14:00");
+    let response = server.handle(&ServerRequest::new("GET", &format!("/Patient/_history?_since={}", earlier)));
+    let since_earlier = Bundle::from_json(response.body()).unwrap();
+    assert_eq!(since_earlier.total(), Some(2));
+    assert_eq!(server.handle(&ServerRequest::new("GET", "/Patient/_history?_since=garbage")).status(), 400);
+
+    let created = server.handle(&ServerRequest::new("PUT", "/Patient/p9").with_body(
+        r#"{"resourceType":"Patient","id":"p9"}"#.to_string(),
+    ));
+    assert_eq!(created.status(), 201);
+}
+
+#[test]
+fn test_patch() {
+    let mut server = FhirServer::new(MemoryRepository::new());
+    server.set_base_url(Some("http://localhost/fhir".to_string()));
+    server.handle(&ServerRequest::new("POST", "/Patient").with_body(PATIENT.to_string()));
+    let patch = ServerRequest::new("PATCH", "/Patient/1")
+        .with_header("Content-Type", "application/json-patch+json")
+        .with_body(r#"[{"op":"test","path":"/gender","value":"female"},{"op":"replace","path":"/gender","value":"male"},{"op":"add","path":"/active","value":true}]"#.to_string());
+    let patched = server.handle(&patch);
+    assert_eq!(patched.status(), 200);
+    let patient = AnyResource::from_json(patched.body()).unwrap().downcast::<Patient>().unwrap();
+    assert_eq!(patient.gender(), Some("male"));
+    assert_eq!(patient.active(), Some(true));
+
+    let invalid = ServerRequest::new("PATCH", "/Patient/1")
+        .with_header("Content-Type", "application/json-patch+json")
+        .with_body(r#"[{"op":"replace","path":"/active","value":"yes"}]"#.to_string());
+    assert_eq!(server.handle(&invalid).status(), 422);
+    assert_eq!(server.handle(&ServerRequest::new("GET", "/Patient/1")).header("ETag"), Some("W/\"2\""));
+}
+
+#[test]
+fn test_delete() {
+    let mut server = FhirServer::new(MemoryRepository::new());
+    server.set_base_url(Some("http://localhost/fhir".to_string()));
+    server.handle(&ServerRequest::new("POST", "/Patient").with_body(PATIENT.to_string()));
+    assert_eq!(server.handle(&ServerRequest::new("DELETE", "/Patient/1")).status(), 204);
+    assert_eq!(server.handle(&ServerRequest::new("DELETE", "/Patient/1")).status(), 204);
+
+    let gone = server.handle(&ServerRequest::new("GET", "/Patient/1"));
+    assert_eq!(gone.status(), 410);
+    assert_eq!(OperationOutcome::from_json(gone.body()).unwrap().issue()[0].code(), "deleted");
+    assert_eq!(server.handle(&ServerRequest::new("GET", "/Patient/1/_history/2")).status(), 410);
+    assert_eq!(server.handle(&ServerRequest::new("GET", "/Patient/1/_history/1")).status(), 200);
+    assert_eq!(server.handle(&ServerRequest::new("DELETE", "/Patient/7")).status(), 404);
+}
+
+#[test]
+fn test_search_get_and_post() {
+    let mut server = FhirServer::new(MemoryRepository::new());
+    server.set_base_url(Some("http://localhost/fhir".to_string()));
+    server.handle(&ServerRequest::new("POST", "/Patient").with_body(PATIENT.to_string()));
+    server.handle(&ServerRequest::new("POST", "/Patient").with_body(PATIENT.replace("female", "male")));
+
+    let response = server.handle(&ServerRequest::new("GET", "/Patient?gender=male&_format=json"));
+    let bundle = Bundle::from_json(response.body()).unwrap();
+    assert_eq!(bundle.total(), Some(1));
+    assert_eq!(bundle.entry()[0].full_url(), Some("http://localhost/fhir/Patient/2"));
+
+    let request = ServerRequest::new("POST", "/Patient/_search")
+        .with_header("Content-Type", "application/x-www-form-urlencoded")
+        .with_body("name=ann+lee".to_string());
+    let bundle = Bundle::from_json(server.handle(&request).body()).unwrap();
+    assert_eq!(bundle.total(), Some(2));
+}
+
+#[test]
+fn test_capabilities() {
+    let server = FhirServer::new(MemoryRepository::new());
+    let response = server.handle(&ServerRequest::new("GET", "/metadata"));
+    assert_eq!(response.status(), 200);
+    let statement: serde_json::Value = serde_json::from_str(response.body()).unwrap();
+    assert_eq!(statement["resourceType"], "CapabilityStatement");
+    assert_eq!(statement["rest"][0]["resource"][1]["type"], "Account");
+}
+
+#[test]
+fn test_errors_are_operation_outcomes() {
+    let mut server = FhirServer::new(MemoryRepository::new());
+    server.set_base_url(Some("http://localhost/fhir".to_string()));
+    let unknown = server.handle(&ServerRequest::new("GET", "/Observation/1"));
+    assert_eq!(unknown.status(), 404);
+    assert_eq!(OperationOutcome::from_json(unknown.body()).unwrap().issue()[0].code(), "not-supported");
+    assert_eq!(server.handle(&ServerRequest::new("GET", "/_foo")).status(), 404);
+    assert_eq!(server.handle(&ServerRequest::new("GET", "/Patient/1")).status(), 404);
+    server.handle(&ServerRequest::new("POST", "/Patient").with_body(PATIENT.to_string()));
+    assert_eq!(server.handle(&ServerRequest::new("GET", "/Patient?shoe-size=9")).status(), 400);
+    assert_eq!(server.handle(&ServerRequest::new("POST", "/Patient").with_body("{".to_string())).status(), 400);
+    assert_eq!(server.handle(&ServerRequest::new("POST", "/Account").with_body(PATIENT.to_string())).status(), 400);
+    assert_eq!(server.handle(&ServerRequest::new("GET", "/Patient").with_header("Accept", "application/fhir+xml")).status(), 406);
+    let xml = ServerRequest::new("POST", "/Patient").with_header("Content-Type", "application/fhir+xml");
+    assert_eq!(server.handle(&xml).status(), 415);
+    assert_eq!(server.handle(&ServerRequest::new("PUT", "/Patient")).status(), 405);
+    let mismatch = ServerRequest::new("PUT", "/Patient/2").with_body(r#"{"resourceType":"Patient","id":"1"}"#.to_string());
+    assert_eq!(server.handle(&mismatch).status(), 400);
+}
+
+#[test]
+fn test_http_round_trip() {
+    let handle = FhirServer::new(MemoryRepository::new()).start("127.0.0.1:0").unwrap();
+    let send = |request: String| {
+        let mut stream = TcpStream::connect(handle.address()).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    };
+    let created = send(format!(
+        "POST /Patient HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/fhir+json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        PATIENT.len(),
+        PATIENT
+    ));
+    assert!(created.starts_with("HTTP/1.1 201"));
+    assert!(created.contains(&format!("Location: {}/Patient/1/_history/1", handle.base_url())));
+
+    let read = send("GET /Patient/1 HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n".to_string());
+    assert!(read.starts_with("HTTP/1.1 200"));
+    assert!(read.contains("application/fhir+json"));
+    handle.stop();
+}
+
+#[test]
+fn test_http_invalid_body_and_minimal_return() {
+    let handle = FhirServer::new(MemoryRepository::new()).start("127.0.0.1:0").unwrap();
+    let send = |head: &str, body: &[u8]| {
+        let mut stream = TcpStream::connect(handle.address()).unwrap();
+        let request = format!("{}\r\nHost: localhost\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", head, body.len());
+        stream.write_all(request.as_bytes()).unwrap();
+        stream.write_all(body).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    };
+
+    let invalid = send("POST /Patient HTTP/1.1", &[0xff, 0xfe, 0x7b]);
+    assert!(invalid.starts_with("HTTP/1.1 400"));
+    let (_, body) = invalid.split_once("\r\n\r\n").unwrap();
+    assert_eq!(OperationOutcome::from_json(body).unwrap().issue()[0].code(), "invalid");
+
+    let minimal = send("POST /Patient HTTP/1.1\r\nPrefer: return=minimal", PATIENT.as_bytes());
+    assert!(minimal.starts_with("HTTP/1.1 201"));
+    assert!(!minimal.to_ascii_lowercase().contains("content-type"));
+    assert!(minimal.ends_with("\r\n\r\n"));
+    handle.stop();
+}
+
+#[test]
+fn test_capabilities_follow_supported_interactions() {
+    let mut server = FhirServer::new(MemoryRepository::new());
+    server.set_base_url(Some("http://localhost/fhir".to_string()));
+    server.set_interactions(vec![TypeInteraction::Read, TypeInteraction::SearchType]);
+    let statement = CapabilityStatement::from_json(server.handle(&ServerRequest::new("GET", "/metadata")).body()).unwrap();
+    let patient = statement.resource("Patient").unwrap();
+    assert!(patient.supports(TypeInteraction::Read));
+    assert!(!patient.supports(TypeInteraction::Create));
+    assert!(statement.patch_format().is_empty());
+
+    let rejected = server.handle(&ServerRequest::new("POST", "/Patient").with_body(PATIENT.to_string()));
+    assert_eq!(rejected.status(), 405);
+    assert_eq!(server.handle(&ServerRequest::new("GET", "/Patient")).status(), 200);
+}
+
+#[test]
+fn test_transaction() {
+    let mut server = FhirServer::new(MemoryRepository::new());
+    server.set_base_url(Some("http://localhost/fhir".to_string()));
+    let body = r#"{"resourceType":"Bundle","type":"transaction","entry":[
+        {"fullUrl":"urn:uuid:1c6b4a5e-3d1f-4a8e-9b1e-0f0f0f0f0f0f","resource":{"resourceType":"Patient"},"request":{"method":"POST","url":"Patient"}},
+        {"resource":{"resourceType":"Account","status":"active","subject":[{"reference":"urn:uuid:1c6b4a5e-3d1f-4a8e-9b1e-0f0f0f0f0f0f"}]},"request":{"method":"POST","url":"Account"}}
+    ]}"#;
+    let response = server.handle(&ServerRequest::new("POST", "/").with_body(body.to_string()));
+    assert_eq!(response.status(), 200);
+    let bundle = Bundle::from_json(response.body()).unwrap();
+    assert_eq!(bundle.bundle_type(), "transaction-response");
+    let response = server.handle(&ServerRequest::new("GET", "/Account/2"));
+    let account: serde_json::Value = serde_json::from_str(response.body()).unwrap();
+    assert_eq!(account["subject"][0]["reference"], "Patient/1");
+
+    let failing = r#"{"resourceType":"Bundle","type":"transaction","entry":[
+        {"resource":{"resourceType":"Patient"},"request":{"method":"POST","url":"Patient"}},
+        {"request":{"method":"DELETE","url":"Patient/42"}}
+    ]}"#;
+    let response = server.handle(&ServerRequest::new("POST", "/").with_body(failing.to_string()));
+    assert_eq!(response.status(), 404);
+    assert_eq!(OperationOutcome::from_json(response.body()).unwrap().issue()[0].code(), "not-found");
+    assert_eq!(server.handle(&ServerRequest::new("GET", "/Patient/3")).status(), 404);
+}
+
+#[test]
+fn test_conditional_interactions() {
+    let mut server = FhirServer::new(MemoryRepository::new());
+    server.set_base_url(Some("http://localhost/fhir".to_string()));
+    let mut patient = Patient::new();
+    patient.add_identifier(Identifier::new(
+        Uri::new_unchecked("official".to_string()),
+        Uri::new_unchecked("http://hospital/mrn".to_string()),
+        "12345".to_string(),
+    ));
+    let body = patient.to_json();
+    let create = |server: &FhirServer<MemoryRepository>| {
+        let request = ServerRequest::new("POST", "/Patient")
+            .with_header("Content-Type", "application/fhir+json")
+            .with_header("If-None-Exist", "identifier=http://hospital/mrn|12345")
+            .with_body(body.clone());
+        server.handle(&request)
+    };
+    assert_eq!(create(&server).status(), 201);
+    let existing = create(&server);
+    assert_eq!(existing.status(), 200);
+    assert_eq!(existing.header("Location"), Some("http://localhost/fhir/Patient/1/_history/1"));
+
+    patient.set_gender(Some("male".to_string()));
+    let update = ServerRequest::new("PUT", "/Patient?identifier=12345")
+        .with_header("Content-Type", "application/fhir+json")
+        .with_body(patient.to_json());
+    let updated = server.handle(&update);
+    assert_eq!(updated.status(), 200);
+    assert_eq!(updated.header("ETag"), Some("W/\"2\""));
+
+    server.handle(&ServerRequest::new("POST", "/Patient").with_body(body.to_string()));
+    assert_eq!(create(&server).status(), 412);
+    let delete = server.handle(&ServerRequest::new("DELETE", "/Patient?identifier=12345"));
+    assert_eq!(delete.status(), 412);
+    assert_eq!(OperationOutcome::from_json(delete.body()).unwrap().issue()[0].code(), "multiple-matches");
+    assert_eq!(server.handle(&ServerRequest::new("DELETE", "/Patient?gender=male")).status(), 204);
+    assert_eq!(server.handle(&ServerRequest::new("GET", "/Patient/1")).status(), 410);
+
+    let statement = server.capability_statement();
+    assert_eq!(statement.resource("Patient").unwrap().conditional_delete(), Some("single"));
+}
+
+#[test]
+fn test_optimistic_concurrency() {
+    let mut server = FhirServer::new(MemoryRepository::new());
+    server.set_base_url(Some("http://localhost/fhir".to_string()));
+    let account = r#"{"resourceType":"Account","status":"active"}"#;
+    server.handle(&ServerRequest::new("POST", "/Account").with_body(account.to_string()));
+    let put = |if_match: Option<&str>, body: &str| {
+        let mut request = ServerRequest::new("PUT", "/Account/1")
+            .with_header("Content-Type", "application/fhir+json")
+            .with_body(body.to_string());
+        if let Some(etag) = if_match {
+            request.add_header("If-Match", etag);
+        }
+        server.handle(&request)
+    };
+    let body = r#"{"resourceType":"Account","id":"1","status":"inactive"}"#;
+
+    assert_eq!(put(Some("W/\"1\""), body).status(), 200);
+    let stale = put(Some("W/\"1\""), body);
+    assert_eq!(stale.status(), 412);
+    assert_eq!(OperationOutcome::from_json(stale.body()).unwrap().issue()[0].code(), "conflict");
+
+    let stale_body = r#"{"resourceType":"Account","id":"1","meta":{"versionId":"1"},"status":"active"}"#;
+    assert_eq!(put(None, stale_body).status(), 409);
+    assert_eq!(put(None, body).status(), 200);
+
+    let patch = ServerRequest::new("PATCH", "/Account/1")
+        .with_header("Content-Type", "application/json-patch+json")
+        .with_header("If-Match", "W/\"2\"")
+        .with_body(r#"[{"op":"replace","path":"/status","value":"active"}]"#.to_string());
+    assert_eq!(server.handle(&patch).status(), 412);
+
+    let statement = server.capability_statement();
+    assert_eq!(statement.resource("Account").unwrap().versioning(), Some("versioned-update"));
+}
+
+#[test]
+fn test_fhirpath_patch() {
+    let mut server = FhirServer::new(MemoryRepository::new());
+    server.set_base_url(Some("http://localhost/fhir".to_string()));
+    server.handle(&ServerRequest::new("POST", "/Patient").with_body(PATIENT.to_string()));
+    let patch = ServerRequest::new("PATCH", "/Patient/1")
+        .with_header("Content-Type", "application/fhir+json")
+        .with_body(r#"{"resourceType":"Parameters","parameter":[{"name":"operation","part":[{"name":"type","valueCode":"replace"},{"name":"path","valueString":"Patient.name[0].family"},{"name":"value","valueString":"Li"}]}]}"#.to_string());
+    let patched = server.handle(&patch);
+    assert_eq!(patched.status(), 200);
+    let patient = AnyResource::from_json(patched.body()).unwrap().downcast::<Patient>().unwrap();
+    assert_eq!(patient.names()[0].family(), "Li");
+    assert!(server.capability_statement().patch_format().contains(&"application/fhir+json".to_string()));
+}
+
+#[test]
+fn test_patient_everything() {
+    let mut server = FhirServer::new(MemoryRepository::new());
+    server.set_base_url(Some("http://localhost/fhir".to_string()));
+    server.handle(&ServerRequest::new("POST", "/Patient").with_body(PATIENT.to_string()));
+    let account = r#"{"resourceType":"Account","status":"active","subject":[{"reference":"Patient/1"}]}"#;
+    assert_eq!(server.handle(&ServerRequest::new("POST", "/Account").with_body(account.to_string())).status(), 201);
+
+    let everything = server.handle(&ServerRequest::new("GET", "/Patient/1/$everything"));
+    assert_eq!(everything.status(), 200);
+    let bundle = Bundle::from_json(everything.body()).unwrap();
+    assert_eq!(bundle.total(), Some(2));
+    assert_eq!(bundle.entry()[1].full_url(), Some("http://localhost/fhir/Account/2"));
+
+    let response = server.handle(&ServerRequest::new("GET", "/Patient/1/$everything?_type=Account"));
+    let accounts = Bundle::from_json(response.body()).unwrap();
+    assert_eq!(accounts.total(), Some(1));
+    assert_eq!(server.handle(&ServerRequest::new("GET", "/Patient/9/$everything")).status(), 404);
+    let unsupported = server.handle(&ServerRequest::new("GET", "/Patient/1/$everything?_type=Organization"));
+    assert_eq!(unsupported.status(), 400);
+    assert_eq!(OperationOutcome::from_json(unsupported.body()).unwrap().issue()[0].code(), "not-supported");
+    assert_eq!(server.handle(&ServerRequest::new("GET", "/Patient/1/$everything?_since=garbage")).status(), 400);
+
+    let statement = server.capability_statement();
+    let operations = statement.resource("Patient").unwrap().operation();
+    assert!(operations.iter().any(|operation| operation.name() == "everything"));
+}
+
+#[test]
+fn test_bulk_export() {
+    let mut server = FhirServer::new(MemoryRepository::new());
+    server.set_base_url(Some("http://localhost/fhir".to_string()));
+    let export_dir = std::env::temp_dir().join(format!("fhir-export-server-tests-{}", std::process::id()));
+    server.set_export_dir(export_dir.clone());
+    server.exporter_mut().add_group("g1", vec!["1".to_string()]);
+    server.handle(&ServerRequest::new("POST", "/Patient").with_body(PATIENT.to_string()));
+    server.handle(&ServerRequest::new("POST", "/Patient").with_body(PATIENT.replace("female", "male")));
+
+    let kickoff = |path: &str| server.handle(&ServerRequest::new("GET", path).with_header("Prefer", "respond-async"));
+    assert_eq!(server.handle(&ServerRequest::new("GET", "/$export")).status(), 400);
+    let accepted = kickoff("/Group/g1/$export?_type=Patient");
+    assert_eq!(accepted.status(), 202);
+    let status_url = accepted.header("Content-Location").unwrap().trim_start_matches("http://localhost/fhir").to_string();
+    assert!(status_url.starts_with("/$export-poll-status/"));
+
+    let mut status = server.handle(&ServerRequest::new("GET", &status_url));
+    while status.status() == 202 {
+        assert_eq!(status.header("X-Progress"), Some("in-progress"));
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        status = server.handle(&ServerRequest::new("GET", &status_url));
+    }
+    assert_eq!(status.status(), 200);
+    assert_eq!(status.header("Content-Type"), Some("application/json"));
+    let manifest = ExportManifest::from_json(status.body()).unwrap();
+    assert_eq!(manifest.request(), "http://localhost/fhir/Group/g1/$export?_type=Patient");
+    assert_eq!(manifest.output().len(), 1);
+    assert_eq!(manifest.output()[0].count(), Some(1));
+
+    let file = server.handle(&ServerRequest::new("GET", manifest.output()[0].url().trim_start_matches("http://localhost/fhir")));
+    assert_eq!(file.status(), 200);
+    assert_eq!(file.header("Content-Type"), Some("application/fhir+ndjson"));
+    assert_eq!(file.body().lines().count(), 1);
+
+    assert_eq!(kickoff("/Group/g2/$export").status(), 404);
+    assert_eq!(kickoff("/$export?_type=Organization").status(), 400);
+    assert_eq!(server.handle(&ServerRequest::new("DELETE", &status_url)).status(), 202);
+    assert_eq!(server.handle(&ServerRequest::new("GET", &status_url)).status(), 404);
+    let statement = server.capability_statement();
+    assert!(statement.rest()[0].operation().iter().any(|operation| operation.name() == "export"));
+    let _ = std::fs::remove_dir_all(export_dir);
+}
@@ -0,0 +1,167 @@
+use fhir_resources_rs::account::Account;
+use fhir_resources_rs::bundle::{Bundle, BundleEntry, BundleEntryRequest};
+use fhir_resources_rs::data_types::reference::Reference;
+use fhir_resources_rs::patient::Patient;
+use fhir_resources_rs::repository::{MemoryRepository, Repository};
+use fhir_resources_rs::resource::AnyResource;
+use fhir_resources_rs::transaction::{process, TransactionError};
+
+const PATIENT_URL: &str = "urn:uuid:61ebe359-bfdc-4613-8bf2-c5e300945f0a";
+
+#[test]
+fn test_transaction_rewrites_placeholders() {
+    let mut repository = MemoryRepository::new();
+    // The Account comes first and references the Patient created after it.
+    let mut account = Account::new();
+    account.set_status(Some("active".to_string()));
+    account.add_subject(Reference::with_reference(PATIENT_URL.to_string()));
+    let mut create_account = BundleEntry::with_resource(None, account.into());
+    create_account.set_request(Some(BundleEntryRequest::new("POST".to_string(), "Account".to_string())));
+    let mut create_patient = BundleEntry::with_resource(Some(PATIENT_URL.to_string()), Patient::new().into());
+    create_patient.set_request(Some(BundleEntryRequest::new("POST".to_string(), "Patient".to_string())));
+    let mut transaction = Bundle::new("transaction".to_string());
+    transaction.set_entry(vec![create_account, create_patient]);
+
+    let response = process(&mut repository, &transaction).unwrap();
+    assert_eq!(response.bundle_type(), "transaction-response");
+    let account_response = response.entry()[0].response().unwrap();
+    assert_eq!(account_response.status(), "201 Created");
+    assert_eq!(account_response.location(), Some("Account/1/_history/1"));
+    assert_eq!(account_response.etag(), Some("W/\"1\""));
+    assert_eq!(response.entry()[1].response().unwrap().location(), Some("Patient/2/_history/1"));
+
+    let account = repository.read("Account", "1").unwrap().downcast::<Account>().unwrap();
+    assert_eq!(account.subject()[0].reference(), Some("Patient/2"));
+}
+
+#[test]
+fn test_transaction_is_atomic() {
+    let mut repository = MemoryRepository::new();
+    repository.create(Patient::new().into()).unwrap();
+    let mut transaction = Bundle::new("transaction".to_string());
+    for (method, url, resource) in [
+        ("POST", "Patient", Some(AnyResource::from(Patient::new()))),
+        ("DELETE", "Patient/1", None),
+        ("GET", "Patient/99", None),
+    ] {
+        let mut entry = BundleEntry::new();
+        entry.set_resource(resource);
+        entry.set_request(Some(BundleEntryRequest::new(method.to_string(), url.to_string())));
+        transaction.add_entry(entry);
+    }
+
+    let error = process(&mut repository, &transaction).unwrap_err();
+    assert!(matches!(error, TransactionError::Entry(2, _)));
+    assert_eq!(error.status(), 404);
+    assert_eq!(repository.list("Patient").unwrap().len(), 1);
+    assert_eq!(repository.history(None, None).unwrap().len(), 1);
+}
+
+#[test]
+fn test_processing_order() {
+    let mut repository = MemoryRepository::new();
+    repository.create(Patient::new().into()).unwrap();
+    // The read runs last and the delete first, whatever their position.
+    let mut batch = Bundle::new("batch".to_string());
+    for (method, url, resource) in [
+        ("GET", "Patient/5", None),
+        ("PUT", "Patient/5", Some(AnyResource::from(Patient::new()))),
+        ("DELETE", "Patient/1", None),
+    ] {
+        let mut entry = BundleEntry::new();
+        entry.set_resource(resource);
+        entry.set_request(Some(BundleEntryRequest::new(method.to_string(), url.to_string())));
+        batch.add_entry(entry);
+    }
+
+    let response = process(&mut repository, &batch).unwrap();
+    let statuses: Vec<&str> = response.entry().iter().map(|entry| entry.response().unwrap().status()).collect();
+    assert_eq!(statuses, vec!["200 OK", "201 Created", "204 No Content"]);
+    assert_eq!(response.entry()[0].resource().unwrap().id(), Some("5"));
+    assert!(repository.read("Patient", "1").is_err());
+}
+
+#[test]
+fn test_batch_entries_fail_independently() {
+    let mut repository = MemoryRepository::new();
+    let mut batch = Bundle::new("batch".to_string());
+    for (method, url, resource) in [
+        ("POST", "Patient", Some(AnyResource::from(Patient::new()))),
+        ("GET", "Account/1", None),
+        // A Patient posted to the Account endpoint
+        ("POST", "Account", Some(AnyResource::from(Patient::new()))),
+        ("PATCH", "Patient/1", None),
+    ] {
+        let mut entry = BundleEntry::new();
+        entry.set_resource(resource);
+        entry.set_request(Some(BundleEntryRequest::new(method.to_string(), url.to_string())));
+        batch.add_entry(entry);
+    }
+
+    let response = process(&mut repository, &batch).unwrap();
+    assert_eq!(response.bundle_type(), "batch-response");
+    let statuses: Vec<&str> = response.entry().iter().map(|entry| entry.response().unwrap().status()).collect();
+    assert_eq!(statuses, vec!["201 Created", "404 Not Found", "400 Bad Request", "405 Method Not Allowed"]);
+    let outcome = response.entry()[1].response().unwrap().outcome().unwrap();
+    assert_eq!(outcome.issue()[0].code(), "not-found");
+    assert_eq!(repository.list("Patient").unwrap().len(), 1);
+}
+
+#[test]
+fn test_invalid_bundles() {
+    let mut repository = MemoryRepository::new();
+    assert!(matches!(
+        process(&mut repository, &Bundle::new("searchset".to_string())),
+        Err(TransactionError::NotProcessable(_))
+    ));
+
+    let mut duplicate = Bundle::new("transaction".to_string());
+    for method in ["DELETE", "GET"] {
+        let mut entry = BundleEntry::new();
+        entry.set_request(Some(BundleEntryRequest::new(method.to_string(), "Patient/1".to_string())));
+        duplicate.add_entry(entry);
+    }
+    assert!(matches!(process(&mut repository, &duplicate), Err(TransactionError::DuplicateEntry(_))));
+}
+
+#[test]
+fn test_mutually_referencing_creates() {
+    let mut repository = MemoryRepository::new();
+    let first = "urn:uuid:7d1a8e8e-6c8f-4b43-9a5e-2b1a9d1c0001";
+    let second = "urn:uuid:7d1a8e8e-6c8f-4b43-9a5e-2b1a9d1c0002";
+    let mut mutual = Bundle::new("transaction".to_string());
+    for (full_url, subject) in [(first, second), (second, first)] {
+        let mut account = Account::new();
+        account.add_subject(Reference::with_reference(subject.to_string()));
+        let mut entry = BundleEntry::with_resource(Some(full_url.to_string()), account.into());
+        entry.set_request(Some(BundleEntryRequest::new("POST".to_string(), "Account".to_string())));
+        mutual.add_entry(entry);
+    }
+
+    let response = process(&mut repository, &mutual).unwrap();
+    let statuses: Vec<&str> = response.entry().iter().map(|entry| entry.response().unwrap().status()).collect();
+    assert_eq!(statuses, vec!["201 Created", "201 Created"]);
+    let first = repository.read("Account", "1").unwrap().downcast::<Account>().unwrap();
+    let second = repository.read("Account", "2").unwrap().downcast::<Account>().unwrap();
+    assert_eq!(first.subject()[0].reference(), Some("Account/2"));
+    assert_eq!(second.subject()[0].reference(), Some("Account/1"));
+    assert_eq!(first.meta().unwrap().version_id(), Some("1"));
+}
+
+#[test]
+fn test_transaction_if_match() {
+    let mut repository = MemoryRepository::new();
+    repository.create(Patient::new().into()).unwrap();
+    let mut patient = Patient::new();
+    patient.set_id(Some("1".to_string()));
+    let mut request = BundleEntryRequest::new("PUT".to_string(), "Patient/1".to_string());
+    request.set_if_match(Some("W/\"2\"".to_string()));
+    let mut update = BundleEntry::with_resource(None, patient.into());
+    update.set_request(Some(request));
+    let mut transaction = Bundle::new("transaction".to_string());
+    transaction.add_entry(update);
+
+    let error = process(&mut repository, &transaction).unwrap_err();
+    assert_eq!(error.status(), 412);
+    assert_eq!(error.issue_code(), "conflict");
+}